@@ -20,7 +20,9 @@ use aptos_config::config::{GasEstimationConfig, NodeConfig, RoleType};
 use aptos_crypto::HashValue;
 use aptos_gas_schedule::{AptosGasParameters, FromOnChainGasSchedule};
 use aptos_logger::{error, info, Schema};
-use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use aptos_mempool::{
+    MempoolClientRequest, MempoolClientSender, MempoolTransactionStatus, SubmissionStatus,
+};
 use aptos_storage_interface::{
     state_store::state_view::db_state_view::{
         DbStateView, DbStateViewAtVersion, LatestDbStateCheckpointView,
@@ -40,7 +42,7 @@ use aptos_types::{
     state_store::{
         state_key::{inner::StateKeyInner, prefix::StateKeyPrefix, StateKey},
         state_value::StateValue,
-        TStateView,
+        StateView, TStateView,
     },
     transaction::{
         block_epilogue::BlockEndInfo,
@@ -59,6 +61,7 @@ use serde::Serialize;
 use std::{
     cmp::Reverse,
     collections::{BTreeMap, HashMap},
+    net::IpAddr,
     ops::{Bound::Included, Deref},
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
@@ -81,6 +84,7 @@ pub struct Context {
     simulate_txn_stats: Arc<FunctionStats>,
     pub indexer_reader: Option<Arc<dyn IndexerReader>>,
     pub wait_for_hash_active_connections: Arc<AtomicUsize>,
+    pub event_stream_active_connections: Arc<AtomicUsize>,
 }
 
 impl std::fmt::Debug for Context {
@@ -133,6 +137,7 @@ impl Context {
             simulate_txn_stats,
             indexer_reader,
             wait_for_hash_active_connections: Arc::new(AtomicUsize::new(0)),
+            event_stream_active_connections: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -206,11 +211,27 @@ impl Context {
         self.node_config.api.max_submit_transaction_batch_size
     }
 
-    pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
+    pub fn max_view_batch_size(&self) -> usize {
+        self.node_config.api.max_view_batch_size
+    }
+
+    pub fn trusted_proxies(&self) -> &[IpAddr] {
+        &self.node_config.api.trusted_proxies
+    }
+
+    /// Submits a transaction to mempool. The client IP, if known, is used by mempool to enforce
+    /// per-client admission quotas.
+    pub async fn submit_transaction(
+        &self,
+        txn: SignedTransaction,
+        client_ip: Option<IpAddr>,
+    ) -> Result<SubmissionStatus> {
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
             .clone()
-            .send(MempoolClientRequest::SubmitTransaction(txn, req_sender))
+            .send(MempoolClientRequest::SubmitTransaction(
+                txn, client_ip, req_sender,
+            ))
             .await?;
 
         callback.await?
@@ -737,6 +758,16 @@ impl Context {
         }
 
         let state_view = self.latest_state_view_poem(ledger_info)?;
+        self.render_transactions_with_state_view(ledger_info, &state_view, data)
+    }
+
+    /// Renders transactions, resolving the types of their payloads and outputs from `state_view`
+    pub fn render_transactions_with_state_view<E: InternalError>(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        data: Vec<TransactionOnChainData>,
+    ) -> Result<Vec<aptos_api_types::Transaction>, E> {
         let converter = state_view.as_converter(self.db.clone(), self.indexer_reader.clone());
         let txns: Vec<aptos_api_types::Transaction> = data
             .into_iter()
@@ -924,6 +955,23 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    pub async fn get_mempool_transaction_status(
+        &self,
+        hash: HashValue,
+    ) -> Result<Option<MempoolTransactionStatus>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetTransactionStatusByHash(
+                hash, req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
@@ -1044,6 +1092,27 @@ impl Context {
         }
     }
 
+    pub fn get_events_by_type(
+        &self,
+        struct_tag: &StructTag,
+        start_version: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.indexer_reader
+            .as_ref()
+            .ok_or_else(|| anyhow!("Internal indexer reader doesn't exist"))?
+            .get_events_by_type(struct_tag, start_version, limit as u64, ledger_version)
+    }
+
+    pub fn get_events_by_type_start_version(&self) -> Result<u64> {
+        self.indexer_reader
+            .as_ref()
+            .ok_or_else(|| anyhow!("Internal indexer reader doesn't exist"))?
+            .get_events_by_type_start_version()?
+            .ok_or_else(|| anyhow!("Events by type are not indexed"))
+    }
+
     pub fn get_indexer_reader(&self) -> Option<&Arc<dyn IndexerReader>> {
         self.indexer_reader.as_ref()
     }
@@ -9,22 +9,41 @@ use crate::{
     failpoint::fail_point_poem,
     page::Page,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        api_disabled, json_api_disabled, version_pruned, BadRequestError, BasicErrorWith404,
+        BasicResponse, BasicResponseStatus, BasicResultWith404, GoneError, InternalError,
+        ServiceUnavailableError,
     },
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
-    MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
+    MoveModuleId, MoveStructTag, TransactionEvents, VerifyInputWithRecursion, VersionedEvent, U64,
 };
-use aptos_types::event::EventKey;
+use aptos_logger::warn;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::{ContractEvent, EventWithVersion},
+    event::EventKey,
+};
+use futures::{channel::mpsc, stream::BoxStream, SinkExt, StreamExt};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use poem_openapi::{
     param::{Path, Query},
+    payload::EventStream,
     OpenApi,
 };
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Number of messages buffered for a slow event stream client before reading from storage pauses.
+const EVENT_STREAM_BUFFER_SIZE: usize = 100;
+const EVENT_STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
 
 #[derive(Clone)]
 pub struct EventsApi {
@@ -150,6 +169,207 @@ impl EventsApi {
         })
         .await
     }
+
+    /// Get events by type
+    ///
+    /// Module events are not emitted to an event handle, so they cannot be queried by
+    /// account. This API returns module events of the given type emitted by any account,
+    /// in version order, from the internal indexer.
+    ///
+    /// The events of a transaction are never split across pages, so the next page can be
+    /// retrieved by setting `start` to one after the version of the last event returned.
+    /// A page may hold fewer events than `limit` for this reason, or more if a single
+    /// transaction emitted more than `limit` of them.
+    ///
+    /// Only versions indexed since the node started indexing events by type can be queried.
+    #[oai(
+        path = "/events/by_type/:event_type",
+        method = "get",
+        operation_id = "get_events_by_type",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_type(
+        &self,
+        accept_type: AcceptType,
+        /// Type of the module events e.g. `0x1::fungible_asset::Deposit`
+        event_type: Path<MoveStructTag>,
+        /// Ledger version to start listing events from.
+        ///
+        /// If unspecified, by default will start from the oldest version available
+        start: Query<Option<U64>>,
+        /// Max number of events to retrieve.
+        ///
+        /// If unspecified, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        fail_point_poem("endpoint_get_events_by_type")?;
+        self.context
+            .check_api_output_enabled("Get events by type", &accept_type)?;
+        if !self.context.node_config.indexer_db_config.enable_event
+            || self.context.indexer_reader.is_none()
+        {
+            return Err(api_disabled("Get events by type"));
+        }
+        let struct_tag = event_type
+            .0
+            .verify(0)
+            .and_then(|_| StructTag::try_from(event_type.0))
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_events_page_size(),
+        );
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            let latest_ledger_info = api.context.get_latest_ledger_info::<BasicErrorWith404>()?;
+            let indexed_start_version = api
+                .context
+                .get_events_by_type_start_version()
+                .context("Failed to get the first version of events indexed by type")
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &latest_ledger_info,
+                    )
+                })?;
+            let oldest_version = latest_ledger_info.oldest_ledger_version.0;
+            let start_version = match page.start_option() {
+                Some(start) if start < oldest_version => {
+                    return Err(version_pruned(start, &latest_ledger_info));
+                },
+                Some(start) if start < indexed_start_version => {
+                    return Err(BasicErrorWith404::gone_with_code(
+                        format!(
+                            "Events by type are only indexed since ledger version({})",
+                            indexed_start_version
+                        ),
+                        AptosErrorCode::VersionPruned,
+                        &latest_ledger_info,
+                    ));
+                },
+                Some(start) => start,
+                None => std::cmp::max(oldest_version, indexed_start_version),
+            };
+            let events = api
+                .context
+                .get_events_by_type(
+                    &struct_tag,
+                    start_version,
+                    page.limit(&latest_ledger_info)?,
+                    latest_ledger_info.version(),
+                )
+                .context(format!("Failed to find events by type {}", struct_tag))
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &latest_ledger_info,
+                    )
+                })?;
+            api.render_events(latest_ledger_info, accept_type, events)
+        })
+        .await
+    }
+
+    /// Stream events
+    ///
+    /// Streams committed transactions emitting events that match the given filters, as
+    /// server-sent events. Each message is a transaction with its matching events, in
+    /// version order. The filters are combined, and an event matches `account` if the
+    /// transaction was sent by the account or the event was emitted to it.
+    ///
+    /// The stream never ends by itself. To resume a dropped stream, reconnect with
+    /// `start_version` set to one after the version of the last message received.
+    #[oai(
+        path = "/events/stream",
+        method = "get",
+        operation_id = "stream_events",
+        tag = "ApiTags::Events"
+    )]
+    async fn stream_events(
+        &self,
+        /// Ledger version to start streaming from.
+        ///
+        /// If unspecified, streams transactions committed after the request.
+        start_version: Query<Option<U64>>,
+        /// Only events of this type e.g. `0x1::coin::CoinDeposit`
+        event_type: Query<Option<MoveStructTag>>,
+        /// Only events whose type is declared in this module e.g. `0x1::coin`
+        module: Query<Option<MoveModuleId>>,
+        /// Hex-encoded 32 byte Aptos account, with or without a `0x` prefix. Only events
+        /// of transactions sent by this account, or emitted to it
+        account: Query<Option<Address>>,
+    ) -> poem::Result<EventStream<BoxStream<'static, TransactionEvents>>, BasicErrorWith404> {
+        fail_point_poem("endpoint_stream_events")?;
+        if !self.context.node_config.api.event_stream_enabled {
+            return Err(api_disabled("Stream events"));
+        }
+        if !self.context.node_config.api.json_output_enabled {
+            return Err(json_api_disabled("Stream events"));
+        }
+        let event_type = event_type
+            .0
+            .map(|tag| {
+                tag.verify(0)?;
+                StructTag::try_from(tag)
+            })
+            .transpose()
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let filter = EventFilter {
+            event_type,
+            module: module.0.map(Into::into),
+            account: account.0.map(Into::into),
+        };
+
+        let context = self.context.clone();
+        let latest_ledger_info =
+            api_spawn_blocking(move || context.get_latest_ledger_info::<BasicErrorWith404>())
+                .await?;
+        let start_version = start_version
+            .0
+            .map_or(latest_ledger_info.version() + 1, |v| v.0);
+        if start_version < latest_ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(start_version, &latest_ledger_info));
+        }
+
+        // Refuse new streams if too many are active.
+        let active_connections = self.context.event_stream_active_connections.clone();
+        if active_connections.fetch_add(1, Ordering::Relaxed)
+            >= self
+                .context
+                .node_config
+                .api
+                .event_stream_max_active_connections
+        {
+            active_connections.fetch_sub(1, Ordering::Relaxed);
+            return Err(BasicErrorWith404::service_unavailable_with_code(
+                "Too many active event streams, please retry later",
+                AptosErrorCode::TooManyEventStreams,
+                &latest_ledger_info,
+            ));
+        }
+
+        let (sender, receiver) = mpsc::channel(EVENT_STREAM_BUFFER_SIZE);
+        let streamer = EventStreamer {
+            context: self.context.clone(),
+            filter,
+            next_version: start_version,
+            timestamp: None,
+            _active_connection: ActiveConnection(active_connections),
+        };
+        tokio::spawn(streamer.run(sender));
+
+        Ok(EventStream::new(receiver.boxed()).keep_alive(EVENT_STREAM_KEEP_ALIVE))
+    }
 }
 
 impl EventsApi {
@@ -178,7 +398,15 @@ impl EventsApi {
                     &latest_ledger_info,
                 )
             })?;
+        self.render_events(latest_ledger_info, accept_type, events)
+    }
 
+    fn render_events(
+        &self,
+        latest_ledger_info: LedgerInfo,
+        accept_type: AcceptType,
+        events: Vec<EventWithVersion>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
         match accept_type {
             AcceptType::Json => {
                 let events = self
@@ -203,3 +431,225 @@ impl EventsApi {
         }
     }
 }
+
+/// Matches events satisfying all the conditions set.
+struct EventFilter {
+    event_type: Option<StructTag>,
+    module: Option<ModuleId>,
+    account: Option<AccountAddress>,
+}
+
+impl EventFilter {
+    fn matches(&self, sender: Option<AccountAddress>, event: &ContractEvent) -> bool {
+        let struct_tag = match event.type_tag() {
+            TypeTag::Struct(tag) => Some(tag.as_ref()),
+            _ => None,
+        };
+        self.event_type
+            .as_ref()
+            .map_or(true, |event_type| struct_tag == Some(event_type))
+            && self.module.as_ref().map_or(true, |module| {
+                struct_tag.map_or(false, |tag| {
+                    &tag.address == module.address() && tag.module.as_ident_str() == module.name()
+                })
+            })
+            && self.account.map_or(true, |account| {
+                sender == Some(account)
+                    || event
+                        .v1()
+                        .map_or(false, |e| e.key().get_creator_address() == account)
+            })
+    }
+}
+
+/// Counts an active event stream until dropped.
+struct ActiveConnection(Arc<AtomicUsize>);
+
+impl Drop for ActiveConnection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Reads committed transactions from storage, following the ledger as it grows, and sends the
+/// ones with matching events to an event stream.
+struct EventStreamer {
+    context: Arc<Context>,
+    filter: EventFilter,
+    next_version: u64,
+    /// Timestamp of the block containing `next_version - 1`.
+    timestamp: Option<u64>,
+    _active_connection: ActiveConnection,
+}
+
+impl EventStreamer {
+    async fn run(mut self, mut sender: mpsc::Sender<TransactionEvents>) {
+        let poll_interval =
+            Duration::from_millis(self.context.node_config.api.event_stream_poll_interval_ms);
+        loop {
+            let (streamer, result) = match tokio::task::spawn_blocking(move || {
+                let result = self.next_batch();
+                (self, result)
+            })
+            .await
+            {
+                Ok(output) => output,
+                Err(err) => {
+                    warn!(
+                        error = ?err,
+                        "Event stream task failed, closing the stream."
+                    );
+                    return;
+                },
+            };
+            self = streamer;
+
+            let (batch, reached_tip) = match result {
+                Ok(result) => result,
+                Err(err) => {
+                    warn!(
+                        next_version = self.next_version,
+                        error = ?err,
+                        "Failed to read transactions for event stream, closing it."
+                    );
+                    return;
+                },
+            };
+            for item in batch {
+                if sender.send(item).await.is_err() {
+                    // The client went away.
+                    return;
+                }
+            }
+            if sender.is_closed() {
+                return;
+            }
+            // Pages without matching events are read back to back while catching up, only
+            // waiting for new transactions once the stream is at the latest ledger version.
+            if reached_tip {
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    }
+
+    /// Reads the next page of committed transactions, returning those with matching events and
+    /// whether the stream has reached the latest ledger version.
+    fn next_batch(&mut self) -> anyhow::Result<(Vec<TransactionEvents>, bool)> {
+        let ledger_version = self
+            .context
+            .get_latest_ledger_info_with_signatures()?
+            .ledger_info()
+            .version();
+        if self.next_version > ledger_version {
+            return Ok((vec![], true));
+        }
+        let limit = std::cmp::min(
+            ledger_version - self.next_version + 1,
+            self.context.max_transactions_page_size() as u64,
+        ) as u16;
+        let txns = self
+            .context
+            .get_transactions(self.next_version, limit, ledger_version)?;
+        let mut timestamp = match self.timestamp {
+            Some(timestamp) => timestamp,
+            None => self.context.db.get_block_timestamp(self.next_version)?,
+        };
+
+        let state_view = self.context.latest_state_view()?;
+        let converter =
+            state_view.as_converter(self.context.db.clone(), self.context.indexer_reader.clone());
+        let mut batch = vec![];
+        for txn in &txns {
+            if let Some(block_metadata) = txn.transaction.try_as_block_metadata_ext() {
+                timestamp = block_metadata.timestamp_usecs();
+            } else if let Some(block_metadata) = txn.transaction.try_as_block_metadata() {
+                timestamp = block_metadata.timestamp_usecs();
+            }
+            let sender = txn
+                .transaction
+                .try_as_signed_user_txn()
+                .map(|user_txn| user_txn.sender());
+            let events: Vec<_> = txn
+                .events
+                .iter()
+                .filter(|event| self.filter.matches(sender, event))
+                .cloned()
+                .collect();
+            if events.is_empty() {
+                continue;
+            }
+            batch.push(TransactionEvents {
+                version: txn.version.into(),
+                hash: txn.info.transaction_hash().into(),
+                sender: sender.map(Into::into),
+                success: txn.info.status().is_success(),
+                timestamp: timestamp.into(),
+                events: converter.try_into_events(&events)?,
+            });
+        }
+
+        self.next_version += txns.len() as u64;
+        self.timestamp = Some(timestamp);
+        Ok((batch, self.next_version > ledger_version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn event(type_tag: &str, key: Option<EventKey>) -> ContractEvent {
+        let type_tag = TypeTag::from_str(type_tag).unwrap();
+        match key {
+            Some(key) => ContractEvent::new_v1(key, 0, type_tag, vec![]),
+            None => ContractEvent::new_v2(type_tag, vec![]),
+        }
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let alice = AccountAddress::from_str("0xa").unwrap();
+        let bob = AccountAddress::from_str("0xb").unwrap();
+        let deposit = event("0x1::coin::CoinDeposit", None);
+        let bob_withdraw = event("0x1::coin::WithdrawEvent", Some(EventKey::new(0, bob)));
+        let primitive = event("u64", None);
+
+        let all = EventFilter {
+            event_type: None,
+            module: None,
+            account: None,
+        };
+        assert!(all.matches(None, &deposit));
+        assert!(all.matches(None, &primitive));
+
+        let by_type = EventFilter {
+            event_type: Some(StructTag::from_str("0x1::coin::CoinDeposit").unwrap()),
+            module: None,
+            account: None,
+        };
+        assert!(by_type.matches(None, &deposit));
+        assert!(!by_type.matches(None, &bob_withdraw));
+        assert!(!by_type.matches(None, &primitive));
+
+        let by_module = EventFilter {
+            event_type: None,
+            module: Some(ModuleId::from_str("0x1::coin").unwrap()),
+            account: None,
+        };
+        assert!(by_module.matches(None, &deposit));
+        assert!(by_module.matches(None, &bob_withdraw));
+        assert!(!by_module.matches(None, &primitive));
+        assert!(!by_module.matches(None, &event("0x1::account::KeyRotation", None)));
+
+        let by_account = EventFilter {
+            event_type: None,
+            module: Some(ModuleId::from_str("0x1::coin").unwrap()),
+            account: Some(bob),
+        };
+        assert!(by_account.matches(Some(bob), &deposit));
+        assert!(!by_account.matches(Some(alice), &deposit));
+        assert!(by_account.matches(Some(alice), &bob_withdraw));
+        assert!(!by_account.matches(Some(bob), &primitive));
+    }
+}
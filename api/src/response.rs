@@ -514,7 +514,8 @@ generate_error_traits!(
     PayloadTooLarge,
     Internal,
     InsufficientStorage,
-    ServiceUnavailable
+    ServiceUnavailable,
+    TooManyRequests
 );
 
 // Group these common errors together
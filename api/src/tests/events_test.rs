@@ -2,9 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_config};
 use crate::tests::new_test_context_with_db_sharding_and_internal_indexer;
-use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_test_context::{current_function_name, ApiSpecificConfig, TestContext};
+use aptos_config::config::NodeConfig;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde_json::json;
 use std::path::PathBuf;
@@ -230,3 +231,155 @@ async fn test_get_events_by_struct_type_has_generic_type_parameter() {
     let resp = context.expect_status_code(404).get(path.as_str()).await;
     context.check_golden_output(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_events() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.event_stream_enabled = true;
+    node_config.api.event_stream_max_active_connections = 1;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let mut user = context.create_account().await;
+    let receiver = context.gen_account();
+    context
+        .api_execute_aptos_account_transfer(&mut user, receiver.address(), 1)
+        .await;
+    let resp = context
+        .get(format!("/accounts/{}/transactions", user.address()).as_str())
+        .await;
+    let user_txn = &resp.as_array().unwrap()[0];
+
+    // The stream never ends, so read it directly from the server rather than through the proxy.
+    let ApiSpecificConfig::V1(address) = &context.api_specific_config;
+    let mut resp = reqwest::get(format!(
+        "http://{}/v1/events/stream?start_version=0&account={}",
+        address,
+        user.address()
+    ))
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), 200);
+    let user_version: u64 = user_txn["version"].as_str().unwrap().parse().unwrap();
+    let mut buffer = String::new();
+    let message = 'outer: loop {
+        let chunk = resp.chunk().await.unwrap().expect("stream ended");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        // Messages are separated by blank lines, the last one might be incomplete.
+        while let Some((message, rest)) = buffer.split_once("\n\n") {
+            let data = message
+                .lines()
+                .find_map(|line| line.strip_prefix("data:"))
+                .map(|data| serde_json::from_str::<serde_json::Value>(data.trim()).unwrap());
+            buffer = rest.to_string();
+            if let Some(data) = data {
+                let version: u64 = data["version"].as_str().unwrap().parse().unwrap();
+                // Events emitted to the account when it was created come first.
+                if version >= user_version {
+                    break 'outer data;
+                }
+            }
+        }
+    };
+    assert_eq!(message["version"], user_txn["version"]);
+    assert_eq!(message["hash"], user_txn["hash"]);
+    assert_eq!(message["sender"], user_txn["sender"]);
+    assert!(!message["events"].as_array().unwrap().is_empty());
+
+    // Further streams are refused while the first one is active.
+    let refused = reqwest::get(format!("http://{}/v1/events/stream", address))
+        .await
+        .unwrap();
+    assert_eq!(refused.status(), 503);
+    let error: serde_json::Value = serde_json::from_str(&refused.text().await.unwrap()).unwrap();
+    assert_eq!(error["error_code"], "too_many_event_streams");
+
+    context
+        .expect_status_code(400)
+        .get("/events/stream?event_type=not_a_type")
+        .await;
+    context
+        .expect_status_code(400)
+        .get("/events/stream?module=0x1")
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type() {
+    let mut context =
+        new_test_context_with_db_sharding_and_internal_indexer(current_function_name!());
+    let mut user = context.create_account().await;
+    let user_addr = user.address();
+    let named_addresses = vec![("event".to_string(), user_addr)];
+    let txn = futures::executor::block_on(async move {
+        let path = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
+            .join("../aptos-move/move-examples/event");
+        TestContext::build_package(path, named_addresses)
+    });
+    context.publish_package(&mut user, txn).await;
+    for num in ["3", "2"] {
+        context
+            .api_execute_entry_function(
+                &mut user,
+                &format!("0x{}::event::emit", user_addr.to_hex()),
+                json!([]),
+                json!([num]),
+            )
+            .await;
+    }
+    let latest_version = context.get_latest_ledger_info().version();
+    context
+        .get_indexer_reader()
+        .unwrap()
+        .wait_for_internal_indexer(latest_version)
+        .unwrap();
+
+    let event_type = format!("{}::event::MyEvent", user_addr.to_hex_literal());
+    let resp = context
+        .get(&format!("/events/by_type/{}", event_type))
+        .await;
+    let seqs = |resp: &serde_json::Value| {
+        resp.as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                assert_eq!(e["type"], event_type);
+                e["data"]["seq"].as_str().unwrap().to_string()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(seqs(&resp), vec!["0", "1", "2", "0", "1"]);
+
+    // The page stops before the second transaction rather than splitting it.
+    let resp = context
+        .get(&format!("/events/by_type/{}?limit=4", event_type))
+        .await;
+    assert_eq!(seqs(&resp), vec!["0", "1", "2"]);
+    let next: u64 = resp[2]["version"].as_str().unwrap().parse().unwrap();
+    let resp = context
+        .get(&format!(
+            "/events/by_type/{}?start={}&limit=4",
+            event_type,
+            next + 1
+        ))
+        .await;
+    assert_eq!(seqs(&resp), vec!["0", "1"]);
+
+    // A transaction with more events than the limit is returned whole.
+    let resp = context
+        .get(&format!("/events/by_type/{}?limit=2", event_type))
+        .await;
+    assert_eq!(seqs(&resp), vec!["0", "1", "2"]);
+
+    context
+        .expect_status_code(400)
+        .get("/events/by_type/not_a_type")
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type_without_internal_indexer() {
+    let mut context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(403)
+        .get("/events/by_type/0x1::fungible_asset::Deposit")
+        .await;
+}
@@ -3,9 +3,13 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, pretty, TestContext};
+use aptos_api_types::HexEncodedBytes;
 use aptos_crypto::ed25519::Ed25519Signature;
+use aptos_framework::{BuildOptions, BuiltPackage};
+use aptos_sdk::types::LocalAccount;
 use aptos_types::{
     account_address::AccountAddress,
+    on_chain_config::FeatureFlag,
     transaction::{
         authenticator::{AccountAuthenticator, TransactionAuthenticator},
         EntryFunction, RawTransaction, SignedTransaction, TransactionPayload,
@@ -426,3 +430,177 @@ async fn test_bcs_execute_fee_payer_transaction_no_authenticator_fail() {
         .unwrap()
         .contains("INVALID_SIGNATURE"));
 }
+
+/// Builds a simulation request in JSON for `txn` with a dummy signature, replacing its payload
+fn simulation_request(txn: &SignedTransaction, payload: serde_json::Value) -> serde_json::Value {
+    let TransactionAuthenticator::Ed25519 { public_key, .. } = txn.authenticator_ref() else {
+        unreachable!("Simulation uses Ed25519 authenticator.");
+    };
+    json!({
+        "sender": txn.sender().to_string(),
+        "sequence_number": txn.sequence_number().to_string(),
+        "max_gas_amount": txn.max_gas_amount().to_string(),
+        "gas_unit_price": txn.gas_unit_price().to_string(),
+        "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+        "payload": payload,
+        "signature": {
+            "type": "ed25519_signature",
+            "public_key": public_key.to_string(),
+            "signature": Ed25519Signature::dummy_signature().to_string(),
+        }
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_at_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let version_before_alice = context.get_latest_ledger_info().version();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), SMALL_TRANSFER_AMOUNT);
+    let request = simulation_request(
+        &txn,
+        json!({
+            "type": "entry_function_payload",
+            "function": "0x1::aptos_account::transfer",
+            "type_arguments": [],
+            "arguments": [bob.address().to_standard_string(), SMALL_TRANSFER_AMOUNT.to_string()]
+        }),
+    );
+
+    let resp = context
+        .post("/transactions/simulate", request.clone())
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap());
+
+    // Alice's account didn't exist yet at that version.
+    let resp = context
+        .post(
+            &format!(
+                "/transactions/simulate?ledger_version={}",
+                version_before_alice
+            ),
+            request.clone(),
+        )
+        .await;
+    assert!(!resp[0]["success"].as_bool().unwrap());
+
+    let latest_version = context.get_latest_ledger_info().version();
+    context
+        .expect_status_code(404)
+        .post(
+            &format!(
+                "/transactions/simulate?ledger_version={}",
+                latest_version + 1000
+            ),
+            request,
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_module_override() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    // The module is never published, it only exists in the simulation.
+    let transaction = emit_event_simulation_request(&mut context, alice);
+    context
+        .expect_status_code(400)
+        .post("/transactions/simulate", transaction.clone())
+        .await;
+
+    let resp = context
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": event_module_overrides(alice.address()),
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap());
+    let event_type = format!("{}::event::MyEvent", alice.address().to_hex_literal());
+    let events = resp[0]["events"].as_array().unwrap();
+    assert_eq!(events.iter().filter(|e| e["type"] == event_type).count(), 3);
+
+    context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": [{"type": "module", "bytecode": "0x00"}],
+            }),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_module_override_without_loader_v2() {
+    let mut context = new_test_context(current_function_name!());
+    context
+        .disable_feature(FeatureFlag::ENABLE_LOADER_V2 as u64)
+        .await;
+    let alice = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    // Module overrides are rejected, as the VM may come from the warm VM cache
+    let transaction = emit_event_simulation_request(&mut context, alice);
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": event_module_overrides(alice.address()),
+            }),
+        )
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+/// Returns a simulation request calling `event::emit` from the event example package
+/// (published at `account`)
+fn emit_event_simulation_request(
+    context: &mut TestContext,
+    account: &mut LocalAccount,
+) -> serde_json::Value {
+    let txn = context.account_transfer_to(account, account.address(), SMALL_TRANSFER_AMOUNT);
+    simulation_request(
+        &txn,
+        json!({
+            "type": "entry_function_payload",
+            "function": format!("{}::event::emit", account.address().to_hex_literal()),
+            "type_arguments": [],
+            "arguments": ["3"]
+        }),
+    )
+}
+
+/// Returns state overrides for the modules of the event example package, built for `address`
+fn event_module_overrides(address: AccountAddress) -> Vec<serde_json::Value> {
+    let mut build_options = BuildOptions::default();
+    build_options
+        .named_addresses
+        .insert("event".to_string(), address);
+    let path =
+        PathBuf::from(std::env!("CARGO_MANIFEST_DIR")).join("../aptos-move/move-examples/event");
+    BuiltPackage::build(path, build_options)
+        .unwrap()
+        .extract_code()
+        .into_iter()
+        .map(|bytecode| {
+            json!({
+                "type": "module",
+                "bytecode": HexEncodedBytes::from(bytecode),
+            })
+        })
+        .collect()
+}
@@ -602,6 +602,68 @@ async fn test_get_pending_transaction_by_hash() {
     context.check_golden_output(not_found);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_mempool_status() {
+    let mut context = new_test_context(current_function_name!());
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let mut root_account = context.root_account().await;
+    let sequence_number = root_account.sequence_number();
+
+    // Skip a sequence number so the second transaction ends up in the parking lot
+    let ready_txn = context.create_user_account_by(&mut root_account, &account1);
+    root_account.increment_sequence_number();
+    let parked_txn = context.create_user_account_by(&mut root_account, &account2);
+    let mut pending_txns = vec![];
+    for txn in [&ready_txn, &parked_txn] {
+        pending_txns.push(
+            context
+                .expect_status_code(202)
+                .post_bcs_txn("/transactions", bcs::to_bytes(txn).unwrap())
+                .await,
+        );
+    }
+
+    let ready_status = context
+        .get(&format!(
+            "/transactions/by_hash/{}/mempool_status",
+            pending_txns[0]["hash"].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(ready_status["hash"], pending_txns[0]["hash"]);
+    assert_eq!(ready_status["sender"], pending_txns[0]["sender"]);
+    assert_eq!(ready_status["sequence_number"], sequence_number.to_string());
+    assert_eq!(ready_status["state"], "ready");
+    assert!(ready_status["blocking_sequence_number"].is_null());
+    assert_eq!(
+        ready_status["expiration_timestamp_secs"],
+        pending_txns[0]["expiration_timestamp_secs"]
+    );
+    assert!(ready_status["last_rejection_reason"].is_null());
+
+    let parked_status = context
+        .get(&format!(
+            "/transactions/by_hash/{}/mempool_status",
+            pending_txns[1]["hash"].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(parked_status["state"], "parking_lot");
+    assert_eq!(
+        parked_status["sequence_number"],
+        (sequence_number + 2).to_string()
+    );
+    assert_eq!(
+        parked_status["blocking_sequence_number"],
+        (sequence_number + 1).to_string()
+    );
+    assert!(parked_status["timeline_id"].is_null());
+
+    context
+        .expect_status_code(404)
+        .get("/transactions/by_hash/0xdadfeddcca7cb6396c735e9094c76c6e4e9cb3e3ef814730693aed59bd87b31d/mempool_status")
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_wait_transaction_by_hash() {
     let mut node_config = NodeConfig::default();
@@ -252,3 +252,59 @@ async fn test_view_aggregator() {
         .await;
     context.check_golden_output_no_prune(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+    context.commit_block(&vec![txn1, txn2]).await;
+    let version = context.get_latest_ledger_info().version();
+    let txn3 = context.account_transfer(creator, owner, 100_000);
+    context.commit_block(&vec![txn3]).await;
+
+    let requests = json!([
+        build_coin_balance_request(&owner.address()),
+        build_coin_decimals_request(),
+        {
+            "function": "0x1::coin::does_not_exist",
+            "arguments": [],
+            "type_arguments": [],
+        },
+    ]);
+    let resp = context
+        .post(
+            &format!("/view/batch?ledger_version={}", version),
+            requests.clone(),
+        )
+        .await;
+    assert_eq!(resp["ledger_version"], version.to_string());
+    let results = resp["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["values"], json!(["100000"]));
+    assert!(results[0]["error"].is_null());
+    assert_eq!(results[1]["values"], json!([8]));
+    assert!(results[2]["values"].is_null());
+    assert_eq!(results[2]["error"]["error_code"], "invalid_input");
+
+    // Without a version, all view functions are executed at the latest version.
+    let resp = context.post("/view/batch", requests).await;
+    assert_eq!(resp["results"][0]["values"], json!(["200000"]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_too_large() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_view_batch_size = 1;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+
+    context
+        .expect_status_code(400)
+        .post(
+            "/view/batch",
+            json!([build_coin_decimals_request(), build_coin_decimals_request()]),
+        )
+        .await;
+}
@@ -15,7 +15,7 @@ use crate::{
         api_disabled, api_forbidden, transaction_not_found_by_hash,
         transaction_not_found_by_version, version_pruned, BadRequestError, BasicError,
         BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
-        ForbiddenError, InsufficientStorageError, InternalError,
+        ForbiddenError, InsufficientStorageError, InternalError, TooManyRequestsError,
     },
     ApiTags,
 };
@@ -23,7 +23,8 @@ use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
     AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
+    HexEncodedBytes, LedgerInfo, MempoolTransactionState, MempoolTransactionStatus, MoveType,
+    PendingTransaction, SimulateTransactionRequest, StateOverride, SubmitTransactionRequest,
     Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion, U64,
 };
@@ -31,21 +32,30 @@ use aptos_crypto::{hash::CryptoHash, signing_message};
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
+    on_chain_config::{Features, OnChainConfig},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        StateView, StateViewResult, TStateView,
+    },
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
-        RawTransactionWithData, SignedTransaction, TransactionPayload,
+        RawTransactionWithData, SignedTransaction, TransactionPayload, Version,
     },
     vm_status::StatusCode,
     AptosCoinType, CoinType,
 };
 use aptos_vm::{AptosSimulationVM, AptosVM};
 use move_core_types::{ident_str, language_storage::ModuleId, vm_status::VMStatus};
+use poem::{http::HeaderMap, Request};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
     ApiRequest, OpenApi,
 };
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, net::IpAddr, sync::Arc, time::Duration};
+
+/// Header in which reverse proxies record the addresses they forwarded a request for
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
 
 generate_success_response!(SubmitTransactionResponse, (202, Accepted));
 
@@ -54,7 +64,9 @@ generate_error_response!(
     (400, BadRequest),
     (403, Forbidden),
     (404, NotFound),
+    (410, Gone),
     (413, PayloadTooLarge),
+    (429, TooManyRequests),
     (500, Internal),
     (503, ServiceUnavailable),
     (507, InsufficientStorage)
@@ -210,6 +222,89 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get mempool status of transaction by hash
+    ///
+    /// Look up where a pending transaction is in this node's mempool: whether it is ready for
+    /// the next block or waiting in the parking lot (and on which sequence number), when it
+    /// expires, and why consensus last rejected it.
+    ///
+    /// Transactions recently rejected by consensus are reported even after they have been
+    /// removed from mempool. Committed transactions are not found by this endpoint, use
+    /// /transactions/by_hash instead.
+    #[oai(
+        path = "/transactions/by_hash/:txn_hash/mempool_status",
+        method = "get",
+        operation_id = "get_transaction_mempool_status",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transaction_mempool_status(
+        &self,
+        accept_type: AcceptType,
+        /// Hash of transaction to retrieve the mempool status of
+        txn_hash: Path<HashValue>,
+    ) -> BasicResultWith404<MempoolTransactionStatus> {
+        fail_point_poem("endpoint_transaction_mempool_status")?;
+        self.context
+            .check_api_output_enabled("Get transaction mempool status", &accept_type)?;
+
+        let hash = txn_hash.0;
+        let context = self.context.clone();
+        let latest_ledger_info =
+            api_spawn_blocking(move || context.get_latest_ledger_info::<BasicErrorWith404>())
+                .await?;
+        let status = self
+            .context
+            .get_mempool_transaction_status(hash.into())
+            .await
+            .context(format!(
+                "Failed to get mempool status of transaction {}",
+                hash
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?
+            .ok_or_else(|| transaction_not_found_by_hash(hash, &latest_ledger_info))?;
+
+        let status = MempoolTransactionStatus {
+            hash,
+            sender: status.sender.into(),
+            sequence_number: status.sequence_number.into(),
+            state: match status.state {
+                aptos_mempool::MempoolTransactionState::Ready => MempoolTransactionState::Ready,
+                aptos_mempool::MempoolTransactionState::ParkingLot => {
+                    MempoolTransactionState::ParkingLot
+                },
+                aptos_mempool::MempoolTransactionState::Rejected => {
+                    MempoolTransactionState::Rejected
+                },
+            },
+            account_sequence_number: status.account_sequence_number.map(U64::from),
+            blocking_sequence_number: status.blocking_sequence_number.map(U64::from),
+            timeline_id: status.timeline_id.map(U64::from),
+            insertion_timestamp_usecs: status.insertion_timestamp_usecs.map(U64::from),
+            expiration_timestamp_secs: status.expiration_timestamp_secs.map(U64::from),
+            system_expiration_timestamp_secs: status
+                .system_expiration_timestamp_secs
+                .map(U64::from),
+            last_rejection_reason: status
+                .last_rejection_reason
+                .map(|reason| format!("{:?}", reason)),
+        };
+
+        match accept_type {
+            AcceptType::Json => {
+                BasicResponse::try_from_json((status, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((status, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Wait for transaction by hash
     ///
     /// Same as /transactions/by_hash, but will wait for a pending transaction to be committed. To be used as a long
@@ -371,6 +466,7 @@ impl TransactionsApi {
     async fn submit_transaction(
         &self,
         accept_type: AcceptType,
+        request: &Request,
         data: SubmitTransactionPost,
     ) -> SubmitTransactionResult<PendingTransaction> {
         data.verify()
@@ -389,8 +485,13 @@ impl TransactionsApi {
             .check_api_output_enabled("Submit transaction", &accept_type)?;
         let ledger_info = self.context.get_latest_ledger_info()?;
         let signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
-        self.create(&accept_type, &ledger_info, signed_transaction)
-            .await
+        self.create(
+            &accept_type,
+            &ledger_info,
+            signed_transaction,
+            client_ip(request, self.context.trusted_proxies()),
+        )
+        .await
     }
 
     /// Submit batch transactions
@@ -424,6 +525,7 @@ impl TransactionsApi {
     async fn submit_transactions_batch(
         &self,
         accept_type: AcceptType,
+        request: &Request,
         data: SubmitTransactionsBatchPost,
     ) -> SubmitTransactionsBatchResult<TransactionsBatchSubmissionResult> {
         data.verify()
@@ -453,8 +555,13 @@ impl TransactionsApi {
                 &ledger_info,
             ));
         }
-        self.create_batch(&accept_type, &ledger_info, signed_transactions_batch)
-            .await
+        self.create_batch(
+            &accept_type,
+            &ledger_info,
+            signed_transactions_batch,
+            client_ip(request, self.context.trusted_proxies()),
+        )
+        .await
     }
 
     /// Simulate transaction
@@ -488,6 +595,10 @@ impl TransactionsApi {
         /// If set to true, the transaction will use a higher price than the original
         /// estimate.
         estimate_prioritized_gas_unit_price: Query<Option<bool>>,
+        /// Ledger version to simulate the transaction at
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
         data: SubmitTransactionPost,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         data.verify()
@@ -505,119 +616,89 @@ impl TransactionsApi {
         self.context
             .check_api_output_enabled("Simulate transaction", &accept_type)?;
 
-        let api = self.clone();
-        let context = self.context.clone();
-        api_spawn_blocking(move || {
-            let ledger_info = context.get_latest_ledger_info()?;
-            let mut signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
+        self.simulate_with_options(
+            accept_type,
+            SimulationOptions {
+                estimate_max_gas_amount: estimate_max_gas_amount.0.unwrap_or_default(),
+                estimate_gas_unit_price: estimate_gas_unit_price.0.unwrap_or_default(),
+                estimate_prioritized_gas_unit_price: estimate_prioritized_gas_unit_price
+                    .0
+                    .unwrap_or_default(),
+                ledger_version: ledger_version.0.map(|v| v.0),
+            },
+            data,
+            vec![],
+        )
+        .await
+    }
 
-            // Confirm the simulation filter allows the transaction. We use HashValue::zero()
-            // here for the block ID because we don't allow filtering by block ID for the
-            // simulation filters. See the ConfigSanitizer for ApiConfig.
-            if !context.node_config.api.simulation_filter.allows(
-                aptos_crypto::HashValue::zero(),
-                ledger_info.timestamp(),
-                &signed_transaction,
-            ) {
-                return Err(SubmitTransactionError::forbidden_with_code(
-                    "Transaction not allowed by simulation filter",
+    /// Simulate transaction with state overrides
+    ///
+    /// Same as simulating a transaction, except the given resources and modules are
+    /// substituted for the state the transaction is simulated against. This can be used to
+    /// see how a transaction would behave after a hypothetical module upgrade, or combined
+    /// with `ledger_version` to reproduce how it would have behaved in the past.
+    ///
+    /// The transaction must be signed with a zero-padded signature, as for simulating a
+    /// transaction. Resources are given BCS-encoded, and modules as their bytecode.
+    /// Module overrides are only supported if the loader v2 feature is enabled.
+    #[oai(
+        path = "/transactions/simulate_with_overrides",
+        method = "post",
+        operation_id = "simulate_transaction_with_overrides",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_with_overrides(
+        &self,
+        accept_type: AcceptType,
+        /// If set to true, the max gas value in the transaction will be ignored
+        /// and the maximum possible gas will be used
+        estimate_max_gas_amount: Query<Option<bool>>,
+        /// If set to true, the gas unit price in the transaction will be ignored
+        /// and the estimated value will be used
+        estimate_gas_unit_price: Query<Option<bool>>,
+        /// If set to true, the transaction will use a higher price than the original
+        /// estimate.
+        estimate_prioritized_gas_unit_price: Query<Option<bool>>,
+        /// Ledger version to simulate the transaction at
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+        data: Json<SimulateTransactionRequest>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.0
+            .verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
                     AptosErrorCode::InvalidInput,
-                    &ledger_info,
-                ));
-            }
-
-            let estimated_gas_unit_price = match (
-                estimate_gas_unit_price.0.unwrap_or_default(),
-                estimate_prioritized_gas_unit_price.0.unwrap_or_default(),
-            ) {
-                (_, true) => {
-                    let gas_estimation = context.estimate_gas_price(&ledger_info)?;
-                    // The prioritized gas estimate should always be set, but if it's not use the gas estimate
-                    Some(
-                        gas_estimation
-                            .prioritized_gas_estimate
-                            .unwrap_or(gas_estimation.gas_estimate),
-                    )
-                },
-                (true, false) => Some(context.estimate_gas_price(&ledger_info)?.gas_estimate),
-                (false, false) => None,
-            };
-
-            // If estimate max gas amount is provided, we will just make it the maximum value
-            let estimated_max_gas_amount = if estimate_max_gas_amount.0.unwrap_or_default() {
-                // Retrieve max possible gas units
-                let (_, gas_params) = context.get_gas_schedule(&ledger_info)?;
-                let min_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.min_transaction_gas_units)
-                        / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
-                let max_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
-
-                // Retrieve account balance to determine max gas available, right now this is using
-                // a view function, but we may want to re-evaluate this based on performance
-                let (_, _, state_view) = context
-                    .state_view::<BasicErrorWith404>(Option::None)
-                    .map_err(|err| {
-                        SubmitTransactionError::bad_request_with_code_no_info(
-                            err,
-                            AptosErrorCode::InvalidInput,
-                        )
-                    })?;
-                let output = AptosVM::execute_view_function(
-                    &state_view,
-                    ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
-                    ident_str!("balance").into(),
-                    vec![AptosCoinType::type_tag()],
-                    vec![signed_transaction.sender().to_vec()],
-                    context.node_config.api.max_gas_view_function,
-                );
-                let values = output.values.map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                    )
-                })?;
-                let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                    )
-                })?;
-
-                let gas_unit_price =
-                    estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
-
-                // With 0 gas price, we set it to max gas units, since we can't divide by 0
-                let max_account_gas_units = if gas_unit_price == 0 {
-                    balance
-                } else {
-                    balance / gas_unit_price
-                };
-
-                // To give better error messaging, we should not go below the minimum number of gas units
-                let max_account_gas_units =
-                    std::cmp::max(min_number_of_gas_units, max_account_gas_units);
-
-                // Minimum of the max account and the max total needs to be used for estimation
-                Some(std::cmp::min(
-                    max_account_gas_units,
-                    max_number_of_gas_units,
-                ))
-            } else {
-                None
-            };
-
-            // If there is an estimation of either, replace the values
-            if estimated_max_gas_amount.is_some() || estimated_gas_unit_price.is_some() {
-                signed_transaction = override_gas_parameters(
-                    &signed_transaction,
-                    estimated_max_gas_amount,
-                    estimated_gas_unit_price,
-                );
-            }
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_overrides")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction", &accept_type)?;
+        let SimulateTransactionRequest {
+            transaction,
+            state_overrides,
+        } = data.0;
 
-            api.simulate(&accept_type, ledger_info, signed_transaction)
-        })
+        self.simulate_with_options(
+            accept_type,
+            SimulationOptions {
+                estimate_max_gas_amount: estimate_max_gas_amount.0.unwrap_or_default(),
+                estimate_gas_unit_price: estimate_gas_unit_price.0.unwrap_or_default(),
+                estimate_prioritized_gas_unit_price: estimate_prioritized_gas_unit_price
+                    .0
+                    .unwrap_or_default(),
+                ledger_version: ledger_version.0.map(|v| v.0),
+            },
+            SubmitTransactionPost::Json(Json(transaction)),
+            state_overrides,
+        )
         .await
     }
 
@@ -740,6 +821,132 @@ impl TransactionsApi {
 }
 
 impl TransactionsApi {
+    /// Simulate a transaction with the given options, over the state at the requested ledger
+    /// version patched with `state_overrides`
+    async fn simulate_with_options(
+        &self,
+        accept_type: AcceptType,
+        options: SimulationOptions,
+        data: SubmitTransactionPost,
+        state_overrides: Vec<StateOverride>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let api = self.clone();
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            let (ledger_info, version, state_view) =
+                context.state_view::<SubmitTransactionError>(options.ledger_version)?;
+            let state_view = OverriddenStateView::new(&state_view, &ledger_info, state_overrides)?;
+            let mut signed_transaction =
+                api.get_signed_transaction_with_state_view(&ledger_info, &state_view, data)?;
+
+            // Confirm the simulation filter allows the transaction. We use HashValue::zero()
+            // here for the block ID because we don't allow filtering by block ID for the
+            // simulation filters. See the ConfigSanitizer for ApiConfig.
+            if !context.node_config.api.simulation_filter.allows(
+                aptos_crypto::HashValue::zero(),
+                ledger_info.timestamp(),
+                &signed_transaction,
+            ) {
+                return Err(SubmitTransactionError::forbidden_with_code(
+                    "Transaction not allowed by simulation filter",
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+
+            let estimated_gas_unit_price = match (
+                options.estimate_gas_unit_price,
+                options.estimate_prioritized_gas_unit_price,
+            ) {
+                (_, true) => {
+                    let gas_estimation = context.estimate_gas_price(&ledger_info)?;
+                    // The prioritized gas estimate should always be set, but if it's not use the gas estimate
+                    Some(
+                        gas_estimation
+                            .prioritized_gas_estimate
+                            .unwrap_or(gas_estimation.gas_estimate),
+                    )
+                },
+                (true, false) => Some(context.estimate_gas_price(&ledger_info)?.gas_estimate),
+                (false, false) => None,
+            };
+
+            // If estimate max gas amount is provided, we will just make it the maximum value
+            let estimated_max_gas_amount = if options.estimate_max_gas_amount {
+                // Retrieve max possible gas units
+                let (_, gas_params) = context.get_gas_schedule(&ledger_info)?;
+                let min_number_of_gas_units =
+                    u64::from(gas_params.vm.txn.min_transaction_gas_units)
+                        / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
+                let max_number_of_gas_units =
+                    u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
+
+                // Retrieve account balance to determine max gas available, right now this is using
+                // a view function, but we may want to re-evaluate this based on performance
+                let output = AptosVM::execute_view_function(
+                    &state_view,
+                    ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
+                    ident_str!("balance").into(),
+                    vec![AptosCoinType::type_tag()],
+                    vec![signed_transaction.sender().to_vec()],
+                    context.node_config.api.max_gas_view_function,
+                );
+                let values = output.values.map_err(|err| {
+                    SubmitTransactionError::bad_request_with_code_no_info(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )
+                })?;
+                let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
+                    SubmitTransactionError::bad_request_with_code_no_info(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )
+                })?;
+
+                let gas_unit_price =
+                    estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
+
+                // With 0 gas price, we set it to max gas units, since we can't divide by 0
+                let max_account_gas_units = if gas_unit_price == 0 {
+                    balance
+                } else {
+                    balance / gas_unit_price
+                };
+
+                // To give better error messaging, we should not go below the minimum number of gas units
+                let max_account_gas_units =
+                    std::cmp::max(min_number_of_gas_units, max_account_gas_units);
+
+                // Minimum of the max account and the max total needs to be used for estimation
+                Some(std::cmp::min(
+                    max_account_gas_units,
+                    max_number_of_gas_units,
+                ))
+            } else {
+                None
+            };
+
+            // If there is an estimation of either, replace the values
+            if estimated_max_gas_amount.is_some() || estimated_gas_unit_price.is_some() {
+                signed_transaction = override_gas_parameters(
+                    &signed_transaction,
+                    estimated_max_gas_amount,
+                    estimated_gas_unit_price,
+                );
+            }
+
+            api.simulate(
+                &accept_type,
+                ledger_info,
+                version,
+                &state_view,
+                signed_transaction,
+            )
+        })
+        .await
+    }
+
     /// List all transactions paging by ledger version
     fn list(&self, accept_type: &AcceptType, page: Page) -> BasicResultWith404<Vec<Transaction>> {
         let latest_ledger_info = self.context.get_latest_ledger_info()?;
@@ -1039,6 +1246,17 @@ impl TransactionsApi {
         &self,
         ledger_info: &LedgerInfo,
         data: SubmitTransactionPost,
+    ) -> Result<SignedTransaction, SubmitTransactionError> {
+        let state_view = self.context.latest_state_view_poem(ledger_info)?;
+        self.get_signed_transaction_with_state_view(ledger_info, &state_view, data)
+    }
+
+    /// Parses a signed transaction, resolving the types of a JSON payload from `state_view`
+    fn get_signed_transaction_with_state_view(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        data: SubmitTransactionPost,
     ) -> Result<SignedTransaction, SubmitTransactionError> {
         pub const MAX_SIGNED_TRANSACTION_DEPTH: usize = 16;
 
@@ -1111,9 +1329,7 @@ impl TransactionsApi {
 
                 Ok(signed_transaction)
             },
-            SubmitTransactionPost::Json(data) => self
-                .context
-                .latest_state_view_poem(ledger_info)?
+            SubmitTransactionPost::Json(data) => state_view
                 .as_converter(self.context.db.clone(), self.context.indexer_reader.clone())
                 .try_into_signed_transaction_poem(data.0, self.context.chain_id())
                 .context("Failed to create SignedTransaction from SubmitTransactionRequest")
@@ -1208,10 +1424,14 @@ impl TransactionsApi {
     }
 
     /// Submits a single transaction, and converts mempool codes to errors
-    async fn create_internal(&self, txn: SignedTransaction) -> Result<(), AptosError> {
+    async fn create_internal(
+        &self,
+        txn: SignedTransaction,
+        client_ip: Option<IpAddr>,
+    ) -> Result<(), AptosError> {
         let (mempool_status, vm_status_opt) = self
             .context
-            .submit_transaction(txn)
+            .submit_transaction(txn, client_ip)
             .await
             .context("Mempool failed to initially evaluate submitted transaction")
             .map_err(|err| {
@@ -1252,6 +1472,20 @@ impl TransactionsApi {
                 mempool_status.message,
                 AptosErrorCode::InvalidTransactionUpdate,
             )),
+            MempoolStatusCode::SenderQuotaExceeded => Err(AptosError::new_with_error_code(
+                mempool_status.message,
+                AptosErrorCode::SenderQuotaExceeded,
+            )),
+            MempoolStatusCode::RejectedByFilter => Err(AptosError::new_with_error_code(
+                "Transaction was denied by the node's transaction filter",
+                AptosErrorCode::TransactionFiltered,
+            )),
+            MempoolStatusCode::ClientRateLimited | MempoolStatusCode::PeerRateLimited => {
+                Err(AptosError::new_with_error_code(
+                    mempool_status.message,
+                    AptosErrorCode::RateLimited,
+                ))
+            },
             MempoolStatusCode::UnknownStatus => Err(AptosError::new_with_error_code(
                 format!("Transaction was rejected with status {}", mempool_status,),
                 AptosErrorCode::InternalError,
@@ -1265,8 +1499,9 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         ledger_info: &LedgerInfo,
         txn: SignedTransaction,
+        client_ip: Option<IpAddr>,
     ) -> SubmitTransactionResult<PendingTransaction> {
-        match self.create_internal(txn.clone()).await {
+        match self.create_internal(txn.clone(), client_ip).await {
             Ok(()) => match accept_type {
                 AcceptType::Json => {
                     let state_view = self
@@ -1321,6 +1556,12 @@ impl TransactionsApi {
                         ledger_info,
                     ),
                 ),
+                AptosErrorCode::TransactionFiltered => Err(
+                    SubmitTransactionError::forbidden_from_aptos_error(error, ledger_info),
+                ),
+                AptosErrorCode::SenderQuotaExceeded | AptosErrorCode::RateLimited => Err(
+                    SubmitTransactionError::too_many_requests_from_aptos_error(error, ledger_info),
+                ),
                 _ => Err(SubmitTransactionError::internal_from_aptos_error(
                     error,
                     ledger_info,
@@ -1335,11 +1576,12 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         ledger_info: &LedgerInfo,
         txns: Vec<SignedTransaction>,
+        client_ip: Option<IpAddr>,
     ) -> SubmitTransactionsBatchResult<TransactionsBatchSubmissionResult> {
         // Iterate through transactions keeping track of failures
         let mut txn_failures = Vec::new();
         for (idx, txn) in txns.iter().enumerate() {
-            if let Err(error) = self.create_internal(txn.clone()).await {
+            if let Err(error) = self.create_internal(txn.clone(), client_ip).await {
                 txn_failures.push(TransactionsBatchSingleSubmissionFailure {
                     error,
                     transaction_index: idx,
@@ -1376,6 +1618,8 @@ impl TransactionsApi {
         &self,
         accept_type: &AcceptType,
         ledger_info: LedgerInfo,
+        version: Version,
+        state_view: &impl StateView,
         txn: SignedTransaction,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         // The caller must ensure that the signature is not valid, as otherwise
@@ -1389,10 +1633,8 @@ impl TransactionsApi {
         }
 
         // Simulate transaction
-        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
         let (vm_status, output) =
-            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
-        let version = ledger_info.version();
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, state_view);
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
         let exe_status = ExecutionStatus::conmbine_vm_status_for_simulation(
@@ -1456,9 +1698,11 @@ impl TransactionsApi {
 
         let result = match accept_type {
             AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
+                let transactions = self.context.render_transactions_with_state_view(
+                    &ledger_info,
+                    state_view,
+                    vec![simulated_txn],
+                )?;
 
                 // Users can only make requests to simulate UserTransactions, so unpack
                 // the Vec<Transaction> into Vec<UserTransaction>.
@@ -1583,8 +1827,171 @@ fn override_gas_parameters(
     SignedTransaction::new_signed_transaction(raw_txn, signed_txn.authenticator())
 }
 
+/// Returns the IP address of the client that made the request, if it's known
+fn client_ip(request: &Request, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let remote_ip = request.remote_addr().as_socket_addr()?.ip();
+    Some(forwarded_client_ip(
+        remote_ip,
+        request.headers(),
+        trusted_proxies,
+    ))
+}
+
+/// Returns the IP address of the client for a request received from `remote_ip`. For requests
+/// forwarded by trusted proxies, this is the rightmost address in X-Forwarded-For that isn't a
+/// trusted proxy, as each proxy appends the address it received the request from, and anything
+/// further left may have been set by the client itself.
+fn forwarded_client_ip(
+    remote_ip: IpAddr,
+    headers: &HeaderMap,
+    trusted_proxies: &[IpAddr],
+) -> IpAddr {
+    let mut client_ip = remote_ip;
+    if !trusted_proxies.contains(&client_ip) {
+        return client_ip;
+    }
+    let forwarded_ips = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .flat_map(|value| value.to_str().unwrap_or_default().split(','))
+        .map(|ip| ip.trim().parse::<IpAddr>())
+        .collect::<Vec<_>>();
+    for forwarded_ip in forwarded_ips.into_iter().rev() {
+        match forwarded_ip {
+            Ok(ip) => client_ip = ip,
+            // The hops before a malformed address can't be trusted
+            Err(_) => break,
+        }
+        if !trusted_proxies.contains(&client_ip) {
+            break;
+        }
+    }
+    client_ip
+}
+
+/// Options of a transaction simulation, other than the transaction itself
+struct SimulationOptions {
+    estimate_max_gas_amount: bool,
+    estimate_gas_unit_price: bool,
+    estimate_prioritized_gas_unit_price: bool,
+    ledger_version: Option<Version>,
+}
+
+/// A state view with some state values substituted, for simulating transactions over a
+/// hypothetical state.
+struct OverriddenStateView<'a, S> {
+    base: &'a S,
+    overrides: HashMap<StateKey, Option<StateValue>>,
+}
+
+impl<'a, S: StateView> OverriddenStateView<'a, S> {
+    fn new(
+        base: &'a S,
+        ledger_info: &LedgerInfo,
+        state_overrides: Vec<StateOverride>,
+    ) -> Result<Self, SubmitTransactionError> {
+        let overrides = state_overrides
+            .iter()
+            .map(StateOverride::try_into_state_write)
+            .collect::<anyhow::Result<_>>()
+            .context("Invalid state override")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    ledger_info,
+                )
+            })?;
+        let state_view = Self { base, overrides };
+
+        // Without loader v2, the VM may come from the process-wide warm VM cache, which would
+        // ignore overridden modules that are already cached (and cache the overridden ones).
+        let has_module_overrides = state_overrides
+            .iter()
+            .any(|state_override| matches!(state_override, StateOverride::Module(_)));
+        if has_module_overrides
+            && !Features::fetch_config(&state_view)
+                .unwrap_or_default()
+                .is_loader_v2_enabled()
+        {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Module overrides are only supported with loader v2 enabled",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+        Ok(state_view)
+    }
+}
+
+impl<'a, S: StateView> TStateView for OverriddenStateView<'a, S> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        match self.overrides.get(state_key) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        self.base.get_usage()
+    }
+}
+
 enum GetByVersionResponse {
     VersionTooNew,
     VersionTooOld,
     Found(TransactionData),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poem::http::HeaderValue;
+
+    #[test]
+    fn test_forwarded_client_ip() {
+        let client: IpAddr = "1.1.1.1".parse().unwrap();
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let inner_proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let trusted_proxies = [proxy, inner_proxy];
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("2.2.2.2, 1.1.1.1"),
+        );
+        headers.append(X_FORWARDED_FOR, HeaderValue::from_static("10.0.0.2"));
+
+        // The header is ignored for requests that don't come from a trusted proxy
+        assert_eq!(
+            forwarded_client_ip(client, &headers, &trusted_proxies),
+            client
+        );
+        assert_eq!(forwarded_client_ip(proxy, &headers, &[]), proxy);
+
+        // The client is the rightmost untrusted address, not the one it claims to be (2.2.2.2)
+        assert_eq!(
+            forwarded_client_ip(proxy, &headers, &trusted_proxies),
+            client
+        );
+
+        // Without the header, the proxy itself is the client
+        assert_eq!(
+            forwarded_client_ip(proxy, &HeaderMap::new(), &trusted_proxies),
+            proxy
+        );
+
+        // Addresses before a malformed one aren't trusted
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("1.1.1.1, garbage, 10.0.0.2"),
+        );
+        assert_eq!(
+            forwarded_client_ip(proxy, &headers, &trusted_proxies),
+            inner_proxy
+        );
+    }
+}
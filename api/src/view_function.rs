@@ -12,12 +12,13 @@ use crate::{
     },
     ApiTags, Context,
 };
-use anyhow::Context as anyhowContext;
+use anyhow::{bail, Context as anyhowContext};
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED,
-    U64,
+    AptosError, AptosErrorCode, AsConverter, MoveValue, ViewBatchResponse, ViewFunction,
+    ViewFunctionResult, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_bcs_utils::serialize_uleb128;
+use aptos_storage_interface::state_store::state_view::db_state_view::DbStateView;
 use aptos_vm::AptosVM;
 use itertools::Itertools;
 use move_core_types::language_storage::TypeTag;
@@ -71,6 +72,51 @@ impl ViewFunctionApi {
         api_spawn_blocking(move || view_request(context, accept_type, request, ledger_version))
             .await
     }
+
+    /// Execute a batch of view functions
+    ///
+    /// Execute the Move view functions against the state at a single ledger version, so their
+    /// results are consistent with each other. The version used is returned along with the
+    /// results.
+    ///
+    /// Each function is executed separately, with the same gas limit as a single view
+    /// function. The result or error of each function is returned in the order of the
+    /// requests, and a failing function doesn't fail the others.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/batch",
+        method = "post",
+        operation_id = "view_batch",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_batch(
+        &self,
+        accept_type: AcceptType,
+        /// View function requests with type and position arguments
+        requests: Json<Vec<ViewRequest>>,
+        /// Ledger version to get state of accounts
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<ViewBatchResponse> {
+        fail_point_poem("endpoint_view_function_batch")?;
+        if accept_type == AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                "BCS is not supported for batch view functions",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Batch view function", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            view_batch_request(context, requests.0, ledger_version.0.map(|v| v.0))
+        })
+        .await
+    }
 }
 
 fn view_request(
@@ -118,19 +164,9 @@ fn view_request(
     };
 
     // Reject the request if it's not allowed by the filter.
-    if !context.node_config.api.view_filter.allows(
-        view_function.module.address(),
-        view_function.module.name().as_str(),
-        view_function.function.as_str(),
-    ) {
-        return Err(BasicErrorWith404::forbidden_with_code_no_info(
-            format!(
-                "Function {}::{} is not allowed",
-                view_function.module, view_function.function
-            ),
-            AptosErrorCode::InvalidInput,
-        ));
-    }
+    check_view_filter(&context, &view_function).map_err(|err| {
+        BasicErrorWith404::forbidden_with_code_no_info(err, AptosErrorCode::InvalidInput)
+    })?;
 
     let output = AptosVM::execute_view_function(
         &state_view,
@@ -166,31 +202,7 @@ fn view_request(
             BasicResponse::try_from_encoded((ret, &ledger_info, BasicResponseStatus::Ok))
         },
         AcceptType::Json => {
-            let return_types = state_view
-                .as_converter(context.db.clone(), context.indexer_reader.clone())
-                .function_return_types(&view_function)
-                .and_then(|tys| {
-                    tys.into_iter()
-                        .map(TypeTag::try_from)
-                        .collect::<anyhow::Result<Vec<_>>>()
-                })
-                .map_err(|err| {
-                    BasicErrorWith404::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
-            let move_vals = values
-                .into_iter()
-                .zip(return_types.into_iter())
-                .map(|(v, ty)| {
-                    state_view
-                        .as_converter(context.db.clone(), context.indexer_reader.clone())
-                        .try_into_move_value(&ty, &v)
-                })
-                .collect::<anyhow::Result<Vec<_>>>()
+            let move_vals = convert_return_values(&context, &state_view, &view_function, values)
                 .map_err(|err| {
                     BasicErrorWith404::bad_request_with_code(
                         err,
@@ -208,3 +220,140 @@ fn view_request(
     );
     result.map(|r| r.with_gas_used(Some(output.gas_used)))
 }
+
+fn view_batch_request(
+    context: Arc<Context>,
+    requests: Vec<ViewRequest>,
+    ledger_version: Option<u64>,
+) -> BasicResultWith404<ViewBatchResponse> {
+    // Retrieve the state of the chain all the view functions are executed against
+    let (ledger_info, requested_version) =
+        context.get_latest_ledger_info_and_verify_lookup_version(ledger_version)?;
+
+    if requests.len() > context.max_view_batch_size() {
+        return Err(BasicErrorWith404::bad_request_with_code(
+            format!(
+                "Requested too many view functions: {}, while limit is {}",
+                requests.len(),
+                context.max_view_batch_size(),
+            ),
+            AptosErrorCode::InvalidInput,
+            &ledger_info,
+        ));
+    }
+
+    let state_view = context
+        .state_view_at_version(requested_version)
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+
+    let mut total_gas_used = 0;
+    let results = requests
+        .into_iter()
+        .map(|request| {
+            let view_function = match state_view
+                .as_converter(context.db.clone(), context.indexer_reader.clone())
+                .convert_view_function(request)
+                .and_then(|view_function| {
+                    check_view_filter(&context, &view_function)?;
+                    Ok(view_function)
+                }) {
+                Ok(view_function) => view_function,
+                Err(err) => {
+                    return ViewFunctionResult {
+                        values: None,
+                        error: Some(AptosError::new_with_error_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                        )),
+                        gas_used: 0.into(),
+                    }
+                },
+            };
+
+            let output = AptosVM::execute_view_function(
+                &state_view,
+                view_function.module.clone(),
+                view_function.function.clone(),
+                view_function.ty_args.clone(),
+                view_function.args.clone(),
+                context.node_config.api.max_gas_view_function,
+            );
+            context.view_function_stats().increment(
+                FunctionStats::function_to_key(&view_function.module, &view_function.function),
+                output.gas_used,
+            );
+            total_gas_used += output.gas_used;
+
+            let (values, error) = match output.values.and_then(|values| {
+                convert_return_values(&context, &state_view, &view_function, values)
+            }) {
+                Ok(values) => (Some(values), None),
+                Err(err) => (
+                    None,
+                    Some(AptosError::new_with_error_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                    )),
+                ),
+            };
+            ViewFunctionResult {
+                values,
+                error,
+                gas_used: output.gas_used.into(),
+            }
+        })
+        .collect();
+
+    BasicResponse::try_from_json((
+        ViewBatchResponse {
+            ledger_version: requested_version.into(),
+            results,
+        },
+        &ledger_info,
+        BasicResponseStatus::Ok,
+    ))
+    .map(|r| r.with_gas_used(Some(total_gas_used)))
+}
+
+/// Returns an error if the view function is not allowed by the view filter
+fn check_view_filter(context: &Context, view_function: &ViewFunction) -> anyhow::Result<()> {
+    if !context.node_config.api.view_filter.allows(
+        view_function.module.address(),
+        view_function.module.name().as_str(),
+        view_function.function.as_str(),
+    ) {
+        bail!(
+            "Function {}::{} is not allowed",
+            view_function.module,
+            view_function.function
+        );
+    }
+    Ok(())
+}
+
+/// Converts the BCS encoded return values of a view function to JSON
+fn convert_return_values(
+    context: &Context,
+    state_view: &DbStateView,
+    view_function: &ViewFunction,
+    values: Vec<Vec<u8>>,
+) -> anyhow::Result<Vec<MoveValue>> {
+    let converter = state_view.as_converter(context.db.clone(), context.indexer_reader.clone());
+    let return_types = converter
+        .function_return_types(view_function)?
+        .into_iter()
+        .map(TypeTag::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    values
+        .into_iter()
+        .zip(return_types)
+        .map(|(v, ty)| converter.try_into_move_value(&ty, &v))
+        .collect()
+}
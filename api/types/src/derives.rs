@@ -296,6 +296,7 @@ impl_poem_parameter!(
     HashValue,
     IdentifierWrapper,
     HexEncodedBytes,
+    MoveModuleId,
     MoveStructTag,
     StateKeyWrapper,
     U64,
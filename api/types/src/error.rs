@@ -96,11 +96,19 @@ pub enum AptosErrorCode {
     SequenceNumberTooOld = 402,
     /// The submitted transaction failed VM checks.
     VmError = 403,
+    /// The sender has too many pending transactions in mempool.
+    SenderQuotaExceeded = 404,
+    /// The transaction was denied by the node's transaction filter.
+    TransactionFiltered = 405,
 
     /// Health check failed.
     HealthCheckFailed = 500,
     /// The mempool is full, no new transactions can be submitted.
     MempoolIsFull = 501,
+    /// Too many transactions were submitted from the client, retry later.
+    RateLimited = 502,
+    /// Too many event streams are active on the node, retry later.
+    TooManyEventStreams = 503,
 
     /// Internal server error
     InternalError = 600,
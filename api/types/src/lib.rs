@@ -15,6 +15,7 @@ mod hash;
 mod headers;
 mod index;
 mod ledger_info;
+mod mempool;
 pub mod mime_types;
 mod move_types;
 mod state;
@@ -33,6 +34,7 @@ pub use hash::HashValue;
 pub use headers::*;
 pub use index::{IndexResponse, IndexResponseBcs};
 pub use ledger_info::LedgerInfo;
+pub use mempool::{MempoolTransactionState, MempoolTransactionStatus};
 pub use move_types::{
     verify_field_identifier, verify_function_identifier, verify_module_identifier, EntryFunctionId,
     HexEncodedBytes, MoveAbility, MoveFunction, MoveFunctionGenericTypeParam,
@@ -48,16 +50,17 @@ pub use transaction::{
     AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
     DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
     FeePayerSignature, GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction,
-    MultiAgentSignature, MultiEd25519Signature, MultiKeySignature, MultisigPayload,
-    MultisigTransactionPayload, NoAccountSignature, PendingTransaction, PublicKey, ScriptPayload,
-    ScriptWriteSet, Signature, SingleKeySignature, SubmitTransactionRequest, Transaction,
-    TransactionData, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
+    ModuleOverride, MultiAgentSignature, MultiEd25519Signature, MultiKeySignature, MultisigPayload,
+    MultisigTransactionPayload, NoAccountSignature, PendingTransaction, PublicKey,
+    ResourceOverride, ScriptPayload, ScriptWriteSet, Signature, SimulateTransactionRequest,
+    SingleKeySignature, StateOverride, SubmitTransactionRequest, Transaction, TransactionData,
+    TransactionEvents, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
     TransactionSignature, TransactionSigningMessage, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserCreateSigningMessageRequest, UserTransaction,
     UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
     WriteSetPayload, WriteTableItem,
 };
-pub use view::{ViewFunction, ViewRequest};
+pub use view::{ViewBatchResponse, ViewFunction, ViewFunctionResult, ViewRequest};
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
 
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
@@ -0,0 +1,47 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, HashValue, U64};
+use poem_openapi::{Enum, Object};
+use serde::{Deserialize, Serialize};
+
+/// Where a transaction is in its mempool lifecycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum MempoolTransactionState {
+    /// The transaction can be included in the next block
+    Ready,
+    /// The transaction is waiting in the parking lot for an earlier sequence number
+    ParkingLot,
+    /// The transaction was rejected by consensus and removed from mempool
+    Rejected,
+}
+
+/// The mempool status of a transaction, as seen by the node serving the request
+///
+/// - `account_sequence_number`: The sequence number of the sender's account as last known
+///   by mempool.
+/// - `blocking_sequence_number`: For parked transactions, the first sequence number missing
+///   from mempool that blocks the transaction from becoming ready.
+/// - `timeline_id`: The position of the transaction in the broadcast timeline, if it's ready
+///   for broadcast.
+/// - `insertion_timestamp_usecs`: When the transaction was inserted into mempool.
+/// - `expiration_timestamp_secs`: The client specified expiration time.
+/// - `system_expiration_timestamp_secs`: When mempool removes the transaction regardless of
+///   its client specified expiration.
+/// - `last_rejection_reason`: The VM status consensus last rejected the transaction with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MempoolTransactionStatus {
+    pub hash: HashValue,
+    pub sender: Address,
+    pub sequence_number: U64,
+    pub state: MempoolTransactionState,
+    pub account_sequence_number: Option<U64>,
+    pub blocking_sequence_number: Option<U64>,
+    pub timeline_id: Option<U64>,
+    pub insertion_timestamp_usecs: Option<U64>,
+    pub expiration_timestamp_secs: Option<U64>,
+    pub system_expiration_timestamp_secs: Option<U64>,
+    pub last_rejection_reason: Option<String>,
+}
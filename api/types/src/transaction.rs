@@ -24,6 +24,7 @@ use aptos_types::{
     dkg::{DKGTranscript, DKGTranscriptMetadata},
     jwks::{jwk::JWK, ProviderJWKs, QuorumCertifiedUpdate},
     keyless,
+    state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{
         authenticator::{
             AccountAuthenticator, AnyPublicKey, AnySignature, MultiKey, MultiKeyAuthenticator,
@@ -33,6 +34,8 @@ use aptos_types::{
         Script, SignedTransaction, TransactionOutput, TransactionWithProof,
     },
 };
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_core_types::language_storage::StructTag;
 use once_cell::sync::Lazy;
 use poem_openapi::{Object, Union};
 use serde::{Deserialize, Serialize};
@@ -440,6 +443,91 @@ impl VerifyInput for SubmitTransactionRequest {
     }
 }
 
+/// A request to simulate a transaction over a patched state
+///
+/// The given state overrides are substituted for the state the transaction is simulated
+/// against, e.g. to see how the transaction would behave after a module upgrade.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionRequest {
+    pub transaction: SubmitTransactionRequest,
+    #[serde(default)]
+    #[oai(default)]
+    pub state_overrides: Vec<StateOverride>,
+}
+
+impl VerifyInput for SimulateTransactionRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.transaction.verify()?;
+        for state_override in &self.state_overrides {
+            state_override.verify()?;
+        }
+        Ok(())
+    }
+}
+
+/// A piece of state to substitute when simulating a transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Union)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
+pub enum StateOverride {
+    Resource(ResourceOverride),
+    Module(ModuleOverride),
+}
+
+impl StateOverride {
+    /// Returns the state key to override, and its new value or `None` if it should be deleted
+    pub fn try_into_state_write(&self) -> Result<(StateKey, Option<StateValue>)> {
+        Ok(match self {
+            StateOverride::Resource(resource) => {
+                let struct_tag: StructTag = resource.resource_type.clone().try_into()?;
+                let state_key = StateKey::resource(&resource.address.into(), &struct_tag)?;
+                let value = resource
+                    .data
+                    .as_ref()
+                    .map(|data| StateValue::from(data.inner().to_vec()));
+                (state_key, value)
+            },
+            StateOverride::Module(module) => {
+                let compiled_module = CompiledModule::deserialize(module.bytecode.inner())
+                    .map_err(|err| anyhow::anyhow!("Invalid module bytecode: {:?}", err))?;
+                (
+                    StateKey::module_id(&compiled_module.self_id()),
+                    Some(StateValue::from(module.bytecode.inner().to_vec())),
+                )
+            },
+        })
+    }
+}
+
+impl VerifyInput for StateOverride {
+    fn verify(&self) -> anyhow::Result<()> {
+        match self {
+            StateOverride::Resource(resource) => resource.resource_type.verify(0),
+            StateOverride::Module(_) => Ok(()),
+        }
+    }
+}
+
+/// Substitutes a resource stored at `address`
+///
+/// `data` is the BCS-encoded value of the resource, or the resource is deleted if it's missing.
+/// Resources stored in a resource group cannot be overridden individually.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    pub address: Address,
+    pub resource_type: MoveStructTag,
+    pub data: Option<HexEncodedBytes>,
+}
+
+/// Substitutes a module with the given bytecode
+///
+/// The address and name of the module are taken from the bytecode, and the module is added if it
+/// doesn't exist yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ModuleOverride {
+    pub bytecode: HexEncodedBytes,
+}
+
 /// Batch transaction submission result
 ///
 /// Tells which transactions failed
@@ -909,6 +997,20 @@ impl From<(&EventWithVersion, serde_json::Value)> for VersionedEvent {
     }
 }
 
+/// Events of a committed transaction, as sent by the event stream
+#[derive(Clone, Debug, Deserialize, Eq, Object, PartialEq, Serialize)]
+pub struct TransactionEvents {
+    pub version: U64,
+    pub hash: HashValue,
+    /// Sender of the transaction, only for user transactions
+    pub sender: Option<Address>,
+    pub success: bool,
+    /// Timestamp in microseconds of the block containing the transaction
+    pub timestamp: U64,
+    /// Events of the transaction matching the stream's filter, in the order they were emitted
+    pub events: Vec<Event>,
+}
+
 /// The writeset payload of the Genesis transaction
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Union)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryFunctionId, MoveType};
+use crate::{AptosError, EntryFunctionId, MoveType, MoveValue, U64};
 use aptos_types::serde_helper::vec_bytes;
 use move_core_types::{
     identifier::Identifier,
@@ -20,6 +20,27 @@ pub struct ViewRequest {
     pub arguments: Vec<serde_json::Value>,
 }
 
+/// Results of a batch of view functions, all executed at the same `ledger_version`
+#[derive(Clone, Debug, Serialize, Object)]
+pub struct ViewBatchResponse {
+    pub ledger_version: U64,
+    /// Result of each view function, in the order of the requests
+    pub results: Vec<ViewFunctionResult>,
+}
+
+/// Result of a single view function of a batch
+///
+/// Exactly one of `values` and `error` is set. `gas_used` is the gas used by the function,
+/// even if it failed.
+#[derive(Clone, Debug, Serialize, Object)]
+pub struct ViewFunctionResult {
+    /// Return values of the function
+    pub values: Option<Vec<MoveValue>>,
+    /// Why the function could not be executed
+    pub error: Option<AptosError>,
+    pub gas_used: U64,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ViewFunction {
     pub module: ModuleId,
@@ -20,7 +20,9 @@ use anyhow::anyhow;
 use aptos_admin_service::AdminService;
 use aptos_api::bootstrap as bootstrap_api;
 use aptos_build_info::build_information;
-use aptos_config::config::{merge_node_config, NodeConfig, PersistableConfig};
+use aptos_config::config::{
+    merge_node_config, transaction_filter_type::TransactionFilters, NodeConfig, PersistableConfig,
+};
 use aptos_framework::ReleaseBundle;
 use aptos_logger::{prelude::*, telemetry_log_writer::TelemetryLog, Level, LoggerFilterUpdater};
 use aptos_state_sync_driver::driver_factory::StateSyncRuntimes;
@@ -621,6 +623,10 @@ pub fn setup_environment_and_start_node(
     // Starts the admin service
     let mut admin_service = services::start_admin_service(&node_config);
 
+    // Create the transaction filters (these can be replaced at runtime via the admin service)
+    let transaction_filters = TransactionFilters::new(&node_config);
+    admin_service.set_transaction_filters(transaction_filters.clone());
+
     // Set up the storage database and any RocksDB checkpoints
     let (db_rw, backup_service, genesis_waypoint, indexer_db_opt, update_receiver) =
         storage::initialize_database_and_checkpoints(&mut node_config)?;
@@ -728,6 +734,7 @@ pub fn setup_environment_and_start_node(
             mempool_listener,
             mempool_client_receiver,
             peers_and_metadata,
+            transaction_filters.mempool.clone(),
         );
 
     // Create the DKG runtime and get the VTxn pool
@@ -756,6 +763,7 @@ pub fn setup_environment_and_start_node(
             consensus_to_mempool_sender.clone(),
             db_rw.clone(),
             consensus_observer_reconfig_subscription,
+            transaction_filters.consensus.clone(),
         );
 
     // Create the consensus runtime (if enabled)
@@ -768,6 +776,7 @@ pub fn setup_environment_and_start_node(
         consensus_to_mempool_sender.clone(),
         vtxn_pool,
         consensus_publisher.clone(),
+        &transaction_filters,
         &mut admin_service,
     );
 
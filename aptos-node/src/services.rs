@@ -4,7 +4,10 @@
 use crate::{bootstrap_api, indexer, mpsc::Receiver, network::ApplicationNetworkInterfaces};
 use aptos_admin_service::AdminService;
 use aptos_build_info::build_information;
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{
+    transaction_filter_type::{ReloadableFilter, TransactionFilters},
+    NodeConfig,
+};
 use aptos_consensus::{
     consensus_observer::publisher::consensus_publisher::ConsensusPublisher,
     network_interface::ConsensusMsg, persistent_liveness_storage::StorageWriteProxy,
@@ -82,7 +85,7 @@ pub fn bootstrap_api_and_indexer(
         db_rw.clone(),
         internal_indexer_db,
         update_receiver,
-    ) {
+    )? {
         Some((runtime, db_indexer)) => (Some(runtime), Some(db_indexer)),
         None => (None, None),
     };
@@ -147,6 +150,7 @@ pub fn start_consensus_runtime(
     consensus_to_mempool_sender: Sender<QuorumStoreRequest>,
     vtxn_pool: VTxnPoolState,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
+    transaction_filters: &TransactionFilters,
 ) -> (Runtime, Arc<StorageWriteProxy>, Arc<QuorumStoreDB>) {
     let instant = Instant::now();
 
@@ -163,6 +167,7 @@ pub fn start_consensus_runtime(
         reconfig_subscription,
         vtxn_pool,
         consensus_publisher,
+        transaction_filters,
     );
     debug!("Consensus started in {} ms", instant.elapsed().as_millis());
 
@@ -178,6 +183,7 @@ pub fn start_mempool_runtime_and_get_consensus_sender(
     mempool_listener: MempoolNotificationListener,
     mempool_client_receiver: Receiver<MempoolClientRequest>,
     peers_and_metadata: Arc<PeersAndMetadata>,
+    transaction_filter: ReloadableFilter,
 ) -> (Runtime, Sender<QuorumStoreRequest>) {
     // Create a communication channel between consensus and mempool
     let (consensus_to_mempool_sender, consensus_to_mempool_receiver) =
@@ -195,6 +201,7 @@ pub fn start_mempool_runtime_and_get_consensus_sender(
         mempool_listener,
         mempool_reconfig_subscription,
         peers_and_metadata,
+        transaction_filter,
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
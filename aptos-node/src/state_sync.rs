@@ -147,9 +147,9 @@ pub fn start_state_sync_and_get_notification_handles(
         setup_aptos_data_client(node_config, network_client, db_rw.reader.clone())?;
 
     // Start the data streaming service
-    let state_sync_config = node_config.state_sync;
+    let state_sync_config = &node_config.state_sync;
     let (streaming_service_client, streaming_service_runtime) =
-        setup_data_streaming_service(state_sync_config, aptos_data_client.clone())?;
+        setup_data_streaming_service(state_sync_config.clone(), aptos_data_client.clone())?;
 
     // Create the chunk executor and persistent storage
     let chunk_executor = Arc::new(ChunkExecutor::<AptosVMBlockExecutor>::new(db_rw.clone()));
@@ -173,7 +173,7 @@ pub fn start_state_sync_and_get_notification_handles(
 
     // Start the state sync storage service
     let storage_service_runtime = setup_state_sync_storage_service(
-        state_sync_config,
+        state_sync_config.clone(),
         peers_and_metadata,
         network_service_events,
         &db_rw,
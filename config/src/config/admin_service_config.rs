@@ -20,6 +20,8 @@ pub struct AdminServiceConfig {
     pub port: u16,
     // If empty, will allow all requests without authentication. (Not allowed on mainnet.)
     pub authentication_configs: Vec<AuthenticationConfig>,
+    // If true, allows replacing the transaction filters at runtime. Requires authentication.
+    pub enable_transaction_filter_updates: bool,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -44,6 +46,7 @@ impl Default for AdminServiceConfig {
             address: "0.0.0.0".to_string(),
             port: 9102,
             authentication_configs: vec![],
+            enable_transaction_filter_updates: false,
         }
     }
 }
@@ -62,6 +65,18 @@ impl ConfigSanitizer for AdminServiceConfig {
     ) -> Result<(), Error> {
         let sanitizer_name = Self::get_sanitizer_name();
 
+        // Replacing the transaction filters changes what the node accepts, so it must
+        // always be authenticated (regardless of the network).
+        if node_config.admin_service.enable_transaction_filter_updates
+            && node_config.admin_service.authentication_configs.is_empty()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "Must enable authentication for AdminService to allow transaction filter updates."
+                    .into(),
+            ));
+        }
+
         if node_config.admin_service.enabled == Some(true) {
             if let Some(chain_id) = chain_id {
                 if chain_id.is_mainnet()
@@ -228,4 +243,46 @@ mod tests {
         assert_eq!(node_config.admin_service.enabled, Some(false));
         assert!(modified_config);
     }
+
+    #[test]
+    fn test_sanitize_transaction_filter_updates_without_authentication() {
+        // Create a node config that allows filter updates without authentication
+        let node_config = NodeConfig {
+            admin_service: AdminServiceConfig {
+                enabled: Some(true),
+                enable_transaction_filter_updates: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization (even on testnet)
+        let error = AdminServiceConfig::sanitize(
+            &node_config,
+            NodeType::Validator,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_transaction_filter_updates_with_authentication() {
+        // Create a node config that allows authenticated filter updates
+        let node_config = NodeConfig {
+            admin_service: AdminServiceConfig {
+                enabled: Some(true),
+                enable_transaction_filter_updates: true,
+                authentication_configs: vec![AuthenticationConfig::PasscodeSha256(
+                    "passcode_sha256".into(),
+                )],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config passes sanitization
+        AdminServiceConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+            .unwrap();
+    }
 }
@@ -12,7 +12,7 @@ use crate::{
 };
 use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -65,6 +65,10 @@ pub struct ApiConfig {
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of view functions that can be executed with the Batch view API
+    ///
+    /// Each view function of a batch is limited by `max_gas_view_function` separately.
+    pub max_view_batch_size: usize,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -89,6 +93,16 @@ pub struct ApiConfig {
     pub wait_by_hash_poll_interval_ms: u64,
     /// The number of active wait_by_hash requests that can be active at any given time.
     pub wait_by_hash_max_active_connections: usize,
+    /// Enables the event stream API
+    #[serde(default = "default_disabled")]
+    pub event_stream_enabled: bool,
+    /// The interval at which event streams poll the storage for new transactions.
+    pub event_stream_poll_interval_ms: u64,
+    /// The number of event streams that can be active at any given time.
+    pub event_stream_max_active_connections: usize,
+    /// Addresses of the reverse proxies in front of the API. For requests from these, the client
+    /// IP used by the mempool admission quotas is read from the X-Forwarded-For header.
+    pub trusted_proxies: Vec<IpAddr>,
 }
 
 const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -98,6 +112,7 @@ pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+const DEFAULT_MAX_VIEW_BATCH_SIZE: usize = 50;
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
 
 fn default_enabled() -> bool {
@@ -131,6 +146,7 @@ impl Default for ApiConfig {
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_batch_size: DEFAULT_MAX_VIEW_BATCH_SIZE,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
@@ -141,6 +157,10 @@ impl Default for ApiConfig {
             wait_by_hash_timeout_ms: 1_000,
             wait_by_hash_poll_interval_ms: 20,
             wait_by_hash_max_active_connections: 100,
+            event_stream_enabled: default_disabled(),
+            event_stream_poll_interval_ms: 100,
+            event_stream_max_active_connections: 100,
+            trusted_proxies: vec![],
         }
     }
 }
@@ -192,7 +212,10 @@ impl ConfigSanitizer for ApiConfig {
 
         // We don't support Block ID based simulation filters.
         for rule in api_config.simulation_filter.rules() {
-            if let Matcher::BlockId(_) = rule.matcher() {
+            if rule
+                .matcher()
+                .any(&|matcher| matches!(matcher, Matcher::BlockId(_)))
+            {
                 return Err(Error::ConfigSanitizerFailed(
                     sanitizer_name,
                     "Block ID based simulation filters are not supported!".into(),
@@ -4,12 +4,14 @@
 
 use crate::config::{
     config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer,
-    node_config_loader::NodeType, Error, NodeConfig, MAX_APPLICATION_MESSAGE_SIZE,
+    node_config_loader::NodeType, transaction_filter_type::Filter, Error, NodeConfig,
+    MAX_APPLICATION_MESSAGE_SIZE,
 };
 use aptos_global_constants::DEFAULT_BUCKETS;
 use aptos_types::chain_id::ChainId;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -37,6 +39,40 @@ impl Default for LoadBalancingThresholdConfig {
     }
 }
 
+/// Fairness limits applied to transactions before they are admitted into the Mempool.
+/// All quotas are disabled by default.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdmissionQuotaConfig {
+    /// Maximum number of pending transactions a single sender can have in the Mempool before
+    /// further client submissions from that sender are rejected. Unlike `capacity_per_user`,
+    /// this only applies to transactions submitted directly to this node (e.g., via the API).
+    pub max_pending_txns_per_sender: Option<usize>,
+    /// Number of transactions per second a single client IP can submit through the API.
+    pub client_ip_txns_per_sec: Option<usize>,
+    /// Maximum number of transactions a single client IP can submit in a burst.
+    pub client_ip_burst_size: usize,
+    /// Number of broadcast transactions per second accepted from a single peer.
+    pub peer_broadcast_txns_per_sec: Option<usize>,
+    /// Maximum number of broadcast transactions accepted from a single peer in a burst.
+    pub peer_broadcast_burst_size: usize,
+    /// Interval at which the rate limits of idle clients and peers are garbage collected.
+    pub rate_limiter_gc_interval_secs: u64,
+}
+
+impl Default for AdmissionQuotaConfig {
+    fn default() -> AdmissionQuotaConfig {
+        AdmissionQuotaConfig {
+            max_pending_txns_per_sender: None,
+            client_ip_txns_per_sec: None,
+            client_ip_burst_size: 100,
+            peer_broadcast_txns_per_sec: None,
+            peer_broadcast_burst_size: 10_000,
+            rate_limiter_gc_interval_secs: 60,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct MempoolConfig {
@@ -84,6 +120,10 @@ pub struct MempoolConfig {
     ///
     /// Overriding this won't make much of a difference if the upstream nodes don't match.
     pub broadcast_buckets: Vec<u64>,
+    /// Minimum percentage by which the gas unit price must increase for a transaction to replace
+    /// the Mempool transaction with the same sender and sequence number (replace-by-fee).
+    /// The replacement may otherwise differ, e.g., in its payload or expiration time.
+    pub replace_by_fee_min_gas_price_bump_pct: u64,
     pub eager_expire_threshold_ms: Option<u64>,
     pub eager_expire_time_ms: u64,
     /// Uses the BroadcastTransactionsRequestWithReadyTime instead of BroadcastTransactionsRequest when sending
@@ -100,6 +140,22 @@ pub struct MempoolConfig {
     /// up to 10 minutes (shared_mempool_priority_update_interval_secs) to enable the load balancing. If this flag is enabled,
     /// then the PFNs will always do load balancing irrespective of the load.
     pub enable_max_load_balancing_at_any_load: bool,
+    /// Whether to persist client-submitted transactions accepted into the Mempool to an on-disk
+    /// journal. On startup, the journal is replayed and the transactions are revalidated.
+    pub enable_journal: bool,
+    /// Location of the journal file. Relative paths are resolved against the node's data directory.
+    pub journal_path: PathBuf,
+    /// Number of records that can be appended to the journal before it is compacted to only
+    /// contain the transactions that are still in the Mempool.
+    pub journal_compaction_threshold: usize,
+    /// Maximum number of journal appends waiting to be written to disk by the journal writer.
+    /// When exceeded, appends are dropped and the journal is compacted on the next GC instead.
+    pub journal_max_pending_appends: usize,
+    /// Per-sender, per-client IP and per-peer admission quotas.
+    pub admission_quotas: AdmissionQuotaConfig,
+    /// Filter applied to client-submitted and broadcast transactions before they are admitted
+    /// into the Mempool. Block based matchers never match on admission.
+    pub transaction_filter: Filter,
 }
 
 impl Default for MempoolConfig {
@@ -125,6 +181,7 @@ impl Default for MempoolConfig {
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
             broadcast_buckets: DEFAULT_BUCKETS.to_vec(),
+            replace_by_fee_min_gas_price_bump_pct: 10,
             eager_expire_threshold_ms: Some(15_000),
             eager_expire_time_ms: 6_000,
             include_ready_time_in_broadcast: false,
@@ -164,17 +221,65 @@ impl Default for MempoolConfig {
                 },
             ],
             enable_max_load_balancing_at_any_load: false,
+            enable_journal: false,
+            journal_path: PathBuf::from("mempool_journal"),
+            journal_compaction_threshold: 10_000,
+            journal_max_pending_appends: 10_000,
+            admission_quotas: AdmissionQuotaConfig::default(),
+            transaction_filter: Filter::empty(),
         }
     }
 }
 
 impl ConfigSanitizer for MempoolConfig {
     fn sanitize(
-        _node_config: &NodeConfig,
+        node_config: &NodeConfig,
         _node_type: NodeType,
         _chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
-        Ok(()) // TODO: add reasonable verifications
+        let sanitizer_name = Self::get_sanitizer_name();
+        let admission_quotas = &node_config.mempool.admission_quotas;
+
+        // Verify that the enabled rate limits are non-zero
+        let rate_limits = [
+            (
+                "client_ip",
+                admission_quotas.client_ip_txns_per_sec,
+                admission_quotas.client_ip_burst_size,
+            ),
+            (
+                "peer_broadcast",
+                admission_quotas.peer_broadcast_txns_per_sec,
+                admission_quotas.peer_broadcast_burst_size,
+            ),
+        ];
+        for (name, txns_per_sec, burst_size) in rate_limits {
+            if txns_per_sec.is_some() && (txns_per_sec == Some(0) || burst_size == 0) {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!(
+                        "The {} rate limit and burst size must be greater than 0!",
+                        name
+                    ),
+                ));
+            }
+        }
+        if admission_quotas.rate_limiter_gc_interval_secs == 0 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The rate limiter garbage collection interval must be greater than 0!".into(),
+            ));
+        }
+        if node_config.mempool.enable_journal
+            && node_config.mempool.journal_max_pending_appends == 0
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The maximum number of pending journal appends must be greater than 0!".into(),
+            ));
+        }
+
+        Ok(())
     }
 }
 
@@ -346,4 +451,38 @@ mod tests {
             local_max_broadcasts_per_peer
         );
     }
+
+    #[test]
+    fn test_sanitize_zero_rate_limit() {
+        // Create a node config with a zero burst size for an enabled rate limit
+        let node_config = NodeConfig {
+            mempool: MempoolConfig {
+                admission_quotas: AdmissionQuotaConfig {
+                    client_ip_txns_per_sec: Some(10),
+                    client_ip_burst_size: 0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error =
+            MempoolConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Disable the rate limit and verify that sanitization succeeds
+        let node_config = NodeConfig {
+            mempool: MempoolConfig {
+                admission_quotas: AdmissionQuotaConfig {
+                    client_ip_burst_size: 0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        MempoolConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap();
+    }
 }
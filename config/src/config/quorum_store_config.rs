@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::{
-    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType,
+    transaction_filter_type::Filter, Error, NodeConfig,
 };
 use aptos_global_constants::DEFAULT_BUCKETS;
 use aptos_types::chain_id::ChainId;
@@ -98,6 +99,9 @@ pub struct QuorumStoreConfig {
     pub allow_batches_without_pos_in_proposal: bool,
     pub enable_opt_quorum_store: bool,
     pub opt_qs_minimum_batch_age_usecs: u64,
+    /// Filter applied to batches received from peers. Batches containing a transaction denied
+    /// by the filter are dropped. Block based matchers never match.
+    pub transaction_filter: Filter,
 }
 
 impl Default for QuorumStoreConfig {
@@ -138,6 +142,7 @@ impl Default for QuorumStoreConfig {
             allow_batches_without_pos_in_proposal: true,
             enable_opt_quorum_store: false,
             opt_qs_minimum_batch_age_usecs: Duration::from_millis(20).as_micros() as u64,
+            transaction_filter: Filter::empty(),
         }
     }
 }
@@ -6,9 +6,14 @@ use crate::config::{
     config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer,
     node_config_loader::NodeType, Error, NodeConfig,
 };
-use aptos_types::chain_id::ChainId;
+use aptos_types::{
+    account_address::AccountAddress, chain_id::ChainId,
+    state_store::partial_state::PartialStateFilter,
+};
+use move_core_types::language_storage::StructTag;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::str::FromStr;
 
 // The maximum message size per state sync message
 const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024; /* 10 MiB */
@@ -23,7 +28,7 @@ const MAX_TRANSACTION_OUTPUT_CHUNK_SIZE: u64 = 3000;
 const MAX_CONCURRENT_REQUESTS: u64 = 6;
 const MAX_CONCURRENT_STATE_REQUESTS: u64 = 6;
 
-#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StateSyncConfig {
     pub data_streaming_service: DataStreamingServiceConfig,
@@ -91,7 +96,7 @@ impl ContinuousSyncingMode {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StateSyncDriverConfig {
     /// The mode by which to bootstrap
@@ -120,6 +125,8 @@ pub struct StateSyncDriverConfig {
     pub max_stream_wait_time_ms: u64,
     /// The version lag we'll tolerate before snapshot syncing
     pub num_versions_to_skip_snapshot_sync: u64,
+    /// The config for only persisting a subset of the state (fullnodes only)
+    pub partial_state_sync: PartialStateSyncConfig,
 }
 
 /// The default state sync driver config will be the one that gets (and keeps)
@@ -140,13 +147,77 @@ impl Default for StateSyncDriverConfig {
             max_pending_mempool_notifications: 100,
             max_stream_wait_time_ms: 5000,
             num_versions_to_skip_snapshot_sync: 400_000_000, // At 5k TPS, this allows a node to fail for about 24 hours.
+            partial_state_sync: PartialStateSyncConfig::default(),
+        }
+    }
+}
+
+/// The partial state sync config allows fullnodes to verify all synced transaction
+/// outputs, but only persist the state values of the tracked accounts and resource
+/// types (as well as the framework accounts). All other state values (including all
+/// table items, as their owners can't be determined) are discarded.
+///
+/// Note: the tracked state cannot be changed once the node has started syncing.
+/// To change the tracked state (or disable partial state sync), the node's storage
+/// must be wiped and the node re-synced.
+///
+/// Partial nodes do not serve state values to peers, and reading an untracked
+/// state value (e.g., via the API, or when mempool validates a transaction from
+/// an untracked sender) fails with an error. The state storage usage is also
+/// inaccurate, as the usage of untracked state values cannot be computed.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PartialStateSyncConfig {
+    /// Whether partial state sync is enabled
+    pub enabled: bool,
+    /// The accounts whose resources and modules are persisted. Table items are not
+    /// persisted, even for tables created by these accounts.
+    pub tracked_accounts: Vec<AccountAddress>,
+    /// The resource types (e.g., "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>")
+    /// that are persisted for all accounts.
+    pub tracked_resource_types: Vec<String>,
+}
+
+impl PartialStateSyncConfig {
+    /// Parses and returns the tracked resource types
+    pub fn tracked_resource_types(&self) -> anyhow::Result<Vec<StructTag>> {
+        self.tracked_resource_types
+            .iter()
+            .map(|resource_type| {
+                StructTag::from_str(resource_type).map_err(|error| {
+                    anyhow::anyhow!(
+                        "Invalid tracked resource type: {}. Error: {:?}",
+                        resource_type,
+                        error
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Creates the state filter for partial state sync. Returns None
+    /// if partial state sync is disabled.
+    pub fn create_filter(&self) -> anyhow::Result<Option<PartialStateFilter>> {
+        if !self.enabled {
+            return Ok(None);
         }
+
+        let tracked_resource_types = self.tracked_resource_types()?;
+        Ok(Some(PartialStateFilter::new(
+            self.tracked_accounts.clone(),
+            tracked_resource_types,
+        )))
     }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StorageServiceConfig {
+    /// Whether to serve requests for state values by key prefix. This is
+    /// disabled by default (each request may require many proofs), and only
+    /// enabled for fullnodes without storage sharding (prefix iteration is
+    /// not supported by sharded storage).
+    pub enable_state_values_by_prefix: bool,
     /// Maximum number of epoch ending ledger infos per chunk
     pub max_epoch_chunk_size: u64,
     /// Maximum number of invalid requests per peer
@@ -180,6 +251,7 @@ pub struct StorageServiceConfig {
 impl Default for StorageServiceConfig {
     fn default() -> Self {
         Self {
+            enable_state_values_by_prefix: false,
             max_epoch_chunk_size: MAX_EPOCH_CHUNK_SIZE,
             max_invalid_requests_per_peer: 500,
             max_lru_cache_size: 500, // At ~0.6MiB per chunk, this should take no more than 0.5GiB
@@ -466,8 +538,8 @@ impl ConfigSanitizer for StateSyncConfig {
 impl ConfigSanitizer for StateSyncDriverConfig {
     fn sanitize(
         node_config: &NodeConfig,
-        _node_type: NodeType,
-        _chain_id: Option<ChainId>,
+        node_type: NodeType,
+        chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
         let sanitizer_name = Self::get_sanitizer_name();
         let state_sync_driver_config = &node_config.state_sync.state_sync_driver;
@@ -483,6 +555,75 @@ impl ConfigSanitizer for StateSyncDriverConfig {
             ));
         }
 
+        // Verify the partial state sync config
+        PartialStateSyncConfig::sanitize(node_config, node_type, chain_id)
+    }
+}
+
+impl ConfigSanitizer for PartialStateSyncConfig {
+    fn sanitize(
+        node_config: &NodeConfig,
+        node_type: NodeType,
+        _chain_id: Option<ChainId>,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let state_sync_driver_config = &node_config.state_sync.state_sync_driver;
+        let partial_state_sync_config = &state_sync_driver_config.partial_state_sync;
+        if !partial_state_sync_config.enabled {
+            return Ok(());
+        }
+
+        // Verify that partial state sync is not enabled for validators
+        if node_type.is_validator() {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "Partial state sync should not be enabled for validators!".to_string(),
+            ));
+        }
+
+        // Verify that the node never executes transactions (execution requires the full state)
+        if matches!(
+            state_sync_driver_config.bootstrapping_mode,
+            BootstrappingMode::ExecuteTransactionsFromGenesis
+                | BootstrappingMode::ExecuteOrApplyFromGenesis
+        ) {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "Partial state sync requires a bootstrapping mode that does not execute transactions! Found: {:?}",
+                    state_sync_driver_config.bootstrapping_mode
+                ),
+            ));
+        }
+        if state_sync_driver_config.continuous_syncing_mode
+            != ContinuousSyncingMode::ApplyTransactionOutputs
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "Partial state sync requires the continuous syncing mode to apply transaction outputs! Found: {:?}",
+                    state_sync_driver_config.continuous_syncing_mode
+                ),
+            ));
+        }
+
+        // Verify that the node tracks some state and that all resource types are valid
+        if partial_state_sync_config.tracked_accounts.is_empty()
+            && partial_state_sync_config.tracked_resource_types.is_empty()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "Partial state sync is enabled, but no accounts or resource types are tracked!"
+                    .to_string(),
+            ));
+        }
+        if let Err(error) = partial_state_sync_config.tracked_resource_types() {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                error.to_string(),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -494,7 +635,9 @@ impl ConfigOptimizer for StateSyncConfig {
         node_type: NodeType,
         chain_id: Option<ChainId>,
     ) -> Result<bool, Error> {
-        // Optimize the driver and data streaming service configs
+        // Optimize the storage service, driver and data streaming service configs
+        let modified_storage_service_config =
+            StorageServiceConfig::optimize(node_config, local_config_yaml, node_type, chain_id)?;
         let modified_driver_config =
             StateSyncDriverConfig::optimize(node_config, local_config_yaml, node_type, chain_id)?;
         let modified_data_streaming_config = DataStreamingServiceConfig::optimize(
@@ -504,7 +647,36 @@ impl ConfigOptimizer for StateSyncConfig {
             chain_id,
         )?;
 
-        Ok(modified_driver_config || modified_data_streaming_config)
+        Ok(modified_storage_service_config
+            || modified_driver_config
+            || modified_data_streaming_config)
+    }
+}
+
+impl ConfigOptimizer for StorageServiceConfig {
+    fn optimize(
+        node_config: &mut NodeConfig,
+        local_config_yaml: &Value,
+        node_type: NodeType,
+        _chain_id: Option<ChainId>,
+    ) -> Result<bool, Error> {
+        let storage_sharding_enabled = node_config.storage.rocksdb_configs.enable_storage_sharding;
+        let storage_service_config = &mut node_config.state_sync.storage_service;
+        let local_storage_service_config_yaml = &local_config_yaml["state_sync"]["storage_service"];
+
+        // Enable state values by prefix for fullnodes, unless storage
+        // sharding is enabled (prefix iteration is not supported by
+        // sharded storage).
+        let mut modified_config = false;
+        if !node_type.is_validator()
+            && !storage_sharding_enabled
+            && local_storage_service_config_yaml["enable_state_values_by_prefix"].is_null()
+        {
+            storage_service_config.enable_state_values_by_prefix = true;
+            modified_config = true;
+        }
+
+        Ok(modified_config)
     }
 }
 
@@ -609,7 +781,7 @@ mod tests {
         assert!(modified_config);
 
         // Verify that the bootstrapping mode is now set to fast sync
-        let state_sync_driver_config = node_config.state_sync.state_sync_driver;
+        let state_sync_driver_config = &node_config.state_sync.state_sync_driver;
         assert!(state_sync_driver_config.bootstrapping_mode.is_fast_sync());
     }
 
@@ -629,7 +801,7 @@ mod tests {
         assert!(modified_config);
 
         // Verify that the bootstrapping mode is now set to fast sync
-        let state_sync_driver_config = node_config.state_sync.state_sync_driver;
+        let state_sync_driver_config = &node_config.state_sync.state_sync_driver;
         assert!(state_sync_driver_config.bootstrapping_mode.is_fast_sync());
     }
 
@@ -665,6 +837,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_optimize_state_values_by_prefix_fullnode() {
+        // Create a default node config
+        let mut node_config = NodeConfig::default();
+
+        // Optimize the config and verify state values by prefix are enabled
+        let modified_config = StateSyncConfig::optimize(
+            &mut node_config,
+            &serde_yaml::from_str("{}").unwrap(), // An empty local config,
+            NodeType::PublicFullnode,
+            None,
+        )
+        .unwrap();
+        assert!(modified_config);
+        assert!(
+            node_config
+                .state_sync
+                .storage_service
+                .enable_state_values_by_prefix
+        );
+    }
+
+    #[test]
+    fn test_optimize_state_values_by_prefix_validator() {
+        // Create a default node config
+        let mut node_config = NodeConfig::default();
+
+        // Optimize the config and verify state values by prefix remain disabled
+        StateSyncConfig::optimize(
+            &mut node_config,
+            &serde_yaml::from_str("{}").unwrap(), // An empty local config,
+            NodeType::Validator,
+            None,
+        )
+        .unwrap();
+        assert!(
+            !node_config
+                .state_sync
+                .storage_service
+                .enable_state_values_by_prefix
+        );
+    }
+
+    #[test]
+    fn test_optimize_state_values_by_prefix_sharding() {
+        // Create a node config with storage sharding enabled
+        let mut node_config = NodeConfig::default();
+        node_config.storage.rocksdb_configs.enable_storage_sharding = true;
+
+        // Optimize the config and verify state values by prefix remain disabled
+        StateSyncConfig::optimize(
+            &mut node_config,
+            &serde_yaml::from_str("{}").unwrap(), // An empty local config,
+            NodeType::PublicFullnode,
+            None,
+        )
+        .unwrap();
+        assert!(
+            !node_config
+                .state_sync
+                .storage_service
+                .enable_state_values_by_prefix
+        );
+    }
+
     #[test]
     fn test_optimize_prefetcher_mainnet_validator() {
         // Create a default node config
@@ -788,6 +1025,98 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_partial_state_sync() {
+        // Create a node config with partial state sync enabled
+        let node_config = create_partial_state_sync_config(
+            BootstrappingMode::DownloadLatestStates,
+            ContinuousSyncingMode::ApplyTransactionOutputs,
+            vec!["0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>".into()],
+        );
+
+        // Verify that sanitization succeeds for fullnodes
+        for node_type in [NodeType::ValidatorFullnode, NodeType::PublicFullnode] {
+            StateSyncConfig::sanitize(&node_config, node_type, Some(ChainId::testnet())).unwrap();
+        }
+
+        // Verify that sanitization fails for validators
+        let error =
+            StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_partial_state_sync_execution() {
+        // Create node configs with partial state sync and transaction execution enabled
+        let node_configs = [
+            create_partial_state_sync_config(
+                BootstrappingMode::ExecuteOrApplyFromGenesis,
+                ContinuousSyncingMode::ApplyTransactionOutputs,
+                vec![],
+            ),
+            create_partial_state_sync_config(
+                BootstrappingMode::ApplyTransactionOutputsFromGenesis,
+                ContinuousSyncingMode::ExecuteTransactionsOrApplyOutputs,
+                vec![],
+            ),
+        ];
+
+        // Verify that sanitization fails
+        for node_config in node_configs {
+            let error = StateSyncConfig::sanitize(
+                &node_config,
+                NodeType::PublicFullnode,
+                Some(ChainId::testnet()),
+            )
+            .unwrap_err();
+            assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        }
+    }
+
+    #[test]
+    fn test_sanitize_partial_state_sync_invalid_resource_type() {
+        // Create a node config with an invalid tracked resource type
+        let node_config = create_partial_state_sync_config(
+            BootstrappingMode::DownloadLatestStates,
+            ContinuousSyncingMode::ApplyTransactionOutputs,
+            vec!["invalid_resource_type".into()],
+        );
+
+        // Verify that sanitization fails
+        let error = StateSyncConfig::sanitize(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    /// Creates and returns a node config with partial state sync enabled
+    fn create_partial_state_sync_config(
+        bootstrapping_mode: BootstrappingMode,
+        continuous_syncing_mode: ContinuousSyncingMode,
+        tracked_resource_types: Vec<String>,
+    ) -> NodeConfig {
+        NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode,
+                    continuous_syncing_mode,
+                    partial_state_sync: PartialStateSyncConfig {
+                        enabled: true,
+                        tracked_accounts: vec![AccountAddress::random()],
+                        tracked_resource_types,
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
     /// Creates and returns a node config with the syncing modes set to execution
     fn create_execution_mode_config() -> NodeConfig {
         NodeConfig {
@@ -1,12 +1,60 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config::NodeConfig;
 use aptos_crypto::HashValue;
 use aptos_types::{
     account_address::AccountAddress,
-    transaction::{SignedTransaction, TransactionPayload},
+    transaction::{
+        authenticator::{AccountAuthenticator, AnyPublicKey},
+        EntryFunction, MultisigTransactionPayload, SignedTransaction, TransactionArgument,
+        TransactionPayload,
+    },
 };
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// The type of a transaction signer's authenticator, as matched by
+/// [`Matcher::AuthenticatorType`]. Single key authenticators are matched by their key type.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AuthenticatorType {
+    Ed25519,
+    MultiEd25519,
+    Secp256k1Ecdsa,
+    Secp256r1Ecdsa,
+    Keyless,
+    FederatedKeyless,
+    MultiKey,
+    NoAccountAuthenticator,
+}
+
+impl AuthenticatorType {
+    fn of(authenticator: &AccountAuthenticator) -> Self {
+        match authenticator {
+            AccountAuthenticator::Ed25519 { .. } => AuthenticatorType::Ed25519,
+            AccountAuthenticator::MultiEd25519 { .. } => AuthenticatorType::MultiEd25519,
+            AccountAuthenticator::SingleKey { authenticator } => match authenticator.public_key() {
+                AnyPublicKey::Ed25519 { .. } => AuthenticatorType::Ed25519,
+                AnyPublicKey::Secp256k1Ecdsa { .. } => AuthenticatorType::Secp256k1Ecdsa,
+                AnyPublicKey::Secp256r1Ecdsa { .. } => AuthenticatorType::Secp256r1Ecdsa,
+                AnyPublicKey::Keyless { .. } => AuthenticatorType::Keyless,
+                AnyPublicKey::FederatedKeyless { .. } => AuthenticatorType::FederatedKeyless,
+            },
+            AccountAuthenticator::MultiKey { .. } => AuthenticatorType::MultiKey,
+            AccountAuthenticator::NoAccountAuthenticator => {
+                AuthenticatorType::NoAccountAuthenticator
+            },
+        }
+    }
+}
+
+/// The block a transaction is filtered in. Block based matchers are indeterminate for
+/// transactions filtered outside of a block (e.g., on mempool admission).
+#[derive(Clone, Copy)]
+struct BlockInfo {
+    block_id: HashValue,
+    timestamp: u64,
+}
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Matcher {
@@ -18,15 +66,75 @@ pub enum Matcher {
     Sender(AccountAddress),
     ModuleAddress(AccountAddress),
     EntryFunction(AccountAddress, String, String),
+    /// Matches any script payload
+    Script,
+    /// Matches script payloads with the given SHA3-256 hash of the script bytecode
+    ScriptHash(HashValue),
+    /// Matches multisig payloads for the given multisig account
+    MultisigAddress(AccountAddress),
+    /// Matches multisig payloads that execute the given entry function
+    MultisigEntryFunction(AccountAddress, String, String),
+    /// Matches transactions with a gas unit price greater than the given value
+    GasUnitPriceGreaterThan(u64),
+    /// Matches transactions with a gas unit price less than the given value
+    GasUnitPriceLessThan(u64),
+    /// Matches transactions where any signer uses the given authenticator type
+    AuthenticatorType(AuthenticatorType),
+    /// Matches transactions where any signer uses the given public key, including as one of the
+    /// keys of a multi-ed25519 or multi-key authenticator
+    PublicKey(AnyPublicKey),
+    /// Matches transactions where the given address is passed as an argument (or within a
+    /// vector argument) to the entry function or script, including multisig entry functions
+    AddressArgument(AccountAddress),
+    /// Matches if any of the given matchers match
+    AnyOf(Vec<Matcher>),
+    /// Matches if all of the given matchers match
+    AllOf(Vec<Matcher>),
+    /// Matches if the given matcher does not match
+    Not(Box<Matcher>),
 }
 
 impl Matcher {
-    fn matches(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> bool {
+    /// Returns whether the matcher matches the transaction, or None if this can't be
+    /// determined because the matcher depends on a block and the transaction is filtered
+    /// outside of one. Combinators follow three-valued logic, so e.g., the negation of an
+    /// indeterminate matcher is also indeterminate.
+    fn matches(&self, block_info: Option<BlockInfo>, txn: &SignedTransaction) -> Option<bool> {
+        match self {
+            Matcher::BlockId(id) => block_info.map(|info| info.block_id == *id),
+            Matcher::BlockTimeStampGreaterThan(ts) => block_info.map(|info| info.timestamp > *ts),
+            Matcher::BlockTimeStampLessThan(ts) => block_info.map(|info| info.timestamp < *ts),
+            Matcher::AnyOf(matchers) => {
+                let mut result = Some(false);
+                for matcher in matchers {
+                    match matcher.matches(block_info, txn) {
+                        Some(true) => return Some(true),
+                        Some(false) => {},
+                        None => result = None,
+                    }
+                }
+                result
+            },
+            Matcher::AllOf(matchers) => {
+                let mut result = Some(true);
+                for matcher in matchers {
+                    match matcher.matches(block_info, txn) {
+                        Some(false) => return Some(false),
+                        Some(true) => {},
+                        None => result = None,
+                    }
+                }
+                result
+            },
+            Matcher::Not(matcher) => matcher.matches(block_info, txn).map(|matches| !matches),
+            _ => Some(self.matches_transaction(txn)),
+        }
+    }
+
+    /// Returns whether a matcher that only depends on the transaction matches it
+    fn matches_transaction(&self, txn: &SignedTransaction) -> bool {
         match self {
             Matcher::All => true,
-            Matcher::BlockId(id) => block_id == *id,
-            Matcher::BlockTimeStampGreaterThan(ts) => timestamp > *ts,
-            Matcher::BlockTimeStampLessThan(ts) => timestamp < *ts,
             Matcher::TransactionId(id) => txn.committed_hash() == *id,
             Matcher::Sender(sender) => txn.sender() == *sender,
             Matcher::ModuleAddress(address) => match txn.payload() {
@@ -37,16 +145,144 @@ impl Matcher {
             },
             Matcher::EntryFunction(address, module_name, function) => match txn.payload() {
                 TransactionPayload::EntryFunction(entry_function) => {
-                    *entry_function.module().address() == *address
-                        && entry_function.module().name().to_string() == *module_name
-                        && entry_function.function().to_string() == *function
+                    matches_entry_function(entry_function, address, module_name, function)
                 },
                 _ => false,
             },
+            Matcher::Script => matches!(txn.payload(), TransactionPayload::Script(_)),
+            Matcher::ScriptHash(hash) => match txn.payload() {
+                TransactionPayload::Script(script) => {
+                    HashValue::sha3_256_of(script.code()) == *hash
+                },
+                _ => false,
+            },
+            Matcher::MultisigAddress(address) => match txn.payload() {
+                TransactionPayload::Multisig(multisig) => multisig.multisig_address == *address,
+                _ => false,
+            },
+            Matcher::MultisigEntryFunction(address, module_name, function) => {
+                match multisig_entry_function(txn) {
+                    Some(entry_function) => {
+                        matches_entry_function(entry_function, address, module_name, function)
+                    },
+                    None => false,
+                }
+            },
+            Matcher::GasUnitPriceGreaterThan(gas_unit_price) => {
+                txn.gas_unit_price() > *gas_unit_price
+            },
+            Matcher::GasUnitPriceLessThan(gas_unit_price) => txn.gas_unit_price() < *gas_unit_price,
+            Matcher::AuthenticatorType(authenticator_type) => txn
+                .authenticator_ref()
+                .all_signers()
+                .iter()
+                .any(|signer| AuthenticatorType::of(signer) == *authenticator_type),
+            Matcher::PublicKey(public_key) => txn
+                .authenticator_ref()
+                .all_signers()
+                .iter()
+                .any(|signer| signer_uses_public_key(signer, public_key)),
+            Matcher::AddressArgument(address) => match txn.payload() {
+                TransactionPayload::Script(script) => script.args().iter().any(|arg| match arg {
+                    TransactionArgument::Address(arg) => arg == address,
+                    TransactionArgument::Serialized(bytes) => bytes_contain_address(bytes, address),
+                    _ => false,
+                }),
+                TransactionPayload::EntryFunction(entry_function) => entry_function
+                    .args()
+                    .iter()
+                    .any(|bytes| bytes_contain_address(bytes, address)),
+                TransactionPayload::Multisig(_) => match multisig_entry_function(txn) {
+                    Some(entry_function) => entry_function
+                        .args()
+                        .iter()
+                        .any(|bytes| bytes_contain_address(bytes, address)),
+                    None => false,
+                },
+                TransactionPayload::ModuleBundle(_) => false,
+            },
+            Matcher::BlockId(_)
+            | Matcher::BlockTimeStampGreaterThan(_)
+            | Matcher::BlockTimeStampLessThan(_)
+            | Matcher::AnyOf(_)
+            | Matcher::AllOf(_)
+            | Matcher::Not(_) => unreachable!("Handled by Matcher::matches"),
+        }
+    }
+
+    /// Returns true if this matcher, or any matcher nested within it, satisfies the predicate
+    pub fn any(&self, predicate: &impl Fn(&Matcher) -> bool) -> bool {
+        if predicate(self) {
+            return true;
         }
+        match self {
+            Matcher::AnyOf(matchers) | Matcher::AllOf(matchers) => {
+                matchers.iter().any(|matcher| matcher.any(predicate))
+            },
+            Matcher::Not(matcher) => matcher.any(predicate),
+            _ => false,
+        }
+    }
+}
+
+fn matches_entry_function(
+    entry_function: &EntryFunction,
+    address: &AccountAddress,
+    module_name: &str,
+    function: &str,
+) -> bool {
+    *entry_function.module().address() == *address
+        && entry_function.module().name().as_str() == module_name
+        && entry_function.function().as_str() == function
+}
+
+fn multisig_entry_function(txn: &SignedTransaction) -> Option<&EntryFunction> {
+    match txn.payload() {
+        TransactionPayload::Multisig(multisig) => match &multisig.transaction_payload {
+            Some(MultisigTransactionPayload::EntryFunction(entry_function)) => Some(entry_function),
+            None => None,
+        },
+        _ => None,
+    }
+}
+
+fn signer_uses_public_key(signer: &AccountAuthenticator, public_key: &AnyPublicKey) -> bool {
+    match (signer, public_key) {
+        (
+            AccountAuthenticator::Ed25519 {
+                public_key: signer_key,
+                ..
+            },
+            AnyPublicKey::Ed25519 { public_key },
+        ) => signer_key == public_key,
+        (
+            AccountAuthenticator::MultiEd25519 {
+                public_key: signer_key,
+                ..
+            },
+            AnyPublicKey::Ed25519 { public_key },
+        ) => signer_key.public_keys().contains(public_key),
+        (AccountAuthenticator::SingleKey { authenticator }, _) => {
+            authenticator.public_key() == public_key
+        },
+        (AccountAuthenticator::MultiKey { authenticator }, _) => authenticator
+            .public_keys()
+            .public_keys()
+            .contains(public_key),
+        _ => false,
     }
 }
 
+/// Returns true if the BCS encoded argument is the address, or a vector (or option) of
+/// addresses containing it
+fn bytes_contain_address(bytes: &[u8], address: &AccountAddress) -> bool {
+    if bytes == address.as_ref() {
+        return true;
+    }
+    bcs::from_bytes::<Vec<AccountAddress>>(bytes)
+        .map_or(false, |addresses| addresses.contains(address))
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Rule {
     Allow(Matcher),
@@ -69,17 +305,18 @@ enum EvalResult {
 }
 
 impl Rule {
-    fn eval(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> EvalResult {
+    fn eval(&self, block_info: Option<BlockInfo>, txn: &SignedTransaction) -> EvalResult {
         match self {
+            // Indeterminate matchers are treated as not matching, so the rule is skipped
             Rule::Allow(matcher) => {
-                if matcher.matches(block_id, timestamp, txn) {
+                if matcher.matches(block_info, txn) == Some(true) {
                     EvalResult::Allow
                 } else {
                     EvalResult::NoMatch
                 }
             },
             Rule::Deny(matcher) => {
-                if matcher.matches(block_id, timestamp, txn) {
+                if matcher.matches(block_info, txn) == Some(true) {
                     EvalResult::Deny
                 } else {
                     EvalResult::NoMatch
@@ -113,6 +350,14 @@ impl Rule {
 /// This filter allows transactions from the sender with address f8871acf2c827d40e23b71f6ff2b9accef8dbb17709b88bd9eb95e6bb748c25a or
 /// from the module with address 0000000000000000000000000000000000000000000000000000000000000001 or entry functions
 /// test::check and test::new from the module 0000000000000000000000000000000000000000000000000000000000000001. All other transactions are denied.
+///
+/// Matchers can also be combined. For example, the following rule denies script transactions
+/// with a gas unit price below 200:
+///             rules:
+///                 - Deny:
+///                     AllOf:
+///                         - Script
+///                         - GasUnitPriceLessThan: 200
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Filter {
     rules: Vec<Rule>,
@@ -197,15 +442,42 @@ impl Filter {
         self
     }
 
+    pub fn add_allow(mut self, matcher: Matcher) -> Self {
+        self.rules.push(Rule::Allow(matcher));
+        self
+    }
+
+    pub fn add_deny(mut self, matcher: Matcher) -> Self {
+        self.rules.push(Rule::Deny(matcher));
+        self
+    }
+
     pub fn rules(&self) -> &[Rule] {
         &self.rules
     }
 
     pub fn allows(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> bool {
+        self.eval(
+            Some(BlockInfo {
+                block_id,
+                timestamp,
+            }),
+            txn,
+        )
+    }
+
+    /// Evaluates the filter for a transaction outside of a block (e.g., on mempool admission).
+    /// Rules whose matcher depends on the block (including negated or combined block based
+    /// matchers) are indeterminate and skipped.
+    pub fn allows_transaction(&self, txn: &SignedTransaction) -> bool {
+        self.eval(None, txn)
+    }
+
+    fn eval(&self, block_info: Option<BlockInfo>, txn: &SignedTransaction) -> bool {
         for rule in &self.rules {
             // Rules are evaluated in the order and the first rule that matches is used. If no rule
             // matches, the transaction is allowed.
-            match rule.eval(block_id, timestamp, txn) {
+            match rule.eval(block_info, txn) {
                 EvalResult::Allow => return true,
                 EvalResult::Deny => return false,
                 EvalResult::NoMatch => continue,
@@ -214,3 +486,91 @@ impl Filter {
         true
     }
 }
+
+/// A filter that can be replaced at runtime (e.g., via the admin service). Clones share the
+/// same underlying filter, so a replacement is observed by every holder of the handle. Each
+/// replacement bumps the version, starting from 0 for the filter loaded from the config.
+#[derive(Clone, Debug)]
+pub struct ReloadableFilter {
+    inner: Arc<RwLock<(u64, Arc<Filter>)>>,
+}
+
+impl ReloadableFilter {
+    pub fn new(filter: Filter) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new((0, Arc::new(filter)))),
+        }
+    }
+
+    /// Returns the currently active filter
+    pub fn filter(&self) -> Arc<Filter> {
+        self.snapshot().1
+    }
+
+    /// Returns the version of the currently active filter
+    pub fn version(&self) -> u64 {
+        self.snapshot().0
+    }
+
+    /// Returns the version and the currently active filter
+    pub fn snapshot(&self) -> (u64, Arc<Filter>) {
+        let inner = self
+            .inner
+            .read()
+            .expect("Failed to acquire the filter lock!");
+        (inner.0, inner.1.clone())
+    }
+
+    /// Replaces the active filter and returns the new version
+    pub fn replace(&self, filter: Filter) -> u64 {
+        let mut inner = self
+            .inner
+            .write()
+            .expect("Failed to acquire the filter lock!");
+        inner.0 += 1;
+        inner.1 = Arc::new(filter);
+        inner.0
+    }
+
+    /// Replaces the active filter only if its version is `expected_version`. Returns the new
+    /// version on success, and the current version otherwise.
+    pub fn compare_and_replace(&self, expected_version: u64, filter: Filter) -> Result<u64, u64> {
+        let mut inner = self
+            .inner
+            .write()
+            .expect("Failed to acquire the filter lock!");
+        if inner.0 != expected_version {
+            return Err(inner.0);
+        }
+        inner.0 += 1;
+        inner.1 = Arc::new(filter);
+        Ok(inner.0)
+    }
+}
+
+/// The runtime reloadable transaction filters of a node
+#[derive(Clone, Debug)]
+pub struct TransactionFilters {
+    /// Filters transactions in blocks before they are sent to execution
+    pub consensus: ReloadableFilter,
+    /// Filters batches received from peers by quorum store
+    pub quorum_store: ReloadableFilter,
+    /// Filters transactions before they are admitted into mempool
+    pub mempool: ReloadableFilter,
+}
+
+impl TransactionFilters {
+    pub fn new(node_config: &NodeConfig) -> Self {
+        Self {
+            consensus: ReloadableFilter::new(node_config.execution.transaction_filter.clone()),
+            quorum_store: ReloadableFilter::new(
+                node_config
+                    .consensus
+                    .quorum_store
+                    .transaction_filter
+                    .clone(),
+            ),
+            mempool: ReloadableFilter::new(node_config.mempool.transaction_filter.clone()),
+        }
+    }
+}
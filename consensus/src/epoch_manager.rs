@@ -57,7 +57,10 @@ use crate::{
 use anyhow::{anyhow, bail, ensure, Context};
 use aptos_bounded_executor::BoundedExecutor;
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
-use aptos_config::config::{ConsensusConfig, DagConsensusConfig, ExecutionConfig, NodeConfig};
+use aptos_config::config::{
+    transaction_filter_type::ReloadableFilter, ConsensusConfig, DagConsensusConfig,
+    ExecutionConfig, NodeConfig,
+};
 use aptos_consensus_types::{
     common::{Author, Round},
     epoch_retrieval::EpochRetrievalRequest,
@@ -173,6 +176,7 @@ pub struct EpochManager<P: OnChainConfigProvider> {
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
     pending_blocks: Arc<Mutex<PendingBlocks>>,
     key_storage: PersistentSafetyStorage,
+    quorum_store_transaction_filter: ReloadableFilter,
 }
 
 impl<P: OnChainConfigProvider> EpochManager<P> {
@@ -193,6 +197,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         vtxn_pool: VTxnPoolState,
         rand_storage: Arc<dyn RandStorage<AugmentedData>>,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
+        quorum_store_transaction_filter: ReloadableFilter,
     ) -> Self {
         let author = node_config.validator_network.as_ref().unwrap().peer_id();
         let config = node_config.consensus.clone();
@@ -244,6 +249,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             consensus_publisher,
             pending_blocks: Arc::new(Mutex::new(PendingBlocks::new())),
             key_storage,
+            quorum_store_transaction_filter,
         }
     }
 
@@ -703,6 +709,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 self.quorum_store_storage.clone(),
                 !consensus_config.is_dag_enabled(),
                 consensus_key,
+                self.quorum_store_transaction_filter.clone(),
             ))
         } else {
             info!("Building DirectMempool");
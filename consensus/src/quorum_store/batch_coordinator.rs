@@ -14,6 +14,7 @@ use crate::{
     },
 };
 use anyhow::ensure;
+use aptos_config::config::transaction_filter_type::ReloadableFilter;
 use aptos_consensus_types::payload::TDataInfo;
 use aptos_logger::prelude::*;
 use aptos_short_hex_str::AsShortHexStr;
@@ -42,6 +43,7 @@ pub struct BatchCoordinator {
     max_total_txns: u64,
     max_total_bytes: u64,
     batch_expiry_gap_when_init_usecs: u64,
+    transaction_filter: ReloadableFilter,
 }
 
 impl BatchCoordinator {
@@ -56,6 +58,7 @@ impl BatchCoordinator {
         max_total_txns: u64,
         max_total_bytes: u64,
         batch_expiry_gap_when_init_usecs: u64,
+        transaction_filter: ReloadableFilter,
     ) -> Self {
         Self {
             my_peer_id,
@@ -68,6 +71,7 @@ impl BatchCoordinator {
             max_total_txns,
             max_total_bytes,
             batch_expiry_gap_when_init_usecs,
+            transaction_filter,
         }
     }
 
@@ -145,6 +149,25 @@ impl BatchCoordinator {
         Ok(())
     }
 
+    /// Ensures that no transaction in the batches is denied by the transaction filter
+    fn ensure_allowed_by_filter(&self, batches: &[Batch]) -> anyhow::Result<()> {
+        let filter = self.transaction_filter.filter();
+        if filter.is_empty() {
+            return Ok(());
+        }
+        for batch in batches.iter() {
+            for txn in batch.txns() {
+                ensure!(
+                    filter.allows_transaction(txn),
+                    "Batch {} contains filtered transaction {}",
+                    batch.batch_id(),
+                    txn.committed_hash(),
+                );
+            }
+        }
+        Ok(())
+    }
+
     async fn handle_batches_msg(&mut self, author: PeerId, batches: Vec<Batch>) {
         if let Err(e) = self.ensure_max_limits(&batches) {
             error!("Batch from {}: {}", author, e);
@@ -152,6 +175,14 @@ impl BatchCoordinator {
             return;
         }
 
+        if author != self.my_peer_id {
+            if let Err(e) = self.ensure_allowed_by_filter(&batches) {
+                warn!("Batch from {}: {}", author, e);
+                counters::RECEIVED_BATCH_REJECTED_BY_FILTER.inc();
+                return;
+            }
+        }
+
         let Some(batch) = batches.first() else {
             error!("Empty batch received from {}", author.short_str().as_str());
             return;
@@ -25,7 +25,9 @@ use crate::{
     round_manager::VerifiedEvent,
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
-use aptos_config::config::{QuorumStoreConfig, SecureBackend};
+use aptos_config::config::{
+    transaction_filter_type::ReloadableFilter, QuorumStoreConfig, SecureBackend,
+};
 use aptos_consensus_types::{
     common::Author, proof_of_store::ProofCache, request_response::GetPayloadCommand,
 };
@@ -149,6 +151,7 @@ pub struct InnerBuilder {
     batch_reader: Option<Arc<dyn BatchReader>>,
     broadcast_proofs: bool,
     consensus_key: Arc<PrivateKey>,
+    transaction_filter: ReloadableFilter,
 }
 
 impl InnerBuilder {
@@ -169,6 +172,7 @@ impl InnerBuilder {
         quorum_store_storage: Arc<dyn QuorumStoreStorage>,
         broadcast_proofs: bool,
         consensus_key: Arc<PrivateKey>,
+        transaction_filter: ReloadableFilter,
     ) -> Self {
         let (coordinator_tx, coordinator_rx) = futures_channel::mpsc::channel(config.channel_size);
         let (batch_generator_cmd_tx, batch_generator_cmd_rx) =
@@ -225,6 +229,7 @@ impl InnerBuilder {
             batch_reader: None,
             broadcast_proofs,
             consensus_key,
+            transaction_filter,
         }
     }
 
@@ -327,6 +332,7 @@ impl InnerBuilder {
                 self.config.receiver_max_total_txns as u64,
                 self.config.receiver_max_total_bytes as u64,
                 self.config.batch_expiry_gap_when_init_usecs,
+                self.transaction_filter.clone(),
             );
             #[allow(unused_variables)]
             let name = format!("batch_coordinator-{}", i);
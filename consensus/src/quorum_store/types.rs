@@ -193,6 +193,10 @@ impl Batch {
         self.payload.into_transactions()
     }
 
+    pub fn txns(&self) -> &[SignedTransaction] {
+        self.payload.txns()
+    }
+
     pub fn batch_info(&self) -> &BatchInfo {
         &self.batch_info
     }
@@ -1,16 +1,23 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_config::config::transaction_filter_type::Filter;
+use aptos_config::config::transaction_filter_type::{Filter, ReloadableFilter};
 use aptos_crypto::HashValue;
 use aptos_types::transaction::SignedTransaction;
 
 pub struct TransactionFilter {
-    filter: Filter,
+    filter: ReloadableFilter,
 }
 
 impl TransactionFilter {
+    #[cfg(test)]
     pub(crate) fn new(filter: Filter) -> Self {
+        Self::reloadable(ReloadableFilter::new(filter))
+    }
+
+    /// Creates a transaction filter that picks up replacements of the given filter (e.g.,
+    /// made via the admin service) for every subsequent block.
+    pub(crate) fn reloadable(filter: ReloadableFilter) -> Self {
         Self { filter }
     }
 
@@ -20,12 +27,13 @@ impl TransactionFilter {
         timestamp: u64,
         txns: Vec<SignedTransaction>,
     ) -> Vec<SignedTransaction> {
+        let filter = self.filter.filter();
         // Special case for no filter to avoid unnecessary iteration through all transactions in the default case
-        if self.filter.is_empty() {
+        if filter.is_empty() {
             return txns;
         }
         txns.into_iter()
-            .filter(|txn| self.filter.allows(block_id, timestamp, txn))
+            .filter(|txn| filter.allows(block_id, timestamp, txn))
             .collect()
     }
 }
@@ -33,33 +41,57 @@ impl TransactionFilter {
 #[cfg(test)]
 mod test {
     use crate::transaction_filter::TransactionFilter;
-    use aptos_config::config::transaction_filter_type::Filter;
-    use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
+    use aptos_config::config::transaction_filter_type::{
+        AuthenticatorType, Filter, Matcher, ReloadableFilter,
+    };
+    use aptos_crypto::{
+        ed25519::Ed25519PrivateKey,
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        HashValue, PrivateKey, SigningKey, Uniform,
+    };
     use aptos_types::{
         chain_id::ChainId,
         move_utils::MemberId,
-        transaction::{EntryFunction, RawTransaction, SignedTransaction, TransactionPayload},
+        transaction::{
+            authenticator::{AnyPublicKey, TransactionAuthenticator},
+            EntryFunction, Multisig, MultisigTransactionPayload, RawTransaction, Script,
+            SignedTransaction, TransactionArgument, TransactionPayload,
+        },
     };
     use move_core_types::account_address::AccountAddress;
 
     fn create_signed_transaction(function: MemberId) -> SignedTransaction {
-        let private_key = Ed25519PrivateKey::generate_for_testing();
-        let public_key = private_key.public_key();
-        let sender = AccountAddress::random();
-        let sequence_number = 0;
         let MemberId {
             module_id,
             member_id: function_id,
         } = function;
-
         let payload = TransactionPayload::EntryFunction(EntryFunction::new(
             module_id,
             function_id,
             vec![],
             vec![],
         ));
-        let raw_transaction =
-            RawTransaction::new(sender, sequence_number, payload, 0, 0, 0, ChainId::new(10));
+        create_signed_transaction_with_payload(payload, 0)
+    }
+
+    fn create_signed_transaction_with_payload(
+        payload: TransactionPayload,
+        gas_unit_price: u64,
+    ) -> SignedTransaction {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let public_key = private_key.public_key();
+        let sender = AccountAddress::random();
+        let sequence_number = 0;
+
+        let raw_transaction = RawTransaction::new(
+            sender,
+            sequence_number,
+            payload,
+            0,
+            gas_unit_price,
+            0,
+            ChainId::new(10),
+        );
 
         SignedTransaction::new(
             raw_transaction.clone(),
@@ -287,4 +319,264 @@ mod test {
         let filtered_txns = allow_list_filter.filter(block_id, 0, txns.clone());
         assert_eq!(filtered_txns, txns[4..].to_vec());
     }
+
+    #[test]
+    fn test_payload_type_filter() {
+        let script = Script::new(vec![1, 2, 3], vec![], vec![]);
+        let multisig_address = AccountAddress::random();
+        let entry_function = EntryFunction::new(
+            str::parse("0x1::test").unwrap(),
+            str::parse("multisig").unwrap(),
+            vec![],
+            vec![],
+        );
+        let txns = vec![
+            create_signed_transaction(str::parse("0x1::test::add").unwrap()),
+            create_signed_transaction_with_payload(TransactionPayload::Script(script.clone()), 0),
+            create_signed_transaction_with_payload(
+                TransactionPayload::Multisig(Multisig {
+                    multisig_address,
+                    transaction_payload: Some(MultisigTransactionPayload::EntryFunction(
+                        entry_function,
+                    )),
+                }),
+                0,
+            ),
+        ];
+        let block_id = HashValue::random();
+
+        let script_filter = TransactionFilter::new(Filter::empty().add_deny(Matcher::Script));
+        let filtered_txns = script_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, vec![txns[0].clone(), txns[2].clone()]);
+
+        let script_hash_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::ScriptHash(HashValue::sha3_256_of(script.code()))),
+        );
+        let filtered_txns = script_hash_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, vec![txns[0].clone(), txns[2].clone()]);
+
+        let multisig_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::MultisigAddress(multisig_address)),
+        );
+        let filtered_txns = multisig_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[0..2].to_vec());
+
+        let multisig_entry_function_filter =
+            TransactionFilter::new(Filter::empty().add_deny(Matcher::MultisigEntryFunction(
+                AccountAddress::ONE,
+                "test".to_string(),
+                "multisig".to_string(),
+            )));
+        let filtered_txns = multisig_entry_function_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[0..2].to_vec());
+    }
+
+    #[test]
+    fn test_combinator_filter() {
+        let txns: Vec<_> = [50, 100, 150, 200]
+            .into_iter()
+            .map(|gas_unit_price| {
+                let payload = get_transactions()[0].payload().clone();
+                create_signed_transaction_with_payload(payload, gas_unit_price)
+            })
+            .collect();
+        let block_id = HashValue::random();
+
+        // Only allow transactions with a gas unit price in (50, 200)
+        let range_filter = TransactionFilter::new(
+            Filter::empty()
+                .add_allow(Matcher::AllOf(vec![
+                    Matcher::GasUnitPriceGreaterThan(50),
+                    Matcher::GasUnitPriceLessThan(200),
+                ]))
+                .add_deny_all(),
+        );
+        let filtered_txns = range_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[1..3].to_vec());
+
+        // Deny transactions from either of the first two senders
+        let any_of_filter = TransactionFilter::new(Filter::empty().add_deny(Matcher::AnyOf(vec![
+            Matcher::Sender(txns[0].sender()),
+            Matcher::Sender(txns[1].sender()),
+        ])));
+        let filtered_txns = any_of_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[2..].to_vec());
+
+        // Deny everything except transactions from the first sender
+        let not_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::Not(Box::new(Matcher::Sender(txns[0].sender())))),
+        );
+        let filtered_txns = not_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[0..1].to_vec());
+    }
+
+    #[test]
+    fn test_authenticator_filter() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+
+        let authenticator_type_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::AuthenticatorType(AuthenticatorType::Ed25519)),
+        );
+        let filtered_txns = authenticator_type_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, vec![]);
+
+        let authenticator_type_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::AuthenticatorType(AuthenticatorType::Keyless)),
+        );
+        let filtered_txns = authenticator_type_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns);
+
+        let public_key = match txns[0].authenticator() {
+            TransactionAuthenticator::Ed25519 { public_key, .. } => public_key,
+            _ => panic!("Unexpected transaction authenticator"),
+        };
+        let public_key_filter = TransactionFilter::new(
+            Filter::empty().add_deny(Matcher::PublicKey(AnyPublicKey::ed25519(public_key))),
+        );
+        let filtered_txns = public_key_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[1..].to_vec());
+
+        // Public keys are also matched against the keys of multi-ed25519 signers
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let raw_transaction = txns[0].clone().into_raw_transaction();
+        let multi_ed25519_txn = SignedTransaction::new_multisig(
+            raw_transaction.clone(),
+            MultiEd25519PublicKey::from(private_key.public_key()),
+            MultiEd25519Signature::from(private_key.sign(&raw_transaction).unwrap()),
+        );
+        let public_key_filter = TransactionFilter::new(Filter::empty().add_deny(
+            Matcher::PublicKey(AnyPublicKey::ed25519(private_key.public_key())),
+        ));
+        let filtered_txns =
+            public_key_filter.filter(block_id, 0, vec![multi_ed25519_txn, txns[1].clone()]);
+        assert_eq!(filtered_txns, vec![txns[1].clone()]);
+    }
+
+    #[test]
+    fn test_address_argument_filter() {
+        let address = AccountAddress::random();
+        let other_address = AccountAddress::random();
+        let create_transaction = |args: Vec<Vec<u8>>| {
+            let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+                str::parse("0x1::coin").unwrap(),
+                str::parse("transfer").unwrap(),
+                vec![],
+                args,
+            ));
+            create_signed_transaction_with_payload(payload, 0)
+        };
+        let txns = vec![
+            create_transaction(vec![bcs::to_bytes(&address).unwrap()]),
+            create_transaction(vec![bcs::to_bytes(&vec![other_address, address]).unwrap()]),
+            create_transaction(vec![bcs::to_bytes(&other_address).unwrap()]),
+            create_signed_transaction_with_payload(
+                TransactionPayload::Script(Script::new(vec![], vec![], vec![
+                    TransactionArgument::Address(address),
+                ])),
+                0,
+            ),
+        ];
+        let block_id = HashValue::random();
+
+        let address_argument_filter =
+            TransactionFilter::new(Filter::empty().add_deny(Matcher::AddressArgument(address)));
+        let filtered_txns = address_argument_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, vec![txns[2].clone()]);
+    }
+
+    #[test]
+    fn test_filter_outside_of_block() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+        let filter = Filter::empty()
+            .add_deny_block_id(block_id)
+            .add_deny_sender(txns[0].sender());
+
+        // Block based matchers never match outside of a block
+        assert!(!filter.allows_transaction(&txns[0]));
+        assert!(filter.allows_transaction(&txns[1]));
+        assert!(!filter.allows(block_id, 0, &txns[1]));
+
+        // Negated and combined block based matchers are also indeterminate outside of a block
+        let filter = Filter::empty()
+            .add_deny(Matcher::Not(Box::new(Matcher::BlockTimeStampGreaterThan(
+                1000,
+            ))))
+            .add_deny(Matcher::AnyOf(vec![
+                Matcher::BlockId(block_id),
+                Matcher::Sender(txns[0].sender()),
+            ]))
+            .add_deny(Matcher::AllOf(vec![
+                Matcher::BlockId(block_id),
+                Matcher::Sender(txns[1].sender()),
+            ]));
+        assert!(!filter.allows_transaction(&txns[0]));
+        assert!(filter.allows_transaction(&txns[1]));
+        assert!(filter.allows_transaction(&txns[2]));
+        assert!(!filter.allows(block_id, 0, &txns[2]));
+        assert!(filter.allows(HashValue::random(), 2000, &txns[2]));
+    }
+
+    #[test]
+    fn test_composite_combinator_filter() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+        let filter = serde_yaml::from_str::<Filter>(r#"
+            rules:
+                - Deny:
+                    AllOf:
+                        - ModuleAddress: "0000000000000000000000000000000000000000000000000000000000000001"
+                        - Not:
+                            EntryFunction:
+                                - "0000000000000000000000000000000000000000000000000000000000000001"
+                                - test
+                                - check
+                - Deny:
+                    AnyOf:
+                        - Script
+                        - GasUnitPriceLessThan: 0
+              "#).unwrap();
+
+        let block_list_filter = TransactionFilter::new(filter);
+        let filtered_txns = block_list_filter.filter(block_id, 0, txns.clone());
+        let mut expected_txns = vec![txns[1].clone()];
+        expected_txns.extend_from_slice(&txns[4..]);
+        assert_eq!(filtered_txns, expected_txns);
+    }
+
+    #[test]
+    fn test_reloadable_filter() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+        let reloadable_filter = ReloadableFilter::new(Filter::empty());
+        let transaction_filter = TransactionFilter::reloadable(reloadable_filter.clone());
+        assert_eq!(reloadable_filter.version(), 0);
+        assert_eq!(transaction_filter.filter(block_id, 0, txns.clone()), txns);
+
+        // Replacing the filter is observed by the transaction filter
+        let version =
+            reloadable_filter.replace(Filter::empty().add_deny_module_address(AccountAddress::ONE));
+        assert_eq!(version, 1);
+        assert_eq!(reloadable_filter.version(), 1);
+        let filtered_txns = transaction_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[4..].to_vec());
+
+        // A stale version is rejected and the filter is left unchanged
+        assert_eq!(
+            reloadable_filter.compare_and_replace(0, Filter::empty()),
+            Err(1)
+        );
+        assert_eq!(
+            transaction_filter.filter(block_id, 0, txns.clone()).len(),
+            3
+        );
+
+        // Reverting the filter bumps the version again
+        assert_eq!(
+            reloadable_filter.compare_and_replace(1, Filter::empty()),
+            Ok(2)
+        );
+        assert_eq!(transaction_filter.filter(block_id, 0, txns.clone()), txns);
+    }
 }
@@ -18,7 +18,10 @@ use crate::{
 use aptos_bounded_executor::BoundedExecutor;
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
 use aptos_config::{
-    config::{NodeConfig, WaypointConfig},
+    config::{
+        transaction_filter_type::{Filter, ReloadableFilter},
+        NodeConfig, WaypointConfig,
+    },
     generator::{self, ValidatorSwarm},
     network_id::{NetworkId, PeerNetworkId},
 };
@@ -164,6 +167,7 @@ impl SMRNode {
             vtxn_pool,
             Arc::new(InMemRandDb::new()),
             None,
+            ReloadableFilter::new(Filter::empty()),
         );
         let (network_task, network_receiver) =
             NetworkTask::new(network_service_events, self_receiver);
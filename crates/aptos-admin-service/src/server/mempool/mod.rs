@@ -1,13 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_crypto::HashValue;
 use aptos_logger::info;
-use aptos_mempool::{MempoolClientRequest, MempoolClientSender};
+use aptos_mempool::{MempoolClientRequest, MempoolClientSender, MempoolTransactionStatus};
 use aptos_system_utils::utils::{reply_with, reply_with_status};
 use aptos_types::account_address::AccountAddress;
 use futures_channel::oneshot::Canceled;
 use http::{Request, Response, StatusCode};
 use hyper::Body;
+use std::collections::HashMap;
 
 pub async fn mempool_handle_parking_lot_address_request(
     _req: Request<Body>,
@@ -53,3 +55,81 @@ async fn get_parking_lot_addresses(
         },
     }
 }
+
+pub async fn mempool_handle_transaction_status_request(
+    req: Request<Body>,
+    mempool_client_sender: MempoolClientSender,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let hash: HashValue = match query_pairs.get("hash") {
+        Some(val) => match val.parse() {
+            Ok(val) => val,
+            Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err.to_string())),
+        },
+        None => {
+            return Ok(reply_with_status(
+                StatusCode::BAD_REQUEST,
+                "Missing transaction hash.",
+            ))
+        },
+    };
+    let bcs: bool = match query_pairs.get("bcs") {
+        Some(val) => match val.parse() {
+            Ok(val) => val,
+            Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err.to_string())),
+        },
+        None => false,
+    };
+
+    match get_transaction_status(mempool_client_sender, hash).await {
+        Ok(Some(status)) => {
+            info!("Finished getting status of transaction ({hash}) from mempool.");
+            if bcs {
+                match bcs::to_bytes(&status) {
+                    Ok(status) => Ok(reply_with(vec![], status)),
+                    Err(e) => {
+                        info!("Failed to bcs serialize transaction status from mempool: {e:?}");
+                        Ok(reply_with_status(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            e.to_string(),
+                        ))
+                    },
+                }
+            } else {
+                Ok(reply_with(vec![], format!("{status:#?}\n")))
+            }
+        },
+        Ok(None) => Ok(reply_with_status(
+            StatusCode::NOT_FOUND,
+            format!("Transaction ({hash}) is not in mempool."),
+        )),
+        Err(e) => {
+            info!("Failed to get transaction status from mempool: {e:?}");
+            Ok(reply_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                e.to_string(),
+            ))
+        },
+    }
+}
+
+async fn get_transaction_status(
+    mempool_client_sender: MempoolClientSender,
+    hash: HashValue,
+) -> Result<Option<MempoolTransactionStatus>, Canceled> {
+    let (sender, receiver) = futures_channel::oneshot::channel();
+
+    match mempool_client_sender
+        .clone()
+        .try_send(MempoolClientRequest::GetTransactionStatusByHash(
+            hash, sender,
+        )) {
+        Ok(_) => receiver.await,
+        Err(e) => {
+            info!("Failed to send request for GetTransactionStatusByHash: {e:?}");
+            Err(Canceled)
+        },
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_config::config::{AuthenticationConfig, NodeConfig};
+use aptos_config::config::{
+    transaction_filter_type::TransactionFilters, AuthenticationConfig, NodeConfig,
+};
 use aptos_consensus::{
     persistent_liveness_storage::StorageWriteProxy, quorum_store::quorum_store_db::QuorumStoreDB,
 };
@@ -15,6 +17,7 @@ use aptos_system_utils::{
     profiling::handle_cpu_profiling_request, thread_dump::handle_thread_dump_request,
 };
 use hyper::{
+    server::conn::AddrStream,
     service::{make_service_fn, service_fn},
     Body, Request, Response, Server, StatusCode,
 };
@@ -28,15 +31,19 @@ use tokio::runtime::Runtime;
 
 mod consensus;
 mod mempool;
+mod transaction_filter;
 
 #[derive(Default)]
 pub struct Context {
     authentication_configs: Vec<AuthenticationConfig>,
+    enable_transaction_filter_updates: bool,
 
     aptos_db: RwLock<Option<Arc<DbReaderWriter>>>,
     consensus_db: RwLock<Option<Arc<StorageWriteProxy>>>,
     quorum_store_db: RwLock<Option<Arc<QuorumStoreDB>>>,
     mempool_client_sender: RwLock<Option<MempoolClientSender>>,
+    transaction_filters: RwLock<Option<TransactionFilters>>,
+    transaction_filter_audit_log: transaction_filter::FilterAuditLog,
 }
 
 impl Context {
@@ -56,6 +63,10 @@ impl Context {
     fn set_mempool_client_sender(&self, mempool_client_sender: MempoolClientSender) {
         *self.mempool_client_sender.write() = Some(mempool_client_sender);
     }
+
+    fn set_transaction_filters(&self, transaction_filters: TransactionFilters) {
+        *self.transaction_filters.write() = Some(transaction_filters);
+    }
 }
 
 pub struct AdminService {
@@ -90,6 +101,9 @@ impl AdminService {
             runtime,
             context: Arc::new(Context {
                 authentication_configs: node_config.admin_service.authentication_configs.clone(),
+                enable_transaction_filter_updates: node_config
+                    .admin_service
+                    .enable_transaction_filter_updates,
                 ..Default::default()
             }),
         };
@@ -119,14 +133,19 @@ impl AdminService {
             .set_mempool_client_sender(mempool_client_sender)
     }
 
+    pub fn set_transaction_filters(&self, transaction_filters: TransactionFilters) {
+        self.context.set_transaction_filters(transaction_filters)
+    }
+
     fn start(&self, address: SocketAddr, enabled: bool) {
         let context = self.context.clone();
         self.runtime.spawn(async move {
-            let make_service = make_service_fn(move |_conn| {
+            let make_service = make_service_fn(move |conn: &AddrStream| {
                 let context = context.clone();
+                let remote_address = conn.remote_addr();
                 async move {
                     Ok::<_, Infallible>(service_fn(move |req| {
-                        Self::serve_requests(context.clone(), req, enabled)
+                        Self::serve_requests(context.clone(), req, remote_address, enabled)
                     }))
                 }
             });
@@ -140,6 +159,7 @@ impl AdminService {
     async fn serve_requests(
         context: Arc<Context>,
         req: Request<Body>,
+        remote_address: SocketAddr,
         enabled: bool,
     ) -> hyper::Result<Response<Body>> {
         if !enabled {
@@ -237,6 +257,64 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::GET, "/debug/mempool/transaction") => {
+                let mempool_client_sender = context.mempool_client_sender.read().clone();
+                if let Some(mempool_client_sender) = mempool_client_sender {
+                    mempool::mempool_handle_transaction_status_request(req, mempool_client_sender)
+                        .await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Mempool is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::GET, "/debug/transaction-filter") => {
+                let transaction_filters = context.transaction_filters.read().clone();
+                if let Some(transaction_filters) = transaction_filters {
+                    transaction_filter::handle_get_transaction_filter_request(
+                        req,
+                        transaction_filters,
+                    )
+                    .await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Transaction filters are not available.",
+                    ))
+                }
+            },
+            (hyper::Method::POST, "/debug/transaction-filter") => {
+                if let Some(response) = transaction_filter::check_transaction_filter_updates_allowed(
+                    context.enable_transaction_filter_updates,
+                    !context.authentication_configs.is_empty(),
+                    remote_address,
+                ) {
+                    return Ok(response);
+                }
+                let transaction_filters = context.transaction_filters.read().clone();
+                if let Some(transaction_filters) = transaction_filters {
+                    transaction_filter::handle_replace_transaction_filter_request(
+                        req,
+                        transaction_filters,
+                        &context.transaction_filter_audit_log,
+                        remote_address,
+                    )
+                    .await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Transaction filters are not available.",
+                    ))
+                }
+            },
+            (hyper::Method::GET, "/debug/transaction-filter/audit-log") => {
+                transaction_filter::handle_transaction_filter_audit_log_request(
+                    req,
+                    &context.transaction_filter_audit_log,
+                )
+                .await
+            },
             _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
         }
     }
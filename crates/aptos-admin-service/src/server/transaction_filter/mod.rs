@@ -0,0 +1,401 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::transaction_filter_type::{Filter, ReloadableFilter, TransactionFilters};
+use aptos_infallible::{duration_since_epoch, Mutex};
+use aptos_logger::{info, warn};
+use aptos_system_utils::utils::{reply_with, reply_with_status};
+use http::{Request, Response, StatusCode};
+use hyper::Body;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+};
+
+/// The maximum number of filter changes kept in the audit log. Every change is also logged,
+/// so older changes can be found in the node logs.
+const MAX_AUDIT_LOG_ENTRIES: usize = 100;
+
+const CONSENSUS_TARGET: &str = "consensus";
+const QUORUM_STORE_TARGET: &str = "quorum-store";
+const MEMPOOL_TARGET: &str = "mempool";
+
+/// A replacement of one of the transaction filters made via the admin service
+#[derive(Clone, Debug, Serialize)]
+struct FilterChange {
+    /// The time of the change (in microseconds since the Unix epoch)
+    timestamp_usecs: u64,
+    /// The address of the client that made the change
+    remote_address: SocketAddr,
+    target: &'static str,
+    previous_version: u64,
+    version: u64,
+    filter: Filter,
+}
+
+/// A bounded, in-memory log of the transaction filter changes (most recent last)
+#[derive(Default)]
+pub struct FilterAuditLog {
+    changes: Mutex<VecDeque<FilterChange>>,
+}
+
+impl FilterAuditLog {
+    fn record(&self, change: FilterChange) {
+        let mut changes = self.changes.lock();
+        if changes.len() >= MAX_AUDIT_LOG_ENTRIES {
+            changes.pop_front();
+        }
+        changes.push_back(change);
+    }
+
+    fn changes(&self) -> Vec<FilterChange> {
+        self.changes.lock().iter().cloned().collect()
+    }
+}
+
+#[derive(Serialize)]
+struct FilterSnapshot<'a> {
+    target: &'static str,
+    version: u64,
+    filter: &'a Filter,
+}
+
+pub async fn handle_get_transaction_filter_request(
+    req: Request<Body>,
+    transaction_filters: TransactionFilters,
+) -> hyper::Result<Response<Body>> {
+    let query_pairs = parse_query(&req);
+    let (target, filter) = match get_target_filter(&query_pairs, &transaction_filters) {
+        Ok(target_filter) => target_filter,
+        Err(response) => return Ok(response),
+    };
+
+    let (version, filter) = filter.snapshot();
+    Ok(reply_with_yaml(&FilterSnapshot {
+        target,
+        version,
+        filter: &filter,
+    }))
+}
+
+/// Rejects a filter replacement that isn't allowed by the admin service config. Returns None
+/// if the replacement is allowed.
+pub fn check_transaction_filter_updates_allowed(
+    updates_enabled: bool,
+    authentication_enabled: bool,
+    remote_address: SocketAddr,
+) -> Option<Response<Body>> {
+    let error = if !updates_enabled {
+        "Transaction filter updates are not enabled."
+    } else if !authentication_enabled {
+        "Transaction filter updates require authentication to be configured."
+    } else {
+        return None;
+    };
+    warn!(
+        remote_address = %remote_address,
+        "Rejected a transaction filter update: {}", error
+    );
+    Some(reply_with_status(StatusCode::FORBIDDEN, error))
+}
+
+pub async fn handle_replace_transaction_filter_request(
+    req: Request<Body>,
+    transaction_filters: TransactionFilters,
+    audit_log: &FilterAuditLog,
+    remote_address: SocketAddr,
+) -> hyper::Result<Response<Body>> {
+    let query_pairs = parse_query(&req);
+    let (target, reloadable_filter) = match get_target_filter(&query_pairs, &transaction_filters) {
+        Ok(target_filter) => target_filter,
+        Err(response) => return Ok(response),
+    };
+    let expected_version: Option<u64> = match query_pairs.get("expected_version") {
+        Some(val) => match val.parse() {
+            Ok(val) => Some(val),
+            Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err.to_string())),
+        },
+        None => None,
+    };
+
+    let body = hyper::body::to_bytes(req.into_body()).await?;
+    let filter: Filter = match serde_yaml::from_slice(&body) {
+        Ok(filter) => filter,
+        Err(err) => {
+            return Ok(reply_with_status(
+                StatusCode::BAD_REQUEST,
+                format!("Failed to parse the transaction filter: {err}"),
+            ))
+        },
+    };
+
+    let version = match expected_version {
+        Some(expected_version) => {
+            match reloadable_filter.compare_and_replace(expected_version, filter.clone()) {
+                Ok(version) => version,
+                Err(current_version) => {
+                    warn!(
+                        remote_address = %remote_address,
+                        filter_target = target,
+                        "Rejected a transaction filter update: expected version {}, current version {}",
+                        expected_version,
+                        current_version
+                    );
+                    return Ok(reply_with_status(
+                        StatusCode::CONFLICT,
+                        format!(
+                            "The {target} transaction filter is at version {current_version}, expected version {expected_version}."
+                        ),
+                    ));
+                },
+            }
+        },
+        None => reloadable_filter.replace(filter.clone()),
+    };
+
+    info!(
+        remote_address = %remote_address,
+        filter_target = target,
+        version = version,
+        "Replaced the {} transaction filter (version {} -> {}): {:?}",
+        target,
+        version - 1,
+        version,
+        filter
+    );
+    audit_log.record(FilterChange {
+        timestamp_usecs: duration_since_epoch().as_micros() as u64,
+        remote_address,
+        target,
+        previous_version: version - 1,
+        version,
+        filter: filter.clone(),
+    });
+
+    Ok(reply_with_yaml(&FilterSnapshot {
+        target,
+        version,
+        filter: &filter,
+    }))
+}
+
+pub async fn handle_transaction_filter_audit_log_request(
+    _req: Request<Body>,
+    audit_log: &FilterAuditLog,
+) -> hyper::Result<Response<Body>> {
+    Ok(reply_with_yaml(&audit_log.changes()))
+}
+
+fn parse_query(req: &Request<Body>) -> HashMap<String, String> {
+    let query = req.uri().query().unwrap_or("");
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Returns the name and the filter of the target given in the query, or the error response
+fn get_target_filter<'a>(
+    query_pairs: &HashMap<String, String>,
+    transaction_filters: &'a TransactionFilters,
+) -> Result<(&'static str, &'a ReloadableFilter), Response<Body>> {
+    match query_pairs.get("target").map(String::as_str) {
+        Some(CONSENSUS_TARGET) => Ok((CONSENSUS_TARGET, &transaction_filters.consensus)),
+        Some(QUORUM_STORE_TARGET) => Ok((QUORUM_STORE_TARGET, &transaction_filters.quorum_store)),
+        Some(MEMPOOL_TARGET) => Ok((MEMPOOL_TARGET, &transaction_filters.mempool)),
+        Some(target) => Err(reply_with_status(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown transaction filter target: {target}. Expected one of: {CONSENSUS_TARGET}, {QUORUM_STORE_TARGET}, {MEMPOOL_TARGET}."
+            ),
+        )),
+        None => Err(reply_with_status(
+            StatusCode::BAD_REQUEST,
+            "Missing transaction filter target.",
+        )),
+    }
+}
+
+fn reply_with_yaml<T: Serialize>(value: &T) -> Response<Body> {
+    match serde_yaml::to_string(value) {
+        Ok(yaml) => reply_with(vec![], yaml),
+        Err(e) => {
+            info!("Failed to serialize transaction filter response: {e:?}");
+            reply_with_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_config::config::NodeConfig;
+    use aptos_types::account_address::AccountAddress;
+
+    fn remote_address() -> SocketAddr {
+        "127.0.0.1:9000".parse().unwrap()
+    }
+
+    fn create_request(method: http::Method, query: &str, body: String) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(format!("/debug/transaction-filter?{}", query))
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn response_body(response: Response<Body>) -> String {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_filter() {
+        let transaction_filters = TransactionFilters::new(&NodeConfig::default());
+
+        // Get the mempool filter
+        let request = create_request(http::Method::GET, "target=mempool", String::new());
+        let response = handle_get_transaction_filter_request(request, transaction_filters.clone())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body(response).await;
+        assert!(body.contains("target: mempool"));
+        assert!(body.contains("version: 0"));
+
+        // Unknown and missing targets are rejected
+        for query in ["target=unknown", ""] {
+            let request = create_request(http::Method::GET, query, String::new());
+            let response =
+                handle_get_transaction_filter_request(request, transaction_filters.clone())
+                    .await
+                    .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_transaction_filter() {
+        let transaction_filters = TransactionFilters::new(&NodeConfig::default());
+        let audit_log = FilterAuditLog::default();
+        let filter = Filter::empty().add_deny_sender(AccountAddress::ONE);
+        let filter_yaml = serde_yaml::to_string(&filter).unwrap();
+
+        // Replace the consensus filter
+        let request = create_request(http::Method::POST, "target=consensus", filter_yaml.clone());
+        let response = handle_replace_transaction_filter_request(
+            request,
+            transaction_filters.clone(),
+            &audit_log,
+            remote_address(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(transaction_filters.consensus.snapshot(), (1, filter.into()));
+        assert_eq!(transaction_filters.mempool.version(), 0);
+
+        // A replacement with a stale version is rejected
+        let request = create_request(
+            http::Method::POST,
+            "target=consensus&expected_version=0",
+            filter_yaml.clone(),
+        );
+        let response = handle_replace_transaction_filter_request(
+            request,
+            transaction_filters.clone(),
+            &audit_log,
+            remote_address(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(transaction_filters.consensus.version(), 1);
+
+        // A replacement with the current version succeeds
+        let request = create_request(
+            http::Method::POST,
+            "target=consensus&expected_version=1",
+            serde_yaml::to_string(&Filter::empty()).unwrap(),
+        );
+        let response = handle_replace_transaction_filter_request(
+            request,
+            transaction_filters.clone(),
+            &audit_log,
+            remote_address(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(transaction_filters.consensus.version(), 2);
+        assert!(transaction_filters.consensus.filter().is_empty());
+
+        // An invalid filter is rejected
+        let request = create_request(
+            http::Method::POST,
+            "target=mempool",
+            "not a filter".to_string(),
+        );
+        let response = handle_replace_transaction_filter_request(
+            request,
+            transaction_filters.clone(),
+            &audit_log,
+            remote_address(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(transaction_filters.mempool.version(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_filter_audit_log() {
+        let transaction_filters = TransactionFilters::new(&NodeConfig::default());
+        let audit_log = FilterAuditLog::default();
+
+        // Make more changes than the audit log holds
+        for _ in 0..MAX_AUDIT_LOG_ENTRIES + 1 {
+            let request = create_request(
+                http::Method::POST,
+                "target=mempool",
+                serde_yaml::to_string(&Filter::empty()).unwrap(),
+            );
+            handle_replace_transaction_filter_request(
+                request,
+                transaction_filters.clone(),
+                &audit_log,
+                remote_address(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // Only the most recent changes are kept, with the caller address
+        let changes = audit_log.changes();
+        assert_eq!(changes.len(), MAX_AUDIT_LOG_ENTRIES);
+        assert_eq!(changes.first().unwrap().version, 2);
+        assert_eq!(
+            changes.last().unwrap().version,
+            MAX_AUDIT_LOG_ENTRIES as u64 + 1
+        );
+        assert!(changes
+            .iter()
+            .all(|change| change.remote_address == remote_address()));
+
+        let request = create_request(http::Method::GET, "", String::new());
+        let response = handle_transaction_filter_audit_log_request(request, &audit_log)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response_body(response)
+            .await
+            .contains("remote_address: 127.0.0.1:9000"));
+    }
+
+    #[test]
+    fn test_transaction_filter_updates_allowed() {
+        assert!(check_transaction_filter_updates_allowed(false, true, remote_address()).is_some());
+        assert!(check_transaction_filter_updates_allowed(true, false, remote_address()).is_some());
+        assert!(check_transaction_filter_updates_allowed(true, true, remote_address()).is_none());
+    }
+}
@@ -174,6 +174,23 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects all buckets that are full and not in use, returning how many were
+    /// removed. A full bucket allows no more than a newly created one, so this is safe to call
+    /// periodically to bound the memory used by keys that are no longer active.
+    pub fn garbage_collect_full_buckets(&self) -> usize {
+        let mut buckets = self.buckets.write();
+        let num_buckets = buckets.len();
+        buckets.retain(|_, bucket| {
+            if Arc::strong_count(bucket) > 1 {
+                return true;
+            }
+            let mut bucket = bucket.lock();
+            bucket.refill();
+            bucket.tokens < bucket.size
+        });
+        num_buckets - buckets.len()
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -549,4 +566,29 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_garbage_collect_full_buckets() {
+        let rate_limiter = TokenBucketRateLimiter::test(2, 1);
+
+        // Create a full bucket, a drained bucket and a full bucket that's in use
+        rate_limiter.bucket("full");
+        rate_limiter
+            .bucket("drained")
+            .lock()
+            .acquire_all_tokens(2)
+            .unwrap();
+        let _bucket_arc = rate_limiter.bucket("in use");
+        assert_num_keys(&rate_limiter, 3);
+
+        // Only the full bucket that's not in use is collected
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 1);
+        assert_num_keys(&rate_limiter, 2);
+        assert!(!rate_limiter.try_garbage_collect_key(&"full"));
+
+        // Once refilled, the drained bucket is collected too
+        sleep(Duration::from_secs(2));
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 1);
+        assert_num_keys(&rate_limiter, 1);
+    }
 }
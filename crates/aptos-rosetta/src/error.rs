@@ -300,16 +300,24 @@ impl From<RestError> for ApiError {
                     ApiError::SequenceNumberTooOld(Some(err.error.message))
                 },
                 AptosErrorCode::VmError => ApiError::VmError(Some(err.error.message)),
+                AptosErrorCode::TransactionFiltered => {
+                    ApiError::InvalidInput(Some(err.error.message))
+                },
                 AptosErrorCode::HealthCheckFailed => {
                     ApiError::InternalError(Some(err.error.message))
                 },
-                AptosErrorCode::MempoolIsFull => ApiError::MempoolIsFull(Some(err.error.message)),
+                AptosErrorCode::MempoolIsFull
+                | AptosErrorCode::SenderQuotaExceeded
+                | AptosErrorCode::RateLimited => ApiError::MempoolIsFull(Some(err.error.message)),
                 AptosErrorCode::WebFrameworkError => {
                     ApiError::InternalError(Some(err.error.message))
                 },
                 AptosErrorCode::BcsNotSupported => ApiError::InvalidInput(Some(err.error.message)),
                 AptosErrorCode::InternalError => ApiError::InternalError(Some(err.error.message)),
                 AptosErrorCode::ApiDisabled => ApiError::InternalError(Some(err.error.message)),
+                AptosErrorCode::TooManyEventStreams => {
+                    ApiError::InternalError(Some(err.error.message))
+                },
             },
             RestError::Bcs(_) => ApiError::DeserializationFailed(None),
             RestError::Json(_) => ApiError::DeserializationFailed(None),
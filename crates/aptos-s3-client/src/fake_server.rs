@@ -0,0 +1,245 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An in-process stand-in of an S3 compatible service, in the spirit of a local MinIO. It serves
+//! path style requests on a single bucket, doesn't check signatures, paginates listings in tiny
+//! pages and fails every `fail_every`th request with a 503 to exercise retries.
+
+use aptos_infallible::Mutex;
+use bytes::Bytes;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use warp::{
+    http::{HeaderMap, Method, Response, StatusCode},
+    hyper::Body,
+    path::FullPath,
+    Filter,
+};
+
+const LIST_PAGE_SIZE: usize = 2;
+
+#[derive(Default)]
+struct State {
+    objects: BTreeMap<String, Bytes>,
+    uploads: HashMap<String, BTreeMap<usize, Bytes>>,
+    next_upload_id: usize,
+}
+
+pub struct FakeS3Server {
+    bucket: String,
+    state: Mutex<State>,
+    num_requests: AtomicUsize,
+    fail_every: Option<usize>,
+}
+
+impl FakeS3Server {
+    /// Starts serving on an ephemeral local port, returns the endpoint.
+    pub fn start(bucket: &str, fail_every: Option<usize>) -> (Arc<Self>, String) {
+        let server = Arc::new(Self {
+            bucket: bucket.to_string(),
+            state: Mutex::new(State::default()),
+            num_requests: AtomicUsize::new(0),
+            fail_every,
+        });
+
+        let s = server.clone();
+        let route = warp::method()
+            .and(warp::path::full())
+            .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+            .and(warp::header::headers_cloned())
+            .and(warp::body::bytes())
+            .map(
+                move |method, path: FullPath, query: String, headers, body| {
+                    s.handle(method, path.as_str(), &query, &headers, body)
+                },
+            );
+        let (addr, svr): (SocketAddr, _) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(svr);
+
+        (server, format!("http://{}", addr))
+    }
+
+    pub fn object(&self, key: &str) -> Option<Bytes> {
+        self.state.lock().objects.get(key).cloned()
+    }
+
+    pub fn num_pending_uploads(&self) -> usize {
+        self.state.lock().uploads.len()
+    }
+
+    fn handle(
+        &self,
+        method: Method,
+        path: &str,
+        query: &str,
+        headers: &HeaderMap,
+        body: Bytes,
+    ) -> Response<Body> {
+        let n = self.num_requests.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.fail_every.map_or(false, |every| n % every == 0) {
+            return reply(StatusCode::SERVICE_UNAVAILABLE, "<Error>SlowDown</Error>");
+        }
+
+        let path = path.trim_start_matches('/');
+        let (bucket, key) = path.split_once('/').unwrap_or((path, ""));
+        if bucket != self.bucket {
+            return reply(StatusCode::NOT_FOUND, "<Error>NoSuchBucket</Error>");
+        }
+        let query: HashMap<String, String> = reqwest::Url::parse(&format!("http://x/?{}", query))
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect();
+
+        let mut state = self.state.lock();
+        match method {
+            Method::GET if key.is_empty() => Self::list(&state, &query),
+            Method::GET => match state.objects.get(key) {
+                Some(data) => get_range(data, headers),
+                None => reply(StatusCode::NOT_FOUND, "<Error>NoSuchKey</Error>"),
+            },
+            Method::HEAD if key.is_empty() => reply(StatusCode::OK, ""),
+            Method::HEAD => match state.objects.get(key) {
+                Some(_) => reply(StatusCode::OK, ""),
+                None => reply(StatusCode::NOT_FOUND, ""),
+            },
+            Method::PUT => {
+                if let Some(src) = headers.get("x-amz-copy-source") {
+                    let src = src.to_str().unwrap();
+                    let src_key = src.trim_start_matches(&format!("/{}/", self.bucket));
+                    match state.objects.get(src_key).cloned() {
+                        Some(data) => {
+                            state.objects.insert(key.to_string(), data);
+                            reply(StatusCode::OK, "<CopyObjectResult></CopyObjectResult>")
+                        },
+                        None => reply(StatusCode::NOT_FOUND, "<Error>NoSuchKey</Error>"),
+                    }
+                } else if let Some(upload_id) = query.get("uploadId") {
+                    let part_number: usize = query["partNumber"].parse().unwrap();
+                    match state.uploads.get_mut(upload_id) {
+                        Some(parts) => {
+                            parts.insert(part_number, body);
+                            Response::builder()
+                                .header("ETag", format!("\"etag-{}\"", part_number))
+                                .body(Body::empty())
+                                .unwrap()
+                        },
+                        None => reply(StatusCode::NOT_FOUND, "<Error>NoSuchUpload</Error>"),
+                    }
+                } else {
+                    state.objects.insert(key.to_string(), body);
+                    reply(StatusCode::OK, "")
+                }
+            },
+            Method::POST if query.contains_key("uploads") => {
+                state.next_upload_id += 1;
+                let upload_id = format!("upload-{}", state.next_upload_id);
+                state.uploads.insert(upload_id.clone(), BTreeMap::new());
+                reply(
+                    StatusCode::OK,
+                    &format!(
+                        "<InitiateMultipartUploadResult><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                        upload_id
+                    ),
+                )
+            },
+            Method::POST => {
+                let upload_id = &query["uploadId"];
+                let parts = match state.uploads.remove(upload_id) {
+                    Some(parts) => parts,
+                    None => return reply(StatusCode::NOT_FOUND, "<Error>NoSuchUpload</Error>"),
+                };
+                // Only complete with the parts listed in the request, in order.
+                let listed = String::from_utf8(body.to_vec()).unwrap();
+                let mut data = Vec::new();
+                for (i, s) in listed.split("<PartNumber>").skip(1).enumerate() {
+                    let part_number: usize = s[..s.find('<').unwrap()].parse().unwrap();
+                    assert_eq!(part_number, i + 1);
+                    data.extend_from_slice(&parts[&part_number]);
+                }
+                state.objects.insert(key.to_string(), data.into());
+                reply(
+                    StatusCode::OK,
+                    "<CompleteMultipartUploadResult></CompleteMultipartUploadResult>",
+                )
+            },
+            Method::DELETE if query.contains_key("uploadId") => {
+                state.uploads.remove(&query["uploadId"]);
+                reply(StatusCode::NO_CONTENT, "")
+            },
+            Method::DELETE => {
+                state.objects.remove(key);
+                reply(StatusCode::NO_CONTENT, "")
+            },
+            _ => reply(StatusCode::METHOD_NOT_ALLOWED, ""),
+        }
+    }
+
+    fn list(state: &State, query: &HashMap<String, String>) -> Response<Body> {
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let keys = state
+            .objects
+            .keys()
+            .filter(|k| k.starts_with(&prefix))
+            .filter(|k| match query.get("continuation-token") {
+                Some(token) => k.as_str() > token.as_str(),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        let page = &keys[..keys.len().min(LIST_PAGE_SIZE)];
+
+        let mut xml = String::from("<ListBucketResult>");
+        for key in page {
+            xml.push_str(&format!("<Contents><Key>{}</Key></Contents>", key));
+        }
+        if keys.len() > page.len() {
+            xml.push_str(&format!(
+                "<IsTruncated>true</IsTruncated><NextContinuationToken>{}</NextContinuationToken>",
+                page.last().unwrap()
+            ));
+        } else {
+            xml.push_str("<IsTruncated>false</IsTruncated>");
+        }
+        xml.push_str("</ListBucketResult>");
+        reply(StatusCode::OK, &xml)
+    }
+}
+
+fn get_range(data: &Bytes, headers: &HeaderMap) -> Response<Body> {
+    let range = match headers.get("range") {
+        Some(range) => range
+            .to_str()
+            .unwrap()
+            .trim_start_matches("bytes=")
+            .to_string(),
+        None => return Response::new(Body::from(data.clone())),
+    };
+    let (start, end) = range.split_once('-').unwrap();
+    let start: usize = start.parse().unwrap();
+    let end: usize = end.parse().unwrap();
+    if start >= data.len() {
+        return reply(StatusCode::RANGE_NOT_SATISFIABLE, "");
+    }
+    let end = end.min(data.len() - 1);
+    Response::builder()
+        .status(StatusCode::PARTIAL_CONTENT)
+        .header(
+            "Content-Range",
+            format!("bytes {}-{}/{}", start, end, data.len()),
+        )
+        .body(Body::from(data.slice(start..end + 1)))
+        .unwrap()
+}
+
+fn reply(status: StatusCode, body: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
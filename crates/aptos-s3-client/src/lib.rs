@@ -0,0 +1,685 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! A minimal client speaking the S3 REST protocol, for a single bucket of an S3 compatible
+//! service. Requests are signed with AWS Signature Version 4 and retried with exponential backoff
+//! on network errors, throttling and server side errors.
+
+#[cfg(any(test, feature = "testing"))]
+pub mod fake_server;
+pub mod signing;
+
+use crate::signing::{canonical_query_string, sign, uri_encode};
+use anyhow::{bail, ensure, format_err, Result};
+use bytes::Bytes;
+use chrono::Utc;
+use futures::Future;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_RANGE, CONTENT_TYPE, ETAG, RANGE},
+    Method, Response, StatusCode, Url,
+};
+use serde::Deserialize;
+use std::{fmt::Write, time::Duration};
+
+const X_AMZ_COPY_SOURCE: &str = "x-amz-copy-source";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+impl S3Credentials {
+    const ACCESS_KEY_ID_ENV: &'static str = "AWS_ACCESS_KEY_ID";
+    const SECRET_ACCESS_KEY_ENV: &'static str = "AWS_SECRET_ACCESS_KEY";
+    const SESSION_TOKEN_ENV: &'static str = "AWS_SESSION_TOKEN";
+
+    /// Reads the credentials from the standard AWS environment variables.
+    pub fn from_env() -> Result<Self> {
+        let var = |key: &str| {
+            std::env::var(key).map_err(|e| format_err!("Failed reading env var {}: {}", key, e))
+        };
+        Ok(Self {
+            access_key_id: var(Self::ACCESS_KEY_ID_ENV)?,
+            secret_access_key: var(Self::SECRET_ACCESS_KEY_ENV)?,
+            session_token: std::env::var(Self::SESSION_TOKEN_ENV).ok(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct S3ClientConfig {
+    /// Like "https://s3.us-west-2.amazonaws.com", or "http://127.0.0.1:9000" for a local MinIO.
+    pub endpoint: String,
+    /// Region used to sign requests. MinIO and most S3 compatible services accept "us-east-1".
+    pub region: String,
+    pub bucket: String,
+    /// Address the bucket as "{endpoint}/{bucket}/{key}" instead of
+    /// "{bucket}.{endpoint host}/{key}". Most self-hosted services require this.
+    pub path_style: bool,
+    pub credentials: S3Credentials,
+    /// Number of times a failed request is retried before giving up.
+    pub max_retries: usize,
+    /// Delay before the first retry, doubled on each succeeding retry.
+    pub retry_base_delay: Duration,
+    pub request_timeout: Duration,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum RequestError {
+    /// Worth retrying, like a dropped connection or a 503.
+    #[error("Transient error: {0}")]
+    Transient(anyhow::Error),
+    #[error(transparent)]
+    Permanent(#[from] anyhow::Error),
+}
+
+/// Result of a ranged read.
+pub struct RangeRead {
+    pub data: Bytes,
+    /// Total size of the object.
+    pub total_size: u64,
+}
+
+/// A part already uploaded in a multipart upload.
+#[derive(Clone, Debug)]
+pub struct CompletedPart {
+    pub part_number: usize,
+    pub etag: String,
+}
+
+#[derive(Clone)]
+pub struct S3Client {
+    http: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    path_style: bool,
+    credentials: S3Credentials,
+    max_retries: usize,
+    retry_base_delay: Duration,
+}
+
+impl S3Client {
+    const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+    pub fn new(config: S3ClientConfig) -> Result<Self> {
+        let endpoint = Url::parse(config.endpoint.trim_end_matches('/'))?;
+        ensure!(
+            endpoint.host_str().is_some(),
+            "endpoint {} has no host.",
+            config.endpoint
+        );
+        ensure!(!config.bucket.is_empty(), "bucket can't be empty.");
+        let http = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .build()?;
+
+        Ok(Self {
+            http,
+            endpoint,
+            bucket: config.bucket,
+            region: config.region,
+            path_style: config.path_style,
+            credentials: config.credentials,
+            max_retries: config.max_retries,
+            retry_base_delay: config.retry_base_delay,
+        })
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub async fn head_bucket(&self) -> Result<()> {
+        self.retry("HeadBucket", "", || async move {
+            let resp = self
+                .send(Method::HEAD, "", &[], HeaderMap::new(), Bytes::new())
+                .await?;
+            Self::ensure_success(resp).await.map(|_| ())
+        })
+        .await
+    }
+
+    pub async fn object_exists(&self, key: &str) -> Result<bool> {
+        self.retry("HeadObject", key, || async move {
+            let resp = self
+                .send(Method::HEAD, key, &[], HeaderMap::new(), Bytes::new())
+                .await?;
+            match resp.status() {
+                StatusCode::NOT_FOUND => Ok(false),
+                s if s.is_success() => Ok(true),
+                _ => Err(Self::unexpected_response(resp).await),
+            }
+        })
+        .await
+    }
+
+    /// Downloads the whole object, returns `None` if it doesn't exist.
+    pub async fn get_object(&self, key: &str) -> Result<Option<Bytes>> {
+        self.retry("GetObject", key, || async move {
+            let resp = self
+                .send(Method::GET, key, &[], HeaderMap::new(), Bytes::new())
+                .await?;
+            if resp.status() == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            let resp = Self::ensure_success(resp).await?;
+            let data = resp
+                .bytes()
+                .await
+                .map_err(|e| RequestError::Transient(e.into()))?;
+            Ok(Some(data))
+        })
+        .await
+    }
+
+    pub async fn put_object(
+        &self,
+        key: &str,
+        body: Bytes,
+        content_type: Option<&str>,
+    ) -> Result<()> {
+        let mut headers = HeaderMap::new();
+        if let Some(content_type) = content_type {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_str(content_type)?);
+        }
+        let (headers, body) = (&headers, &body);
+        self.retry("PutObject", key, || async move {
+            let resp = self
+                .send(Method::PUT, key, &[], headers.clone(), body.clone())
+                .await?;
+            Self::ensure_success(resp).await.map(|_| ())
+        })
+        .await
+    }
+
+    /// Reads up to `len` bytes starting at `offset`. Returns empty data if `offset` is beyond the
+    /// end of the object.
+    pub async fn get_range(&self, key: &str, offset: u64, len: usize) -> Result<RangeRead> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RANGE,
+            HeaderValue::from_str(&format!("bytes={}-{}", offset, offset + len as u64 - 1))?,
+        );
+        let headers = &headers;
+        self.retry("GetObject", key, || async move {
+            let resp = self
+                .send(Method::GET, key, &[], headers.clone(), Bytes::new())
+                .await?;
+            match resp.status() {
+                StatusCode::PARTIAL_CONTENT => {
+                    let total_size = Self::parse_content_range_total(&resp)?;
+                    let data = resp
+                        .bytes()
+                        .await
+                        .map_err(|e| RequestError::Transient(e.into()))?;
+                    Ok(RangeRead { data, total_size })
+                },
+                // Range ignored by the server, the whole object is returned.
+                StatusCode::OK => {
+                    let data = resp
+                        .bytes()
+                        .await
+                        .map_err(|e| RequestError::Transient(e.into()))?;
+                    let total_size = data.len() as u64;
+                    let start = (offset as usize).min(data.len());
+                    let end = start.saturating_add(len).min(data.len());
+                    Ok(RangeRead {
+                        data: data.slice(start..end),
+                        total_size,
+                    })
+                },
+                // Empty object, or reading past the end.
+                StatusCode::RANGE_NOT_SATISFIABLE => Ok(RangeRead {
+                    data: Bytes::new(),
+                    total_size: offset,
+                }),
+                _ => Err(Self::unexpected_response(resp).await),
+            }
+        })
+        .await
+    }
+
+    /// Lists all keys under `prefix`, following continuation tokens.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        static KEY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<Key>([^<]*)</Key>").unwrap());
+
+        let mut keys = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token", token.clone()));
+            }
+            let query = &query;
+            let body = self
+                .retry("ListObjectsV2", prefix, || async move {
+                    let query = query.as_slice();
+                    let resp = self
+                        .send(Method::GET, "", query, HeaderMap::new(), Bytes::new())
+                        .await?;
+                    Self::read_text(Self::ensure_success(resp).await?).await
+                })
+                .await?;
+
+            keys.extend(KEY_RE.captures_iter(&body).map(|c| xml_unescape(&c[1])));
+            continuation_token = match xml_element(&body, "IsTruncated").as_deref() {
+                Some("true") => Some(
+                    xml_element(&body, "NextContinuationToken")
+                        .ok_or_else(|| format_err!("Truncated listing without a token."))?,
+                ),
+                _ => break,
+            };
+        }
+        Ok(keys)
+    }
+
+    pub async fn copy_object(&self, src_key: &str, dst_key: &str) -> Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static(X_AMZ_COPY_SOURCE),
+            HeaderValue::from_str(&format!(
+                "/{}/{}",
+                uri_encode(&self.bucket, false),
+                uri_encode(src_key, false)
+            ))?,
+        );
+        let headers = &headers;
+        self.retry("CopyObject", dst_key, || async move {
+            let resp = self
+                .send(Method::PUT, dst_key, &[], headers.clone(), Bytes::new())
+                .await?;
+            // CopyObject can fail after responding with 200, in which case the body is an error.
+            let body = Self::read_text(Self::ensure_success(resp).await?).await?;
+            Self::ensure_not_error_body(&body)
+        })
+        .await
+    }
+
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.retry("DeleteObject", key, || async move {
+            let resp = self
+                .send(Method::DELETE, key, &[], HeaderMap::new(), Bytes::new())
+                .await?;
+            Self::ensure_success(resp).await.map(|_| ())
+        })
+        .await
+    }
+
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        self.retry("CreateMultipartUpload", key, || async move {
+            let resp = self
+                .send(
+                    Method::POST,
+                    key,
+                    &[("uploads", String::new())],
+                    HeaderMap::new(),
+                    Bytes::new(),
+                )
+                .await?;
+            let body = Self::read_text(Self::ensure_success(resp).await?).await?;
+            xml_element(&body, "UploadId").ok_or_else(|| {
+                RequestError::Permanent(format_err!("No UploadId in response: {}", body))
+            })
+        })
+        .await
+    }
+
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<CompletedPart> {
+        let query = [
+            ("partNumber", part_number.to_string()),
+            ("uploadId", upload_id.to_string()),
+        ];
+        let (query, body) = (&query, &body);
+        self.retry("UploadPart", key, || async move {
+            let resp = self
+                .send(Method::PUT, key, query, HeaderMap::new(), body.clone())
+                .await?;
+            let resp = Self::ensure_success(resp).await?;
+            let etag = resp
+                .headers()
+                .get(ETAG)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| {
+                    RequestError::Permanent(format_err!("No ETag in UploadPart response."))
+                })?;
+            Ok(CompletedPart {
+                part_number,
+                etag: etag.to_string(),
+            })
+        })
+        .await
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> Result<()> {
+        let mut xml = String::from("<CompleteMultipartUpload>");
+        for part in parts {
+            write!(
+                xml,
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part.part_number,
+                xml_escape(&part.etag)
+            )?;
+        }
+        xml.push_str("</CompleteMultipartUpload>");
+        let body = Bytes::from(xml);
+
+        let query = [("uploadId", upload_id.to_string())];
+        let (query, body) = (&query, &body);
+        self.retry("CompleteMultipartUpload", key, || async move {
+            let resp = self
+                .send(Method::POST, key, query, HeaderMap::new(), body.clone())
+                .await?;
+            // Like CopyObject, this can fail after responding with 200.
+            let body = Self::read_text(Self::ensure_success(resp).await?).await?;
+            Self::ensure_not_error_body(&body)
+        })
+        .await
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        let query = [("uploadId", upload_id.to_string())];
+        let query = &query;
+        self.retry("AbortMultipartUpload", key, || async move {
+            let resp = self
+                .send(Method::DELETE, key, query, HeaderMap::new(), Bytes::new())
+                .await?;
+            Self::ensure_success(resp).await.map(|_| ())
+        })
+        .await
+    }
+
+    async fn retry<T, F, Fut>(&self, op: &str, key: &str, f: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = std::result::Result<T, RequestError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Ok(t) => return Ok(t),
+                Err(RequestError::Transient(e)) if attempt < self.max_retries => {
+                    let delay = self
+                        .retry_base_delay
+                        .saturating_mul(1 << attempt.min(16))
+                        .min(Self::MAX_RETRY_DELAY);
+                    attempt += 1;
+                    tracing::warn!(
+                        op = op,
+                        key = key,
+                        attempt = attempt,
+                        error = %e,
+                        "S3 request failed, retrying in {:?}.",
+                        delay,
+                    );
+                    tokio::time::sleep(delay).await;
+                },
+                Err(RequestError::Transient(e)) | Err(RequestError::Permanent(e)) => {
+                    bail!("S3 {} failed on key \"{}\": {}", op, key, e)
+                },
+            }
+        }
+    }
+
+    /// Signs and sends one request. Network errors, throttling and server side errors are
+    /// returned as transient errors, any other response is returned as is.
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        mut headers: HeaderMap,
+        body: Bytes,
+    ) -> std::result::Result<Response, RequestError> {
+        let url = self.url(key, query)?;
+        sign(
+            &method,
+            &url,
+            &mut headers,
+            &body,
+            &self.region,
+            &self.credentials,
+            Utc::now(),
+        )?;
+
+        let resp = self
+            .http
+            .request(method, url)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| RequestError::Transient(e.into()))?;
+
+        let status = resp.status();
+        if status.is_server_error()
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+        {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(RequestError::Transient(format_err!(
+                "Status {}: {}",
+                status,
+                text
+            )));
+        }
+        Ok(resp)
+    }
+
+    /// The URL of the given key, an empty key addresses the bucket itself.
+    fn url(&self, key: &str, query: &[(&str, String)]) -> Result<Url> {
+        let mut url = if self.path_style {
+            let mut url = self.endpoint.to_string();
+            if !url.ends_with('/') {
+                url.push('/');
+            }
+            url.push_str(&uri_encode(&self.bucket, true));
+            if !key.is_empty() {
+                url.push('/');
+            }
+            url
+        } else {
+            let mut endpoint = self.endpoint.clone();
+            let host = endpoint.host_str().expect("Checked on construction.");
+            let host = format!("{}.{}", self.bucket, host);
+            endpoint.set_host(Some(&host))?;
+            let mut url = endpoint.to_string();
+            if !url.ends_with('/') {
+                url.push('/');
+            }
+            url
+        };
+        url.push_str(&uri_encode(key, false));
+        let query_string = canonical_query_string(query);
+        if !query_string.is_empty() {
+            url.push('?');
+            url.push_str(&query_string);
+        }
+        Ok(Url::parse(&url)?)
+    }
+
+    fn parse_content_range_total(resp: &Response) -> std::result::Result<u64, RequestError> {
+        // Like "bytes 0-99/1234".
+        resp.headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit('/').next())
+            .and_then(|total| total.parse().ok())
+            .ok_or_else(|| {
+                RequestError::Permanent(format_err!(
+                    "Bad Content-Range header: {:?}",
+                    resp.headers().get(CONTENT_RANGE)
+                ))
+            })
+    }
+
+    async fn ensure_success(resp: Response) -> std::result::Result<Response, RequestError> {
+        if resp.status().is_success() {
+            Ok(resp)
+        } else {
+            Err(Self::unexpected_response(resp).await)
+        }
+    }
+
+    async fn unexpected_response(resp: Response) -> RequestError {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        RequestError::Permanent(format_err!("Unexpected status {}: {}", status, text))
+    }
+
+    async fn read_text(resp: Response) -> std::result::Result<String, RequestError> {
+        resp.text()
+            .await
+            .map_err(|e| RequestError::Transient(e.into()))
+    }
+
+    fn ensure_not_error_body(body: &str) -> std::result::Result<(), RequestError> {
+        if body.contains("<Error>") {
+            // Errors reported with a 200 status are meant to be retried.
+            Err(RequestError::Transient(format_err!(
+                "Error in body: {}",
+                body
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Extracts the text of the first element with the given name. Good enough for the flat responses
+/// S3 returns.
+fn xml_element(xml: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let len = xml[start..].find(&close)?;
+    Some(xml_unescape(&xml[start..start + len]))
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&#34;", "\"")
+        .replace("&amp;", "&")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_server::FakeS3Server;
+
+    fn test_config(endpoint: &str, bucket: &str, path_style: bool) -> S3ClientConfig {
+        S3ClientConfig {
+            endpoint: endpoint.to_string(),
+            region: "us-east-1".to_string(),
+            bucket: bucket.to_string(),
+            path_style,
+            credentials: S3Credentials {
+                access_key_id: "a".to_string(),
+                secret_access_key: "b".to_string(),
+                session_token: None,
+            },
+            max_retries: 5,
+            retry_base_delay: Duration::from_millis(1),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn test_virtual_hosted_style_url() {
+        let client = S3Client::new(test_config(
+            "https://s3.amazonaws.com",
+            "examplebucket",
+            false,
+        ))
+        .unwrap();
+        assert_eq!(
+            client.url("test.txt", &[]).unwrap().as_str(),
+            "https://examplebucket.s3.amazonaws.com/test.txt"
+        );
+    }
+
+    #[test]
+    fn test_path_style_url() {
+        let client = S3Client::new(test_config("http://127.0.0.1:9000", "backups", true)).unwrap();
+
+        assert_eq!(
+            client
+                .url("", &[
+                    ("list-type", "2".to_string()),
+                    ("prefix", "a/b".to_string())
+                ])
+                .unwrap()
+                .as_str(),
+            "http://127.0.0.1:9000/backups?list-type=2&prefix=a%2Fb"
+        );
+        assert_eq!(
+            client
+                .url("e1/metadata/x.meta", &[("uploads", String::new())])
+                .unwrap()
+                .as_str(),
+            "http://127.0.0.1:9000/backups/e1/metadata/x.meta?uploads="
+        );
+    }
+
+    #[test]
+    fn test_xml_element() {
+        let xml = "<R><UploadId>a&amp;b</UploadId><IsTruncated>false</IsTruncated></R>";
+        assert_eq!(xml_element(xml, "UploadId").unwrap(), "a&b");
+        assert_eq!(xml_element(xml, "IsTruncated").unwrap(), "false");
+        assert!(xml_element(xml, "NextContinuationToken").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_and_put_object_with_retries() {
+        // Every other request fails with a 503.
+        let (server, endpoint) = FakeS3Server::start("bucket", Some(2));
+        let client = S3Client::new(test_config(&endpoint, "bucket", true)).unwrap();
+
+        client.head_bucket().await.unwrap();
+        assert!(client.get_object("a/b").await.unwrap().is_none());
+        assert!(!client.object_exists("a/b").await.unwrap());
+
+        client
+            .put_object("a/b", Bytes::from_static(b"data"), Some("application/json"))
+            .await
+            .unwrap();
+        assert_eq!(server.object("a/b").unwrap(), Bytes::from_static(b"data"));
+        assert_eq!(
+            client.get_object("a/b").await.unwrap().unwrap(),
+            Bytes::from_static(b"data")
+        );
+        assert!(client.object_exists("a/b").await.unwrap());
+    }
+}
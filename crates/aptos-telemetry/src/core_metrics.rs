@@ -106,7 +106,7 @@ fn collect_state_sync_metrics(
     core_metrics: &mut BTreeMap<String, String>,
     node_config: &NodeConfig,
 ) {
-    let state_sync_driver_config = node_config.state_sync.state_sync_driver;
+    let state_sync_driver_config = &node_config.state_sync.state_sync_driver;
 
     // Get the state sync code version
     core_metrics.insert(STATE_SYNC_CODE_VERSION.into(), "2".into());
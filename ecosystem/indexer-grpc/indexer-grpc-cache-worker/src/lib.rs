@@ -6,7 +6,10 @@ pub mod worker;
 
 use anyhow::{Context, Result};
 use aptos_indexer_grpc_server_framework::RunnableConfig;
-use aptos_indexer_grpc_utils::{config::IndexerGrpcFileStoreConfig, types::RedisUrl};
+use aptos_indexer_grpc_utils::{
+    cache_backend::CacheClient, compression_util::StorageFormat,
+    config::IndexerGrpcFileStoreConfig, types::RedisUrl,
+};
 use serde::{Deserialize, Serialize};
 use url::Url;
 use worker::Worker;
@@ -44,14 +47,19 @@ impl IndexerGrpcCacheWorkerConfig {
 #[async_trait::async_trait]
 impl RunnableConfig for IndexerGrpcCacheWorkerConfig {
     async fn run(&self) -> Result<()> {
+        let cache_storage_format = if self.enable_cache_compression {
+            StorageFormat::Lz4CompressedProto
+        } else {
+            StorageFormat::Base64UncompressedProto
+        };
+        let cache_client =
+            CacheClient::new_redis(&self.redis_main_instance_address, cache_storage_format)
+                .context("Failed to create cache worker")?;
         let mut worker = Worker::new(
             self.fullnode_grpc_address.clone(),
-            self.redis_main_instance_address.clone(),
+            cache_client,
             self.file_store_config.clone(),
-            self.enable_cache_compression,
-        )
-        .await
-        .context("Failed to create cache worker")?;
+        );
         worker
             .run()
             .await
@@ -4,13 +4,12 @@
 use crate::metrics::{ERROR_COUNT, WAIT_FOR_FILE_STORE_COUNTER};
 use anyhow::{bail, Context, Result};
 use aptos_indexer_grpc_utils::{
-    cache_operator::CacheOperator,
-    compression_util::{FileStoreMetadata, StorageFormat},
+    cache_backend::{CacheBackend, CacheClient},
+    compression_util::FileStoreMetadata,
     config::IndexerGrpcFileStoreConfig,
     counters::{log_grpc_step, IndexerGrpcStep},
     create_grpc_client,
     file_store_operator::FileStoreOperator,
-    types::RedisUrl,
 };
 use aptos_moving_average::MovingAverage;
 use aptos_protos::internal::fullnode::v1::{
@@ -19,6 +18,7 @@ use aptos_protos::internal::fullnode::v1::{
 };
 use futures::{self, future::join_all, StreamExt};
 use prost::Message;
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 use tracing::{error, info};
 use url::Url;
@@ -38,14 +38,12 @@ const FILE_STORE_METADATA_WAIT_MS: u64 = 2000;
 const SERVICE_TYPE: &str = "cache_worker";
 
 pub struct Worker {
-    /// Redis client.
-    redis_client: redis::Client,
+    /// Cache client, either Redis or an in-process cache.
+    cache_client: CacheClient,
     /// Fullnode grpc address.
     fullnode_grpc_address: Url,
     /// File store config
     file_store: IndexerGrpcFileStoreConfig,
-    /// Cache storage format.
-    cache_storage_format: StorageFormat,
 }
 
 /// GRPC data status enum is to identify the data frame.
@@ -70,30 +68,16 @@ pub(crate) enum GrpcDataStatus {
 }
 
 impl Worker {
-    pub async fn new(
+    pub fn new(
         fullnode_grpc_address: Url,
-        redis_main_instance_address: RedisUrl,
+        cache_client: CacheClient,
         file_store: IndexerGrpcFileStoreConfig,
-        enable_cache_compression: bool,
-    ) -> Result<Self> {
-        let cache_storage_format = if enable_cache_compression {
-            StorageFormat::Lz4CompressedProto
-        } else {
-            StorageFormat::Base64UncompressedProto
-        };
-        let redis_client = redis::Client::open(redis_main_instance_address.0.clone())
-            .with_context(|| {
-                format!(
-                    "[Indexer Cache] Failed to create redis client for {}",
-                    redis_main_instance_address
-                )
-            })?;
-        Ok(Self {
-            redis_client,
+    ) -> Self {
+        Self {
+            cache_client,
             file_store,
             fullnode_grpc_address,
-            cache_storage_format,
-        })
+        }
     }
 
     /// The main loop of the worker is:
@@ -109,11 +93,7 @@ impl Worker {
     pub async fn run(&mut self) -> Result<()> {
         // Re-connect if lost.
         loop {
-            let conn = self
-                .redis_client
-                .get_tokio_connection_manager()
-                .await
-                .context("Get redis connection failed.")?;
+            let cache = self.cache_client.connect().await?;
             let mut rpc_client = create_grpc_client(self.fullnode_grpc_address.clone()).await;
 
             // 1. Fetch metadata.
@@ -164,13 +144,7 @@ impl Worker {
                 "[Indexer Cache] Streaming RPC started."
             );
             // 3&4. Infinite streaming until error happens. Either stream ends or worker crashes.
-            process_streaming_response(
-                conn,
-                self.cache_storage_format,
-                file_store_metadata,
-                response.into_inner(),
-            )
-            .await?;
+            process_streaming_response(cache, file_store_metadata, response.into_inner()).await?;
 
             info!(
                 service_type = SERVICE_TYPE,
@@ -182,7 +156,7 @@ impl Worker {
 
 async fn process_transactions_from_node_response(
     response: TransactionsFromNodeResponse,
-    cache_operator: &mut CacheOperator<redis::aio::ConnectionManager>,
+    cache: &Arc<dyn CacheBackend>,
     download_start_time: std::time::Instant,
 ) -> Result<GrpcDataStatus> {
     let size_in_bytes = response.encoded_len();
@@ -209,7 +183,7 @@ async fn process_transactions_from_node_response(
         Response::Data(data) => {
             let transaction_len = data.transactions.len();
             let data_download_duration_in_secs = download_start_time.elapsed().as_secs_f64();
-            let mut cache_operator_clone = cache_operator.clone();
+            let cache = cache.clone();
             let task: JoinHandle<anyhow::Result<()>> = tokio::spawn({
                 let first_transaction = data
                     .transactions
@@ -242,10 +216,7 @@ async fn process_transactions_from_node_response(
 
                 async move {
                     // Push to cache.
-                    match cache_operator_clone
-                        .update_cache_transactions(data.transactions)
-                        .await
-                    {
+                    match cache.update_cache_transactions(data.transactions).await {
                         Ok(_) => {
                             log_grpc_step(
                                 SERVICE_TYPE,
@@ -282,9 +253,9 @@ async fn process_transactions_from_node_response(
     }
 }
 
-// Setup the cache operator with init signal, including chain id and starting version from fullnode.
+// Verify the init signal against the cache, including chain id and starting version from fullnode.
 async fn verify_fullnode_init_signal(
-    cache_operator: &mut CacheOperator<redis::aio::ConnectionManager>,
+    cache: &dyn CacheBackend,
     init_signal: TransactionsFromNodeResponse,
     file_store_metadata: FileStoreMetadata,
 ) -> Result<(ChainID, StartingVersion)> {
@@ -308,7 +279,7 @@ async fn verify_fullnode_init_signal(
     };
 
     // Guaranteed that chain id is here at this point because we already ensure that fileworker did the set up
-    let chain_id = cache_operator.get_chain_id().await?.unwrap();
+    let chain_id = cache.get_chain_id().await?.unwrap();
     if chain_id != fullnode_chain_id as u64 {
         bail!("[Indexer Cache] Chain ID mismatch between fullnode init signal and cache.");
     }
@@ -326,25 +297,22 @@ async fn verify_fullnode_init_signal(
 
 /// Infinite streaming processing. Retry if error happens; crash if fatal.
 async fn process_streaming_response(
-    conn: redis::aio::ConnectionManager,
-    cache_storage_format: StorageFormat,
+    cache: Arc<dyn CacheBackend>,
     file_store_metadata: FileStoreMetadata,
     mut resp_stream: impl futures_core::Stream<Item = Result<TransactionsFromNodeResponse, tonic::Status>>
         + std::marker::Unpin,
 ) -> Result<()> {
     let mut tps_calculator = MovingAverage::new(10_000);
     let mut transaction_count = 0;
-    // 3. Set up the cache operator with init signal.
+    // 3. Verify the init signal against the cache.
     let init_signal = match resp_stream.next().await {
         Some(Ok(r)) => r,
         _ => {
             bail!("[Indexer Cache] Streaming error: no response.");
         },
     };
-    let mut cache_operator = CacheOperator::new(conn, cache_storage_format);
-
     let (fullnode_chain_id, starting_version) =
-        verify_fullnode_init_signal(&mut cache_operator, init_signal, file_store_metadata)
+        verify_fullnode_init_signal(cache.as_ref(), init_signal, file_store_metadata)
             .await
             .context("[Indexer Cache] Failed to verify init signal")?;
 
@@ -384,13 +352,7 @@ async fn process_streaming_response(
         }
 
         let size_in_bytes = received.encoded_len();
-        match process_transactions_from_node_response(
-            received,
-            &mut cache_operator,
-            download_start_time,
-        )
-        .await
-        {
+        match process_transactions_from_node_response(received, &cache, download_start_time).await {
             Ok(status) => match status {
                 GrpcDataStatus::ChunkDataOk {
                     num_of_transactions,
@@ -441,7 +403,7 @@ async fn process_streaming_response(
                             .inc();
                         break;
                     }
-                    cache_operator
+                    cache
                         .update_cache_latest_version(transaction_count, current_version)
                         .await
                         .context("Failed to update the latest version in the cache")?;
@@ -477,10 +439,7 @@ async fn process_streaming_response(
 
         // Check if the file store isn't too far away
         loop {
-            let file_store_version = cache_operator
-                .get_file_store_latest_version()
-                .await?
-                .unwrap();
+            let file_store_version = cache.get_file_store_latest_version().await?.unwrap();
             if file_store_version + FILE_STORE_VERSIONS_RESERVED < current_version {
                 tokio::time::sleep(std::time::Duration::from_millis(
                     CACHE_WORKER_WAIT_FOR_FILE_STORE_MS,
@@ -2,11 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::service::RawDataServerWrapper;
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use aptos_indexer_grpc_cache_worker::worker::Worker;
+use aptos_indexer_grpc_file_store::processor::Processor;
 use aptos_indexer_grpc_server_framework::RunnableConfig;
 use aptos_indexer_grpc_utils::{
-    compression_util::StorageFormat, config::IndexerGrpcFileStoreConfig,
-    in_memory_cache::InMemoryCacheConfig, types::RedisUrl,
+    cache_backend::CacheClient,
+    compression_util::StorageFormat,
+    config::IndexerGrpcFileStoreConfig,
+    in_memory_cache::{InMemoryCache, InMemoryCacheConfig},
+    in_process_cache::{InProcessCache, InProcessCacheConfig},
+    types::RedisUrl,
+    DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES,
 };
 use aptos_protos::{
     indexer::v1::FILE_DESCRIPTOR_SET as INDEXER_V1_FILE_DESCRIPTOR_SET,
@@ -17,6 +24,7 @@ use aptos_transaction_filter::BooleanTransactionFilter;
 use serde::{Deserialize, Serialize};
 use std::{net::SocketAddr, sync::Arc};
 use tonic::{codec::CompressionEncoding, transport::Server};
+use url::Url;
 
 pub const SERVER_NAME: &str = "idxdatasvc";
 
@@ -45,6 +53,19 @@ pub struct NonTlsConfig {
     pub data_service_grpc_listen_address: SocketAddr,
 }
 
+/// Runs the cache worker and the file store processor inside the data service, with the
+/// recent transactions kept in an in-process cache instead of Redis.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmbeddedCacheConfig {
+    /// The fullnode the embedded cache worker streams transactions from.
+    pub fullnode_grpc_address: Url,
+    /// Chain id, used by the embedded file store processor to initialize the file store.
+    pub chain_id: u64,
+    #[serde(default)]
+    pub in_process_cache_config: InProcessCacheConfig,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct IndexerGrpcDataServiceConfig {
@@ -63,8 +84,13 @@ pub struct IndexerGrpcDataServiceConfig {
     pub disable_auth_check: bool,
     /// File store config.
     pub file_store_config: IndexerGrpcFileStoreConfig,
-    /// Redis read replica address.
-    pub redis_read_replica_address: RedisUrl,
+    /// Redis read replica address. Required unless `embedded_cache_config` is set.
+    #[serde(default)]
+    pub redis_read_replica_address: Option<RedisUrl>,
+    /// If given, the cache worker and the file store processor run in this process and
+    /// no Redis is needed.
+    #[serde(default)]
+    pub embedded_cache_config: Option<EmbeddedCacheConfig>,
     /// Support compressed cache data.
     #[serde(default = "IndexerGrpcDataServiceConfig::default_enable_cache_compression")]
     pub enable_cache_compression: bool,
@@ -82,6 +108,10 @@ pub struct IndexerGrpcDataServiceConfig {
     /// separate filters that describe each type of txn we want to strip.
     #[serde(default = "IndexerGrpcDataServiceConfig::default_txns_to_strip_filter")]
     pub txns_to_strip_filter: BooleanTransactionFilter,
+    /// The maximum size of the (JSON-serialized) transaction filter of a request. Larger
+    /// filters are rejected.
+    #[serde(default = "IndexerGrpcDataServiceConfig::default_max_transaction_filter_size_bytes")]
+    pub max_transaction_filter_size_bytes: usize,
 }
 
 impl IndexerGrpcDataServiceConfig {
@@ -91,10 +121,12 @@ impl IndexerGrpcDataServiceConfig {
         data_service_response_channel_size: Option<usize>,
         disable_auth_check: bool,
         file_store_config: IndexerGrpcFileStoreConfig,
-        redis_read_replica_address: RedisUrl,
+        redis_read_replica_address: Option<RedisUrl>,
+        embedded_cache_config: Option<EmbeddedCacheConfig>,
         enable_cache_compression: bool,
         in_memory_cache_config: InMemoryCacheConfig,
         txns_to_strip_filter: BooleanTransactionFilter,
+        max_transaction_filter_size_bytes: Option<usize>,
     ) -> Self {
         Self {
             data_service_grpc_tls_config,
@@ -105,9 +137,12 @@ impl IndexerGrpcDataServiceConfig {
             disable_auth_check,
             file_store_config,
             redis_read_replica_address,
+            embedded_cache_config,
             enable_cache_compression,
             in_memory_cache_config,
             txns_to_strip_filter,
+            max_transaction_filter_size_bytes: max_transaction_filter_size_bytes
+                .unwrap_or_else(Self::default_max_transaction_filter_size_bytes),
         }
     }
 
@@ -123,6 +158,10 @@ impl IndexerGrpcDataServiceConfig {
         // This filter matches no txns.
         BooleanTransactionFilter::new_or(vec![])
     }
+
+    pub const fn default_max_transaction_filter_size_bytes() -> usize {
+        DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES
+    }
 }
 
 #[async_trait::async_trait]
@@ -133,6 +172,18 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
         {
             bail!("At least one of data_service_grpc_non_tls_config and data_service_grpc_tls_config must be set");
         }
+        match (
+            &self.redis_read_replica_address,
+            &self.embedded_cache_config,
+        ) {
+            (Some(_), None) => {},
+            (None, Some(embedded_cache_config)) => {
+                embedded_cache_config.in_process_cache_config.validate()?;
+            },
+            _ => bail!(
+                "Exactly one of redis_read_replica_address and embedded_cache_config must be set"
+            ),
+        }
         self.in_memory_cache_config.validate()?;
         Ok(())
     }
@@ -159,32 +210,79 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
             StorageFormat::Base64UncompressedProto
         };
 
-        println!(
-            ">>>> Starting Redis connection: {:?}",
-            &self.redis_read_replica_address.0
-        );
-        let redis_conn = redis::Client::open(self.redis_read_replica_address.0.clone())?
-            .get_tokio_connection_manager()
-            .await?;
-        println!(">>>> Redis connection established");
-        // InMemoryCache.
-        let in_memory_cache =
-            aptos_indexer_grpc_utils::in_memory_cache::InMemoryCache::new_with_redis_connection(
-                self.in_memory_cache_config.clone(),
-                redis_conn,
-                cache_storage_format,
-            )
-            .await?;
-        println!(">>>> InMemoryCache established");
+        let mut tasks = vec![];
+        let (cache_client, in_memory_cache) = match (
+            &self.redis_read_replica_address,
+            &self.embedded_cache_config,
+        ) {
+            (Some(redis_read_replica_address), _) => {
+                println!(
+                    ">>>> Starting Redis connection: {:?}",
+                    &redis_read_replica_address.0
+                );
+                let redis_conn = redis::Client::open(redis_read_replica_address.0.clone())?
+                    .get_tokio_connection_manager()
+                    .await?;
+                println!(">>>> Redis connection established");
+                // InMemoryCache.
+                let in_memory_cache = InMemoryCache::new_with_redis_connection(
+                    self.in_memory_cache_config.clone(),
+                    redis_conn,
+                    cache_storage_format,
+                )
+                .await?;
+                println!(">>>> InMemoryCache established");
+                let cache_client =
+                    CacheClient::new_redis(redis_read_replica_address, cache_storage_format)?;
+                (cache_client, Some(Arc::new(in_memory_cache)))
+            },
+            (None, Some(embedded_cache_config)) => {
+                // The in-process cache is already in memory, so no InMemoryCache on top of it.
+                let cache_client = CacheClient::InProcess(InProcessCache::new(
+                    embedded_cache_config.in_process_cache_config.clone(),
+                ));
+                // The processor sets up the file store and the cache, which the cache worker
+                // and the data service wait for.
+                let mut processor = Processor::new(
+                    cache_client.clone(),
+                    self.file_store_config.clone(),
+                    embedded_cache_config.chain_id,
+                )
+                .await
+                .context("Failed to create embedded file store processor")?;
+                tasks.push(tokio::spawn(async move {
+                    processor
+                        .run()
+                        .await
+                        .context("Embedded file store processor exited unexpectedly")
+                }));
+                let mut worker = Worker::new(
+                    embedded_cache_config.fullnode_grpc_address.clone(),
+                    cache_client.clone(),
+                    self.file_store_config.clone(),
+                );
+                tasks.push(tokio::spawn(async move {
+                    worker
+                        .run()
+                        .await
+                        .context("Embedded cache worker exited unexpectedly")
+                }));
+                tracing::info!(
+                    "[Data Service] Embedded file store processor and cache worker started."
+                );
+                (cache_client, None)
+            },
+            (None, None) => bail!("No cache is configured"),
+        };
         // Add authentication interceptor.
         let server = RawDataServerWrapper::new(
-            self.redis_read_replica_address.clone(),
+            cache_client,
             self.file_store_config.clone(),
             self.data_service_response_channel_size,
             self.txns_to_strip_filter.clone(),
-            cache_storage_format,
-            Arc::new(in_memory_cache),
-        )?;
+            self.max_transaction_filter_size_bytes,
+            in_memory_cache,
+        );
         let svc = aptos_protos::indexer::v1::raw_data_server::RawDataServer::new(server)
             .send_compressed(CompressionEncoding::Zstd)
             .accept_compressed(CompressionEncoding::Zstd)
@@ -194,7 +292,6 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
         let svc_clone = svc.clone();
         let reflection_service_clone = reflection_service.clone();
 
-        let mut tasks = vec![];
         if let Some(config) = &self.data_service_grpc_non_tls_config {
             let listen_address = config.data_service_grpc_listen_address;
             tracing::info!(
@@ -234,7 +331,14 @@ impl RunnableConfig for IndexerGrpcDataServiceConfig {
             }));
         }
 
-        futures::future::try_join_all(tasks).await?;
+        // Fail as soon as any task fails, including the embedded processor and cache worker,
+        // rather than only when one panics.
+        futures::future::try_join_all(
+            tasks
+                .into_iter()
+                .map(|task| async move { task.await.context("Data service task panicked")? }),
+        )
+        .await?;
         Ok(())
     }
 
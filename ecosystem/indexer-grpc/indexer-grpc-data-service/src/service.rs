@@ -7,11 +7,11 @@ use crate::metrics::{
     NUM_TRANSACTIONS_STRIPPED, PROCESSED_LATENCY_IN_SECS_PER_PROCESSOR,
     PROCESSED_VERSIONS_COUNT_PER_PROCESSOR, SHORT_CONNECTION_COUNT,
 };
-use anyhow::{Context, Result};
+use anyhow::Result;
 use aptos_indexer_grpc_utils::{
-    cache_operator::{CacheBatchGetStatus, CacheCoverageStatus, CacheOperator},
+    cache_backend::{CacheBackend, CacheClient},
+    cache_operator::CacheCoverageStatus,
     chunk_transactions,
-    compression_util::{CacheEntry, StorageFormat},
     config::IndexerGrpcFileStoreConfig,
     constants::{
         IndexerGrpcRequestMetadata, GRPC_AUTH_TOKEN_HEADER, GRPC_REQUEST_NAME_HEADER,
@@ -21,18 +21,18 @@ use aptos_indexer_grpc_utils::{
     counters::{log_grpc_step, IndexerGrpcStep, NUM_MULTI_FETCH_OVERLAPPED_VERSIONS},
     file_store_operator::FileStoreOperator,
     in_memory_cache::InMemoryCache,
-    time_diff_since_pb_timestamp_in_secs,
-    types::RedisUrl,
+    parse_transaction_filter, time_diff_since_pb_timestamp_in_secs,
 };
 use aptos_moving_average::MovingAverage;
 use aptos_protos::{
-    indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
+    indexer::v1::{
+        raw_data_server::RawData, GetTransactionsRequest, ProcessedRange, TransactionsResponse,
+    },
     transaction::v1::{transaction::TxnData, Transaction},
 };
 use aptos_transaction_filter::{BooleanTransactionFilter, Filterable};
 use futures::Stream;
 use prost::Message;
-use redis::Client;
 use std::{
     collections::HashMap,
     pin::Pin,
@@ -76,51 +76,78 @@ const MAX_FETCH_TASKS_PER_REQUEST: u64 = 5;
 const TRANSACTIONS_PER_STORAGE_BLOCK: u64 = 1000;
 
 pub struct RawDataServerWrapper {
-    pub redis_client: Arc<redis::Client>,
+    pub cache_client: CacheClient,
     pub file_store_config: IndexerGrpcFileStoreConfig,
     pub data_service_response_channel_size: usize,
     pub txns_to_strip_filter: BooleanTransactionFilter,
-    pub cache_storage_format: StorageFormat,
-    in_memory_cache: Arc<InMemoryCache>,
+    pub max_transaction_filter_size_bytes: usize,
+    /// Only used with Redis; the in-process cache is already in memory.
+    in_memory_cache: Option<Arc<InMemoryCache>>,
+    /// Shared by the requests, so that the zstd dictionary is only downloaded once.
+    file_store_operator: Arc<Box<dyn FileStoreOperator>>,
 }
 
-// Exclude in_memory-cache
+// Exclude cache client and in_memory-cache
 impl std::fmt::Debug for RawDataServerWrapper {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RawDataServerWrapper")
-            .field("redis_client", &"Arc<redis::Client>")
             .field("file_store_config", &self.file_store_config)
             .field(
                 "data_service_response_channel_size",
                 &self.data_service_response_channel_size,
             )
             .field("txns_to_strip_filter", &self.txns_to_strip_filter)
-            .field("cache_storage_format", &self.cache_storage_format)
+            .field(
+                "max_transaction_filter_size_bytes",
+                &self.max_transaction_filter_size_bytes,
+            )
             .finish()
     }
 }
 
 impl RawDataServerWrapper {
     pub fn new(
-        redis_address: RedisUrl,
+        cache_client: CacheClient,
         file_store_config: IndexerGrpcFileStoreConfig,
         data_service_response_channel_size: usize,
         txns_to_strip_filter: BooleanTransactionFilter,
-        cache_storage_format: StorageFormat,
-        in_memory_cache: Arc<InMemoryCache>,
-    ) -> anyhow::Result<Self> {
-        Ok(Self {
-            redis_client: Arc::new(
-                redis::Client::open(redis_address.0.clone()).with_context(|| {
-                    format!("Failed to create redis client for {}", redis_address)
-                })?,
-            ),
+        max_transaction_filter_size_bytes: usize,
+        in_memory_cache: Option<Arc<InMemoryCache>>,
+    ) -> Self {
+        let file_store_operator = Arc::new(file_store_config.create());
+        Self {
+            cache_client,
             file_store_config,
             data_service_response_channel_size,
             txns_to_strip_filter,
-            cache_storage_format,
+            max_transaction_filter_size_bytes,
             in_memory_cache,
-        })
+            file_store_operator,
+        }
+    }
+
+    /// Returns the next version to be cached.
+    async fn latest_version(&self) -> Result<u64, Status> {
+        if let Some(in_memory_cache) = &self.in_memory_cache {
+            return Ok(in_memory_cache.latest_version().await);
+        }
+        let cache = self.cache_client.connect().await.map_err(|e| {
+            error!(
+                error = e.to_string(),
+                "[Data Service] Failed to get cache connection."
+            );
+            Status::unavailable("[Data Service] Cannot connect to the cache; please retry.")
+        })?;
+        let latest_version = cache.get_latest_version().await.map_err(|e| {
+            error!(
+                error = e.to_string(),
+                "[Data Service] Failed to get latest version from the cache."
+            );
+            Status::unavailable(
+                "[Data Service] Cannot get the latest version from the cache; please retry.",
+            )
+        })?;
+        Ok(latest_version.unwrap_or_default())
     }
 }
 
@@ -145,6 +172,8 @@ impl RawData for RawDataServerWrapper {
     ///    1.4  If error happens, retry after a short sleep.
     /// 2. Push data into channel to stream to the client.
     ///    2.1. If the channel is full, do not fetch and retry after a short sleep.
+    ///    2.2. If a transaction filter is given, only the matching transactions are pushed;
+    ///         each response marks the range of versions processed, including the filtered ones.
     async fn get_transactions(
         &self,
         req: Request<GetTransactionsRequest>,
@@ -160,21 +189,20 @@ impl RawData for RawDataServerWrapper {
         let request = req.into_inner();
 
         let transactions_count = request.transactions_count;
+        let transaction_filter = parse_transaction_filter(
+            request.transaction_filter.as_deref(),
+            self.max_transaction_filter_size_bytes,
+        )?;
 
         // Response channel to stream the data to the client.
         let (tx, rx) = channel(self.data_service_response_channel_size);
         let current_version = match &request.starting_version {
             Some(version) => *version,
             // Live mode if starting version isn't specified
-            None => self
-                .in_memory_cache
-                .latest_version()
-                .await
-                .saturating_sub(1),
+            None => self.latest_version().await?.saturating_sub(1),
         };
 
-        let file_store_operator: Box<dyn FileStoreOperator> = self.file_store_config.create();
-        let file_store_operator = Arc::new(file_store_operator);
+        let file_store_operator = self.file_store_operator.clone();
 
         // Adds tracing context for the request.
         log_grpc_step(
@@ -190,8 +218,7 @@ impl RawData for RawDataServerWrapper {
             Some(&request_metadata),
         );
 
-        let redis_client = self.redis_client.clone();
-        let cache_storage_format = self.cache_storage_format;
+        let cache_client = self.cache_client.clone();
         let request_metadata = Arc::new(request_metadata);
         let txns_to_strip_filter = self.txns_to_strip_filter.clone();
         let in_memory_cache = self.in_memory_cache.clone();
@@ -199,13 +226,13 @@ impl RawData for RawDataServerWrapper {
             let request_metadata = request_metadata.clone();
             async move {
                 data_fetcher_task(
-                    redis_client,
+                    cache_client,
                     file_store_operator,
-                    cache_storage_format,
                     request_metadata,
                     transactions_count,
                     tx,
                     txns_to_strip_filter,
+                    transaction_filter,
                     current_version,
                     in_memory_cache,
                 )
@@ -235,14 +262,16 @@ async fn get_data_with_tasks(
     start_version: u64,
     transactions_count: Option<u64>,
     chain_id: u64,
-    cache_operator: &mut CacheOperator<redis::aio::ConnectionManager>,
+    cache: &Arc<dyn CacheBackend>,
     file_store_operator: Arc<Box<dyn FileStoreOperator>>,
     request_metadata: Arc<IndexerGrpcRequestMetadata>,
-    cache_storage_format: StorageFormat,
-    in_memory_cache: Arc<InMemoryCache>,
+    in_memory_cache: Option<Arc<InMemoryCache>>,
 ) -> DataFetchSubTaskResult {
     let start_time = Instant::now();
-    let in_memory_transactions = in_memory_cache.get_transactions(start_version).await;
+    let in_memory_transactions = match in_memory_cache {
+        Some(in_memory_cache) => in_memory_cache.get_transactions(start_version).await,
+        None => vec![],
+    };
     if !in_memory_transactions.is_empty() {
         log_grpc_step(
             SERVICE_TYPE,
@@ -261,9 +290,7 @@ async fn get_data_with_tasks(
             MESSAGE_SIZE_LIMIT,
         ));
     }
-    let cache_coverage_status = cache_operator
-        .check_cache_coverage_status(start_version)
-        .await;
+    let cache_coverage_status = cache.check_cache_coverage_status(start_version).await;
 
     let num_tasks_to_use = match cache_coverage_status {
         Ok(CacheCoverageStatus::DataNotReady) => return DataFetchSubTaskResult::NoResults,
@@ -294,18 +321,16 @@ async fn get_data_with_tasks(
 
     for _ in 0..num_tasks_to_use {
         tasks.spawn({
-            // TODO: arc this instead of cloning
-            let mut cache_operator = cache_operator.clone();
+            let cache = cache.clone();
             let file_store_operator = file_store_operator.clone();
             let request_metadata = request_metadata.clone();
             async move {
                 get_data_in_task(
                     current_version,
                     chain_id,
-                    &mut cache_operator,
+                    cache.as_ref(),
                     file_store_operator,
                     request_metadata.clone(),
-                    cache_storage_format,
                 )
                 .await
             }
@@ -343,19 +368,17 @@ async fn get_data_with_tasks(
 async fn get_data_in_task(
     start_version: u64,
     chain_id: u64,
-    cache_operator: &mut CacheOperator<redis::aio::ConnectionManager>,
+    cache: &dyn CacheBackend,
     file_store_operator: Arc<Box<dyn FileStoreOperator>>,
     request_metadata: Arc<IndexerGrpcRequestMetadata>,
-    cache_storage_format: StorageFormat,
 ) -> DataFetchSubTaskResult {
     let current_batch_start_time = std::time::Instant::now();
 
     let fetched = data_fetch(
         start_version,
-        cache_operator,
+        cache,
         file_store_operator,
         request_metadata.clone(),
-        cache_storage_format,
     );
 
     let transaction_data = match fetched.await {
@@ -387,22 +410,22 @@ async fn get_data_in_task(
 
 // This is a task spawned off for servicing a users' request
 async fn data_fetcher_task(
-    redis_client: Arc<Client>,
+    cache_client: CacheClient,
     file_store_operator: Arc<Box<dyn FileStoreOperator>>,
-    cache_storage_format: StorageFormat,
     request_metadata: Arc<IndexerGrpcRequestMetadata>,
     transactions_count: Option<u64>,
     tx: tokio::sync::mpsc::Sender<Result<TransactionsResponse, Status>>,
     txns_to_strip_filter: BooleanTransactionFilter,
+    transaction_filter: Option<BooleanTransactionFilter>,
     mut current_version: u64,
-    in_memory_cache: Arc<InMemoryCache>,
+    in_memory_cache: Option<Arc<InMemoryCache>>,
 ) {
     let mut connection_start_time = Some(std::time::Instant::now());
     let mut transactions_count = transactions_count;
 
-    // Establish redis connection
-    let conn = match redis_client.get_tokio_connection_manager().await {
-        Ok(conn) => conn,
+    // Establish cache connection
+    let cache = match cache_client.connect().await {
+        Ok(cache) => cache,
         Err(e) => {
             ERROR_COUNT
                 .with_label_values(&["redis_connection_failed"])
@@ -411,19 +434,18 @@ async fn data_fetcher_task(
             let _result = tx
                 .send_timeout(
                     Err(Status::unavailable(
-                        "[Data Service] Cannot connect to Redis; please retry.",
+                        "[Data Service] Cannot connect to the cache; please retry.",
                     )),
                     RESPONSE_CHANNEL_SEND_TIMEOUT,
                 )
                 .await;
             error!(
                 error = e.to_string(),
-                "[Data Service] Failed to get redis connection."
+                "[Data Service] Failed to get cache connection."
             );
             return;
         },
     };
-    let mut cache_operator = CacheOperator::new(conn, cache_storage_format);
 
     // Validate chain id
     let mut metadata = file_store_operator.get_file_store_metadata().await;
@@ -441,8 +463,8 @@ async fn data_fetcher_task(
 
     let metadata_chain_id = metadata.unwrap().chain_id;
 
-    // Validate redis chain id. Must be present by the time it gets here
-    let chain_id = match cache_operator.get_chain_id().await {
+    // Validate cache chain id. Must be present by the time it gets here
+    let chain_id = match cache.get_chain_id().await {
         Ok(chain_id) => chain_id.unwrap(),
         Err(e) => {
             ERROR_COUNT
@@ -452,14 +474,14 @@ async fn data_fetcher_task(
             let _result = tx
                 .send_timeout(
                     Err(Status::unavailable(
-                        "[Data Service] Cannot get the chain id from redis; please retry.",
+                        "[Data Service] Cannot get the chain id from the cache; please retry.",
                     )),
                     RESPONSE_CHANNEL_SEND_TIMEOUT,
                 )
                 .await;
             error!(
                 error = e.to_string(),
-                "[Data Service] Failed to get chain id from redis."
+                "[Data Service] Failed to get chain id from the cache."
             );
             return;
         },
@@ -485,10 +507,9 @@ async fn data_fetcher_task(
             current_version,
             transactions_count,
             chain_id,
-            &mut cache_operator,
+            &cache,
             file_store_operator.clone(),
             request_metadata.clone(),
-            cache_storage_format,
             in_memory_cache.clone(),
         )
         .await
@@ -528,10 +549,17 @@ async fn data_fetcher_task(
         // 2. Push the data to the response channel, i.e. stream the data to the client.
         let current_batch_size = transaction_data.as_slice().len();
         let end_of_batch_version = transaction_data.as_slice().last().unwrap().version;
+        let data_latency_in_secs = transaction_data
+            .last()
+            .unwrap()
+            .timestamp
+            .as_ref()
+            .map(time_diff_since_pb_timestamp_in_secs);
         let (resp_items, num_stripped) = get_transactions_responses_builder(
             transaction_data,
             chain_id as u32,
             &txns_to_strip_filter,
+            &transaction_filter,
         );
         NUM_TRANSACTIONS_STRIPPED
             .with_label_values(&request_metadata.get_label_values())
@@ -544,15 +572,6 @@ async fn data_fetcher_task(
         BYTES_READY_TO_TRANSFER_FROM_SERVER_AFTER_STRIPPING
             .with_label_values(&request_metadata.get_label_values())
             .inc_by(bytes_ready_to_transfer_after_stripping as u64);
-        let data_latency_in_secs = resp_items
-            .last()
-            .unwrap()
-            .transactions
-            .last()
-            .unwrap()
-            .timestamp
-            .as_ref()
-            .map(time_diff_since_pb_timestamp_in_secs);
 
         match channel_send_multiple_with_timeout(resp_items, tx.clone(), request_metadata.clone())
             .await
@@ -685,71 +704,81 @@ fn ensure_sequential_transactions(mut batches: Vec<Vec<Transaction>>) -> Vec<Tra
 /// Builds the response for the get transactions request. Partial batch is ok, i.e., a
 /// batch with transactions < 1000.
 ///
+/// Only the transactions that match the transaction filter (if any) are returned. Each
+/// response marks the range of versions it covers, so the responses together cover the
+/// whole batch, even if all the transactions were filtered out.
+///
 /// It also returns the number of txns that were stripped.
 fn get_transactions_responses_builder(
     transactions: Vec<Transaction>,
     chain_id: u32,
     txns_to_strip_filter: &BooleanTransactionFilter,
+    transaction_filter: &Option<BooleanTransactionFilter>,
 ) -> (Vec<TransactionsResponse>, usize) {
+    let first_version = transactions.first().unwrap().version;
+    let last_version = transactions.last().unwrap().version;
+    let filtered_transactions = transaction_filter.filter_vec(transactions);
     let (stripped_transactions, num_stripped) =
-        strip_transactions(transactions, txns_to_strip_filter);
+        strip_transactions(filtered_transactions, txns_to_strip_filter);
     let chunks = chunk_transactions(stripped_transactions, MESSAGE_SIZE_LIMIT);
+    if chunks.is_empty() {
+        let response = TransactionsResponse {
+            chain_id: Some(chain_id as u64),
+            transactions: vec![],
+            processed_range: Some(ProcessedRange {
+                first_version,
+                last_version,
+            }),
+        };
+        return (vec![response], num_stripped);
+    }
+
+    let num_chunks = chunks.len();
+    let mut next_version = first_version;
     let responses = chunks
         .into_iter()
-        .map(|chunk| TransactionsResponse {
-            chain_id: Some(chain_id as u64),
-            transactions: chunk,
+        .enumerate()
+        .map(|(index, chunk)| {
+            // The last response also covers the filtered transactions at the end of the batch.
+            let chunk_last_version = if index == num_chunks - 1 {
+                last_version
+            } else {
+                chunk.last().unwrap().version
+            };
+            let processed_range = ProcessedRange {
+                first_version: next_version,
+                last_version: chunk_last_version,
+            };
+            next_version = chunk_last_version + 1;
+            TransactionsResponse {
+                chain_id: Some(chain_id as u64),
+                transactions: chunk,
+                processed_range: Some(processed_range),
+            }
         })
         .collect();
     (responses, num_stripped)
 }
 
-// This is a CPU bound operation, so we spawn_blocking
-async fn deserialize_cached_transactions(
-    transactions: Vec<Vec<u8>>,
-    storage_format: StorageFormat,
-) -> anyhow::Result<Vec<Transaction>> {
-    let task = tokio::task::spawn_blocking(move || {
-        transactions
-            .into_iter()
-            .map(|transaction| {
-                let cache_entry = CacheEntry::new(transaction, storage_format);
-                cache_entry.into_transaction()
-            })
-            .collect::<Vec<Transaction>>()
-    })
-    .await;
-    task.context("Transaction bytes to CacheEntry deserialization task failed")
-}
-
 /// Fetches data from cache or the file store. It returns the data if it is ready in the cache or file store.
 /// Otherwise, it returns the status of the data fetching.
 async fn data_fetch(
     starting_version: u64,
-    cache_operator: &mut CacheOperator<redis::aio::ConnectionManager>,
+    cache: &dyn CacheBackend,
     file_store_operator: Arc<Box<dyn FileStoreOperator>>,
     request_metadata: Arc<IndexerGrpcRequestMetadata>,
-    storage_format: StorageFormat,
 ) -> anyhow::Result<TransactionsDataStatus> {
-    let current_batch_start_time = std::time::Instant::now();
-    let batch_get_result = cache_operator
-        .batch_get_encoded_proto_data(starting_version)
-        .await;
-
-    match batch_get_result {
+    match cache.check_cache_coverage_status(starting_version).await? {
         // Data is not ready yet in the cache.
-        Ok(CacheBatchGetStatus::NotReady) => Ok(TransactionsDataStatus::AheadOfCache),
-        Ok(CacheBatchGetStatus::Ok(transactions)) => {
-            let decoding_start_time = std::time::Instant::now();
+        CacheCoverageStatus::DataNotReady => Ok(TransactionsDataStatus::AheadOfCache),
+        CacheCoverageStatus::CacheHit(num_of_transactions) => {
+            let (transactions, io_duration, decoding_duration) = cache
+                .get_transactions_with_durations(starting_version, num_of_transactions)
+                .await?;
             let size_in_bytes = transactions
                 .iter()
-                .map(|transaction| transaction.len())
+                .map(|transaction| transaction.encoded_len())
                 .sum::<usize>();
-            let num_of_transactions = transactions.len();
-            let duration_in_secs = current_batch_start_time.elapsed().as_secs_f64();
-
-            let transactions =
-                deserialize_cached_transactions(transactions, storage_format).await?;
             let start_version_timestamp = transactions.first().unwrap().timestamp.as_ref();
             let end_version_timestamp = transactions.last().unwrap().timestamp.as_ref();
 
@@ -760,7 +789,7 @@ async fn data_fetch(
                 Some(starting_version as i64 + num_of_transactions as i64 - 1),
                 start_version_timestamp,
                 end_version_timestamp,
-                Some(duration_in_secs),
+                Some(io_duration),
                 Some(size_in_bytes),
                 Some(num_of_transactions as i64),
                 Some(&request_metadata),
@@ -772,7 +801,7 @@ async fn data_fetch(
                 Some(starting_version as i64 + num_of_transactions as i64 - 1),
                 start_version_timestamp,
                 end_version_timestamp,
-                Some(decoding_start_time.elapsed().as_secs_f64()),
+                Some(decoding_duration),
                 Some(size_in_bytes),
                 Some(num_of_transactions as i64),
                 Some(&request_metadata),
@@ -780,13 +809,12 @@ async fn data_fetch(
 
             Ok(TransactionsDataStatus::Success(transactions))
         },
-        Ok(CacheBatchGetStatus::EvictedFromCache) => {
+        CacheCoverageStatus::CacheEvicted => {
             let transactions =
                 data_fetch_from_filestore(starting_version, file_store_operator, request_metadata)
                     .await?;
             Ok(TransactionsDataStatus::Success(transactions))
         },
-        Err(e) => Err(e),
     }
 }
 
@@ -906,33 +934,49 @@ async fn channel_send_multiple_with_timeout(
         .iter()
         .map(|resp_item| resp_item.encoded_len())
         .sum::<usize>();
-    let overall_start_txn = resp_items.first().unwrap().transactions.first().unwrap();
-    let overall_end_txn = resp_items.last().unwrap().transactions.last().unwrap();
-    let overall_start_version = overall_start_txn.version;
-    let overall_end_version = overall_end_txn.version;
-    let overall_start_txn_timestamp = overall_start_txn.clone().timestamp;
-    let overall_end_txn_timestamp = overall_end_txn.clone().timestamp;
+    // Note: with a transaction filter, the responses may not contain any transactions.
+    let overall_start_version = resp_items
+        .first()
+        .unwrap()
+        .processed_range
+        .as_ref()
+        .unwrap()
+        .first_version;
+    let overall_end_version = resp_items
+        .last()
+        .unwrap()
+        .processed_range
+        .as_ref()
+        .unwrap()
+        .last_version;
+    let overall_start_txn_timestamp = resp_items
+        .first()
+        .unwrap()
+        .transactions
+        .first()
+        .and_then(|txn| txn.timestamp.clone());
+    let overall_end_txn_timestamp = resp_items
+        .last()
+        .unwrap()
+        .transactions
+        .last()
+        .and_then(|txn| txn.timestamp.clone());
 
     for resp_item in resp_items {
         let send_start_time = Instant::now();
         let response_size = resp_item.encoded_len();
         let num_of_transactions = resp_item.transactions.len();
-        let start_version = resp_item.transactions.first().unwrap().version;
-        let end_version = resp_item.transactions.last().unwrap().version;
+        let processed_range = resp_item.processed_range.as_ref().unwrap();
+        let start_version = processed_range.first_version;
+        let end_version = processed_range.last_version;
         let start_version_txn_timestamp = resp_item
             .transactions
             .first()
-            .unwrap()
-            .timestamp
-            .as_ref()
-            .unwrap();
+            .and_then(|txn| txn.timestamp.clone());
         let end_version_txn_timestamp = resp_item
             .transactions
             .last()
-            .unwrap()
-            .timestamp
-            .as_ref()
-            .unwrap();
+            .and_then(|txn| txn.timestamp.clone());
 
         tx.send_timeout(
             Result::<TransactionsResponse, Status>::Ok(resp_item.clone()),
@@ -945,8 +989,8 @@ async fn channel_send_multiple_with_timeout(
             IndexerGrpcStep::DataServiceChunkSent,
             Some(start_version as i64),
             Some(end_version as i64),
-            Some(start_version_txn_timestamp),
-            Some(end_version_txn_timestamp),
+            start_version_txn_timestamp.as_ref(),
+            end_version_txn_timestamp.as_ref(),
             Some(send_start_time.elapsed().as_secs_f64()),
             Some(response_size),
             Some(num_of_transactions as i64),
@@ -1010,6 +1054,7 @@ fn strip_transactions(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aptos_indexer_grpc_utils::DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES;
     use aptos_protos::transaction::v1::{
         transaction::TxnData, transaction_payload::Payload, EntryFunctionId, EntryFunctionPayload,
         Event, MoveModuleId, Signature, Transaction, TransactionInfo, TransactionPayload,
@@ -1316,4 +1361,115 @@ mod tests {
         assert_ne!(user_transaction.events.len(), 0);
         assert_ne!(txn.info.as_ref().unwrap().changes.len(), 0);
     }
+
+    #[test]
+    fn test_transactions_responses_are_filtered() {
+        let module_address = "0x5678".to_string();
+        let transactions = (1..=6)
+            .map(|version| {
+                // Only the transactions with an even version match the filter
+                let mut txn = if version % 2 == 0 {
+                    create_test_transaction(
+                        MODULE_ADDRESS.to_string(),
+                        MODULE_NAME.to_string(),
+                        FUNCTION_NAME.to_string(),
+                    )
+                } else {
+                    create_test_transaction(
+                        module_address.clone(),
+                        MODULE_NAME.to_string(),
+                        FUNCTION_NAME.to_string(),
+                    )
+                };
+                txn.version = version;
+                txn
+            })
+            .collect::<Vec<_>>();
+
+        // The filter is sent in the request as JSON
+        let filter = BooleanTransactionFilter::from(APIFilter::UserTransactionFilter(
+            UserTransactionFilterBuilder::default()
+                .payload(
+                    UserTransactionPayloadFilterBuilder::default()
+                        .function(
+                            EntryFunctionFilterBuilder::default()
+                                .address(MODULE_ADDRESS.to_string())
+                                .build()
+                                .unwrap(),
+                        )
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap(),
+        ));
+        let filter_json = serde_json::to_string(&filter).unwrap();
+        let transaction_filter = parse_transaction_filter(
+            Some(&filter_json),
+            DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES,
+        )
+        .unwrap();
+        assert!(transaction_filter.is_some());
+
+        // Only the matching transactions are returned, but the whole batch is processed
+        let strip_nothing_filter = BooleanTransactionFilter::new_or(vec![]);
+        let (responses, num_stripped) = get_transactions_responses_builder(
+            transactions.clone(),
+            1,
+            &strip_nothing_filter,
+            &transaction_filter,
+        );
+        assert_eq!(num_stripped, 0);
+        assert_eq!(responses.len(), 1);
+        let versions: Vec<u64> = responses[0]
+            .transactions
+            .iter()
+            .map(|t| t.version)
+            .collect();
+        assert_eq!(versions, vec![2, 4, 6]);
+        assert_eq!(
+            responses[0].processed_range,
+            Some(ProcessedRange {
+                first_version: 1,
+                last_version: 6,
+            })
+        );
+
+        // If nothing matches, a single response marks the processed range
+        let (responses, _) = get_transactions_responses_builder(
+            transactions
+                .into_iter()
+                .filter(|t| t.version % 2 == 1)
+                .collect(),
+            1,
+            &strip_nothing_filter,
+            &transaction_filter,
+        );
+        assert_eq!(responses.len(), 1);
+        assert!(responses[0].transactions.is_empty());
+        assert_eq!(
+            responses[0].processed_range,
+            Some(ProcessedRange {
+                first_version: 1,
+                last_version: 5,
+            })
+        );
+
+        // Malformed and oversized filters are rejected
+        assert_eq!(
+            parse_transaction_filter(
+                Some("not a filter"),
+                DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES
+            )
+            .unwrap_err()
+            .code(),
+            tonic::Code::InvalidArgument
+        );
+        assert_eq!(
+            parse_transaction_filter(Some(&filter_json), filter_json.len() - 1)
+                .unwrap_err()
+                .code(),
+            tonic::Code::InvalidArgument
+        );
+    }
 }
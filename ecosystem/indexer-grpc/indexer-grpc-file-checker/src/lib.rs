@@ -5,6 +5,7 @@ pub mod processor;
 
 use anyhow::Result;
 use aptos_indexer_grpc_server_framework::RunnableConfig;
+use aptos_indexer_grpc_utils::config::S3Endpoint;
 use processor::Processor;
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +15,9 @@ pub struct IndexerGrpcFileCheckerConfig {
     pub existing_bucket_name: String,
     pub new_bucket_name: String,
     pub starting_version: u64,
+    /// If set, both buckets are on this S3 compatible service; otherwise, they're on GCS.
+    #[serde(default)]
+    pub s3_endpoint: Option<S3Endpoint>,
 }
 
 impl From<IndexerGrpcFileCheckerConfig> for Processor {
@@ -22,6 +26,7 @@ impl From<IndexerGrpcFileCheckerConfig> for Processor {
             existing_bucket_name: val.existing_bucket_name,
             new_bucket_name: val.new_bucket_name,
             starting_version: val.starting_version,
+            s3_endpoint: val.s3_endpoint,
         }
     }
 }
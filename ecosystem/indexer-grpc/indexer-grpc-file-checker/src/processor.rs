@@ -2,8 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{ensure, Context, Result};
-use aptos_indexer_grpc_utils::compression_util::{FileEntry, StorageFormat};
+use aptos_indexer_grpc_utils::{
+    compression_util::{FileEntry, StorageFormat},
+    config::S3Endpoint,
+};
 use aptos_metrics_core::{register_int_counter, IntCounter};
+use aptos_s3_client::{S3Client, S3Credentials};
 use cloud_storage::Client;
 use once_cell::sync::Lazy;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -31,6 +35,70 @@ pub struct Processor {
     pub new_bucket_name: String,
     /// The version to start from. This is for **bootstrapping** the file checker only.
     pub starting_version: u64,
+    /// The S3 compatible service hosting the buckets; if not set, the buckets are on GCS.
+    pub s3_endpoint: Option<S3Endpoint>,
+}
+
+/// A bucket on GCS or on an S3 compatible service.
+enum Bucket {
+    Gcs { client: Client, bucket_name: String },
+    S3(S3Client),
+}
+
+impl Bucket {
+    fn name(&self) -> &str {
+        match self {
+            Bucket::Gcs { bucket_name, .. } => bucket_name,
+            Bucket::S3(client) => client.bucket(),
+        }
+    }
+
+    async fn download_raw_file(&self, file_name: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Bucket::Gcs {
+                client,
+                bucket_name,
+            } => download_raw_file(client, bucket_name, file_name).await,
+            Bucket::S3(client) => Ok(client
+                .get_object(file_name)
+                .await?
+                .map(|file| file.to_vec())),
+        }
+    }
+
+    async fn download_file<T>(&self, file_name: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let file = self.download_raw_file(file_name).await?;
+        match file {
+            Some(file) => {
+                let file = serde_json::from_slice(&file).context("Failed to parse file.")?;
+                Ok(Some(file))
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn upload_json_file(&self, file_name: &str, bytes: Vec<u8>) -> Result<()> {
+        match self {
+            Bucket::Gcs {
+                client,
+                bucket_name,
+            } => {
+                client
+                    .object()
+                    .create(bucket_name, bytes, file_name, "application/json")
+                    .await?;
+            },
+            Bucket::S3(client) => {
+                client
+                    .put_object(file_name, bytes.into(), Some("application/json"))
+                    .await?;
+            },
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,7 +115,7 @@ pub struct MetadataFile {
 
 impl Processor {
     pub async fn run(&self) -> Result<()> {
-        let (client, mut progress_file) = self.init().await?;
+        let (existing_bucket, new_bucket, mut progress_file) = self.init().await?;
         let mut last_update_time = std::time::Instant::now();
 
         loop {
@@ -55,14 +123,13 @@ impl Processor {
 
             let file_name =
                 FileEntry::build_key(current_version, StorageFormat::Lz4CompressedProto);
-            let existing_file =
-                download_raw_file(&client, &self.existing_bucket_name, &file_name).await?;
-            let new_file = download_raw_file(&client, &self.new_bucket_name, &file_name).await?;
+            let existing_file = existing_bucket.download_raw_file(&file_name).await?;
+            let new_file = new_bucket.download_raw_file(&file_name).await?;
             if existing_file.is_none() || new_file.is_none() {
                 let bucket_name = if existing_file.is_none() {
-                    &self.existing_bucket_name
+                    existing_bucket.name()
                 } else {
-                    &self.new_bucket_name
+                    new_bucket.name()
                 };
                 tracing::info!(
                     bucket_name = bucket_name,
@@ -100,14 +167,8 @@ impl Processor {
             // Upload the progress file.
             let progress_file_bytes =
                 serde_json::to_vec(&progress_file).context("Failed to serialize progress file.")?;
-            client
-                .object()
-                .create(
-                    &self.new_bucket_name,
-                    progress_file_bytes,
-                    PROGRESS_FILE_NAME,
-                    "application/json",
-                )
+            new_bucket
+                .upload_json_file(PROGRESS_FILE_NAME, progress_file_bytes)
                 .await
                 .context("Update progress file failure")?;
             tracing::info!("Progress file is updated.");
@@ -116,20 +177,41 @@ impl Processor {
     }
 
     /// Initialize the processor.
-    pub async fn init(&self) -> Result<(Client, ProgressFile)> {
-        let client = Client::new();
+    async fn init(&self) -> Result<(Bucket, Bucket, ProgressFile)> {
+        let (existing_bucket, new_bucket) = match &self.s3_endpoint {
+            Some(s3_endpoint) => {
+                let credentials = S3Credentials::from_env()?;
+                (
+                    Bucket::S3(
+                        s3_endpoint
+                            .client(self.existing_bucket_name.clone(), credentials.clone())?,
+                    ),
+                    Bucket::S3(s3_endpoint.client(self.new_bucket_name.clone(), credentials)?),
+                )
+            },
+            None => (
+                Bucket::Gcs {
+                    client: Client::new(),
+                    bucket_name: self.existing_bucket_name.clone(),
+                },
+                Bucket::Gcs {
+                    client: Client::new(),
+                    bucket_name: self.new_bucket_name.clone(),
+                },
+            ),
+        };
 
         // All errors are considered fatal: files must exist for the processor to work.
-        let existing_metadata =
-            download_file::<MetadataFile>(&client, &self.existing_bucket_name, METADATA_FILE_NAME)
-                .await
-                .context("Failed to get metadata.")?
-                .expect("Failed to download metadata file");
-        let new_metadata =
-            download_file::<MetadataFile>(&client, &self.new_bucket_name, METADATA_FILE_NAME)
-                .await
-                .context("Failed to get metadata.")?
-                .expect("Failed to download metadata file");
+        let existing_metadata = existing_bucket
+            .download_file::<MetadataFile>(METADATA_FILE_NAME)
+            .await
+            .context("Failed to get metadata.")?
+            .expect("Failed to download metadata file");
+        let new_metadata = new_bucket
+            .download_file::<MetadataFile>(METADATA_FILE_NAME)
+            .await
+            .context("Failed to get metadata.")?
+            .expect("Failed to download metadata file");
 
         // Ensure the chain IDs match.
         ensure!(
@@ -139,14 +221,14 @@ impl Processor {
             new_metadata.chain_id
         );
 
-        let progress_file =
-            download_file::<ProgressFile>(&client, &self.new_bucket_name, PROGRESS_FILE_NAME)
-                .await
-                .context("Failed to get progress file.")?
-                .unwrap_or(ProgressFile {
-                    file_checker_version: self.starting_version,
-                    file_checker_chain_id: existing_metadata.chain_id,
-                });
+        let progress_file = new_bucket
+            .download_file::<ProgressFile>(PROGRESS_FILE_NAME)
+            .await
+            .context("Failed to get progress file.")?
+            .unwrap_or(ProgressFile {
+                file_checker_version: self.starting_version,
+                file_checker_chain_id: existing_metadata.chain_id,
+            });
         // Ensure the chain IDs match.
         ensure!(
             existing_metadata.chain_id == progress_file.file_checker_chain_id,
@@ -159,7 +241,7 @@ impl Processor {
             "Processor initialized.",
         );
 
-        Ok((client, progress_file))
+        Ok((existing_bucket, new_bucket, progress_file))
     }
 }
 
@@ -184,17 +266,3 @@ async fn download_raw_file(
         Err(e) => Err(e.into()),
     }
 }
-
-async fn download_file<T>(client: &Client, bucket_name: &str, file_name: &str) -> Result<Option<T>>
-where
-    T: DeserializeOwned,
-{
-    let file = download_raw_file(client, bucket_name, file_name).await?;
-    match file {
-        Some(file) => {
-            let file = serde_json::from_slice(&file).context("Failed to parse file.")?;
-            Ok(Some(file))
-        },
-        None => Ok(None),
-    }
-}
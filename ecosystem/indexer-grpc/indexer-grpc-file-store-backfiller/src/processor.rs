@@ -41,6 +41,16 @@ pub struct ProgressFile {
     version: u64,
 }
 
+/// The backfiller can't train a zstd dictionary, so it uses the one recorded in the file store
+/// metadata. It's loaded up front so that the metadata updates keep its version.
+async fn load_zstd_dictionary(file_store_operator: &dyn FileStoreOperator) -> Result<()> {
+    file_store_operator
+        .get_zstd_dictionary()
+        .await
+        .context("The file store must have a zstd dictionary before it can be backfilled.")?;
+    Ok(())
+}
+
 impl Processor {
     pub async fn new(
         fullnode_grpc_address: url::Url,
@@ -60,8 +70,10 @@ impl Processor {
             StorageFormat::Base64UncompressedProto
         };
         if validation_mode {
+            let file_store_operator: Box<dyn FileStoreOperator> = file_store_config.create();
+            load_zstd_dictionary(file_store_operator.as_ref()).await?;
             return Ok(Self {
-                file_store_operator: file_store_config.create(),
+                file_store_operator,
                 chain_id,
                 grpc_stream: None,
                 starting_version: starting_version.unwrap_or(0),
@@ -117,6 +129,7 @@ impl Processor {
         // Metadata is guaranteed to exist now
         let metadata = file_store_operator.get_file_store_metadata().await.unwrap();
         ensure!(metadata.chain_id == chain_id, "Chain ID mismatch.");
+        load_zstd_dictionary(file_store_operator.as_ref()).await?;
         Ok(Self {
             file_store_operator,
             chain_id,
@@ -4,9 +4,12 @@
 pub mod metrics;
 pub mod processor;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use aptos_indexer_grpc_server_framework::RunnableConfig;
-use aptos_indexer_grpc_utils::{config::IndexerGrpcFileStoreConfig, types::RedisUrl};
+use aptos_indexer_grpc_utils::{
+    cache_backend::CacheClient, compression_util::StorageFormat,
+    config::IndexerGrpcFileStoreConfig, types::RedisUrl,
+};
 use processor::Processor;
 use serde::{Deserialize, Serialize};
 
@@ -46,14 +49,18 @@ impl IndexerGrpcFileStoreWorkerConfig {
 #[async_trait::async_trait]
 impl RunnableConfig for IndexerGrpcFileStoreWorkerConfig {
     async fn run(&self) -> Result<()> {
-        let mut processor = Processor::new(
-            self.redis_main_instance_address.clone(),
-            self.file_store_config.clone(),
-            self.chain_id,
-            self.enable_cache_compression,
-        )
-        .await
-        .expect("Failed to create file store processor");
+        let cache_storage_format = if self.enable_cache_compression {
+            StorageFormat::Lz4CompressedProto
+        } else {
+            StorageFormat::Base64UncompressedProto
+        };
+        let cache_client =
+            CacheClient::new_redis(&self.redis_main_instance_address, cache_storage_format)
+                .context("Failed to create file store processor")?;
+        let mut processor =
+            Processor::new(cache_client, self.file_store_config.clone(), self.chain_id)
+                .await
+                .expect("Failed to create file store processor");
         processor
             .run()
             .await
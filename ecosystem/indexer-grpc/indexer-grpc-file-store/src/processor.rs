@@ -2,61 +2,45 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::metrics::{METADATA_UPLOAD_FAILURE_COUNT, PROCESSED_VERSIONS_COUNT};
-use anyhow::{ensure, Context, Result};
+use anyhow::{ensure, Result};
 use aptos_indexer_grpc_utils::{
-    cache_operator::CacheOperator,
-    compression_util::{FileStoreMetadata, StorageFormat, FILE_ENTRY_TRANSACTION_COUNT},
+    cache_backend::{CacheBackend, CacheClient},
+    compression_util::{
+        FileStoreMetadata, StorageFormat, ZstdDictionary, FILE_ENTRY_TRANSACTION_COUNT,
+        ZSTD_DICTIONARY_MAX_SIZE,
+    },
     config::IndexerGrpcFileStoreConfig,
     counters::{log_grpc_step, IndexerGrpcStep},
     file_store_operator::FileStoreOperator,
-    types::RedisUrl,
 };
 use aptos_moving_average::MovingAverage;
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tracing::debug;
 
 // If the version is ahead of the cache head, retry after a short sleep.
 const AHEAD_OF_CACHE_SLEEP_DURATION_IN_MILLIS: u64 = 100;
 const SERVICE_TYPE: &str = "file_worker";
 const MAX_CONCURRENT_BATCHES: usize = 50;
+// The zstd dictionary is trained from the first transactions uploaded to the file store.
+const ZSTD_DICTIONARY_TRAINING_TRANSACTION_COUNT: u64 = 10_000;
+// A file store uses a single dictionary; files can't be read without the one they're written with.
+const ZSTD_DICTIONARY_VERSION: u64 = 1;
 
 /// Processor tails the data in cache and stores the data in file store.
 pub struct Processor {
-    cache_operator: CacheOperator<redis::aio::ConnectionManager>,
+    cache: Arc<dyn CacheBackend>,
     file_store_operator: Box<dyn FileStoreOperator>,
     chain_id: u64,
 }
 
 impl Processor {
     pub async fn new(
-        redis_main_instance_address: RedisUrl,
+        cache_client: CacheClient,
         file_store_config: IndexerGrpcFileStoreConfig,
         chain_id: u64,
-        enable_cache_compression: bool,
     ) -> Result<Self> {
-        let cache_storage_format = if enable_cache_compression {
-            StorageFormat::Lz4CompressedProto
-        } else {
-            StorageFormat::Base64UncompressedProto
-        };
-
-        // Connection to redis is a hard dependency for file store processor.
-        let conn = redis::Client::open(redis_main_instance_address.0.clone())
-            .with_context(|| {
-                format!(
-                    "Create redis client for {} failed",
-                    redis_main_instance_address.0
-                )
-            })?
-            .get_tokio_connection_manager()
-            .await
-            .with_context(|| {
-                format!(
-                    "Create redis connection to {} failed.",
-                    redis_main_instance_address.0
-                )
-            })?;
-        let mut cache_operator = CacheOperator::new(conn, cache_storage_format);
+        // Connection to the cache is a hard dependency for file store processor.
+        let cache = cache_client.connect().await?;
 
         let mut file_store_operator: Box<dyn FileStoreOperator> = file_store_config.create();
         file_store_operator.verify_storage_bucket_existence().await;
@@ -82,22 +66,26 @@ impl Processor {
         let metadata = file_store_operator.get_file_store_metadata().await.unwrap();
 
         ensure!(metadata.chain_id == chain_id, "Chain ID mismatch.");
+        if metadata.zstd_dictionary_version.is_some() {
+            // Load the dictionary up front, so that the metadata updates keep its version.
+            file_store_operator.get_zstd_dictionary().await?;
+        }
         let batch_start_version = metadata.version;
         // Cache config in the cache
-        cache_operator.cache_setup_if_needed().await?;
-        match cache_operator.get_chain_id().await? {
+        cache.cache_setup_if_needed().await?;
+        match cache.get_chain_id().await? {
             Some(id) => {
                 ensure!(id == chain_id, "Chain ID mismatch.");
             },
             None => {
-                cache_operator.set_chain_id(chain_id).await?;
+                cache.set_chain_id(chain_id).await?;
             },
         }
-        cache_operator
+        cache
             .update_file_store_latest_version(batch_start_version)
             .await?;
         Ok(Self {
-            cache_operator,
+            cache,
             file_store_operator,
             chain_id,
         })
@@ -121,11 +109,16 @@ impl Processor {
         ensure!(metadata.chain_id == chain_id, "Chain ID mismatch.");
 
         let mut batch_start_version = metadata.version;
+        if self.file_store_operator.storage_format() == StorageFormat::ZstdCompressedProto
+            && metadata.zstd_dictionary_version.is_none()
+        {
+            self.train_zstd_dictionary(batch_start_version).await?;
+        }
 
         let mut tps_calculator = MovingAverage::new(10_000);
         loop {
             let latest_loop_time = std::time::Instant::now();
-            let cache_worker_latest = self.cache_operator.get_latest_version().await?.unwrap();
+            let cache_worker_latest = self.cache.get_latest_version().await?.unwrap();
 
             // batches tracks the start version of the batches to fetch. 1000 at the time
             let mut batches = vec![];
@@ -155,11 +148,11 @@ impl Processor {
             let mut tasks = vec![];
 
             for start_version in batches {
-                let mut cache_operator_clone = self.cache_operator.clone();
+                let cache = self.cache.clone();
                 let mut file_store_operator_clone = self.file_store_operator.clone_box();
                 let task = tokio::spawn(async move {
                     let fetch_start_time = std::time::Instant::now();
-                    let transactions = cache_operator_clone
+                    let transactions = cache
                         .get_transactions(start_version, FILE_ENTRY_TRANSACTION_COUNT)
                         .await
                         .unwrap();
@@ -255,7 +248,7 @@ impl Processor {
 
             // Update filestore metadata. First do it in cache for performance then update metadata file
             let start_metadata_upload_time = std::time::Instant::now();
-            self.cache_operator
+            self.cache
                 .update_file_store_latest_version(batch_start_version)
                 .await?;
             while self
@@ -301,4 +294,48 @@ impl Processor {
             );
         }
     }
+
+    /// Trains the zstd dictionary from the transactions starting at `start_version`, then
+    /// uploads it and records its version in the metadata before any file is written.
+    async fn train_zstd_dictionary(&mut self, start_version: u64) -> Result<()> {
+        let end_version = start_version + ZSTD_DICTIONARY_TRAINING_TRANSACTION_COUNT;
+        while self.cache.get_latest_version().await?.unwrap_or_default() < end_version {
+            debug!(
+                end_version = end_version,
+                "[Filestore] Waiting for the transactions to train the zstd dictionary"
+            );
+            tokio::time::sleep(Duration::from_millis(
+                AHEAD_OF_CACHE_SLEEP_DURATION_IN_MILLIS,
+            ))
+            .await;
+        }
+        let mut transactions =
+            Vec::with_capacity(ZSTD_DICTIONARY_TRAINING_TRANSACTION_COUNT as usize);
+        for version in (start_version..end_version).step_by(FILE_ENTRY_TRANSACTION_COUNT as usize) {
+            transactions.extend(
+                self.cache
+                    .get_transactions(version, FILE_ENTRY_TRANSACTION_COUNT)
+                    .await?,
+            );
+        }
+        let dictionary = tokio::task::spawn_blocking(move || {
+            ZstdDictionary::train(
+                ZSTD_DICTIONARY_VERSION,
+                &transactions,
+                ZSTD_DICTIONARY_MAX_SIZE,
+            )
+        })
+        .await??;
+        tracing::info!(
+            dictionary_version = dictionary.version(),
+            dictionary_size = dictionary.as_bytes().len(),
+            "[Filestore] Zstd dictionary is trained"
+        );
+        self.file_store_operator
+            .upload_zstd_dictionary(dictionary)
+            .await?;
+        self.file_store_operator
+            .update_file_store_metadata_internal(self.chain_id, start_version)
+            .await
+    }
 }
@@ -2,11 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{stream_coordinator::IndexerStreamCoordinator, ServiceContext};
+use aptos_indexer_grpc_utils::{
+    parse_transaction_filter, DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES,
+};
 use aptos_logger::{error, info};
 use aptos_protos::{
-    indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
+    indexer::v1::{
+        raw_data_server::RawData, GetTransactionsRequest, ProcessedRange, TransactionsResponse,
+    },
     internal::fullnode::v1::transactions_from_node_response,
 };
+use aptos_transaction_filter::Filterable;
 use futures::Stream;
 use std::pin::Pin;
 use tokio::sync::mpsc;
@@ -44,6 +50,10 @@ impl RawData for LocalnetDataService {
         let output_batch_size = self.service_context.output_batch_size;
         let ledger_chain_id = context.chain_id().id();
         let transactions_count = r.transactions_count;
+        let transaction_filter = parse_transaction_filter(
+            r.transaction_filter.as_deref(),
+            DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES,
+        )?;
         // Creates a channel to send the stream to the client
         let (tx, mut rx) = mpsc::channel(TRANSACTION_CHANNEL_SIZE);
         let (external_service_tx, external_service_rx) = mpsc::channel(TRANSACTION_CHANNEL_SIZE);
@@ -90,9 +100,8 @@ impl RawData for LocalnetDataService {
                     }
                 }
 
-                let response = response.map(|t| TransactionsResponse {
-                    chain_id: Some(ledger_chain_id as u64),
-                    transactions: match t.response.expect("Response must be set") {
+                let response = response.map(|t| {
+                    let transactions = match t.response.expect("Response must be set") {
                         transactions_from_node_response::Response::Data(transaction_output) => {
                             let mut transactions = transaction_output.transactions;
                             let current_transactions_count = transactions.len() as u64;
@@ -104,7 +113,21 @@ impl RawData for LocalnetDataService {
                             transactions
                         },
                         _ => panic!("Unexpected response type."),
-                    },
+                    };
+                    // The processed range also covers the transactions that are filtered out.
+                    let processed_range =
+                        transactions
+                            .first()
+                            .zip(transactions.last())
+                            .map(|(first, last)| ProcessedRange {
+                                first_version: first.version,
+                                last_version: last.version,
+                            });
+                    TransactionsResponse {
+                        chain_id: Some(ledger_chain_id as u64),
+                        transactions: transaction_filter.filter_vec(transactions),
+                        processed_range,
+                    }
                 });
                 match external_service_tx.send(response).await {
                     Ok(_) => {},
@@ -32,12 +32,12 @@ impl InternalIndexerDBService {
         db_reader: Arc<dyn DbReader>,
         internal_indexer_db: InternalIndexerDB,
         update_receiver: WatchReceiver<Version>,
-    ) -> Self {
-        let internal_db_indexer = Arc::new(DBIndexer::new(internal_indexer_db, db_reader));
-        Self {
+    ) -> Result<Self> {
+        let internal_db_indexer = Arc::new(DBIndexer::new(internal_indexer_db, db_reader)?);
+        Ok(Self {
             db_indexer: internal_db_indexer,
             update_receiver,
-        }
+        })
     }
 
     pub fn get_indexer_db_for_restore(db_dir: &Path) -> Option<InternalIndexerDB> {
@@ -246,7 +246,8 @@ impl MockInternalIndexerDBService {
         let db = InternalIndexerDBService::get_indexer_db(node_config).unwrap();
         let handle = Handle::current();
         let mut internal_indexer_db_service =
-            InternalIndexerDBService::new(db_reader, db, update_receiver);
+            InternalIndexerDBService::new(db_reader, db, update_receiver)
+                .expect("Failed to create the internal indexer db service");
         let db_indexer = internal_indexer_db_service.get_db_indexer();
         let config_clone = node_config.to_owned();
         handle.spawn(async move {
@@ -25,17 +25,17 @@ pub fn bootstrap_internal_indexer_db(
     db_rw: DbReaderWriter,
     internal_indexer_db: Option<InternalIndexerDB>,
     update_receiver: Option<WatchReceiver<Version>>,
-) -> Option<(Runtime, Arc<DBIndexer>)> {
+) -> anyhow::Result<Option<(Runtime, Arc<DBIndexer>)>> {
     if !config.indexer_db_config.is_internal_indexer_db_enabled() || internal_indexer_db.is_none() {
-        return None;
+        return Ok(None);
     }
-    let runtime = aptos_runtimes::spawn_named_runtime("index-db".to_string(), None);
     // Set up db config and open up the db initially to read metadata
     let mut indexer_service = InternalIndexerDBService::new(
         db_rw.reader,
         internal_indexer_db.unwrap(),
         update_receiver.expect("Internal indexer db update receiver is missing"),
-    );
+    )?;
+    let runtime = aptos_runtimes::spawn_named_runtime("index-db".to_string(), None);
     let db_indexer = indexer_service.get_db_indexer();
     // Spawn task for db indexer
     let config_clone = config.to_owned();
@@ -43,7 +43,7 @@ pub fn bootstrap_internal_indexer_db(
         indexer_service.run(&config_clone).await.unwrap();
     });
 
-    Some((runtime, db_indexer))
+    Ok(Some((runtime, db_indexer)))
 }
 
 /// Creates a runtime which creates a thread pool which sets up fullnode indexer table info service
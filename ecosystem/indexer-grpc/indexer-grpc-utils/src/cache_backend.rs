@@ -0,0 +1,173 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    cache_operator::{CacheCoverageStatus, CacheOperator},
+    compression_util::StorageFormat,
+    in_process_cache::InProcessCache,
+    types::RedisUrl,
+};
+use anyhow::Context;
+use aptos_protos::transaction::v1::Transaction;
+use std::sync::Arc;
+
+/// Cache of the most recent transactions. It's written by the cache worker, and read by
+/// the file store processor and the data service.
+#[async_trait::async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Initializes the latest version if the cache is empty. Returns true if initialized.
+    async fn cache_setup_if_needed(&self) -> anyhow::Result<bool>;
+
+    async fn set_chain_id(&self, chain_id: u64) -> anyhow::Result<()>;
+
+    async fn get_chain_id(&self) -> anyhow::Result<Option<u64>>;
+
+    /// Returns the next version to be cached, i.e., the latest version is exclusive.
+    async fn get_latest_version(&self) -> anyhow::Result<Option<u64>>;
+
+    async fn get_file_store_latest_version(&self) -> anyhow::Result<Option<u64>>;
+
+    async fn update_file_store_latest_version(&self, latest_version: u64) -> anyhow::Result<()>;
+
+    async fn check_cache_coverage_status(
+        &self,
+        requested_version: u64,
+    ) -> anyhow::Result<CacheCoverageStatus>;
+
+    /// Returns the transactions with the io and decoding durations in seconds.
+    /// Fails if not all transactions requested are returned.
+    async fn get_transactions_with_durations(
+        &self,
+        start_version: u64,
+        transaction_count: u64,
+    ) -> anyhow::Result<(Vec<Transaction>, f64, f64)>;
+
+    /// Fails if not all transactions requested are returned.
+    async fn get_transactions(
+        &self,
+        start_version: u64,
+        transaction_count: u64,
+    ) -> anyhow::Result<Vec<Transaction>> {
+        let (transactions, _, _) = self
+            .get_transactions_with_durations(start_version, transaction_count)
+            .await?;
+        Ok(transactions)
+    }
+
+    /// Chunks of the same batch may be written concurrently and out of order; they're only
+    /// visible to readers after the latest version is updated.
+    async fn update_cache_transactions(&self, transactions: Vec<Transaction>)
+        -> anyhow::Result<()>;
+
+    /// Fails if `version` leaves a gap after the current latest version.
+    async fn update_cache_latest_version(
+        &self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<()>;
+}
+
+// Redis connections are cheap to clone, so every call works on its own copy of the operator.
+#[async_trait::async_trait]
+impl<T> CacheBackend for CacheOperator<T>
+where
+    T: redis::aio::ConnectionLike + Send + Sync + Clone + 'static,
+{
+    async fn cache_setup_if_needed(&self) -> anyhow::Result<bool> {
+        CacheOperator::cache_setup_if_needed(&mut self.clone()).await
+    }
+
+    async fn set_chain_id(&self, chain_id: u64) -> anyhow::Result<()> {
+        CacheOperator::set_chain_id(&mut self.clone(), chain_id).await
+    }
+
+    async fn get_chain_id(&self) -> anyhow::Result<Option<u64>> {
+        CacheOperator::get_chain_id(&mut self.clone()).await
+    }
+
+    async fn get_latest_version(&self) -> anyhow::Result<Option<u64>> {
+        CacheOperator::get_latest_version(&mut self.clone()).await
+    }
+
+    async fn get_file_store_latest_version(&self) -> anyhow::Result<Option<u64>> {
+        CacheOperator::get_file_store_latest_version(&mut self.clone()).await
+    }
+
+    async fn update_file_store_latest_version(&self, latest_version: u64) -> anyhow::Result<()> {
+        CacheOperator::update_file_store_latest_version(&mut self.clone(), latest_version).await
+    }
+
+    async fn check_cache_coverage_status(
+        &self,
+        requested_version: u64,
+    ) -> anyhow::Result<CacheCoverageStatus> {
+        CacheOperator::check_cache_coverage_status(&mut self.clone(), requested_version).await
+    }
+
+    async fn get_transactions_with_durations(
+        &self,
+        start_version: u64,
+        transaction_count: u64,
+    ) -> anyhow::Result<(Vec<Transaction>, f64, f64)> {
+        CacheOperator::get_transactions_with_durations(
+            &mut self.clone(),
+            start_version,
+            transaction_count,
+        )
+        .await
+    }
+
+    async fn update_cache_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+    ) -> anyhow::Result<()> {
+        CacheOperator::update_cache_transactions(&mut self.clone(), transactions).await
+    }
+
+    async fn update_cache_latest_version(
+        &self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        CacheOperator::update_cache_latest_version(&mut self.clone(), num_of_versions, version)
+            .await
+    }
+}
+
+/// Opens handles to the cache, which lives either in Redis or in the current process.
+#[derive(Clone)]
+pub enum CacheClient {
+    Redis {
+        client: redis::Client,
+        storage_format: StorageFormat,
+    },
+    InProcess(InProcessCache),
+}
+
+impl CacheClient {
+    pub fn new_redis(address: &RedisUrl, storage_format: StorageFormat) -> anyhow::Result<Self> {
+        let client = redis::Client::open(address.0.clone())
+            .with_context(|| format!("Failed to create redis client for {}", address))?;
+        Ok(Self::Redis {
+            client,
+            storage_format,
+        })
+    }
+
+    /// Returns a handle to the cache. For Redis, this opens a new connection.
+    pub async fn connect(&self) -> anyhow::Result<Arc<dyn CacheBackend>> {
+        match self {
+            Self::Redis {
+                client,
+                storage_format,
+            } => {
+                let conn = client
+                    .get_tokio_connection_manager()
+                    .await
+                    .context("Get redis connection failed.")?;
+                Ok(Arc::new(CacheOperator::new(conn, *storage_format)))
+            },
+            Self::InProcess(cache) => Ok(Arc::new(cache.clone())),
+        }
+    }
+}
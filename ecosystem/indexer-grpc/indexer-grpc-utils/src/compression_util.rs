@@ -7,9 +7,15 @@ use lz4::{Decoder, EncoderBuilder};
 use prost::Message;
 use ripemd::{Digest, Ripemd128};
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Write};
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
 
 pub const FILE_ENTRY_TRANSACTION_COUNT: u64 = 1000;
+// The same as the zstd CLI default.
+pub const ZSTD_DICTIONARY_MAX_SIZE: usize = 112_640;
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
 pub enum StorageFormat {
@@ -20,6 +26,9 @@ pub enum StorageFormat {
     // Only used for legacy file format.
     // Use by file store only.
     JsonBase64UncompressedProto,
+    // Zstd compressed with a dictionary trained from transaction protobufs.
+    // Use by file store only.
+    ZstdCompressedProto,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -43,15 +52,24 @@ pub struct FileStoreMetadata {
     // Storage format; backward compatible.
     #[serde(default = "default_file_storage_format")]
     pub storage_format: StorageFormat,
+    // The version of the dictionary used by the zstd storage format; set once the dictionary is trained.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zstd_dictionary_version: Option<u64>,
 }
 
 impl FileStoreMetadata {
-    pub fn new(chain_id: u64, version: u64, storage_format: StorageFormat) -> Self {
+    pub fn new(
+        chain_id: u64,
+        version: u64,
+        storage_format: StorageFormat,
+        zstd_dictionary_version: Option<u64>,
+    ) -> Self {
         Self {
             chain_id,
             file_folder_size: FILE_ENTRY_TRANSACTION_COUNT as usize,
             version,
             storage_format,
+            zstd_dictionary_version,
         }
     }
 
@@ -65,6 +83,52 @@ impl FileStoreMetadata {
     }
 }
 
+/// ZstdDictionary is the dictionary used by the zstd storage format. Transaction protobufs are
+/// very repetitive, so a dictionary trained from them greatly improves the compression ratio.
+/// A file store uses a single dictionary, whose version is recorded in the metadata; files
+/// can't be decompressed without it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZstdDictionary {
+    version: u64,
+    bytes: Arc<Vec<u8>>,
+}
+
+impl ZstdDictionary {
+    pub fn new(version: u64, bytes: Vec<u8>) -> Self {
+        Self {
+            version,
+            bytes: Arc::new(bytes),
+        }
+    }
+
+    /// Trains a dictionary of at most `max_size` bytes, using each transaction as a sample.
+    pub fn train(
+        version: u64,
+        transactions: &[Transaction],
+        max_size: usize,
+    ) -> anyhow::Result<Self> {
+        let samples = transactions
+            .iter()
+            .map(|transaction| transaction.encode_to_vec())
+            .collect::<Vec<_>>();
+        let bytes = zstd::dict::from_samples(&samples, max_size)
+            .map_err(|e| anyhow::anyhow!("Zstd dictionary training failed: {}", e))?;
+        Ok(Self::new(version, bytes))
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn build_key(version: u64) -> String {
+        format!("zstd_dictionaries/{}.dict", version)
+    }
+}
+
 pub enum CacheEntry {
     Lz4CompressionProto(Vec<u8>),
     // Only used for legacy cache entry.
@@ -80,6 +144,9 @@ impl CacheEntry {
             StorageFormat::JsonBase64UncompressedProto => {
                 panic!("JsonBase64UncompressedProto is not supported.")
             },
+            StorageFormat::ZstdCompressedProto => {
+                panic!("ZstdCompressedProto is not supported.")
+            },
         }
     }
 
@@ -121,6 +188,9 @@ impl CacheEntry {
                 // This is fatal to see that we are using legacy file format in cache side.
                 panic!("JsonBase64UncompressedProto is not supported in cache.")
             },
+            StorageFormat::ZstdCompressedProto => {
+                panic!("ZstdCompressedProto is not supported in cache.")
+            },
         }
     }
 
@@ -136,6 +206,9 @@ impl CacheEntry {
                 // This is fatal to see that we are using legacy file format in cache side.
                 panic!("JsonBase64UncompressedProto is not supported in cache.")
             },
+            StorageFormat::ZstdCompressedProto => {
+                panic!("ZstdCompressedProto is not supported in cache.")
+            },
         }
     }
 
@@ -161,6 +234,7 @@ pub enum FileEntry {
     Lz4CompressionProto(Vec<u8>),
     // Only used for legacy file format.
     JsonBase64UncompressedProto(Vec<u8>),
+    ZstdCompressionProto(Vec<u8>),
 }
 
 impl FileEntry {
@@ -171,6 +245,7 @@ impl FileEntry {
                 panic!("Base64UncompressedProto is not supported.")
             },
             StorageFormat::JsonBase64UncompressedProto => Self::JsonBase64UncompressedProto(bytes),
+            StorageFormat::ZstdCompressedProto => Self::ZstdCompressionProto(bytes),
         }
     }
 
@@ -178,6 +253,7 @@ impl FileEntry {
         match self {
             FileEntry::Lz4CompressionProto(bytes) => bytes,
             FileEntry::JsonBase64UncompressedProto(bytes) => bytes,
+            FileEntry::ZstdCompressionProto(bytes) => bytes,
         }
     }
 
@@ -185,12 +261,15 @@ impl FileEntry {
         match self {
             FileEntry::Lz4CompressionProto(bytes) => bytes.len(),
             FileEntry::JsonBase64UncompressedProto(bytes) => bytes.len(),
+            FileEntry::ZstdCompressionProto(bytes) => bytes.len(),
         }
     }
 
+    /// The zstd dictionary is required by the zstd storage format, and ignored otherwise.
     pub fn from_transactions(
         transactions: Vec<Transaction>,
         storage_format: StorageFormat,
+        zstd_dictionary: Option<&ZstdDictionary>,
     ) -> Self {
         let mut bytes = Vec::new();
         let starting_version = transactions
@@ -241,6 +320,27 @@ impl FileEntry {
                 let json = serde_json::to_vec(&file).expect("json serialization failed.");
                 FileEntry::JsonBase64UncompressedProto(json)
             },
+            StorageFormat::ZstdCompressedProto => {
+                let zstd_dictionary =
+                    zstd_dictionary.expect("Zstd dictionary is required for ZstdCompressedProto.");
+                let t = TransactionsInStorage {
+                    starting_version: Some(starting_version),
+                    transactions,
+                };
+                t.encode(&mut bytes).expect("proto serialization failed.");
+                let mut compressed = zstd::stream::write::Encoder::with_dictionary(
+                    Vec::new(),
+                    ZSTD_COMPRESSION_LEVEL,
+                    zstd_dictionary.as_bytes(),
+                )
+                .expect("Zstd compression failed.");
+                compressed
+                    .write_all(&bytes)
+                    .expect("Zstd compression failed.");
+                FileEntry::ZstdCompressionProto(
+                    compressed.finish().expect("Zstd compression failed."),
+                )
+            },
         }
     }
 
@@ -260,13 +360,23 @@ impl FileEntry {
             StorageFormat::JsonBase64UncompressedProto => {
                 format!("files/{}.json", starting_version)
             },
+            StorageFormat::ZstdCompressedProto => {
+                format!(
+                    "compressed_files/zstd/{}_{}.bin",
+                    file_prefix, starting_version
+                )
+            },
             StorageFormat::Base64UncompressedProto => {
                 panic!("Base64UncompressedProto is not supported.")
             },
         }
     }
 
-    pub fn into_transactions_in_storage(self) -> TransactionsInStorage {
+    /// The zstd dictionary is required by the zstd storage format, and ignored otherwise.
+    pub fn into_transactions_in_storage(
+        self,
+        zstd_dictionary: Option<&ZstdDictionary>,
+    ) -> TransactionsInStorage {
         match self {
             FileEntry::Lz4CompressionProto(bytes) => {
                 let mut decompressor = Decoder::new(&bytes[..]).expect("Lz4 decompression failed.");
@@ -295,6 +405,21 @@ impl FileEntry {
                     transactions,
                 }
             },
+            FileEntry::ZstdCompressionProto(bytes) => {
+                let zstd_dictionary =
+                    zstd_dictionary.expect("Zstd dictionary is required for ZstdCompressedProto.");
+                let mut decompressor = zstd::stream::read::Decoder::with_dictionary(
+                    &bytes[..],
+                    zstd_dictionary.as_bytes(),
+                )
+                .expect("Zstd decompression failed.");
+                let mut decompressed = Vec::new();
+                decompressor
+                    .read_to_end(&mut decompressed)
+                    .expect("Zstd decompression failed.");
+                TransactionsInStorage::decode(decompressed.as_slice())
+                    .expect("proto deserialization failed.")
+            },
         }
     }
 }
@@ -302,6 +427,7 @@ impl FileEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aptos_protos::transaction::v1::TransactionInfo;
 
     #[test]
     fn test_cache_entry_builder_base64_uncompressed_proto() {
@@ -359,8 +485,11 @@ mod tests {
                 ..Transaction::default()
             })
             .collect::<Vec<Transaction>>();
-        let _file_entry =
-            FileEntry::from_transactions(transactions, StorageFormat::Base64UncompressedProto);
+        let _file_entry = FileEntry::from_transactions(
+            transactions,
+            StorageFormat::Base64UncompressedProto,
+            None,
+        );
     }
 
     #[test]
@@ -375,8 +504,9 @@ mod tests {
         let file_entry = FileEntry::from_transactions(
             transactions.clone(),
             StorageFormat::JsonBase64UncompressedProto,
+            None,
         );
-        let deserialized_transactions = file_entry.into_transactions_in_storage();
+        let deserialized_transactions = file_entry.into_transactions_in_storage(None);
         for (i, transaction) in transactions.iter().enumerate() {
             assert_eq!(transaction, &deserialized_transactions.transactions[i]);
         }
@@ -396,15 +526,84 @@ mod tests {
             transactions: transactions.clone(),
         };
         let transactions_in_storage_size = transactions_in_storage.encoded_len();
-        let file_entry =
-            FileEntry::from_transactions(transactions.clone(), StorageFormat::Lz4CompressedProto);
+        let file_entry = FileEntry::from_transactions(
+            transactions.clone(),
+            StorageFormat::Lz4CompressedProto,
+            None,
+        );
         assert_ne!(file_entry.size(), transactions_in_storage_size);
-        let deserialized_transactions = file_entry.into_transactions_in_storage();
+        let deserialized_transactions = file_entry.into_transactions_in_storage(None);
         for (i, transaction) in transactions.iter().enumerate() {
             assert_eq!(transaction, &deserialized_transactions.transactions[i]);
         }
     }
 
+    fn create_sample_transactions(start_version: u64, count: u64) -> Vec<Transaction> {
+        (start_version..start_version + count)
+            .map(|version| Transaction {
+                version,
+                epoch: 333,
+                block_height: version / 10,
+                info: Some(TransactionInfo {
+                    hash: version.to_le_bytes().repeat(4),
+                    state_change_hash: (version * 7).to_be_bytes().repeat(4),
+                    success: true,
+                    vm_status: "Executed successfully".to_string(),
+                    ..TransactionInfo::default()
+                }),
+                ..Transaction::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_file_entry_builder_zstd_compressed_proto() {
+        let dictionary =
+            ZstdDictionary::train(1, &create_sample_transactions(0, 1000), 4096).unwrap();
+        assert_eq!(dictionary.version(), 1);
+        assert!(!dictionary.as_bytes().is_empty());
+
+        let transactions = create_sample_transactions(1000, 1000);
+        let file_entry = FileEntry::from_transactions(
+            transactions.clone(),
+            StorageFormat::ZstdCompressedProto,
+            Some(&dictionary),
+        );
+        let lz4_file_entry = FileEntry::from_transactions(
+            transactions.clone(),
+            StorageFormat::Lz4CompressedProto,
+            None,
+        );
+        assert!(file_entry.size() < lz4_file_entry.size());
+        let deserialized_transactions =
+            FileEntry::new(file_entry.into_inner(), StorageFormat::ZstdCompressedProto)
+                .into_transactions_in_storage(Some(&dictionary));
+        assert_eq!(deserialized_transactions.starting_version, Some(1000));
+        assert_eq!(deserialized_transactions.transactions, transactions);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_file_entry_builder_zstd_compressed_proto_requires_dictionary() {
+        let _file_entry = FileEntry::from_transactions(
+            create_sample_transactions(1000, 1000),
+            StorageFormat::ZstdCompressedProto,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cache_entry_builder_zstd_compressed_proto_not_supported() {
+        let transaction = Transaction {
+            version: 42,
+            epoch: 333,
+            ..Transaction::default()
+        };
+        let _cache_entry =
+            CacheEntry::from_transaction(transaction, StorageFormat::ZstdCompressedProto);
+    }
+
     #[test]
     fn test_cache_entry_key_to_string_lz4_compressed_proto() {
         assert_eq!(
@@ -435,6 +634,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_file_entry_key_to_string_zstd_compressed_proto() {
+        assert_eq!(
+            FileEntry::build_key(42, StorageFormat::ZstdCompressedProto),
+            "compressed_files/zstd/3d1bff1ba654ca5fdb6ac1370533d876_0.bin"
+        );
+        assert_eq!(ZstdDictionary::build_key(1), "zstd_dictionaries/1.dict");
+    }
+
     #[test]
     #[should_panic]
     fn test_file_entry_key_to_string_base64_uncompressed_proto() {
@@ -464,6 +672,7 @@ mod tests {
             file_metadata.storage_format,
             StorageFormat::JsonBase64UncompressedProto
         );
+        assert_eq!(file_metadata.zstd_dictionary_version, None);
         assert_eq!(file_metadata.chain_id, 1);
         assert_eq!(file_metadata.file_folder_size, 1000);
     }
@@ -487,4 +696,29 @@ mod tests {
         assert_eq!(file_metadata.chain_id, 1);
         assert_eq!(file_metadata.file_folder_size, 1000);
     }
+
+    #[test]
+    fn test_zstd_format_can_be_parsed() {
+        let file_metadata_serialized_json = r#"{
+            "chain_id": 1,
+            "file_folder_size": 1000,
+            "version": 1,
+            "storage_format": "ZstdCompressedProto",
+            "zstd_dictionary_version": 2
+        }"#;
+
+        let file_metadata: FileStoreMetadata = serde_json::from_str(file_metadata_serialized_json)
+            .expect("FileStoreMetadata deserialization failed.");
+
+        assert_eq!(
+            file_metadata.storage_format,
+            StorageFormat::ZstdCompressedProto
+        );
+        assert_eq!(file_metadata.zstd_dictionary_version, Some(2));
+        // The metadata without a dictionary is the same as before.
+        let file_metadata = FileStoreMetadata::new(1, 1, StorageFormat::Lz4CompressedProto, None);
+        assert!(!String::from_utf8(file_metadata.into_bytes())
+            .unwrap()
+            .contains("zstd_dictionary_version"));
+    }
 }
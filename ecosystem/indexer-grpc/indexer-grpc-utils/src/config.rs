@@ -1,9 +1,16 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::compression_util::StorageFormat;
+use anyhow::Result;
+use aptos_s3_client::{S3Client, S3ClientConfig, S3Credentials};
 use serde::{Deserialize, Serialize};
 /// Common configuration for Indexer GRPC Store.
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
+
+const S3_MAX_RETRIES: usize = 5;
+const S3_RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+const S3_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GcsFileStore {
@@ -13,6 +20,10 @@ pub struct GcsFileStore {
     pub gcs_file_store_service_account_key_path: String,
     #[serde(default = "default_enable_compression")]
     pub enable_compression: bool,
+    /// Compress the files with zstd and a dictionary trained from the first transactions.
+    /// Takes precedence over `enable_compression`.
+    #[serde(default)]
+    pub enable_zstd_compression: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -20,17 +31,79 @@ pub struct LocalFileStore {
     pub local_file_store_path: PathBuf,
     #[serde(default = "default_enable_compression")]
     pub enable_compression: bool,
+    /// Compress the files with zstd and a dictionary trained from the first transactions.
+    /// Takes precedence over `enable_compression`.
+    #[serde(default)]
+    pub enable_zstd_compression: bool,
+}
+
+/// An S3 compatible service, e.g., AWS S3 or a local MinIO.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct S3Endpoint {
+    /// Like "https://s3.us-west-2.amazonaws.com", or "http://127.0.0.1:9000" for a local MinIO.
+    pub endpoint: String,
+    /// The region used to sign the requests; most S3 compatible services accept "us-east-1".
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    /// Address the buckets as "{endpoint}/{bucket}" instead of "{bucket}.{endpoint host}".
+    /// Most self-hosted services require this.
+    #[serde(default)]
+    pub path_style: bool,
+}
+
+impl S3Endpoint {
+    /// Creates a client for one bucket of this service.
+    pub fn client(&self, bucket: String, credentials: S3Credentials) -> Result<S3Client> {
+        S3Client::new(S3ClientConfig {
+            endpoint: self.endpoint.clone(),
+            region: self.region.clone(),
+            bucket,
+            path_style: self.path_style,
+            credentials,
+            max_retries: S3_MAX_RETRIES,
+            retry_base_delay: S3_RETRY_BASE_DELAY,
+            request_timeout: S3_REQUEST_TIMEOUT,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct S3FileStore {
+    pub s3_file_store_endpoint: S3Endpoint,
+    pub s3_file_store_bucket_name: String,
+    pub s3_file_store_bucket_sub_dir: Option<PathBuf>,
+    #[serde(default = "default_enable_compression")]
+    pub enable_compression: bool,
+    /// Compress the files with zstd and a dictionary trained from the first transactions.
+    /// Takes precedence over `enable_compression`.
+    #[serde(default)]
+    pub enable_zstd_compression: bool,
 }
 
 const fn default_enable_compression() -> bool {
     false
 }
 
+fn file_storage_format(enable_compression: bool, enable_zstd_compression: bool) -> StorageFormat {
+    if enable_zstd_compression {
+        StorageFormat::ZstdCompressedProto
+    } else if enable_compression {
+        StorageFormat::Lz4CompressedProto
+    } else {
+        StorageFormat::JsonBase64UncompressedProto
+    }
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "file_store_type")]
 pub enum IndexerGrpcFileStoreConfig {
     GcsFileStore(GcsFileStore),
     LocalFileStore(LocalFileStore),
+    S3FileStore(S3FileStore),
 }
 
 impl Default for IndexerGrpcFileStoreConfig {
@@ -38,6 +111,7 @@ impl Default for IndexerGrpcFileStoreConfig {
         IndexerGrpcFileStoreConfig::LocalFileStore(LocalFileStore {
             local_file_store_path: std::env::current_dir().unwrap(),
             enable_compression: false,
+            enable_zstd_compression: false,
         })
     }
 }
@@ -52,15 +126,32 @@ impl IndexerGrpcFileStoreConfig {
                     gcs_file_store
                         .gcs_file_store_service_account_key_path
                         .clone(),
-                    gcs_file_store.enable_compression,
+                    file_storage_format(
+                        gcs_file_store.enable_compression,
+                        gcs_file_store.enable_zstd_compression,
+                    ),
                 ))
             },
             IndexerGrpcFileStoreConfig::LocalFileStore(local_file_store) => Box::new(
                 crate::file_store_operator::local::LocalFileStoreOperator::new(
                     local_file_store.local_file_store_path.clone(),
-                    local_file_store.enable_compression,
+                    file_storage_format(
+                        local_file_store.enable_compression,
+                        local_file_store.enable_zstd_compression,
+                    ),
                 ),
             ),
+            IndexerGrpcFileStoreConfig::S3FileStore(s3_file_store) => {
+                Box::new(crate::file_store_operator::s3::S3FileStoreOperator::new(
+                    s3_file_store.s3_file_store_endpoint.clone(),
+                    s3_file_store.s3_file_store_bucket_name.clone(),
+                    s3_file_store.s3_file_store_bucket_sub_dir.clone(),
+                    file_storage_format(
+                        s3_file_store.enable_compression,
+                        s3_file_store.enable_zstd_compression,
+                    ),
+                ))
+            },
         }
     }
 }
@@ -2,14 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compression_util::{FileEntry, FileStoreMetadata, StorageFormat, FILE_ENTRY_TRANSACTION_COUNT},
+    compression_util::{
+        FileEntry, FileStoreMetadata, StorageFormat, ZstdDictionary, FILE_ENTRY_TRANSACTION_COUNT,
+    },
     counters::{log_grpc_step, IndexerGrpcStep},
-    file_store_operator::{FileStoreOperator, METADATA_FILE_NAME},
+    file_store_operator::{FileStoreOperator, METADATA_FILE_NAME, ZSTD_DICTIONARY_FILE_TYPE},
 };
 use anyhow::bail;
 use aptos_protos::transaction::v1::Transaction;
 use cloud_storage::{Bucket, Object};
-use std::{env, path::PathBuf};
+use std::{env, path::PathBuf, sync::Arc};
+use tokio::sync::OnceCell;
 
 const JSON_FILE_TYPE: &str = "application/json";
 // The environment variable to set the service account path.
@@ -23,6 +26,7 @@ pub struct GcsFileStoreOperator {
     file_store_metadata_last_updated: std::time::Instant,
     storage_format: StorageFormat,
     metadata_file_path: PathBuf,
+    zstd_dictionary: Arc<OnceCell<ZstdDictionary>>,
 }
 
 impl GcsFileStoreOperator {
@@ -30,14 +34,9 @@ impl GcsFileStoreOperator {
         bucket_name: String,
         bucket_sub_dir: Option<PathBuf>,
         service_account_path: String,
-        enable_compression: bool,
+        storage_format: StorageFormat,
     ) -> Self {
         env::set_var(SERVICE_ACCOUNT_ENV_VAR, service_account_path);
-        let storage_format = if enable_compression {
-            StorageFormat::Lz4CompressedProto
-        } else {
-            StorageFormat::JsonBase64UncompressedProto
-        };
         let metadata_file_path = match &bucket_sub_dir {
             Some(sub_dir) => {
                 let mut metadata_file_path = sub_dir.clone();
@@ -56,6 +55,7 @@ impl GcsFileStoreOperator {
             file_store_metadata_last_updated: std::time::Instant::now(),
             storage_format,
             metadata_file_path,
+            zstd_dictionary: Arc::new(OnceCell::new()),
         }
     }
 
@@ -63,14 +63,22 @@ impl GcsFileStoreOperator {
     /// to use a sub directory, in the case of a shared bucket. The key path can be used directly as a GCS bucket file path.
     fn get_file_entry_key_path(&self, version: u64) -> String {
         let file_entry_key = FileEntry::build_key(version, self.storage_format).to_string();
-        // If the sub directory is set, the file entry key will be prefixed with the sub directory.
+        self.get_key_path(file_entry_key)
+    }
+
+    fn get_zstd_dictionary_key_path(&self, dictionary_version: u64) -> String {
+        self.get_key_path(ZstdDictionary::build_key(dictionary_version))
+    }
+
+    fn get_key_path(&self, key: String) -> String {
+        // If the sub directory is set, the key will be prefixed with the sub directory.
         match &self.bucket_sub_dir {
             Some(sub_dir) => {
-                let mut file_entry_key_path = sub_dir.clone();
-                file_entry_key_path.push(file_entry_key);
-                file_entry_key_path.to_string_lossy().into_owned()
+                let mut key_path = sub_dir.clone();
+                key_path.push(key);
+                key_path.to_string_lossy().into_owned()
             },
-            None => file_entry_key,
+            None => key,
         }
     }
 }
@@ -120,6 +128,38 @@ impl FileStoreOperator for GcsFileStoreOperator {
         }
     }
 
+    async fn get_raw_zstd_dictionary(&self, dictionary_version: u64) -> anyhow::Result<Vec<u8>> {
+        let key_path = self.get_zstd_dictionary_key_path(dictionary_version);
+        match Object::download(&self.bucket_name, key_path.as_str()).await {
+            Ok(dictionary) => Ok(dictionary),
+            Err(err) => bail!(
+                "[Indexer File] Error happens when downloading zstd dictionary {}. {}",
+                key_path,
+                err
+            ),
+        }
+    }
+
+    async fn upload_raw_zstd_dictionary(
+        &self,
+        dictionary_version: u64,
+        bytes: &[u8],
+    ) -> anyhow::Result<()> {
+        Object::create(
+            self.bucket_name.as_str(),
+            bytes.to_vec(),
+            self.get_zstd_dictionary_key_path(dictionary_version)
+                .as_str(),
+            ZSTD_DICTIONARY_FILE_TYPE,
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn zstd_dictionary_cell(&self) -> &OnceCell<ZstdDictionary> {
+        &self.zstd_dictionary
+    }
+
     /// Gets the metadata from the file store. Operator will panic if error happens when accessing the metadata file(except not found).
     async fn get_file_store_metadata(&self) -> Option<FileStoreMetadata> {
         match Object::download(
@@ -184,7 +224,12 @@ impl FileStoreOperator for GcsFileStoreOperator {
         chain_id: u64,
         version: u64,
     ) -> anyhow::Result<()> {
-        let metadata = FileStoreMetadata::new(chain_id, version, self.storage_format);
+        let metadata = FileStoreMetadata::new(
+            chain_id,
+            version,
+            self.storage_format,
+            self.zstd_dictionary.get().map(ZstdDictionary::version),
+        );
         // If the metadata is not updated, the indexer will be restarted.
         Object::create(
             self.bucket_name.as_str(),
@@ -219,7 +264,12 @@ impl FileStoreOperator for GcsFileStoreOperator {
         );
         let start_time = std::time::Instant::now();
         let bucket_name = self.bucket_name.clone();
-        let file_entry = FileEntry::from_transactions(transactions, self.storage_format);
+        let zstd_dictionary = self.get_zstd_dictionary().await?;
+        let file_entry = FileEntry::from_transactions(
+            transactions,
+            self.storage_format,
+            zstd_dictionary.as_ref(),
+        );
         let file_entry_key_path = self.get_file_entry_key_path(start_version);
         log_grpc_step(
             "file_worker",
@@ -2,14 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compression_util::{FileEntry, FileStoreMetadata, StorageFormat, FILE_ENTRY_TRANSACTION_COUNT},
+    compression_util::{
+        FileEntry, FileStoreMetadata, StorageFormat, ZstdDictionary, FILE_ENTRY_TRANSACTION_COUNT,
+    },
     file_store_operator::{
         FileStoreOperator, FILE_STORE_UPDATE_FREQUENCY_SECS, METADATA_FILE_NAME,
     },
 };
 use aptos_protos::transaction::v1::Transaction;
 use itertools::{any, Itertools};
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::OnceCell;
 use tracing::info;
 
 #[derive(Clone)]
@@ -18,19 +21,16 @@ pub struct LocalFileStoreOperator {
     /// The timestamp of the latest metadata update; this is to avoid too frequent metadata update.
     latest_metadata_update_timestamp: Option<std::time::Instant>,
     storage_format: StorageFormat,
+    zstd_dictionary: Arc<OnceCell<ZstdDictionary>>,
 }
 
 impl LocalFileStoreOperator {
-    pub fn new(path: PathBuf, enable_compression: bool) -> Self {
-        let storage_format = if enable_compression {
-            StorageFormat::Lz4CompressedProto
-        } else {
-            StorageFormat::JsonBase64UncompressedProto
-        };
+    pub fn new(path: PathBuf, storage_format: StorageFormat) -> Self {
         Self {
             path,
             latest_metadata_update_timestamp: None,
             storage_format,
+            zstd_dictionary: Arc::new(OnceCell::new()),
         }
     }
 }
@@ -73,6 +73,39 @@ impl FileStoreOperator for LocalFileStoreOperator {
         }
     }
 
+    async fn get_raw_zstd_dictionary(&self, dictionary_version: u64) -> anyhow::Result<Vec<u8>> {
+        let dictionary_path = self
+            .path
+            .join(ZstdDictionary::build_key(dictionary_version));
+        match tokio::fs::read(&dictionary_path).await {
+            Ok(dictionary) => Ok(dictionary),
+            Err(err) => anyhow::bail!(
+                "[Indexer File] Error happens when reading zstd dictionary {}. {}",
+                dictionary_path.display(),
+                err
+            ),
+        }
+    }
+
+    async fn upload_raw_zstd_dictionary(
+        &self,
+        dictionary_version: u64,
+        bytes: &[u8],
+    ) -> anyhow::Result<()> {
+        let dictionary_path = self
+            .path
+            .join(ZstdDictionary::build_key(dictionary_version));
+        if let Some(parent_dir) = dictionary_path.parent() {
+            tokio::fs::create_dir_all(parent_dir).await?;
+        }
+        tokio::fs::write(dictionary_path, bytes).await?;
+        Ok(())
+    }
+
+    fn zstd_dictionary_cell(&self) -> &OnceCell<ZstdDictionary> {
+        &self.zstd_dictionary
+    }
+
     async fn get_file_store_metadata(&self) -> Option<FileStoreMetadata> {
         let metadata_path = self.path.join(METADATA_FILE_NAME);
         match tokio::fs::read(metadata_path).await {
@@ -128,7 +161,12 @@ impl FileStoreOperator for LocalFileStoreOperator {
         chain_id: u64,
         version: u64,
     ) -> anyhow::Result<()> {
-        let metadata = FileStoreMetadata::new(chain_id, version, self.storage_format);
+        let metadata = FileStoreMetadata::new(
+            chain_id,
+            version,
+            self.storage_format,
+            self.zstd_dictionary.get().map(ZstdDictionary::version),
+        );
         // If the metadata is not updated, the indexer will be restarted.
         let metadata_path = self.path.join(METADATA_FILE_NAME);
         info!(
@@ -162,12 +200,17 @@ impl FileStoreOperator for LocalFileStoreOperator {
             "The number of transactions to upload has to be multiplier of BLOB_STORAGE_SIZE."
         );
         let mut tasks = vec![];
+        let zstd_dictionary = self.get_zstd_dictionary().await?;
 
         // Split the transactions into batches of BLOB_STORAGE_SIZE.
         for i in transactions.chunks(FILE_ENTRY_TRANSACTION_COUNT as usize) {
             let current_batch = i.iter().cloned().collect_vec();
             let starting_version = current_batch.first().unwrap().version;
-            let file_entry = FileEntry::from_transactions(current_batch, self.storage_format);
+            let file_entry = FileEntry::from_transactions(
+                current_batch,
+                self.storage_format,
+                zstd_dictionary.as_ref(),
+            );
             let file_entry_key =
                 FileEntry::build_key(starting_version, self.storage_format).to_string();
             let txns_path = self.path.join(file_entry_key.as_str());
@@ -225,3 +268,88 @@ impl FileStoreOperator for LocalFileStoreOperator {
         Box::new(self.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::transaction::v1::TransactionInfo;
+
+    fn create_transactions(start_version: u64) -> Vec<Transaction> {
+        (start_version..start_version + FILE_ENTRY_TRANSACTION_COUNT)
+            .map(|version| Transaction {
+                version,
+                info: Some(TransactionInfo {
+                    hash: version.to_le_bytes().repeat(4),
+                    vm_status: "Executed successfully".to_string(),
+                    ..TransactionInfo::default()
+                }),
+                ..Transaction::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_local_file_store_operator_zstd() {
+        let dir = tempfile::tempdir().unwrap();
+        let create_operator = || {
+            LocalFileStoreOperator::new(
+                dir.path().to_path_buf(),
+                StorageFormat::ZstdCompressedProto,
+            )
+        };
+        let transactions = create_transactions(0);
+
+        // Upload the dictionary, then the transactions written with it
+        let mut operator = create_operator();
+        operator
+            .update_file_store_metadata_with_timeout(1, 0)
+            .await
+            .unwrap();
+        let dictionary = ZstdDictionary::train(1, &transactions, 4096).unwrap();
+        operator
+            .upload_zstd_dictionary(dictionary.clone())
+            .await
+            .unwrap();
+        operator
+            .upload_transaction_batch(1, transactions)
+            .await
+            .unwrap();
+        operator
+            .update_file_store_metadata_internal(1, FILE_ENTRY_TRANSACTION_COUNT)
+            .await
+            .unwrap();
+        assert!(dir.path().join(ZstdDictionary::build_key(1)).exists());
+        let metadata = operator.get_file_store_metadata().await.unwrap();
+        assert_eq!(metadata.storage_format, StorageFormat::ZstdCompressedProto);
+        assert_eq!(metadata.zstd_dictionary_version, Some(1));
+
+        // Another writer that loads the dictionary up front keeps it in the metadata
+        let mut writer = create_operator();
+        assert_eq!(
+            writer.get_zstd_dictionary().await.unwrap(),
+            Some(dictionary.clone())
+        );
+        writer
+            .upload_transaction_batch(1, create_transactions(FILE_ENTRY_TRANSACTION_COUNT))
+            .await
+            .unwrap();
+        let metadata = writer.get_file_store_metadata().await.unwrap();
+        assert_eq!(metadata.version, 2 * FILE_ENTRY_TRANSACTION_COUNT);
+        assert_eq!(metadata.zstd_dictionary_version, Some(1));
+
+        // A reader loads the dictionary recorded in the metadata
+        let reader = create_operator();
+        for start_version in [10, FILE_ENTRY_TRANSACTION_COUNT] {
+            let transactions = reader.get_transactions(start_version, 0).await.unwrap();
+            assert_eq!(
+                transactions.len() as u64,
+                FILE_ENTRY_TRANSACTION_COUNT - start_version % FILE_ENTRY_TRANSACTION_COUNT
+            );
+            assert_eq!(transactions.first().unwrap().version, start_version);
+        }
+        assert_eq!(
+            reader.get_zstd_dictionary().await.unwrap(),
+            Some(dictionary)
+        );
+    }
+}
@@ -2,19 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::compression_util::{
-    FileEntry, FileStoreMetadata, StorageFormat, FILE_ENTRY_TRANSACTION_COUNT,
+    FileEntry, FileStoreMetadata, StorageFormat, ZstdDictionary, FILE_ENTRY_TRANSACTION_COUNT,
 };
 use anyhow::{Context, Result};
 use aptos_protos::transaction::v1::Transaction;
+use tokio::sync::OnceCell;
 
 pub mod gcs;
 pub use gcs::*;
 pub mod local;
 use crate::counters::TRANSACTION_STORE_FETCH_RETRIES;
 pub use local::*;
+pub mod s3;
+pub use s3::*;
 
 const METADATA_FILE_NAME: &str = "metadata.json";
 const FILE_STORE_UPDATE_FREQUENCY_SECS: u64 = 5;
+const ZSTD_DICTIONARY_FILE_TYPE: &str = "application/octet-stream";
 
 #[async_trait::async_trait]
 pub trait FileStoreOperator: Send + Sync {
@@ -36,6 +40,47 @@ pub trait FileStoreOperator: Send + Sync {
 
     async fn get_raw_file(&self, version: u64) -> Result<Vec<u8>>;
 
+    async fn get_raw_zstd_dictionary(&self, dictionary_version: u64) -> Result<Vec<u8>>;
+
+    async fn upload_raw_zstd_dictionary(&self, dictionary_version: u64, bytes: &[u8])
+        -> Result<()>;
+
+    /// The zstd dictionary in use; it's shared by the clones of the operator.
+    fn zstd_dictionary_cell(&self) -> &OnceCell<ZstdDictionary>;
+
+    /// Gets the zstd dictionary recorded in the file store metadata, or None if the storage
+    /// format doesn't use a dictionary. The dictionary is downloaded once.
+    async fn get_zstd_dictionary(&self) -> Result<Option<ZstdDictionary>> {
+        if self.storage_format() != StorageFormat::ZstdCompressedProto {
+            return Ok(None);
+        }
+        let dictionary = self
+            .zstd_dictionary_cell()
+            .get_or_try_init(|| async {
+                let metadata = self
+                    .get_file_store_metadata()
+                    .await
+                    .context("File store metadata is not found.")?;
+                let dictionary_version = metadata
+                    .zstd_dictionary_version
+                    .context("Zstd dictionary is not trained yet.")?;
+                let bytes = self.get_raw_zstd_dictionary(dictionary_version).await?;
+                anyhow::Ok(ZstdDictionary::new(dictionary_version, bytes))
+            })
+            .await?;
+        Ok(Some(dictionary.clone()))
+    }
+
+    /// Uploads a newly trained zstd dictionary and starts using it. The dictionary version is
+    /// recorded in the metadata at the next metadata update.
+    async fn upload_zstd_dictionary(&mut self, dictionary: ZstdDictionary) -> Result<()> {
+        self.upload_raw_zstd_dictionary(dictionary.version(), dictionary.as_bytes())
+            .await?;
+        self.zstd_dictionary_cell()
+            .set(dictionary)
+            .context("Zstd dictionary is already set.")
+    }
+
     async fn get_raw_file_with_retries(&self, version: u64, retries: u8) -> Result<Vec<u8>> {
         let mut retries = retries;
         loop {
@@ -66,9 +111,11 @@ pub trait FileStoreOperator: Send + Sync {
         let io_duration = io_start_time.elapsed().as_secs_f64();
         let decoding_start_time = std::time::Instant::now();
         let storage_format = self.storage_format();
+        let zstd_dictionary = self.get_zstd_dictionary().await?;
 
         let transactions_in_storage = tokio::task::spawn_blocking(move || {
-            FileEntry::new(bytes, storage_format).into_transactions_in_storage()
+            FileEntry::new(bytes, storage_format)
+                .into_transactions_in_storage(zstd_dictionary.as_ref())
         })
         .await
         .context("Converting storage bytes to FileEntry transactions thread panicked")?;
@@ -0,0 +1,400 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    compression_util::{
+        FileEntry, FileStoreMetadata, StorageFormat, ZstdDictionary, FILE_ENTRY_TRANSACTION_COUNT,
+    },
+    config::S3Endpoint,
+    counters::{log_grpc_step, IndexerGrpcStep},
+    file_store_operator::{FileStoreOperator, METADATA_FILE_NAME, ZSTD_DICTIONARY_FILE_TYPE},
+};
+use anyhow::bail;
+use aptos_protos::transaction::v1::Transaction;
+use aptos_s3_client::{S3Client, S3Credentials};
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::OnceCell;
+
+const JSON_FILE_TYPE: &str = "application/json";
+const FILE_STORE_METADATA_TIMEOUT_MILLIS: u128 = 200;
+
+#[derive(Clone)]
+pub struct S3FileStoreOperator {
+    client: S3Client,
+    bucket_sub_dir: Option<PathBuf>,
+    file_store_metadata_last_updated: std::time::Instant,
+    storage_format: StorageFormat,
+    metadata_file_path: String,
+    zstd_dictionary: Arc<OnceCell<ZstdDictionary>>,
+}
+
+impl S3FileStoreOperator {
+    /// The credentials are read from the standard `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`
+    /// and (optional) `AWS_SESSION_TOKEN` environment variables.
+    pub fn new(
+        endpoint: S3Endpoint,
+        bucket_name: String,
+        bucket_sub_dir: Option<PathBuf>,
+        storage_format: StorageFormat,
+    ) -> Self {
+        let credentials = S3Credentials::from_env().expect("Failed to read the S3 credentials.");
+        let client = endpoint
+            .client(bucket_name, credentials)
+            .expect("Failed to create the S3 client.");
+        let metadata_file_path = match &bucket_sub_dir {
+            Some(sub_dir) => sub_dir
+                .join(METADATA_FILE_NAME)
+                .to_string_lossy()
+                .into_owned(),
+            None => METADATA_FILE_NAME.to_string(),
+        };
+        Self {
+            client,
+            bucket_sub_dir,
+            file_store_metadata_last_updated: std::time::Instant::now(),
+            storage_format,
+            metadata_file_path,
+            zstd_dictionary: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Given a version number, builds the key path for the file entry. This uses the same layout
+    /// as the GCS file store, i.e., the key path is prefixed with the sub directory (if any).
+    fn get_file_entry_key_path(&self, version: u64) -> String {
+        let file_entry_key = FileEntry::build_key(version, self.storage_format).to_string();
+        self.get_key_path(file_entry_key)
+    }
+
+    fn get_zstd_dictionary_key_path(&self, dictionary_version: u64) -> String {
+        self.get_key_path(ZstdDictionary::build_key(dictionary_version))
+    }
+
+    fn get_key_path(&self, key: String) -> String {
+        match &self.bucket_sub_dir {
+            Some(sub_dir) => sub_dir.join(key).to_string_lossy().into_owned(),
+            None => key,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FileStoreOperator for S3FileStoreOperator {
+    /// Bootstraps the file store operator. This is required before any other operations.
+    async fn verify_storage_bucket_existence(&self) {
+        tracing::info!(
+            bucket_name = self.client.bucket(),
+            "Before file store operator starts, verify the bucket exists."
+        );
+        // Verifies the bucket exists.
+        self.client
+            .head_bucket()
+            .await
+            .expect("Failed to read bucket.");
+    }
+
+    fn storage_format(&self) -> StorageFormat {
+        self.storage_format
+    }
+
+    fn store_name(&self) -> &str {
+        "S3"
+    }
+
+    async fn get_raw_file(&self, version: u64) -> anyhow::Result<Vec<u8>> {
+        let file_entry_key_path = self.get_file_entry_key_path(version);
+        match self.client.get_object(&file_entry_key_path).await {
+            Ok(Some(file)) => Ok(file.to_vec()),
+            Ok(None) => bail!(
+                "[Indexer File] Transactions file not found. Gap might happen between cache and file store. {}",
+                file_entry_key_path
+            ),
+            Err(err) => bail!(
+                "[Indexer File] Error happens when downloading transaction file. {}",
+                err
+            ),
+        }
+    }
+
+    async fn get_raw_zstd_dictionary(&self, dictionary_version: u64) -> anyhow::Result<Vec<u8>> {
+        let key_path = self.get_zstd_dictionary_key_path(dictionary_version);
+        match self.client.get_object(&key_path).await {
+            Ok(Some(dictionary)) => Ok(dictionary.to_vec()),
+            Ok(None) => bail!("[Indexer File] Zstd dictionary not found. {}", key_path),
+            Err(err) => bail!(
+                "[Indexer File] Error happens when downloading zstd dictionary. {}",
+                err
+            ),
+        }
+    }
+
+    async fn upload_raw_zstd_dictionary(
+        &self,
+        dictionary_version: u64,
+        bytes: &[u8],
+    ) -> anyhow::Result<()> {
+        self.client
+            .put_object(
+                &self.get_zstd_dictionary_key_path(dictionary_version),
+                bytes.to_vec().into(),
+                Some(ZSTD_DICTIONARY_FILE_TYPE),
+            )
+            .await
+    }
+
+    fn zstd_dictionary_cell(&self) -> &OnceCell<ZstdDictionary> {
+        &self.zstd_dictionary
+    }
+
+    /// Gets the metadata from the file store. Operator will panic if error happens when accessing the metadata file(except not found).
+    async fn get_file_store_metadata(&self) -> Option<FileStoreMetadata> {
+        match self.client.get_object(&self.metadata_file_path).await {
+            Ok(metadata) => {
+                metadata.map(|metadata| FileStoreMetadata::from_bytes(metadata.to_vec()))
+            },
+            Err(err) => {
+                panic!(
+                    "[Indexer File] Error happens when accessing metadata file. {}",
+                    err
+                );
+            },
+        }
+    }
+
+    /// If the file store is empty, the metadata will be created; otherwise, return the existing metadata.
+    async fn update_file_store_metadata_with_timeout(
+        &mut self,
+        expected_chain_id: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        if let Some(metadata) = self.get_file_store_metadata().await {
+            assert_eq!(metadata.chain_id, expected_chain_id, "Chain ID mismatch.");
+            assert_eq!(
+                metadata.storage_format, self.storage_format,
+                "Storage format mismatch."
+            );
+        }
+        if self.file_store_metadata_last_updated.elapsed().as_millis()
+            < FILE_STORE_METADATA_TIMEOUT_MILLIS
+        {
+            bail!("File store metadata is updated too frequently.")
+        }
+        self.update_file_store_metadata_internal(expected_chain_id, version)
+            .await?;
+        Ok(())
+    }
+
+    /// Updates the file store metadata. This is only performed by the operator when new file transactions are uploaded.
+    async fn update_file_store_metadata_internal(
+        &mut self,
+        chain_id: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let metadata = FileStoreMetadata::new(
+            chain_id,
+            version,
+            self.storage_format,
+            self.zstd_dictionary.get().map(ZstdDictionary::version),
+        );
+        // If the metadata is not updated, the indexer will be restarted.
+        self.client
+            .put_object(
+                &self.metadata_file_path,
+                serde_json::to_vec(&metadata).unwrap().into(),
+                Some(JSON_FILE_TYPE),
+            )
+            .await?;
+        self.file_store_metadata_last_updated = std::time::Instant::now();
+        Ok(())
+    }
+
+    /// Uploads the transactions to the file store. The transactions are grouped into batches of BLOB_STORAGE_SIZE.
+    /// Updates the file store metadata after the upload.
+    async fn upload_transaction_batch(
+        &mut self,
+        _chain_id: u64,
+        transactions: Vec<Transaction>,
+    ) -> anyhow::Result<(u64, u64)> {
+        let start_version = transactions.first().unwrap().version;
+        let end_version = transactions.last().unwrap().version;
+        let batch_size = transactions.len();
+        anyhow::ensure!(
+            start_version % FILE_ENTRY_TRANSACTION_COUNT == 0,
+            "Starting version has to be a multiple of BLOB_STORAGE_SIZE."
+        );
+        anyhow::ensure!(
+            batch_size == FILE_ENTRY_TRANSACTION_COUNT as usize,
+            "The number of transactions to upload has to be multiplier of BLOB_STORAGE_SIZE."
+        );
+        let start_time = std::time::Instant::now();
+        let zstd_dictionary = self.get_zstd_dictionary().await?;
+        let file_entry = FileEntry::from_transactions(
+            transactions,
+            self.storage_format,
+            zstd_dictionary.as_ref(),
+        );
+        let file_entry_key_path = self.get_file_entry_key_path(start_version);
+        log_grpc_step(
+            "file_worker",
+            IndexerGrpcStep::FileStoreEncodedTxns,
+            Some(start_version as i64),
+            Some((start_version + FILE_ENTRY_TRANSACTION_COUNT - 1) as i64),
+            None,
+            None,
+            Some(start_time.elapsed().as_secs_f64()),
+            None,
+            Some(FILE_ENTRY_TRANSACTION_COUNT as i64),
+            None,
+        );
+        self.client
+            .put_object(
+                &file_entry_key_path,
+                file_entry.into_inner().into(),
+                Some(JSON_FILE_TYPE),
+            )
+            .await?;
+        Ok((start_version, end_version))
+    }
+
+    fn clone_box(&self) -> Box<dyn FileStoreOperator> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::transaction::v1::TransactionInfo;
+    use aptos_s3_client::fake_server::FakeS3Server;
+    use std::time::Duration;
+
+    fn test_client(endpoint: &str) -> S3Client {
+        let endpoint = S3Endpoint {
+            endpoint: endpoint.to_string(),
+            region: "us-east-1".to_string(),
+            path_style: true,
+        };
+        let credentials = S3Credentials {
+            access_key_id: "access_key_id".to_string(),
+            secret_access_key: "secret_access_key".to_string(),
+            session_token: None,
+        };
+        endpoint.client("indexer".to_string(), credentials).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_s3_file_store_operator() {
+        // Every other request fails with a 503.
+        let (server, endpoint) = FakeS3Server::start("indexer", Some(2));
+        let mut operator = S3FileStoreOperator {
+            client: test_client(&endpoint),
+            bucket_sub_dir: Some(PathBuf::from("testnet")),
+            // Allow the metadata to be updated right away.
+            file_store_metadata_last_updated: std::time::Instant::now() - Duration::from_secs(1),
+            storage_format: StorageFormat::Lz4CompressedProto,
+            metadata_file_path: "testnet/metadata.json".to_string(),
+            zstd_dictionary: Arc::new(OnceCell::new()),
+        };
+        operator.verify_storage_bucket_existence().await;
+
+        // The file store is empty
+        assert!(operator.get_file_store_metadata().await.is_none());
+        assert!(operator.get_raw_file(0).await.is_err());
+
+        // Upload a batch of transactions and update the metadata
+        let transactions = (0..FILE_ENTRY_TRANSACTION_COUNT)
+            .map(|version| Transaction {
+                version,
+                ..Transaction::default()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            operator
+                .upload_transaction_batch(1, transactions)
+                .await
+                .unwrap(),
+            (0, FILE_ENTRY_TRANSACTION_COUNT - 1)
+        );
+        operator
+            .update_file_store_metadata_with_timeout(1, FILE_ENTRY_TRANSACTION_COUNT)
+            .await
+            .unwrap();
+
+        // The layout is the same as for the other file stores
+        let file_entry_key = FileEntry::build_key(0, StorageFormat::Lz4CompressedProto);
+        assert!(server
+            .object(&format!("testnet/{}", file_entry_key))
+            .is_some());
+        let metadata = operator.get_file_store_metadata().await.unwrap();
+        assert_eq!(metadata.chain_id, 1);
+        assert_eq!(metadata.version, FILE_ENTRY_TRANSACTION_COUNT);
+        assert_eq!(metadata.storage_format, StorageFormat::Lz4CompressedProto);
+
+        // The transactions can be read back, starting from any version in the file
+        let transactions = operator.get_transactions(10, 0).await.unwrap();
+        assert_eq!(transactions.len() as u64, FILE_ENTRY_TRANSACTION_COUNT - 10);
+        assert_eq!(transactions.first().unwrap().version, 10);
+    }
+
+    #[tokio::test]
+    async fn test_s3_file_store_operator_zstd() {
+        let (server, endpoint) = FakeS3Server::start("indexer", Some(2));
+        let create_operator = || S3FileStoreOperator {
+            client: test_client(&endpoint),
+            bucket_sub_dir: None,
+            file_store_metadata_last_updated: std::time::Instant::now() - Duration::from_secs(1),
+            storage_format: StorageFormat::ZstdCompressedProto,
+            metadata_file_path: "metadata.json".to_string(),
+            zstd_dictionary: Arc::new(OnceCell::new()),
+        };
+        let transactions = (0..FILE_ENTRY_TRANSACTION_COUNT)
+            .map(|version| Transaction {
+                version,
+                info: Some(TransactionInfo {
+                    hash: version.to_le_bytes().repeat(4),
+                    vm_status: "Executed successfully".to_string(),
+                    ..TransactionInfo::default()
+                }),
+                ..Transaction::default()
+            })
+            .collect::<Vec<_>>();
+
+        // Files can't be written before the dictionary is trained
+        let mut operator = create_operator();
+        operator
+            .update_file_store_metadata_internal(1, 0)
+            .await
+            .unwrap();
+        assert!(operator
+            .upload_transaction_batch(1, transactions.clone())
+            .await
+            .is_err());
+
+        let dictionary = ZstdDictionary::train(1, &transactions, 4096).unwrap();
+        operator
+            .upload_zstd_dictionary(dictionary.clone())
+            .await
+            .unwrap();
+        operator
+            .upload_transaction_batch(1, transactions)
+            .await
+            .unwrap();
+        operator
+            .update_file_store_metadata_with_timeout(1, FILE_ENTRY_TRANSACTION_COUNT)
+            .await
+            .unwrap();
+        assert!(server.object(&ZstdDictionary::build_key(1)).is_some());
+        let metadata = operator.get_file_store_metadata().await.unwrap();
+        assert_eq!(metadata.storage_format, StorageFormat::ZstdCompressedProto);
+        assert_eq!(metadata.zstd_dictionary_version, Some(1));
+
+        // A reader loads the dictionary recorded in the metadata
+        let reader = create_operator();
+        let transactions = reader.get_transactions(10, 0).await.unwrap();
+        assert_eq!(transactions.len() as u64, FILE_ENTRY_TRANSACTION_COUNT - 10);
+        assert_eq!(transactions.first().unwrap().version, 10);
+        assert_eq!(
+            reader.get_zstd_dictionary().await.unwrap(),
+            Some(dictionary)
+        );
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    cache_backend::CacheBackend,
+    cache_operator::{CacheCoverageStatus, CACHE_SIZE_ESTIMATION},
+    compression_util::FILE_ENTRY_TRANSACTION_COUNT,
+};
+use anyhow::{bail, ensure};
+use aptos_protos::transaction::v1::Transaction;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+// The cache worker runs up to 150_000 versions ahead of the file store, and the file store
+// processor reads from the cache; the cache has to keep at least that many transactions.
+const MIN_IN_PROCESS_CACHE_CAPACITY: u64 = 200_000;
+// Extra slots for chunks written ahead of the latest version, so that they don't overwrite
+// transactions that are still reported as cached.
+const IN_PROCESS_CACHE_WRITE_AHEAD_SLOTS: u64 = 50_000;
+
+/// Configuration for the in-process cache.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+#[serde(default)]
+pub struct InProcessCacheConfig {
+    /// The number of most recent transactions served from the cache.
+    capacity: u64,
+}
+
+impl Default for InProcessCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: CACHE_SIZE_ESTIMATION,
+        }
+    }
+}
+
+impl InProcessCacheConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.capacity < MIN_IN_PROCESS_CACHE_CAPACITY {
+            return Err(anyhow::anyhow!(
+                "In-process cache capacity must be at least {}",
+                MIN_IN_PROCESS_CACHE_CAPACITY
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheMetadata {
+    chain_id: Option<u64>,
+    /// Exclusive; transactions below it are visible to readers.
+    latest_version: Option<u64>,
+    /// The first version cached since the process started.
+    first_version: Option<u64>,
+    file_store_latest_version: Option<u64>,
+}
+
+#[derive(Debug)]
+struct InProcessCacheInner {
+    capacity: u64,
+    /// Ring buffer indexed by version modulo its length.
+    slots: RwLock<Vec<Option<Arc<Transaction>>>>,
+    metadata: RwLock<CacheMetadata>,
+}
+
+/// InProcessCache keeps the most recent transactions in a ring buffer. It's fed directly by
+/// a cache worker running in the same process, which removes the need for Redis.
+#[derive(Clone, Debug)]
+pub struct InProcessCache {
+    inner: Arc<InProcessCacheInner>,
+}
+
+impl InProcessCache {
+    pub fn new(config: InProcessCacheConfig) -> Self {
+        let num_of_slots = (config.capacity + IN_PROCESS_CACHE_WRITE_AHEAD_SLOTS) as usize;
+        Self {
+            inner: Arc::new(InProcessCacheInner {
+                capacity: config.capacity,
+                slots: RwLock::new(vec![None; num_of_slots]),
+                metadata: RwLock::new(CacheMetadata::default()),
+            }),
+        }
+    }
+
+    /// Returns the lowest version that is still served, if any transaction is cached.
+    fn lowest_available_version(&self, metadata: &CacheMetadata) -> Option<u64> {
+        let latest_version = metadata.latest_version?;
+        let first_version = metadata.first_version?;
+        Some(std::cmp::max(
+            first_version,
+            latest_version.saturating_sub(self.inner.capacity),
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheBackend for InProcessCache {
+    async fn cache_setup_if_needed(&self) -> anyhow::Result<bool> {
+        let mut metadata = self.inner.metadata.write().unwrap();
+        if metadata.latest_version.is_some() {
+            return Ok(false);
+        }
+        metadata.latest_version = Some(0);
+        tracing::info!("In-process cache latest version is initialized.");
+        Ok(true)
+    }
+
+    async fn set_chain_id(&self, chain_id: u64) -> anyhow::Result<()> {
+        self.inner.metadata.write().unwrap().chain_id = Some(chain_id);
+        Ok(())
+    }
+
+    async fn get_chain_id(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self.inner.metadata.read().unwrap().chain_id)
+    }
+
+    async fn get_latest_version(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self.inner.metadata.read().unwrap().latest_version)
+    }
+
+    async fn get_file_store_latest_version(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self
+            .inner
+            .metadata
+            .read()
+            .unwrap()
+            .file_store_latest_version)
+    }
+
+    async fn update_file_store_latest_version(&self, latest_version: u64) -> anyhow::Result<()> {
+        self.inner
+            .metadata
+            .write()
+            .unwrap()
+            .file_store_latest_version = Some(latest_version);
+        Ok(())
+    }
+
+    async fn check_cache_coverage_status(
+        &self,
+        requested_version: u64,
+    ) -> anyhow::Result<CacheCoverageStatus> {
+        let metadata = self.inner.metadata.read().unwrap();
+        let latest_version = metadata.latest_version.unwrap_or_default();
+        if requested_version >= latest_version {
+            return Ok(CacheCoverageStatus::DataNotReady);
+        }
+        match self.lowest_available_version(&metadata) {
+            Some(lowest_version) if requested_version >= lowest_version => {
+                Ok(CacheCoverageStatus::CacheHit(std::cmp::min(
+                    latest_version - requested_version,
+                    FILE_ENTRY_TRANSACTION_COUNT,
+                )))
+            },
+            _ => Ok(CacheCoverageStatus::CacheEvicted),
+        }
+    }
+
+    async fn get_transactions_with_durations(
+        &self,
+        start_version: u64,
+        transaction_count: u64,
+    ) -> anyhow::Result<(Vec<Transaction>, f64, f64)> {
+        let start_time = std::time::Instant::now();
+        let end_version = start_version + transaction_count;
+        {
+            let metadata = self.inner.metadata.read().unwrap();
+            ensure!(
+                end_version <= metadata.latest_version.unwrap_or_default(),
+                "Transactions up to version {} are not cached yet.",
+                end_version
+            );
+        }
+        let arc_transactions = {
+            let slots = self.inner.slots.read().unwrap();
+            let mut arc_transactions = Vec::with_capacity(transaction_count as usize);
+            for version in start_version..end_version {
+                match &slots[(version % slots.len() as u64) as usize] {
+                    Some(transaction) if transaction.version == version => {
+                        arc_transactions.push(transaction.clone());
+                    },
+                    _ => bail!("Transaction {} is evicted from the cache.", version),
+                }
+            }
+            arc_transactions
+        };
+        let io_duration = start_time.elapsed().as_secs_f64();
+        let start_time = std::time::Instant::now();
+        let transactions = arc_transactions
+            .into_iter()
+            .map(|t| t.as_ref().clone())
+            .collect();
+        Ok((
+            transactions,
+            io_duration,
+            start_time.elapsed().as_secs_f64(),
+        ))
+    }
+
+    async fn update_cache_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+    ) -> anyhow::Result<()> {
+        let mut slots = self.inner.slots.write().unwrap();
+        let num_of_slots = slots.len() as u64;
+        for transaction in transactions {
+            let slot = (transaction.version % num_of_slots) as usize;
+            slots[slot] = Some(Arc::new(transaction));
+        }
+        Ok(())
+    }
+
+    async fn update_cache_latest_version(
+        &self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let mut metadata = self.inner.metadata.write().unwrap();
+        if metadata.first_version.is_none() {
+            // Nothing is cached yet; the cache starts from wherever the cache worker starts.
+            metadata.first_version = Some(version.saturating_sub(num_of_versions));
+            metadata.latest_version = Some(version);
+            return Ok(());
+        }
+        let latest_version = metadata.latest_version.unwrap_or_default();
+        if latest_version + num_of_versions < version {
+            tracing::error!(
+                version = version,
+                "In-process cache latest version update failed. The version is beyond the next expected version."
+            );
+            bail!("Version is not right.");
+        }
+        metadata.latest_version = Some(std::cmp::max(latest_version, version));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_transactions(start_version: u64, count: u64) -> Vec<Transaction> {
+        (start_version..start_version + count)
+            .map(|version| Transaction {
+                version,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn create_cache() -> InProcessCache {
+        InProcessCache::new(InProcessCacheConfig {
+            capacity: MIN_IN_PROCESS_CACHE_CAPACITY,
+        })
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(InProcessCacheConfig::default().validate().is_ok());
+        assert!(InProcessCacheConfig { capacity: 1000 }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chunks_are_visible_after_latest_version_update() {
+        let cache = create_cache();
+        assert!(cache.cache_setup_if_needed().await.unwrap());
+        assert!(!cache.cache_setup_if_needed().await.unwrap());
+
+        // The worker starts from the file store version, not 0.
+        cache
+            .update_cache_transactions(create_transactions(3000, 1000))
+            .await
+            .unwrap();
+        cache
+            .update_cache_transactions(create_transactions(2000, 1000))
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.check_cache_coverage_status(2000).await.unwrap(),
+            CacheCoverageStatus::DataNotReady
+        );
+
+        cache.update_cache_latest_version(2000, 4000).await.unwrap();
+        assert_eq!(cache.get_latest_version().await.unwrap(), Some(4000));
+        assert_eq!(
+            cache.check_cache_coverage_status(1999).await.unwrap(),
+            CacheCoverageStatus::CacheEvicted
+        );
+        assert_eq!(
+            cache.check_cache_coverage_status(3500).await.unwrap(),
+            CacheCoverageStatus::CacheHit(500)
+        );
+        let transactions = cache.get_transactions(2500, 1000).await.unwrap();
+        assert_eq!(transactions.len(), 1000);
+        assert_eq!(transactions.first().unwrap().version, 2500);
+        assert_eq!(transactions.last().unwrap().version, 3499);
+        assert!(cache.get_transactions(3500, 1000).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_latest_version_gap_is_rejected() {
+        let cache = create_cache();
+        cache
+            .update_cache_transactions(create_transactions(0, 1000))
+            .await
+            .unwrap();
+        cache.update_cache_latest_version(1000, 1000).await.unwrap();
+        assert!(cache.update_cache_latest_version(1000, 3000).await.is_err());
+        assert_eq!(cache.get_latest_version().await.unwrap(), Some(1000));
+    }
+
+    #[tokio::test]
+    async fn test_old_transactions_are_evicted() {
+        let cache = create_cache();
+        let batch_size = 50_000;
+        let mut version = 0;
+        while version < 2 * MIN_IN_PROCESS_CACHE_CAPACITY {
+            cache
+                .update_cache_transactions(create_transactions(version, batch_size))
+                .await
+                .unwrap();
+            version += batch_size;
+            cache
+                .update_cache_latest_version(batch_size, version)
+                .await
+                .unwrap();
+        }
+
+        let lowest_version = version - MIN_IN_PROCESS_CACHE_CAPACITY;
+        assert_eq!(
+            cache
+                .check_cache_coverage_status(lowest_version - 1)
+                .await
+                .unwrap(),
+            CacheCoverageStatus::CacheEvicted
+        );
+        assert_eq!(
+            cache
+                .check_cache_coverage_status(lowest_version)
+                .await
+                .unwrap(),
+            CacheCoverageStatus::CacheHit(FILE_ENTRY_TRANSACTION_COUNT)
+        );
+        assert!(cache.get_transactions(lowest_version, 1000).await.is_ok());
+        assert!(cache.get_transactions(0, 1000).await.is_err());
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod cache_backend;
 pub mod cache_operator;
 pub mod compression_util;
 pub mod config;
@@ -8,6 +9,7 @@ pub mod constants;
 pub mod counters;
 pub mod file_store_operator;
 pub mod in_memory_cache;
+pub mod in_process_cache;
 pub mod types;
 
 use anyhow::{Context, Result};
@@ -16,9 +18,10 @@ use aptos_protos::{
     internal::fullnode::v1::fullnode_data_client::FullnodeDataClient, transaction::v1::Transaction,
     util::timestamp::Timestamp,
 };
+use aptos_transaction_filter::{BooleanTransactionFilter, Filterable};
 use prost::Message;
 use std::time::Duration;
-use tonic::codec::CompressionEncoding;
+use tonic::{codec::CompressionEncoding, Status};
 use url::Url;
 
 pub type GrpcClientType = FullnodeDataClient<tonic::transport::Channel>;
@@ -147,6 +150,36 @@ pub fn chunk_transactions(
     chunked_transactions
 }
 
+/// The default maximum size of the (JSON-serialized) transaction filter of a request.
+pub const DEFAULT_MAX_TRANSACTION_FILTER_SIZE_BYTES: usize = 10_000;
+
+/// Parses the (JSON-serialized) transaction filter of a `GetTransactionsRequest`, if any.
+/// Malformed or invalid filters, and filters larger than `max_filter_size_bytes`, are rejected
+/// with an invalid argument status.
+pub fn parse_transaction_filter(
+    transaction_filter: Option<&str>,
+    max_filter_size_bytes: usize,
+) -> tonic::Result<Option<BooleanTransactionFilter>> {
+    let Some(transaction_filter) = transaction_filter else {
+        return Ok(None);
+    };
+    if transaction_filter.len() > max_filter_size_bytes {
+        return Err(Status::invalid_argument(format!(
+            "The transaction filter is {} bytes, the maximum is {} bytes.",
+            transaction_filter.len(),
+            max_filter_size_bytes
+        )));
+    }
+    let transaction_filter: BooleanTransactionFilter = serde_json::from_str(transaction_filter)
+        .map_err(|e| {
+            Status::invalid_argument(format!("Failed to parse the transaction filter: {}", e))
+        })?;
+    transaction_filter
+        .is_valid()
+        .map_err(|e| Status::invalid_argument(format!("Invalid transaction filter: {}", e)))?;
+    Ok(Some(transaction_filter))
+}
+
 // Tests
 #[cfg(test)]
 mod tests {
@@ -4,6 +4,10 @@
 use aptos_cached_packages::aptos_stdlib;
 use aptos_db::AptosDB;
 use aptos_db_indexer::db_indexer::DBIndexer;
+use aptos_db_indexer_schemas::{
+    metadata::{MetadataKey, MetadataValue},
+    schema::indexer_metadata::InternalIndexerMetadataSchema,
+};
 use aptos_executor_test_helpers::{
     gen_block_id, gen_ledger_info_with_sigs, integration_test_impl::create_db_and_executor,
 };
@@ -148,7 +152,7 @@ fn test_db_indexer_data() {
 
     let internal_indexer_db = InternalIndexerDBService::get_indexer_db(&node_config).unwrap();
 
-    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone());
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone()).unwrap();
     // assert the data matches the expected data
     let version = internal_indexer_db.get_persisted_version().unwrap();
     assert_eq!(version, None);
@@ -393,6 +397,58 @@ fn test_db_indexer_data() {
     assert_vec_eq(&resources, &expected_resources);
 }
 
+#[test]
+fn test_db_indexer_events_by_type_start_version() {
+    use std::{thread, time::Duration};
+    let (aptos_db, _core_account) = create_test_db();
+    let total_version = aptos_db.expect_synced_version();
+    let temp_path = TempPath::new();
+    let mut node_config = aptos_config::config::NodeConfig::default();
+    node_config.storage.dir = temp_path.path().to_path_buf();
+    node_config.indexer_db_config.enable_event = true;
+
+    // Events up to version 5 were indexed before the events by type index existed.
+    let internal_indexer_db = InternalIndexerDBService::get_indexer_db(&node_config).unwrap();
+    for key in [MetadataKey::LatestVersion, MetadataKey::EventVersion] {
+        internal_indexer_db
+            .db
+            .put::<InternalIndexerMetadataSchema>(&key, &MetadataValue::Version(5))
+            .unwrap();
+    }
+
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone()).unwrap();
+    assert_eq!(
+        internal_indexer_db
+            .get_events_by_type_start_version()
+            .unwrap(),
+        Some(6)
+    );
+    let mut start_version = 6;
+    while start_version < total_version {
+        start_version = db_indexer.process_a_batch(start_version).unwrap();
+    }
+    // wait for the commit to finish
+    thread::sleep(Duration::from_millis(100));
+
+    // Recorded once, not moved forward by later indexing.
+    drop(db_indexer);
+    let db_indexer = DBIndexer::new(internal_indexer_db.clone(), aptos_db.clone()).unwrap();
+    assert_eq!(
+        internal_indexer_db
+            .get_events_by_type_start_version()
+            .unwrap(),
+        Some(6)
+    );
+
+    let struct_tag = StructTag::from_str("0x1::coin::CoinDeposit").unwrap();
+    assert!(db_indexer
+        .get_events_by_type(&struct_tag, 5, 10, total_version)
+        .is_err());
+    assert!(db_indexer
+        .get_events_by_type(&struct_tag, 6, 10, total_version)
+        .is_ok());
+}
+
 fn assert_vec_eq<T: Eq + Debug>(left: &[T], right: &[T]) {
     for i in 0..left.len().min(right.len()) {
         assert_eq!(left[i], right[i], "difference at position {}", i);
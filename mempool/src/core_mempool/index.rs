@@ -368,13 +368,22 @@ impl MultiBucketTimelineIndex {
 
     #[inline]
     fn get_timeline(&mut self, ranking_score: u64) -> &mut TimelineIndex {
-        let index = self
-            .bucket_mins
-            .binary_search(&ranking_score)
-            .unwrap_or_else(|i| i - 1);
+        let index = self.get_timeline_index_identifier(ranking_score) as usize;
         self.timelines.get_mut(index).unwrap()
     }
 
+    /// Returns the identifier of the timeline that a transaction with the given ranking score
+    /// is broadcast from.
+    #[inline]
+    pub(crate) fn get_timeline_index_identifier(
+        &self,
+        ranking_score: u64,
+    ) -> TimelineIndexIdentifier {
+        self.bucket_mins
+            .binary_search(&ranking_score)
+            .unwrap_or_else(|i| i - 1) as TimelineIndexIdentifier
+    }
+
     pub(crate) fn insert(&mut self, txn: &mut MempoolTransaction) {
         self.get_timeline(txn.ranking_score).insert(txn);
     }
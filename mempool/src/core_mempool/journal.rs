@@ -0,0 +1,225 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An append-only, on-disk journal of client-submitted transactions accepted into mempool.
+//!
+//! Each record is a little-endian `u32` length prefix followed by the BCS-encoded
+//! `SignedTransaction`. A partially written record at the tail (e.g. after a crash) is
+//! ignored when the journal is loaded.
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use aptos_types::transaction::SignedTransaction;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::{self, JoinHandle},
+};
+
+const LENGTH_PREFIX_BYTES: usize = 4;
+
+enum JournalCommand {
+    Append(SignedTransaction),
+    Compact(Vec<SignedTransaction>),
+}
+
+/// Handle to the journal. Writes are performed by a dedicated writer thread, so that the
+/// mempool lock isn't held while waiting on disk I/O.
+pub(crate) struct MempoolJournal {
+    sender: Option<SyncSender<JournalCommand>>,
+    writer_thread: Option<JoinHandle<()>>,
+    // Number of records appended since the journal was last compacted
+    num_appended: usize,
+    // Set when an append or a compaction was dropped because the writer fell behind, in which
+    // case only a compaction brings the journal back in sync with the mempool.
+    out_of_sync: bool,
+}
+
+impl MempoolJournal {
+    /// Opens (or creates) the journal at `path`, returning it along with all the transactions
+    /// that could be read from it. At most `max_pending_appends` appends are buffered while the
+    /// writer thread catches up.
+    pub(crate) fn open(
+        path: &Path,
+        max_pending_appends: usize,
+    ) -> Result<(Self, Vec<SignedTransaction>)> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let txns = match File::open(path) {
+            Ok(mut file) => {
+                let mut bytes = vec![];
+                file.read_to_end(&mut bytes)?;
+                decode(&bytes)
+            },
+            Err(err) if err.kind() == ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err.into()),
+        };
+
+        // Rewrite the journal so that a truncated or corrupted tail doesn't prevent
+        // subsequent appends from being read back.
+        let mut writer = JournalWriter {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(open_for_append(path)?),
+        };
+        writer.compact(&txns)?;
+
+        let (sender, receiver) = mpsc::sync_channel(max_pending_appends);
+        let writer_thread = thread::Builder::new()
+            .name("mempool-journal".into())
+            .spawn(move || writer.run(receiver))?;
+        let journal = Self {
+            sender: Some(sender),
+            writer_thread: Some(writer_thread),
+            num_appended: 0,
+            out_of_sync: false,
+        };
+        Ok((journal, txns))
+    }
+
+    /// Queues a transaction to be appended to the journal. If the writer thread has fallen
+    /// behind, the append is dropped and the journal is marked for compaction instead.
+    pub(crate) fn append(&mut self, txn: &SignedTransaction) {
+        if self.send(JournalCommand::Append(txn.clone())) {
+            self.num_appended += 1;
+        }
+    }
+
+    /// Whether the journal should be compacted, either because `compaction_threshold` records
+    /// were appended since the last compaction or because writes were dropped.
+    pub(crate) fn needs_compaction(&self, compaction_threshold: usize) -> bool {
+        self.out_of_sync || self.num_appended >= compaction_threshold
+    }
+
+    /// Queues an atomic replacement of the contents of the journal with the given transactions.
+    pub(crate) fn compact<'a>(&mut self, txns: impl Iterator<Item = &'a SignedTransaction>) {
+        if self.send(JournalCommand::Compact(txns.cloned().collect())) {
+            self.num_appended = 0;
+            self.out_of_sync = false;
+        }
+    }
+
+    fn send(&mut self, command: JournalCommand) -> bool {
+        let Some(sender) = self.sender.as_ref() else {
+            return false;
+        };
+        match sender.try_send(command) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                if !self.out_of_sync {
+                    warn!("Mempool journal writer is falling behind, dropping writes until compaction");
+                }
+                self.out_of_sync = true;
+                false
+            },
+            Err(TrySendError::Disconnected(_)) => {
+                error!("Mempool journal writer has stopped, journaling is disabled");
+                self.sender = None;
+                false
+            },
+        }
+    }
+}
+
+impl Drop for MempoolJournal {
+    fn drop(&mut self) {
+        // Closing the channel lets the writer thread flush the pending writes and exit
+        self.sender.take();
+        if let Some(writer_thread) = self.writer_thread.take() {
+            if writer_thread.join().is_err() {
+                error!("Mempool journal writer thread panicked");
+            }
+        }
+    }
+}
+
+struct JournalWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl JournalWriter {
+    /// Processes commands until the journal handle is dropped. Appends received together are
+    /// written as one batch and flushed once.
+    fn run(mut self, receiver: Receiver<JournalCommand>) {
+        while let Ok(command) = receiver.recv() {
+            self.process(command);
+            while let Ok(command) = receiver.try_recv() {
+                self.process(command);
+            }
+            if let Err(err) = self.writer.flush() {
+                warn!("Failed to flush mempool journal: {}", err);
+            }
+        }
+    }
+
+    fn process(&mut self, command: JournalCommand) {
+        match command {
+            JournalCommand::Append(txn) => {
+                if let Err(err) = encode_into(&txn, &mut self.writer) {
+                    warn!("Failed to append transaction to mempool journal: {}", err);
+                }
+            },
+            JournalCommand::Compact(txns) => {
+                if let Err(err) = self.compact(&txns) {
+                    warn!("Failed to compact mempool journal: {}", err);
+                }
+            },
+        }
+    }
+
+    fn compact(&mut self, txns: &[SignedTransaction]) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            for txn in txns {
+                encode_into(txn, &mut writer)?;
+            }
+            writer.into_inner()?.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        self.writer = BufWriter::new(open_for_append(&self.path)?);
+        Ok(())
+    }
+}
+
+fn open_for_append(path: &Path) -> Result<File> {
+    Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
+
+fn decode(mut bytes: &[u8]) -> Vec<SignedTransaction> {
+    let mut txns = vec![];
+    while bytes.len() >= LENGTH_PREFIX_BYTES {
+        let (prefix, rest) = bytes.split_at(LENGTH_PREFIX_BYTES);
+        let len = u32::from_le_bytes(prefix.try_into().expect("prefix is 4 bytes")) as usize;
+        if rest.len() < len {
+            break;
+        }
+        let (record, rest) = rest.split_at(len);
+        match bcs::from_bytes::<SignedTransaction>(record) {
+            Ok(txn) => txns.push(txn),
+            Err(err) => {
+                warn!(
+                    "Stopped reading mempool journal at corrupted record: {}",
+                    err
+                );
+                break;
+            },
+        }
+        bytes = rest;
+    }
+    txns
+}
+
+fn encode_into(txn: &SignedTransaction, writer: &mut impl Write) -> Result<()> {
+    let bytes = bcs::to_bytes(txn)?;
+    ensure!(
+        bytes.len() <= u32::MAX as usize,
+        "Transaction too large for mempool journal: {} bytes",
+        bytes.len()
+    );
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
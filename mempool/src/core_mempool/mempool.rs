@@ -7,6 +7,7 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
+        journal::MempoolJournal,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
         transaction_store::{sender_bucket, TransactionStore},
     },
@@ -14,7 +15,8 @@ use crate::{
     logging::{LogEntry, LogSchema, TxnsLog},
     network::BroadcastPeerPriority,
     shared_mempool::types::{
-        MempoolSenderBucket, MultiBucketTimelineIndexIds, TimelineIndexIdentifier,
+        MempoolSenderBucket, MempoolTransactionState, MempoolTransactionStatus,
+        MultiBucketTimelineIndexIds, TimelineIndexIdentifier,
     },
 };
 use aptos_config::config::NodeConfig;
@@ -27,26 +29,88 @@ use aptos_types::{
     transaction::{use_case::UseCaseKey, SignedTransaction},
     vm_status::DiscardedVMStatus,
 };
+use lru::LruCache;
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
     sync::atomic::Ordering,
     time::{Duration, Instant, SystemTime},
 };
 
+/// Number of recently rejected transactions whose rejection reason is remembered
+const REJECTED_TRANSACTIONS_CACHE_SIZE: usize = 10_000;
+
+/// A transaction rejected by consensus, remembered to answer transaction status queries
+struct RejectedTransaction {
+    sender: AccountAddress,
+    sequence_number: u64,
+    expiration_timestamp_secs: Option<u64>,
+    reason: DiscardedVMStatus,
+}
+
 pub struct Mempool {
     // Stores the metadata of all transactions in mempool (of all states).
     transactions: TransactionStore,
 
     pub system_transaction_timeout: Duration,
+
+    // On-disk journal of accepted client-submitted transactions, if enabled.
+    journal: Option<MempoolJournal>,
+    journal_compaction_threshold: usize,
+    // Transactions loaded from the journal at startup, waiting to be replayed.
+    journaled_transactions: Vec<SignedTransaction>,
+
+    // The most recent rejections received from consensus, keyed by transaction hash.
+    rejected_transactions: LruCache<HashValue, RejectedTransaction>,
 }
 
 impl Mempool {
     pub fn new(config: &NodeConfig) -> Self {
+        let (journal, journaled_transactions) = if config.mempool.enable_journal {
+            let path = config.get_data_dir().join(&config.mempool.journal_path);
+            match MempoolJournal::open(&path, config.mempool.journal_max_pending_appends) {
+                Ok((journal, txns)) => {
+                    info!(
+                        "Loaded {} transactions from mempool journal at {:?}",
+                        txns.len(),
+                        path
+                    );
+                    (Some(journal), txns)
+                },
+                Err(err) => {
+                    error!(
+                        "Failed to open mempool journal at {:?}, journaling is disabled: {}",
+                        path, err
+                    );
+                    (None, vec![])
+                },
+            }
+        } else {
+            (None, vec![])
+        };
+
         Mempool {
             transactions: TransactionStore::new(&config.mempool),
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
+            journal,
+            journal_compaction_threshold: config.mempool.journal_compaction_threshold,
+            journaled_transactions,
+            rejected_transactions: LruCache::new(REJECTED_TRANSACTIONS_CACHE_SIZE),
+        }
+    }
+
+    /// Returns the transactions loaded from the journal at startup, so they can be revalidated
+    /// and re-inserted. Subsequent calls return nothing.
+    pub(crate) fn take_journaled_transactions(&mut self) -> Vec<SignedTransaction> {
+        std::mem::take(&mut self.journaled_transactions)
+    }
+
+    /// Rewrites the journal so that it only contains the client-submitted transactions that are
+    /// currently in mempool.
+    pub(crate) fn compact_journal(&mut self) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.compact(self.transactions.iter_client_submitted());
         }
     }
 
@@ -109,10 +173,20 @@ impl Mempool {
     ) {
         if *reason == DiscardedVMStatus::SEQUENCE_NUMBER_TOO_NEW {
             self.log_reject_transaction(sender, sequence_number, counters::COMMIT_IGNORED_LABEL);
-            // Do not remove the transaction from mempool
+            // Do not remove the transaction from mempool (nor report it as rejected)
             return;
         }
 
+        self.rejected_transactions.put(*hash, RejectedTransaction {
+            sender: *sender,
+            sequence_number,
+            expiration_timestamp_secs: self
+                .transactions
+                .get(sender, sequence_number)
+                .map(|txn| txn.expiration_timestamp_secs()),
+            reason: *reason,
+        });
+
         let label = if *reason == DiscardedVMStatus::SEQUENCE_NUMBER_TOO_OLD {
             counters::COMMIT_REJECTED_DUPLICATE_LABEL
         } else {
@@ -270,6 +344,40 @@ impl Mempool {
         self.transactions.get_by_hash(hash)
     }
 
+    /// Returns the mempool status of the transaction with the given hash, along with the sender
+    /// bucket and timeline it's broadcast from (if it's ready for broadcast). Transactions
+    /// recently rejected by consensus are reported even after they were removed from mempool.
+    pub(crate) fn get_status_by_hash(
+        &self,
+        hash: &HashValue,
+    ) -> Option<(
+        MempoolTransactionStatus,
+        Option<(MempoolSenderBucket, TimelineIndexIdentifier)>,
+    )> {
+        let rejection = self.rejected_transactions.peek(hash);
+        if let Some((mut status, timeline)) = self.transactions.get_status_by_hash(hash) {
+            status.last_rejection_reason = rejection.map(|rejection| rejection.reason);
+            return Some((status, timeline));
+        }
+
+        rejection.map(|rejection| {
+            let status = MempoolTransactionStatus {
+                sender: rejection.sender,
+                sequence_number: rejection.sequence_number,
+                state: MempoolTransactionState::Rejected,
+                account_sequence_number: None,
+                blocking_sequence_number: None,
+                timeline_id: None,
+                broadcast_peers: vec![],
+                insertion_timestamp_usecs: None,
+                expiration_timestamp_secs: rejection.expiration_timestamp_secs,
+                system_expiration_timestamp_secs: None,
+                last_rejection_reason: Some(rejection.reason),
+            };
+            (status, None)
+        })
+    }
+
     /// Used to add a transaction to the Mempool.
     /// Performs basic validation: checks account's sequence number.
     pub(crate) fn add_txn(
@@ -321,6 +429,11 @@ impl Mempool {
         let now = aptos_infallible::duration_since_epoch().as_millis() as u64;
 
         if status.code == MempoolStatusCode::Accepted {
+            if client_submitted {
+                if let Some(journal) = self.journal.as_mut() {
+                    journal.append(&txn);
+                }
+            }
             counters::SENDER_BUCKET_FREQUENCIES
                 .with_label_values(&[sender_bucket(
                     &sender,
@@ -531,6 +644,14 @@ impl Mempool {
     pub(crate) fn gc(&mut self) {
         let now = aptos_infallible::duration_since_epoch();
         self.transactions.gc_by_system_ttl(now);
+
+        if self
+            .journal
+            .as_ref()
+            .is_some_and(|journal| journal.needs_compaction(self.journal_compaction_threshold))
+        {
+            self.compact_journal();
+        }
     }
 
     /// Garbage collection based on client-specified expiration time.
@@ -9,13 +9,14 @@ use crate::{
             PriorityQueueIter, TTLIndex,
         },
         mempool::Mempool,
-        transaction::{InsertionInfo, MempoolTransaction, TimelineState},
+        transaction::{InsertionInfo, MempoolTransaction, SubmittedBy, TimelineState},
     },
     counters::{self, BROADCAST_BATCHED_LABEL, BROADCAST_READY_LABEL, CONSENSUS_READY_LABEL},
     logging::{LogEntry, LogEvent, LogSchema, TxnsLog},
     network::BroadcastPeerPriority,
     shared_mempool::types::{
-        MempoolSenderBucket, MultiBucketTimelineIndexIds, TimelineIndexIdentifier,
+        MempoolSenderBucket, MempoolTransactionState, MempoolTransactionStatus,
+        MultiBucketTimelineIndexIds, TimelineIndexIdentifier,
     },
 };
 use aptos_config::config::MempoolConfig;
@@ -40,6 +41,13 @@ pub const TXN_INDEX_ESTIMATED_BYTES: usize = size_of::<crate::core_mempool::inde
     + (size_of::<u64>() * 3 + size_of::<AccountAddress>()) // timeline_index
     + (size_of::<HashValue>() + size_of::<u64>() + size_of::<AccountAddress>()); // hash_index
 
+/// Returns the minimum gas unit price for a transaction to replace one with the given gas unit
+/// price, i.e., at least `bump_pct` percent (and at least 1) higher.
+fn min_replacement_gas_price(gas_price: u64, bump_pct: u64) -> u64 {
+    let bump = (gas_price as u128 * bump_pct as u128).div_ceil(100).max(1);
+    (gas_price as u128 + bump).min(u64::MAX as u128) as u64
+}
+
 pub fn sender_bucket(
     address: &AccountAddress,
     num_sender_buckets: MempoolSenderBucket,
@@ -89,6 +97,9 @@ pub struct TransactionStore {
     // eager expiration
     eager_expire_threshold: Option<Duration>,
     eager_expire_time: Duration,
+
+    // replace-by-fee
+    replace_by_fee_min_gas_price_bump_pct: u64,
 }
 
 impl TransactionStore {
@@ -127,6 +138,7 @@ impl TransactionStore {
             // eager expiration
             eager_expire_threshold: config.eager_expire_threshold_ms.map(Duration::from_millis),
             eager_expire_time: Duration::from_millis(config.eager_expire_time_ms),
+            replace_by_fee_min_gas_price_bump_pct: config.replace_by_fee_min_gas_price_bump_pct,
         }
     }
 
@@ -153,6 +165,18 @@ impl TransactionStore {
         None
     }
 
+    /// Returns the number of transactions from `address` in mempool, excluding the one with
+    /// `sequence_number` (which a new transaction with that sequence number would replace).
+    pub(crate) fn get_num_other_transactions(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> usize {
+        self.transactions.get(address).map_or(0, |txns| {
+            txns.len() - usize::from(txns.contains_key(&sequence_number))
+        })
+    }
+
     /// Fetch transaction by account address + sequence_number, including ranking score
     pub(crate) fn get_with_ranking_score(
         &self,
@@ -172,6 +196,86 @@ impl TransactionStore {
         }
     }
 
+    /// Returns the mempool status of the transaction with the given hash, along with the sender
+    /// bucket and timeline it's broadcast from (if it's ready for broadcast). The broadcast peers
+    /// and rejection reason are left for the caller to fill in.
+    pub(crate) fn get_status_by_hash(
+        &self,
+        hash: &HashValue,
+    ) -> Option<(
+        MempoolTransactionStatus,
+        Option<(MempoolSenderBucket, TimelineIndexIdentifier)>,
+    )> {
+        let (address, sequence_number) = self.hash_index.get(hash)?;
+        let txn = self.get_mempool_txn(address, *sequence_number)?;
+
+        let account_sequence_number = self
+            .get_sequence_number(address)
+            .copied()
+            .unwrap_or(txn.sequence_info.account_sequence_number);
+        let in_parking_lot = self
+            .parking_lot_index
+            .contains(address, *sequence_number, *hash);
+        // A parked transaction is blocked by the first gap in the sequence numbers of the
+        // account's transactions, starting from the account's sequence number
+        let blocking_sequence_number = if in_parking_lot {
+            let mut next_sequence_number = account_sequence_number;
+            for (seq, _) in self
+                .transactions
+                .get(address)?
+                .range(account_sequence_number..)
+            {
+                if *seq != next_sequence_number {
+                    break;
+                }
+                next_sequence_number += 1;
+            }
+            (next_sequence_number < *sequence_number).then_some(next_sequence_number)
+        } else {
+            None
+        };
+
+        let sender_bucket = sender_bucket(address, self.num_sender_buckets);
+        let (timeline_id, timeline) = match txn.timeline_state {
+            TimelineState::Ready(timeline_id) => (
+                Some(timeline_id),
+                self.timeline_index
+                    .get(&sender_bucket)
+                    .map(|timeline_index| {
+                        (
+                            sender_bucket,
+                            timeline_index.get_timeline_index_identifier(txn.ranking_score),
+                        )
+                    }),
+            ),
+            TimelineState::NotReady | TimelineState::NonQualified => (None, None),
+        };
+
+        let status = MempoolTransactionStatus {
+            sender: *address,
+            sequence_number: *sequence_number,
+            state: if in_parking_lot {
+                MempoolTransactionState::ParkingLot
+            } else {
+                MempoolTransactionState::Ready
+            },
+            account_sequence_number: Some(account_sequence_number),
+            blocking_sequence_number,
+            timeline_id,
+            broadcast_peers: vec![],
+            insertion_timestamp_usecs: txn
+                .insertion_info
+                .insertion_time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .map(|duration| duration.as_micros() as u64),
+            expiration_timestamp_secs: Some(txn.txn.expiration_timestamp_secs()),
+            system_expiration_timestamp_secs: Some(txn.expiration_time.as_secs()),
+            last_rejection_reason: None,
+        };
+        Some((status, timeline))
+    }
+
     pub(crate) fn get_insertion_info_and_bucket(
         &self,
         address: &AccountAddress,
@@ -226,44 +330,44 @@ impl TransactionStore {
         let txn_seq_num = txn.sequence_info.transaction_sequence_number;
         let acc_seq_num = txn.sequence_info.account_sequence_number;
 
-        // If the transaction is already in Mempool, we only allow the user to
-        // increase the gas unit price to speed up a transaction, but not the max gas.
+        // If a transaction with the same sequence number is already in Mempool, it can only be
+        // replaced by a transaction with a sufficiently higher gas unit price (replace-by-fee).
+        // The replacement is free to change anything else, e.g., the payload or the expiration.
         //
         // Transactions with all the same inputs (but possibly signed differently) are idempotent
         // since the raw transaction is the same
         if let Some(txns) = self.transactions.get_mut(&address) {
-            if let Some(current_version) = txns.get_mut(&txn_seq_num) {
-                if current_version.txn.payload() != txn.txn.payload() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a different payload".to_string(),
-                    );
-                } else if current_version.txn.expiration_timestamp_secs()
-                    != txn.txn.expiration_timestamp_secs()
-                {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a different expiration timestamp"
-                            .to_string(),
-                    );
-                } else if current_version.txn.max_gas_amount() != txn.txn.max_gas_amount() {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a different max gas amount"
-                            .to_string(),
-                    );
-                } else if current_version.get_gas_price() < txn.get_gas_price() {
-                    // Update txn if gas unit price is a larger value than before
+            if let Some(current_version) = txns.get(&txn_seq_num) {
+                let current_gas_price = current_version.get_gas_price();
+                let is_same_transaction = current_version.txn.payload() == txn.txn.payload()
+                    && current_version.txn.expiration_timestamp_secs()
+                        == txn.txn.expiration_timestamp_secs()
+                    && current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
+                    && current_gas_price == txn.get_gas_price();
+                let min_replacement_gas_price = min_replacement_gas_price(
+                    current_gas_price,
+                    self.replace_by_fee_min_gas_price_bump_pct,
+                );
+
+                if is_same_transaction {
+                    // If the transaction is the same, it's an idempotent call
+                    // Updating signers is not supported, the previous submission must fail
+                    counters::CORE_MEMPOOL_IDEMPOTENT_TXNS.inc();
+                    return MempoolStatus::new(MempoolStatusCode::Accepted);
+                } else if txn.get_gas_price() >= min_replacement_gas_price {
+                    // Evict the current transaction, the replacement is inserted below with
+                    // fresh priority and timeline entries so that it's broadcast again
                     if let Some(txn) = txns.remove(&txn_seq_num) {
                         self.index_remove(&txn);
                     };
                     counters::CORE_MEMPOOL_GAS_UPGRADED_TXNS.inc();
-                } else if current_version.get_gas_price() > txn.get_gas_price() {
+                } else {
                     return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
-                        "Transaction already in mempool with a higher gas price".to_string(),
+                        format!(
+                            "Transaction already in mempool with gas unit price {}. Replacing it requires a gas unit price of at least {}",
+                            current_gas_price, min_replacement_gas_price,
+                        ),
                     );
-                } else {
-                    // If the transaction is the same, it's an idempotent call
-                    // Updating signers is not supported, the previous submission must fail
-                    counters::CORE_MEMPOOL_IDEMPOTENT_TXNS.inc();
-                    return MempoolStatus::new(MempoolStatusCode::Accepted);
                 }
             }
         }
@@ -917,6 +1021,15 @@ impl TransactionStore {
         &self.transactions
     }
 
+    /// Iterates over all the transactions in the store that were submitted by clients of this node.
+    pub(crate) fn iter_client_submitted(&self) -> impl Iterator<Item = &SignedTransaction> {
+        self.transactions
+            .values()
+            .flat_map(|txns| txns.values())
+            .filter(|txn| txn.insertion_info.submitted_by == SubmittedBy::Client)
+            .map(|txn| &txn.txn)
+    }
+
     pub(crate) fn get_parking_lot_addresses(&self) -> Vec<(AccountAddress, u64)> {
         self.parking_lot_index.get_addresses()
     }
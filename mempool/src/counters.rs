@@ -100,6 +100,11 @@ pub const SUBMITTED_BY_CLIENT_LABEL: &str = "client";
 pub const SUBMITTED_BY_DOWNSTREAM_LABEL: &str = "downstream";
 pub const SUBMITTED_BY_PEER_VALIDATOR_LABEL: &str = "peer_validator";
 
+// Admission quota labels
+pub const SENDER_QUOTA_LABEL: &str = "sender";
+pub const CLIENT_IP_QUOTA_LABEL: &str = "client_ip";
+pub const PEER_BROADCAST_QUOTA_LABEL: &str = "peer_broadcast";
+
 // Histogram buckets with a large range of 0-500s and some constant sized buckets between:
 // 0-1.5s (every 25ms), 1.5-2s (every 100ms), 2-5s (250ms), 5-10s (1s), and 10-25s (2.5s).
 const MEMPOOL_LATENCY_BUCKETS: &[f64] = &[
@@ -631,6 +636,22 @@ pub fn network_send_fail_inc(label: &'static str) {
     NETWORK_SEND_FAIL.with_label_values(&[label]).inc();
 }
 
+/// Counter for transactions rejected because an admission quota was exceeded
+static ADMISSION_QUOTA_REJECTED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_shared_mempool_admission_quota_rejected_count",
+        "Number of transactions rejected because an admission quota was exceeded",
+        &["quota"]
+    )
+    .unwrap()
+});
+
+pub fn admission_quota_rejected_inc(quota_label: &'static str, num_txns: u64) {
+    ADMISSION_QUOTA_REJECTED_COUNT
+        .with_label_values(&[quota_label])
+        .inc_by(num_txns);
+}
+
 static UNEXPECTED_NETWORK_MSG_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "aptos_mempool_unexpected_network_count",
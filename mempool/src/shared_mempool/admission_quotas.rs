@@ -0,0 +1,132 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fairness quotas applied to transactions before they are admitted into mempool. These
+//! prevent a single sender, client or peer from consuming a disproportionate share of the
+//! mempool capacity.
+use crate::{core_mempool::CoreMempool, counters};
+use aptos_config::{config::AdmissionQuotaConfig, network_id::PeerNetworkId};
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use aptos_types::{
+    mempool_status::{MempoolStatus, MempoolStatusCode},
+    transaction::SignedTransaction,
+};
+use std::{fmt::Debug, hash::Hash, net::IpAddr};
+
+const CLIENT_IP_RATE_LIMITER_LABEL: &str = "mempool_client_ip";
+const PEER_BROADCAST_RATE_LIMITER_LABEL: &str = "mempool_peer_broadcast";
+
+pub(crate) struct AdmissionQuotas {
+    max_pending_txns_per_sender: Option<usize>,
+    client_ip_rate_limiter: Option<TokenBucketRateLimiter<IpAddr>>,
+    peer_broadcast_rate_limiter: Option<TokenBucketRateLimiter<PeerNetworkId>>,
+}
+
+impl AdmissionQuotas {
+    pub(crate) fn new(config: &AdmissionQuotaConfig) -> Self {
+        Self {
+            max_pending_txns_per_sender: config.max_pending_txns_per_sender,
+            client_ip_rate_limiter: new_rate_limiter(
+                CLIENT_IP_RATE_LIMITER_LABEL,
+                config.client_ip_txns_per_sec,
+                config.client_ip_burst_size,
+            ),
+            peer_broadcast_rate_limiter: new_rate_limiter(
+                PEER_BROADCAST_RATE_LIMITER_LABEL,
+                config.peer_broadcast_txns_per_sec,
+                config.peer_broadcast_burst_size,
+            ),
+        }
+    }
+
+    /// Checks a client submission against the per-IP rate limit. Returns the rejection status if
+    /// the limit is exceeded.
+    pub(crate) fn check_client_ip(&self, client_ip: Option<IpAddr>) -> Option<MempoolStatus> {
+        let (Some(rate_limiter), Some(client_ip)) = (&self.client_ip_rate_limiter, client_ip)
+        else {
+            return None;
+        };
+        let bucket = rate_limiter.bucket(client_ip);
+        if bucket.lock().acquire_all_tokens(1).is_err() {
+            counters::admission_quota_rejected_inc(counters::CLIENT_IP_QUOTA_LABEL, 1);
+            return Some(
+                MempoolStatus::new(MempoolStatusCode::ClientRateLimited).with_message(format!(
+                    "Too many transactions submitted from {}",
+                    client_ip
+                )),
+            );
+        }
+        None
+    }
+
+    /// Checks a client submitted transaction against the per-sender quota. Returns the rejection
+    /// status if the quota is exceeded. This must be called under the same mempool lock as the
+    /// insertion of the transaction, or concurrent submissions could all pass the check.
+    pub(crate) fn check_sender_quota(
+        &self,
+        mempool: &CoreMempool,
+        transaction: &SignedTransaction,
+    ) -> Option<MempoolStatus> {
+        let max_pending_txns = self.max_pending_txns_per_sender?;
+        // Replacing a pending transaction doesn't count against the quota
+        let num_pending_txns = mempool
+            .get_transaction_store()
+            .get_num_other_transactions(&transaction.sender(), transaction.sequence_number());
+        if num_pending_txns >= max_pending_txns {
+            counters::admission_quota_rejected_inc(counters::SENDER_QUOTA_LABEL, 1);
+            return Some(
+                MempoolStatus::new(MempoolStatusCode::SenderQuotaExceeded).with_message(format!(
+                    "Sender has {} pending transactions in mempool. Max pending transactions per sender: {}",
+                    num_pending_txns, max_pending_txns,
+                )),
+            );
+        }
+        None
+    }
+
+    /// Returns how many of the `num_txns` transactions broadcast by `peer` can be admitted
+    /// under the peer's rate limit. The remaining transactions should be rejected.
+    pub(crate) fn num_broadcast_txns_allowed(&self, peer: PeerNetworkId, num_txns: usize) -> usize {
+        let Some(rate_limiter) = &self.peer_broadcast_rate_limiter else {
+            return num_txns;
+        };
+        let bucket = rate_limiter.bucket(peer);
+        let num_allowed = bucket.lock().acquire_tokens(num_txns).unwrap_or(0);
+        if num_allowed < num_txns {
+            counters::admission_quota_rejected_inc(
+                counters::PEER_BROADCAST_QUOTA_LABEL,
+                (num_txns - num_allowed) as u64,
+            );
+        }
+        num_allowed
+    }
+
+    /// Drops the rate limiter buckets of clients and peers that have been idle long enough for
+    /// their buckets to refill, so they don't accumulate forever
+    pub(crate) fn garbage_collect_rate_limiters(&self) {
+        if let Some(rate_limiter) = &self.client_ip_rate_limiter {
+            rate_limiter.garbage_collect_full_buckets();
+        }
+        if let Some(rate_limiter) = &self.peer_broadcast_rate_limiter {
+            rate_limiter.garbage_collect_full_buckets();
+        }
+    }
+}
+
+/// Creates a rate limiter with the given rate and burst size, if a rate is configured
+fn new_rate_limiter<Key: Eq + Hash + Clone + Debug>(
+    label: &'static str,
+    txns_per_sec: Option<usize>,
+    burst_size: usize,
+) -> Option<TokenBucketRateLimiter<Key>> {
+    txns_per_sec.map(|txns_per_sec| {
+        TokenBucketRateLimiter::new(
+            label,
+            label.to_string(),
+            100,
+            burst_size,
+            txns_per_sec,
+            None,
+        )
+    })
+}
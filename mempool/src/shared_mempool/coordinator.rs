@@ -10,6 +10,7 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     network::{BroadcastPeerPriority, MempoolSyncMsg},
     shared_mempool::{
+        admission_quotas::AdmissionQuotas,
         tasks::{self, process_committed_transactions},
         types::{
             notify_subscribers, MempoolMessageId, ScheduledBroadcast, SharedMempool,
@@ -104,6 +105,18 @@ pub(crate) async fn coordinator<NetworkClient, TransactionValidator, ConfigProvi
     )
     .await;
 
+    // Replay the transactions persisted by the mempool journal, now that the validator is
+    // configured for the current epoch
+    let journaled_transactions = smp.mempool.lock().take_journaled_transactions();
+    if !journaled_transactions.is_empty() {
+        bounded_executor
+            .spawn(tasks::process_journaled_transactions(
+                smp.clone(),
+                journaled_transactions,
+            ))
+            .await;
+    }
+
     loop {
         let _timer = counters::MAIN_LOOP.start_timer();
         ::futures::select! {
@@ -173,7 +186,7 @@ async fn handle_client_request<NetworkClient, TransactionValidator>(
     TransactionValidator: TransactionValidation + 'static,
 {
     match request {
-        MempoolClientRequest::SubmitTransaction(txn, callback) => {
+        MempoolClientRequest::SubmitTransaction(txn, client_ip, callback) => {
             // This timer measures how long it took for the bounded executor to *schedule* the
             // task.
             let _timer = counters::task_spawn_latency_timer(
@@ -191,6 +204,7 @@ async fn handle_client_request<NetworkClient, TransactionValidator>(
                 .spawn(tasks::process_client_transaction_submission(
                     smp.clone(),
                     txn,
+                    client_ip,
                     callback,
                     task_start_timer,
                 ))
@@ -217,6 +231,15 @@ async fn handle_client_request<NetworkClient, TransactionValidator>(
                 ))
                 .await;
         },
+        MempoolClientRequest::GetTransactionStatusByHash(hash, callback) => {
+            bounded_executor
+                .spawn(tasks::process_client_get_transaction_status(
+                    smp.clone(),
+                    hash,
+                    callback,
+                ))
+                .await;
+        },
         MempoolClientRequest::GetAddressesFromParkingLot(callback) => {
             bounded_executor
                 .spawn(tasks::process_parking_lot_addresses(smp.clone(), callback))
@@ -460,6 +483,17 @@ pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval
     ));
 }
 
+/// Periodically garbage collects the admission quota rate limits of idle clients and peers.
+pub(crate) async fn admission_quota_gc_job(
+    admission_quotas: Arc<AdmissionQuotas>,
+    gc_interval_secs: u64,
+) {
+    let mut interval = IntervalStream::new(interval(Duration::from_secs(gc_interval_secs)));
+    while let Some(_interval) = interval.next().await {
+        admission_quotas.garbage_collect_rate_limiters();
+    }
+}
+
 /// Periodically logs a snapshot of transactions in core mempool.
 /// In the future we may want an interactive way to directly query mempool's internal state.
 /// For now, we will rely on this periodic snapshot to observe the internal state.
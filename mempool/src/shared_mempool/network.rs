@@ -12,7 +12,7 @@ use crate::{
         tasks,
         types::{
             notify_subscribers, MempoolMessageId, MempoolSenderBucket, PeerSyncState,
-            SharedMempool, SharedMempoolNotification,
+            SharedMempool, SharedMempoolNotification, TimelineIndexIdentifier,
         },
     },
 };
@@ -668,4 +668,32 @@ impl<NetworkClient: NetworkClientInterface<MempoolSyncMsg>> MempoolNetworkInterf
     pub fn sync_states_exists(&self, peer: &PeerNetworkId) -> bool {
         self.sync_states.read().get(peer).is_some()
     }
+
+    /// Returns the peers that have been broadcast the transaction at the given position in the
+    /// timeline of the given sender bucket.
+    pub fn get_broadcast_peers(
+        &self,
+        sender_bucket: MempoolSenderBucket,
+        timeline_index_identifier: TimelineIndexIdentifier,
+        timeline_id: u64,
+    ) -> Vec<PeerNetworkId> {
+        self.sync_states
+            .read()
+            .iter()
+            .filter(|(_peer, state)| {
+                state
+                    .timelines
+                    .get(&sender_bucket)
+                    .and_then(|timeline| {
+                        timeline
+                            .id_per_bucket
+                            .get(timeline_index_identifier as usize)
+                    })
+                    .map_or(false, |broadcast_timeline_id| {
+                        *broadcast_timeline_id >= timeline_id
+                    })
+            })
+            .map(|(peer, _state)| *peer)
+            .collect()
+    }
 }
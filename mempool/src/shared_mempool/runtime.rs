@@ -6,12 +6,12 @@ use crate::{
     core_mempool::CoreMempool,
     network::MempoolSyncMsg,
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
+        coordinator::{admission_quota_gc_job, coordinator, gc_coordinator, snapshot_job},
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     QuorumStoreRequest,
 };
-use aptos_config::config::{NodeConfig, NodeType};
+use aptos_config::config::{transaction_filter_type::ReloadableFilter, NodeConfig, NodeType};
 use aptos_event_notifications::{DbBackedOnChainConfig, ReconfigNotificationListener};
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::Level;
@@ -32,6 +32,7 @@ use tokio::runtime::{Handle, Runtime};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers).
 ///   - inbound_network_task (task that handles inbound mempool messages and network events).
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL).
+///   - admission_quota_gc_task (task that performs GC of the rate limits of idle clients and peers).
 pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
     executor: &Handle,
     config: &NodeConfig,
@@ -46,6 +47,7 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
     validator: Arc<RwLock<TransactionValidator>>,
     subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
     peers_and_metadata: Arc<PeersAndMetadata>,
+    transaction_filter: ReloadableFilter,
 ) where
     TransactionValidator: TransactionValidation + 'static,
     ConfigProvider: OnChainConfigProvider,
@@ -60,8 +62,17 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
             validator,
             subscribers,
             node_type,
+            transaction_filter,
         );
 
+    executor.spawn(admission_quota_gc_job(
+        smp.admission_quotas.clone(),
+        config
+            .mempool
+            .admission_quotas
+            .rate_limiter_gc_interval_secs,
+    ));
+
     executor.spawn(coordinator(
         smp,
         executor.clone(),
@@ -97,6 +108,7 @@ pub fn bootstrap(
     mempool_listener: MempoolNotificationListener,
     mempool_reconfig_events: ReconfigNotificationListener<DbBackedOnChainConfig>,
     peers_and_metadata: Arc<PeersAndMetadata>,
+    transaction_filter: ReloadableFilter,
 ) -> Runtime {
     let runtime = aptos_runtimes::spawn_named_runtime("shared-mem".into(), None);
     let mempool = Arc::new(Mutex::new(CoreMempool::new(config)));
@@ -118,6 +130,7 @@ pub fn bootstrap(
         vm_validator,
         vec![],
         peers_and_metadata,
+        transaction_filter,
     );
     runtime
 }
@@ -11,8 +11,8 @@ use crate::{
     network::{BroadcastError, BroadcastPeerPriority, MempoolSyncMsg},
     shared_mempool::{
         types::{
-            notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification,
-            SubmissionStatusBundle,
+            notify_subscribers, MempoolTransactionStatus, ScheduledBroadcast, SharedMempool,
+            SharedMempoolNotification, SubmissionStatusBundle,
         },
         use_case_history::UseCaseHistory,
     },
@@ -41,6 +41,7 @@ use futures::{channel::oneshot, stream::FuturesUnordered};
 use rayon::prelude::*;
 use std::{
     cmp,
+    net::IpAddr,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -116,6 +117,7 @@ pub(crate) async fn execute_broadcast<NetworkClient, TransactionValidator>(
 pub(crate) async fn process_client_transaction_submission<NetworkClient, TransactionValidator>(
     smp: SharedMempool<NetworkClient, TransactionValidator>,
     transaction: SignedTransaction,
+    client_ip: Option<IpAddr>,
     callback: oneshot::Sender<Result<SubmissionStatus>>,
     timer: HistogramTimer,
 ) where
@@ -124,20 +126,28 @@ pub(crate) async fn process_client_transaction_submission<NetworkClient, Transac
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer_client();
-    let ineligible_for_broadcast =
-        smp.network_interface.is_validator() && !smp.broadcast_within_validator_network();
-    let timeline_state = if ineligible_for_broadcast {
-        TimelineState::NonQualified
-    } else {
-        TimelineState::NotReady
-    };
+
+    // Reject the transaction early if the client is over its rate limit. The sender quota is
+    // checked when the transaction is inserted.
+    let quota_status = smp.admission_quotas.check_client_ip(client_ip);
     let statuses: Vec<(SignedTransaction, (MempoolStatus, Option<StatusCode>))> =
-        process_incoming_transactions(
-            &smp,
-            vec![(transaction, None, Some(BroadcastPeerPriority::Primary))],
-            timeline_state,
-            true,
-        );
+        if let Some(quota_status) = quota_status {
+            vec![(transaction, (quota_status, None))]
+        } else {
+            let ineligible_for_broadcast =
+                smp.network_interface.is_validator() && !smp.broadcast_within_validator_network();
+            let timeline_state = if ineligible_for_broadcast {
+                TimelineState::NonQualified
+            } else {
+                TimelineState::NotReady
+            };
+            process_incoming_transactions(
+                &smp,
+                vec![(transaction, None, Some(BroadcastPeerPriority::Primary))],
+                timeline_state,
+                true,
+            )
+        };
     log_txn_process_results(&statuses, None);
 
     if let Some(status) = statuses.first() {
@@ -151,6 +161,77 @@ pub(crate) async fn process_client_transaction_submission<NetworkClient, Transac
     }
 }
 
+/// Revalidates and re-inserts the transactions loaded from the mempool journal at startup.
+/// Afterwards, the journal is compacted to only contain the transactions that were accepted.
+pub(crate) async fn process_journaled_transactions<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    transactions: Vec<SignedTransaction>,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation + 'static,
+{
+    let ineligible_for_broadcast =
+        smp.network_interface.is_validator() && !smp.broadcast_within_validator_network();
+    let timeline_state = if ineligible_for_broadcast {
+        TimelineState::NonQualified
+    } else {
+        TimelineState::NotReady
+    };
+    let num_journaled = transactions.len();
+    let statuses = process_incoming_transactions(
+        &smp,
+        transactions
+            .into_iter()
+            .map(|txn| (txn, None, Some(BroadcastPeerPriority::Primary)))
+            .collect(),
+        timeline_state,
+        true,
+    );
+    log_txn_process_results(&statuses, None);
+
+    let num_accepted = statuses
+        .iter()
+        .filter(|(_, (status, _))| status.code == MempoolStatusCode::Accepted)
+        .count();
+    info!(
+        "Replayed mempool journal: {} of {} transactions accepted",
+        num_accepted, num_journaled
+    );
+    smp.mempool.lock().compact_journal();
+}
+
+/// Processes get transaction status by hash request by client.
+pub(crate) async fn process_client_get_transaction_status<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    hash: HashValue,
+    callback: oneshot::Sender<Option<MempoolTransactionStatus>>,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+{
+    let status = smp.mempool.lock().get_status_by_hash(&hash);
+    let status = status.map(|(mut status, timeline)| {
+        if let (Some((sender_bucket, timeline_index_identifier)), Some(timeline_id)) =
+            (timeline, status.timeline_id)
+        {
+            status.broadcast_peers = smp.network_interface.get_broadcast_peers(
+                sender_bucket,
+                timeline_index_identifier,
+                timeline_id,
+            );
+        }
+        status
+    });
+
+    if callback.send(status).is_err() {
+        warn!(LogSchema::event_log(
+            LogEntry::GetTransaction,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes request for all addresses in parking lot
 pub(crate) async fn process_parking_lot_addresses<NetworkClient, TransactionValidator>(
     smp: SharedMempool<NetworkClient, TransactionValidator>,
@@ -199,7 +280,7 @@ pub(crate) async fn process_transaction_broadcast<NetworkClient, TransactionVali
     // The sender of the transactions can send the time at which the transactions were inserted
     // in the sender's mempool. The sender can also send the priority of this node for the sender
     // of the transactions.
-    transactions: Vec<(
+    mut transactions: Vec<(
         SignedTransaction,
         Option<u64>,
         Option<BroadcastPeerPriority>,
@@ -214,7 +295,24 @@ pub(crate) async fn process_transaction_broadcast<NetworkClient, TransactionVali
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer(peer.network_id());
-    let results = process_incoming_transactions(&smp, transactions, timeline_state, false);
+
+    // Only process the transactions allowed by the peer's broadcast rate limit, and ask the
+    // peer to retry the rest later.
+    let num_allowed = smp
+        .admission_quotas
+        .num_broadcast_txns_allowed(peer, transactions.len());
+    let rate_limited_transactions = transactions.split_off(num_allowed);
+    let mut results = if transactions.is_empty() {
+        vec![]
+    } else {
+        process_incoming_transactions(&smp, transactions, timeline_state, false)
+    };
+    results.extend(rate_limited_transactions.into_iter().map(|(txn, _, _)| {
+        (
+            txn,
+            (MempoolStatus::new(MempoolStatusCode::PeerRateLimited), None),
+        )
+    }));
     log_txn_process_results(&results, Some(peer));
 
     let ack_response = gen_ack_response(message_id, results, &peer);
@@ -237,7 +335,8 @@ pub(crate) async fn process_transaction_broadcast<NetworkClient, TransactionVali
     notify_subscribers(SharedMempoolNotification::ACK, &smp.subscribers);
 }
 
-/// If `MempoolIsFull` on any of the transactions, provide backpressure to the downstream peer.
+/// If `MempoolIsFull` or `PeerRateLimited` on any of the transactions, provide backpressure to
+/// the downstream peer.
 fn gen_ack_response(
     message_id: MempoolMessageId,
     results: Vec<SubmissionStatusBundle>,
@@ -245,7 +344,10 @@ fn gen_ack_response(
 ) -> MempoolSyncMsg {
     let mut backoff_and_retry = false;
     for (_, (mempool_status, _)) in results.into_iter() {
-        if mempool_status.code == MempoolStatusCode::MempoolIsFull {
+        if matches!(
+            mempool_status.code,
+            MempoolStatusCode::MempoolIsFull | MempoolStatusCode::PeerRateLimited
+        ) {
             backoff_and_retry = true;
             break;
         }
@@ -304,6 +406,33 @@ where
 {
     let mut statuses = vec![];
 
+    // Reject the transactions denied by the mempool transaction filter
+    let transaction_filter = smp.transaction_filter.filter();
+    let transactions: Vec<_> = if transaction_filter.is_empty() {
+        transactions
+    } else {
+        transactions
+            .into_iter()
+            .filter_map(|(t, ready_time_at_sender, priority)| {
+                if transaction_filter.allows_transaction(&t) {
+                    Some((t, ready_time_at_sender, priority))
+                } else {
+                    statuses.push((
+                        t,
+                        (
+                            MempoolStatus::new(MempoolStatusCode::RejectedByFilter),
+                            None,
+                        ),
+                    ));
+                    None
+                }
+            })
+            .collect()
+    };
+    if transactions.is_empty() {
+        return statuses;
+    }
+
     let start_storage_read = Instant::now();
     let state_view = smp
         .db
@@ -415,6 +544,17 @@ fn validate_and_add_transactions<NetworkClient, TransactionValidator>(
             if let Ok(validation_result) = &validation_results[idx] {
                 match validation_result.status() {
                     None => {
+                        // Checked under the same lock as the insertion, so that concurrent
+                        // submissions from a sender can't all pass the quota check
+                        if client_submitted {
+                            if let Some(quota_status) = smp
+                                .admission_quotas
+                                .check_sender_quota(&mempool, &transaction)
+                            {
+                                statuses.push((transaction, (quota_status, None)));
+                                continue;
+                            }
+                        }
                         let ranking_score = validation_result.score();
                         let mempool_status = mempool.add_txn(
                             transaction.clone(),
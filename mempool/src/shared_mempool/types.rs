@@ -6,11 +6,11 @@
 use crate::{
     core_mempool::CoreMempool,
     network::{MempoolNetworkInterface, MempoolSyncMsg},
-    shared_mempool::use_case_history::UseCaseHistory,
+    shared_mempool::{admission_quotas::AdmissionQuotas, use_case_history::UseCaseHistory},
 };
 use anyhow::Result;
 use aptos_config::{
-    config::{MempoolConfig, NodeType},
+    config::{transaction_filter_type::ReloadableFilter, MempoolConfig, NodeType},
     network_id::PeerNetworkId,
 };
 use aptos_consensus_types::common::{
@@ -35,6 +35,7 @@ use std::{
     cmp::Ordering,
     collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
+    net::IpAddr,
     pin::Pin,
     sync::Arc,
     task::Waker,
@@ -56,6 +57,8 @@ pub(crate) struct SharedMempool<NetworkClient, TransactionValidator> {
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
     pub broadcast_within_validator_network: Arc<RwLock<bool>>,
     pub use_case_history: Arc<Mutex<UseCaseHistory>>,
+    pub admission_quotas: Arc<AdmissionQuotas>,
+    pub transaction_filter: ReloadableFilter,
 }
 
 impl<
@@ -71,6 +74,7 @@ impl<
         validator: Arc<RwLock<TransactionValidator>>,
         subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
         node_type: NodeType,
+        transaction_filter: ReloadableFilter,
     ) -> Self {
         let network_interface =
             MempoolNetworkInterface::new(network_client, node_type, config.clone());
@@ -78,6 +82,7 @@ impl<
             config.usecase_stats_num_blocks_to_track,
             config.usecase_stats_num_top_to_track,
         );
+        let admission_quotas = AdmissionQuotas::new(&config.admission_quotas);
         SharedMempool {
             mempool,
             config,
@@ -87,6 +92,8 @@ impl<
             subscribers,
             broadcast_within_validator_network: Arc::new(RwLock::new(true)),
             use_case_history: Arc::new(Mutex::new(use_case_history)),
+            admission_quotas: Arc::new(admission_quotas),
+            transaction_filter,
         }
     }
 
@@ -235,11 +242,55 @@ pub type SubmissionStatus = (MempoolStatus, Option<DiscardedVMStatus>);
 
 pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 
+/// Where a transaction is in its mempool lifecycle
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MempoolTransactionState {
+    /// The transaction can be included in the next block
+    Ready,
+    /// The transaction is waiting in the parking lot for an earlier sequence number
+    ParkingLot,
+    /// The transaction was rejected by consensus and removed from mempool
+    Rejected,
+}
+
+/// The mempool status of a single transaction, used to debug transactions that don't commit
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MempoolTransactionStatus {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub state: MempoolTransactionState,
+    /// The sequence number of the sender's account as last known by mempool
+    pub account_sequence_number: Option<u64>,
+    /// For parked transactions, the first sequence number missing from mempool that blocks the
+    /// transaction from becoming ready
+    pub blocking_sequence_number: Option<u64>,
+    /// The position of the transaction in the broadcast timeline, if it's ready for broadcast
+    pub timeline_id: Option<u64>,
+    /// The peers the transaction has been broadcast to
+    pub broadcast_peers: Vec<PeerNetworkId>,
+    /// The time the transaction was inserted into mempool, in microseconds since the epoch
+    pub insertion_timestamp_usecs: Option<u64>,
+    /// The client specified expiration time, in seconds since the epoch
+    pub expiration_timestamp_secs: Option<u64>,
+    /// The time at which mempool removes the transaction regardless of its client specified
+    /// expiration, in seconds since the epoch
+    pub system_expiration_timestamp_secs: Option<u64>,
+    /// The reason consensus last rejected the transaction, if any
+    pub last_rejection_reason: Option<DiscardedVMStatus>,
+}
+
 pub enum MempoolClientRequest {
-    /// Submits a transaction to the mempool and returns its submission status
-    SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
+    /// Submits a transaction to the mempool and returns its submission status. The optional IP
+    /// address of the submitting client is used to enforce per-client rate limits.
+    SubmitTransaction(
+        SignedTransaction,
+        Option<IpAddr>,
+        oneshot::Sender<Result<SubmissionStatus>>,
+    ),
     /// Retrieves a signed transaction from the mempool using its hash
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Retrieves the mempool status of a transaction using its hash
+    GetTransactionStatusByHash(HashValue, oneshot::Sender<Option<MempoolTransactionStatus>>),
     /// Retrieves all addresses with transactions in the mempool's parking lot and
     /// the number of transactions for each address
     GetAddressesFromParkingLot(oneshot::Sender<Vec<(AccountAddress, u64)>>),
@@ -19,7 +19,11 @@ use aptos_types::{
 };
 use itertools::Itertools;
 use maplit::btreemap;
-use std::time::{Duration, Instant, SystemTime};
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    time::{Duration, Instant, SystemTime},
+};
 
 #[test]
 fn test_transaction_ordering_only_seqnos() {
@@ -159,26 +163,51 @@ fn test_fail_for_same_gas_amount_and_not_same_expiration_time() {
 }
 
 #[test]
-fn test_update_invalid_transaction_in_mempool() {
+fn test_replace_transaction_in_mempool() {
     let (mut mempool, mut consensus) = setup_mempool();
     let txns = add_txns_to_mempool(&mut mempool, vec![
         TestTransaction::new(0, 0, 1),
         TestTransaction::new(1, 0, 2),
     ]);
-    let updated_txn = TestTransaction::make_signed_transaction_with_max_gas_amount(
+    let replacement_txn = TestTransaction::make_signed_transaction_with_max_gas_amount(
         &TestTransaction::new(0, 0, 5),
         200,
     );
-    let _added_tnx = add_signed_txn(&mut mempool, updated_txn);
+    add_signed_txn(&mut mempool, replacement_txn.clone()).unwrap();
 
-    // Since both gas price and mas gas amount were updated, the ordering should not have changed.
-    // The second transaction with gas price 2 should come first.
+    // The replacement evicts the original transaction, and is ordered by its new gas price
+    assert_eq!(consensus.get_block(&mut mempool, 1, 1024), vec![
+        replacement_txn
+    ]);
     assert_eq!(consensus.get_block(&mut mempool, 1, 1024), vec![
         txns[1].clone()
     ]);
-    let next_tnx = consensus.get_block(&mut mempool, 1, 1024);
-    assert_eq!(next_tnx, vec![txns[0].clone()]);
-    assert_eq!(next_tnx[0].gas_unit_price(), 1);
+    assert!(consensus.get_block(&mut mempool, 1, 1024).is_empty());
+}
+
+#[test]
+fn test_replace_transaction_requires_gas_price_bump() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.replace_by_fee_min_gas_price_bump_pct = 10;
+    let mut mempool = CoreMempool::new(&config);
+    let txn = add_txn(&mut mempool, TestTransaction::new(0, 0, 100)).unwrap();
+
+    // A replacement must increase the gas unit price by at least 10%
+    for gas_price in [99, 100, 109] {
+        let replacement_txn = TestTransaction::new(0, 0, gas_price)
+            .make_signed_transaction_with_expiration_time(u64::MAX - 1000);
+        assert!(add_signed_txn(&mut mempool, replacement_txn).is_err());
+    }
+    assert_eq!(mempool.get_by_hash(txn.committed_hash()), Some(txn.clone()));
+
+    let replacement_txn = TestTransaction::new(0, 0, 110)
+        .make_signed_transaction_with_expiration_time(u64::MAX - 1000);
+    add_signed_txn(&mut mempool, replacement_txn.clone()).unwrap();
+    assert_eq!(mempool.get_by_hash(txn.committed_hash()), None);
+    assert_eq!(
+        mempool.get_by_hash(replacement_txn.committed_hash()),
+        Some(replacement_txn)
+    );
 }
 
 #[test]
@@ -261,6 +290,8 @@ fn test_reject_transaction() {
         .get_transaction_store()
         .get(&TestTransaction::get_address(0), 1)
         .is_some());
+    let (status, _) = pool.get_status_by_hash(&txns[1].committed_hash()).unwrap();
+    assert_eq!(status.last_rejection_reason, None);
 
     // reject with correct hash should have effect
     pool.reject_transaction(
@@ -1311,3 +1342,59 @@ fn test_include_gas_upgraded() {
     });
     assert_eq!(batch.len(), 0);
 }
+
+#[test]
+fn test_journal_replay() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.enable_journal = true;
+
+    // Only client-submitted transactions are journaled
+    let mut pool = CoreMempool::new(&config);
+    assert!(pool.take_journaled_transactions().is_empty());
+    let client_txns: Vec<_> = (0..2)
+        .map(|seq| TestTransaction::new(0, seq, 1).make_signed_transaction())
+        .collect();
+    for txn in &client_txns {
+        let status = pool.add_txn(
+            txn.clone(),
+            txn.gas_unit_price(),
+            0,
+            TimelineState::NotReady,
+            true,
+            None,
+            Some(BroadcastPeerPriority::Primary),
+        );
+        assert_eq!(status.code, MempoolStatusCode::Accepted);
+    }
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    drop(pool);
+
+    // A partially written record at the tail of the journal is ignored
+    let journal_path = config.get_data_dir().join(&config.mempool.journal_path);
+    let mut journal_file = OpenOptions::new().append(true).open(&journal_path).unwrap();
+    journal_file.write_all(&[100, 0, 0, 0, 1, 2]).unwrap();
+    drop(journal_file);
+
+    let mut pool = CoreMempool::new(&config);
+    assert_eq!(pool.take_journaled_transactions(), client_txns);
+    assert!(pool.take_journaled_transactions().is_empty());
+
+    // Compaction only keeps the client-submitted transactions still in mempool
+    add_signed_txn(&mut pool, client_txns[0].clone()).unwrap();
+    pool.add_txn(
+        client_txns[1].clone(),
+        client_txns[1].gas_unit_price(),
+        0,
+        TimelineState::NotReady,
+        true,
+        None,
+        Some(BroadcastPeerPriority::Primary),
+    );
+    pool.compact_journal();
+    drop(pool);
+
+    let mut pool = CoreMempool::new(&config);
+    assert_eq!(pool.take_journaled_transactions(), vec![
+        client_txns[1].clone()
+    ]);
+}
@@ -8,7 +8,7 @@ use crate::{
     shared_mempool::{tasks, types::SharedMempool},
 };
 use aptos_config::{
-    config::{NodeConfig, NodeType},
+    config::{transaction_filter_type::ReloadableFilter, NodeConfig, NodeType},
     network_id::NetworkId,
 };
 use aptos_infallible::{Mutex, RwLock};
@@ -90,6 +90,7 @@ pub fn test_mempool_process_incoming_transactions_impl(
         vm_validator,
         vec![],
         NodeType::extract_from_config(&config),
+        ReloadableFilter::new(config.mempool.transaction_filter.clone()),
     );
 
     let _ = tasks::process_incoming_transactions(&smp, txns, timeline_state, false);
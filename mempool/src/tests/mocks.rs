@@ -11,7 +11,7 @@ use crate::{
 use anyhow::{format_err, Result};
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
 use aptos_config::{
-    config::{NetworkConfig, NodeConfig},
+    config::{transaction_filter_type::ReloadableFilter, NetworkConfig, NodeConfig},
     network_id::NetworkId,
 };
 use aptos_event_notifications::{ReconfigNotification, ReconfigNotificationListener};
@@ -60,9 +60,15 @@ impl MockSharedMempool {
     /// Returns the runtime on which the shared mempool is running
     /// and the channel through which shared mempool receives client events.
     pub fn new() -> Self {
+        Self::new_with_config(Self::default_config())
+    }
+
+    /// Creates a mock of a running instance of shared mempool with the given node config.
+    pub fn new_with_config(config: NodeConfig) -> Self {
         // Create the shared mempool
         let (ac_client, mempool, quorum_store_sender, mempool_notifier) = Self::start(
             &Handle::current(),
+            &config,
             &DbReaderWriter::new(MockDbReaderWriter),
             MockVMValidator,
         );
@@ -94,7 +100,7 @@ impl MockSharedMempool {
     ) -> Self {
         let handle = Handle::current();
         let (ac_client, mempool, quorum_store_sender, mempool_notifier) =
-            Self::start(&handle, db, validator);
+            Self::start(&handle, &Self::default_config(), db, validator);
         Self {
             _runtime: None,
             _handle: Some(handle),
@@ -105,8 +111,16 @@ impl MockSharedMempool {
         }
     }
 
+    /// Returns the node config used by the mock shared mempool by default.
+    pub fn default_config() -> NodeConfig {
+        let mut config = NodeConfig::generate_random_config();
+        config.validator_network = Some(NetworkConfig::network_with_id(NetworkId::Validator));
+        config
+    }
+
     pub fn start<V: TransactionValidation + 'static>(
         handle: &Handle,
+        config: &NodeConfig,
         db: &DbReaderWriter,
         validator: V,
     ) -> (
@@ -115,10 +129,7 @@ impl MockSharedMempool {
         mpsc::Sender<QuorumStoreRequest>,
         MempoolNotifier,
     ) {
-        let mut config = NodeConfig::generate_random_config();
-        config.validator_network = Some(NetworkConfig::network_with_id(NetworkId::Validator));
-
-        let mempool = Arc::new(Mutex::new(CoreMempool::new(&config)));
+        let mempool = Arc::new(Mutex::new(CoreMempool::new(config)));
         let (network_reqs_tx, _network_reqs_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
         let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
         let (_network_notifs_tx, network_notifs_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
@@ -157,7 +168,7 @@ impl MockSharedMempool {
 
         start_shared_mempool(
             handle,
-            &config,
+            config,
             mempool.clone(),
             network_client,
             network_service_events,
@@ -169,6 +180,7 @@ impl MockSharedMempool {
             Arc::new(RwLock::new(validator)),
             vec![],
             peers_and_metadata,
+            ReloadableFilter::new(config.mempool.transaction_filter.clone()),
         );
 
         (ac_client, mempool, quorum_store_sender, mempool_notifier)
@@ -10,7 +10,7 @@ use crate::{
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use aptos_config::{
-    config::{Identity, NodeConfig, PeerRole, RoleType},
+    config::{transaction_filter_type::ReloadableFilter, Identity, NodeConfig, PeerRole, RoleType},
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_crypto::{x25519::PrivateKey, Uniform};
@@ -602,6 +602,7 @@ fn start_node_mempool(
         Arc::new(RwLock::new(MockVMValidator)),
         vec![sender],
         peers_and_metadata,
+        ReloadableFilter::new(config.mempool.transaction_filter.clone()),
     );
 
     (mempool, runtime, subscriber)
@@ -3,17 +3,31 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::sender_bucket,
+    core_mempool::{sender_bucket, CoreMempool, TimelineState},
     mocks::MockSharedMempool,
     network::BroadcastPeerPriority,
-    tests::common::{batch_add_signed_txn, TestTransaction},
+    shared_mempool::admission_quotas::AdmissionQuotas,
+    tests::common::{add_txn, batch_add_signed_txn, setup_mempool, TestTransaction},
     QuorumStoreRequest,
 };
-use aptos_config::config::MempoolConfig;
+use aptos_config::{
+    config::{AdmissionQuotaConfig, MempoolConfig},
+    network_id::{NetworkId, PeerNetworkId},
+};
 use aptos_consensus_types::common::RejectedTransactionSummary;
 use aptos_mempool_notifications::MempoolNotificationSender;
-use aptos_types::{transaction::Transaction, vm_status::DiscardedVMStatus};
+use aptos_types::{
+    mempool_status::MempoolStatusCode, transaction::Transaction, vm_status::DiscardedVMStatus,
+    PeerId,
+};
+use aptos_vm_validator::mocks::mock_vm_validator::{
+    SEQ_NUMBER_TOO_OLD_TEST_ADD, TXN_EXPIRATION_TIME_TEST_ADD,
+};
 use futures::{channel::oneshot, sink::SinkExt};
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::Duration,
+};
 use tokio::time::timeout;
 
 #[tokio::test]
@@ -152,3 +166,103 @@ async fn test_mempool_notify_committed_txns() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_journal_replay_drops_invalid_txns() {
+    let mut config = MockSharedMempool::default_config();
+    config.mempool.enable_journal = true;
+
+    // Journal a valid, an expired and a stale transaction
+    let valid_txn = TestTransaction::new(1, 0, 1).make_signed_transaction();
+    let expired_txn = TestTransaction::new_with_address(TXN_EXPIRATION_TIME_TEST_ADD, 0, 1)
+        .make_signed_transaction();
+    let stale_txn = TestTransaction::new_with_address(SEQ_NUMBER_TOO_OLD_TEST_ADD, 0, 1)
+        .make_signed_transaction();
+    {
+        let mut pool = CoreMempool::new(&config);
+        for txn in [&valid_txn, &expired_txn, &stale_txn] {
+            let status = pool.add_txn(
+                txn.clone(),
+                txn.gas_unit_price(),
+                0,
+                TimelineState::NotReady,
+                true,
+                None,
+                Some(BroadcastPeerPriority::Primary),
+            );
+            assert_eq!(status.code, MempoolStatusCode::Accepted);
+        }
+    }
+
+    // Only the valid transaction is re-inserted on replay
+    let smp = MockSharedMempool::new_with_config(config);
+    timeout(Duration::from_secs(10), async {
+        while smp.get_txns(10).is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Timed out waiting for the journal to be replayed");
+    assert_eq!(smp.get_txns(10), vec![valid_txn]);
+}
+
+#[test]
+fn test_sender_quota() {
+    let (mut mempool, _) = setup_mempool();
+    let quotas = AdmissionQuotas::new(&AdmissionQuotaConfig {
+        max_pending_txns_per_sender: Some(2),
+        ..Default::default()
+    });
+    add_txn(&mut mempool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut mempool, TestTransaction::new(0, 1, 1)).unwrap();
+
+    // A new sequence number exceeds the quota
+    let txn = TestTransaction::new(0, 2, 1).make_signed_transaction();
+    let status = quotas.check_sender_quota(&mempool, &txn).unwrap();
+    assert_eq!(status.code, MempoolStatusCode::SenderQuotaExceeded);
+
+    // Replacing a pending transaction and other senders are unaffected
+    let txn = TestTransaction::new(0, 1, 10).make_signed_transaction();
+    assert!(quotas.check_sender_quota(&mempool, &txn).is_none());
+    let txn = TestTransaction::new(1, 0, 1).make_signed_transaction();
+    assert!(quotas.check_sender_quota(&mempool, &txn).is_none());
+}
+
+#[test]
+fn test_client_ip_rate_limit() {
+    let quotas = AdmissionQuotas::new(&AdmissionQuotaConfig {
+        client_ip_txns_per_sec: Some(1),
+        client_ip_burst_size: 2,
+        ..Default::default()
+    });
+    let client_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    // The burst is allowed, after which the client is rate limited
+    for _ in 0..2 {
+        assert!(quotas.check_client_ip(Some(client_ip)).is_none());
+    }
+    let status = quotas.check_client_ip(Some(client_ip)).unwrap();
+    assert_eq!(status.code, MempoolStatusCode::ClientRateLimited);
+
+    // Other clients and submissions without a client IP are unaffected
+    let other_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+    assert!(quotas.check_client_ip(Some(other_ip)).is_none());
+    assert!(quotas.check_client_ip(None).is_none());
+}
+
+#[test]
+fn test_peer_broadcast_rate_limit() {
+    let quotas = AdmissionQuotas::new(&AdmissionQuotaConfig {
+        peer_broadcast_txns_per_sec: Some(1),
+        peer_broadcast_burst_size: 10,
+        ..Default::default()
+    });
+    let peer = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    assert_eq!(quotas.num_broadcast_txns_allowed(peer, 6), 6);
+    assert_eq!(quotas.num_broadcast_txns_allowed(peer, 6), 4);
+    assert_eq!(quotas.num_broadcast_txns_allowed(peer, 6), 0);
+
+    // Without a configured rate, all broadcast transactions are allowed
+    let quotas = AdmissionQuotas::new(&AdmissionQuotaConfig::default());
+    assert_eq!(quotas.num_broadcast_txns_allowed(peer, 100_000), 100_000);
+}
@@ -13,7 +13,7 @@ use crate::{
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use aptos_config::{
-    config::NodeConfig,
+    config::{transaction_filter_type::ReloadableFilter, NodeConfig},
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_event_notifications::{ReconfigNotification, ReconfigNotificationListener};
@@ -152,7 +152,7 @@ impl MempoolNode {
             let (sender, receiver) = oneshot::channel();
 
             self.mempool_client_sender
-                .send(MempoolClientRequest::SubmitTransaction(txn, sender))
+                .send(MempoolClientRequest::SubmitTransaction(txn, None, sender))
                 .await
                 .unwrap();
             let status = receiver.await.unwrap().unwrap();
@@ -662,6 +662,7 @@ fn setup_mempool(
         vm_validator,
         vec![sender],
         peers_and_metadata,
+        ReloadableFilter::new(config.mempool.transaction_filter.clone()),
     );
 
     (
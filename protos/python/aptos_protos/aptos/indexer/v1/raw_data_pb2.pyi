@@ -28,32 +28,53 @@ class TransactionsInStorage(_message.Message):
     ) -> None: ...
 
 class GetTransactionsRequest(_message.Message):
-    __slots__ = ["starting_version", "transactions_count", "batch_size"]
+    __slots__ = [
+        "starting_version",
+        "transactions_count",
+        "batch_size",
+        "transaction_filter",
+    ]
     STARTING_VERSION_FIELD_NUMBER: _ClassVar[int]
     TRANSACTIONS_COUNT_FIELD_NUMBER: _ClassVar[int]
     BATCH_SIZE_FIELD_NUMBER: _ClassVar[int]
+    TRANSACTION_FILTER_FIELD_NUMBER: _ClassVar[int]
     starting_version: int
     transactions_count: int
     batch_size: int
+    transaction_filter: str
     def __init__(
         self,
         starting_version: _Optional[int] = ...,
         transactions_count: _Optional[int] = ...,
         batch_size: _Optional[int] = ...,
+        transaction_filter: _Optional[str] = ...,
     ) -> None: ...
 
 class TransactionsResponse(_message.Message):
-    __slots__ = ["transactions", "chain_id"]
+    __slots__ = ["transactions", "chain_id", "processed_range"]
     TRANSACTIONS_FIELD_NUMBER: _ClassVar[int]
     CHAIN_ID_FIELD_NUMBER: _ClassVar[int]
+    PROCESSED_RANGE_FIELD_NUMBER: _ClassVar[int]
     transactions: _containers.RepeatedCompositeFieldContainer[
         _transaction_pb2.Transaction
     ]
     chain_id: int
+    processed_range: ProcessedRange
     def __init__(
         self,
         transactions: _Optional[
             _Iterable[_Union[_transaction_pb2.Transaction, _Mapping]]
         ] = ...,
         chain_id: _Optional[int] = ...,
+        processed_range: _Optional[_Union[ProcessedRange, _Mapping]] = ...,
+    ) -> None: ...
+
+class ProcessedRange(_message.Message):
+    __slots__ = ["first_version", "last_version"]
+    FIRST_VERSION_FIELD_NUMBER: _ClassVar[int]
+    LAST_VERSION_FIELD_NUMBER: _ClassVar[int]
+    first_version: int
+    last_version: int
+    def __init__(
+        self, first_version: _Optional[int] = ..., last_version: _Optional[int] = ...
     ) -> None: ...
@@ -38,7 +38,14 @@ export interface GetTransactionsRequest {
    * Optional; number of transactions in each `TransactionsResponse` for current stream.
    * If not present, default to 1000. If larger than 1000, request will be rejected.
    */
-  batchSize?: bigint | undefined;
+  batchSize?:
+    | bigint
+    | undefined;
+  /**
+   * Optional; if present, only the transactions that match the filter are returned.
+   * The filter is a JSON-serialized `BooleanTransactionFilter` (see the `aptos-transaction-filter` crate).
+   */
+  transactionFilter?: string | undefined;
 }
 
 /** TransactionsResponse is a batch of transactions. */
@@ -48,7 +55,25 @@ export interface TransactionsResponse {
     | Transaction[]
     | undefined;
   /** Required; chain id. */
-  chainId?: bigint | undefined;
+  chainId?:
+    | bigint
+    | undefined;
+  /**
+   * Optional; the range of versions processed for this response. When a transaction filter is used,
+   * this includes the versions of the transactions that were filtered out, i.e., a response may
+   * contain no transactions and only mark the processed range.
+   */
+  processedRange?: ProcessedRange | undefined;
+}
+
+/** ProcessedRange is a range of versions that has been processed (inclusive). */
+export interface ProcessedRange {
+  /** Required; the first version of the range. */
+  firstVersion?:
+    | bigint
+    | undefined;
+  /** Required; the last version of the range. */
+  lastVersion?: bigint | undefined;
 }
 
 function createBaseTransactionsInStorage(): TransactionsInStorage {
@@ -167,7 +192,12 @@ export const TransactionsInStorage = {
 };
 
 function createBaseGetTransactionsRequest(): GetTransactionsRequest {
-  return { startingVersion: undefined, transactionsCount: undefined, batchSize: undefined };
+  return {
+    startingVersion: undefined,
+    transactionsCount: undefined,
+    batchSize: undefined,
+    transactionFilter: undefined,
+  };
 }
 
 export const GetTransactionsRequest = {
@@ -190,6 +220,9 @@ export const GetTransactionsRequest = {
       }
       writer.uint32(24).uint64(message.batchSize.toString());
     }
+    if (message.transactionFilter !== undefined) {
+      writer.uint32(34).string(message.transactionFilter);
+    }
     return writer;
   },
 
@@ -221,6 +254,13 @@ export const GetTransactionsRequest = {
 
           message.batchSize = longToBigint(reader.uint64() as Long);
           continue;
+        case 4:
+          if (tag !== 34) {
+            break;
+          }
+
+          message.transactionFilter = reader.string();
+          continue;
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -269,6 +309,7 @@ export const GetTransactionsRequest = {
       startingVersion: isSet(object.startingVersion) ? BigInt(object.startingVersion) : undefined,
       transactionsCount: isSet(object.transactionsCount) ? BigInt(object.transactionsCount) : undefined,
       batchSize: isSet(object.batchSize) ? BigInt(object.batchSize) : undefined,
+      transactionFilter: isSet(object.transactionFilter) ? globalThis.String(object.transactionFilter) : undefined,
     };
   },
 
@@ -283,6 +324,9 @@ export const GetTransactionsRequest = {
     if (message.batchSize !== undefined) {
       obj.batchSize = message.batchSize.toString();
     }
+    if (message.transactionFilter !== undefined) {
+      obj.transactionFilter = message.transactionFilter;
+    }
     return obj;
   },
 
@@ -294,12 +338,13 @@ export const GetTransactionsRequest = {
     message.startingVersion = object.startingVersion ?? undefined;
     message.transactionsCount = object.transactionsCount ?? undefined;
     message.batchSize = object.batchSize ?? undefined;
+    message.transactionFilter = object.transactionFilter ?? undefined;
     return message;
   },
 };
 
 function createBaseTransactionsResponse(): TransactionsResponse {
-  return { transactions: [], chainId: undefined };
+  return { transactions: [], chainId: undefined, processedRange: undefined };
 }
 
 export const TransactionsResponse = {
@@ -315,6 +360,9 @@ export const TransactionsResponse = {
       }
       writer.uint32(16).uint64(message.chainId.toString());
     }
+    if (message.processedRange !== undefined) {
+      ProcessedRange.encode(message.processedRange, writer.uint32(26).fork()).ldelim();
+    }
     return writer;
   },
 
@@ -339,6 +387,13 @@ export const TransactionsResponse = {
 
           message.chainId = longToBigint(reader.uint64() as Long);
           continue;
+        case 3:
+          if (tag !== 26) {
+            break;
+          }
+
+          message.processedRange = ProcessedRange.decode(reader, reader.uint32());
+          continue;
       }
       if ((tag & 7) === 4 || tag === 0) {
         break;
@@ -388,6 +443,7 @@ export const TransactionsResponse = {
         ? object.transactions.map((e: any) => Transaction.fromJSON(e))
         : [],
       chainId: isSet(object.chainId) ? BigInt(object.chainId) : undefined,
+      processedRange: isSet(object.processedRange) ? ProcessedRange.fromJSON(object.processedRange) : undefined,
     };
   },
 
@@ -399,6 +455,9 @@ export const TransactionsResponse = {
     if (message.chainId !== undefined) {
       obj.chainId = message.chainId.toString();
     }
+    if (message.processedRange !== undefined) {
+      obj.processedRange = ProcessedRange.toJSON(message.processedRange);
+    }
     return obj;
   },
 
@@ -409,13 +468,131 @@ export const TransactionsResponse = {
     const message = createBaseTransactionsResponse();
     message.transactions = object.transactions?.map((e) => Transaction.fromPartial(e)) || [];
     message.chainId = object.chainId ?? undefined;
+    message.processedRange = (object.processedRange !== undefined && object.processedRange !== null)
+      ? ProcessedRange.fromPartial(object.processedRange)
+      : undefined;
+    return message;
+  },
+};
+
+function createBaseProcessedRange(): ProcessedRange {
+  return { firstVersion: undefined, lastVersion: undefined };
+}
+
+export const ProcessedRange = {
+  encode(message: ProcessedRange, writer: _m0.Writer = _m0.Writer.create()): _m0.Writer {
+    if (message.firstVersion !== undefined) {
+      if (BigInt.asUintN(64, message.firstVersion) !== message.firstVersion) {
+        throw new globalThis.Error("value provided for field message.firstVersion of type uint64 too large");
+      }
+      writer.uint32(8).uint64(message.firstVersion.toString());
+    }
+    if (message.lastVersion !== undefined) {
+      if (BigInt.asUintN(64, message.lastVersion) !== message.lastVersion) {
+        throw new globalThis.Error("value provided for field message.lastVersion of type uint64 too large");
+      }
+      writer.uint32(16).uint64(message.lastVersion.toString());
+    }
+    return writer;
+  },
+
+  decode(input: _m0.Reader | Uint8Array, length?: number): ProcessedRange {
+    const reader = input instanceof _m0.Reader ? input : _m0.Reader.create(input);
+    let end = length === undefined ? reader.len : reader.pos + length;
+    const message = createBaseProcessedRange();
+    while (reader.pos < end) {
+      const tag = reader.uint32();
+      switch (tag >>> 3) {
+        case 1:
+          if (tag !== 8) {
+            break;
+          }
+
+          message.firstVersion = longToBigint(reader.uint64() as Long);
+          continue;
+        case 2:
+          if (tag !== 16) {
+            break;
+          }
+
+          message.lastVersion = longToBigint(reader.uint64() as Long);
+          continue;
+      }
+      if ((tag & 7) === 4 || tag === 0) {
+        break;
+      }
+      reader.skipType(tag & 7);
+    }
+    return message;
+  },
+
+  // encodeTransform encodes a source of message objects.
+  // Transform<ProcessedRange, Uint8Array>
+  async *encodeTransform(
+    source: AsyncIterable<ProcessedRange | ProcessedRange[]> | Iterable<ProcessedRange | ProcessedRange[]>,
+  ): AsyncIterable<Uint8Array> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [ProcessedRange.encode(p).finish()];
+        }
+      } else {
+        yield* [ProcessedRange.encode(pkt as any).finish()];
+      }
+    }
+  },
+
+  // decodeTransform decodes a source of encoded messages.
+  // Transform<Uint8Array, ProcessedRange>
+  async *decodeTransform(
+    source: AsyncIterable<Uint8Array | Uint8Array[]> | Iterable<Uint8Array | Uint8Array[]>,
+  ): AsyncIterable<ProcessedRange> {
+    for await (const pkt of source) {
+      if (globalThis.Array.isArray(pkt)) {
+        for (const p of (pkt as any)) {
+          yield* [ProcessedRange.decode(p)];
+        }
+      } else {
+        yield* [ProcessedRange.decode(pkt as any)];
+      }
+    }
+  },
+
+  fromJSON(object: any): ProcessedRange {
+    return {
+      firstVersion: isSet(object.firstVersion) ? BigInt(object.firstVersion) : undefined,
+      lastVersion: isSet(object.lastVersion) ? BigInt(object.lastVersion) : undefined,
+    };
+  },
+
+  toJSON(message: ProcessedRange): unknown {
+    const obj: any = {};
+    if (message.firstVersion !== undefined) {
+      obj.firstVersion = message.firstVersion.toString();
+    }
+    if (message.lastVersion !== undefined) {
+      obj.lastVersion = message.lastVersion.toString();
+    }
+    return obj;
+  },
+
+  create(base?: DeepPartial<ProcessedRange>): ProcessedRange {
+    return ProcessedRange.fromPartial(base ?? {});
+  },
+  fromPartial(object: DeepPartial<ProcessedRange>): ProcessedRange {
+    const message = createBaseProcessedRange();
+    message.firstVersion = object.firstVersion ?? undefined;
+    message.lastVersion = object.lastVersion ?? undefined;
     return message;
   },
 };
 
 export type RawDataService = typeof RawDataService;
 export const RawDataService = {
-  /** Get transactions batch without any filtering from starting version and end if transaction count is present. */
+  /**
+   * Get transactions batch from starting version and end if transaction count is present.
+   * If a transaction filter is present, only the matching transactions are returned.
+   */
   getTransactions: {
     path: "/aptos.indexer.v1.RawData/GetTransactions",
     requestStream: false,
@@ -428,12 +605,18 @@ export const RawDataService = {
 } as const;
 
 export interface RawDataServer extends UntypedServiceImplementation {
-  /** Get transactions batch without any filtering from starting version and end if transaction count is present. */
+  /**
+   * Get transactions batch from starting version and end if transaction count is present.
+   * If a transaction filter is present, only the matching transactions are returned.
+   */
   getTransactions: handleServerStreamingCall<GetTransactionsRequest, TransactionsResponse>;
 }
 
 export interface RawDataClient extends Client {
-  /** Get transactions batch without any filtering from starting version and end if transaction count is present. */
+  /**
+   * Get transactions batch from starting version and end if transaction count is present.
+   * If a transaction filter is present, only the matching transactions are returned.
+   */
   getTransactions(
     request: GetTransactionsRequest,
     options?: Partial<CallOptions>,
@@ -6,7 +6,7 @@ use crate::{
     driver::DriverConfiguration,
     error::Error,
     logging::{LogEntry, LogSchema},
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{MetadataStorageInterface, TrackedState},
     metrics,
     metrics::ExecutingComponent,
     storage_synchronizer::{NotificationMetadata, StorageSynchronizerInterface},
@@ -344,7 +344,7 @@ impl<
             .expect("Unable to fetch latest epoch state!");
         let verified_epoch_states = VerifiedEpochStates::new(latest_epoch_state);
 
-        Self {
+        let bootstrapper = Self {
             state_value_syncer: StateValueSyncer::new(),
             active_data_stream: None,
             bootstrap_notifier_channel: None,
@@ -357,7 +357,65 @@ impl<
             storage,
             storage_synchronizer,
             verified_epoch_states,
+        };
+
+        // Verify the tracked state (if the node is partially syncing state)
+        bootstrapper
+            .verify_tracked_state()
+            .expect("Unable to verify the tracked state for partial state sync!");
+
+        bootstrapper
+    }
+
+    /// Verifies that the state tracked by the node (when partial state sync is
+    /// enabled) matches the state previously tracked by the node. If the node
+    /// has never tracked any state, the tracked state is recorded, but only if
+    /// the node has not yet synced any state beyond genesis.
+    fn verify_tracked_state(&self) -> Result<(), Error> {
+        let partial_state_sync_config = &self.driver_configuration.config.partial_state_sync;
+        if !partial_state_sync_config.enabled {
+            return Ok(());
         }
+
+        let tracked_state = TrackedState::new(partial_state_sync_config);
+        match self.metadata_storage.get_tracked_state()? {
+            Some(previously_tracked_state) => {
+                if previously_tracked_state != tracked_state {
+                    return Err(Error::UnexpectedError(format!(
+                        "The tracked state does not match the state previously tracked by the node! \
+                        To change the tracked state, delete your storage and restart your node. \
+                        Tracked state: {:?}, previously tracked state: {:?}",
+                        tracked_state, previously_tracked_state
+                    )));
+                }
+            },
+            None => {
+                // The node must not have synced any state beyond genesis
+                let highest_synced_version =
+                    utils::fetch_pre_committed_version(self.storage.clone())?;
+                if highest_synced_version != GENESIS_TRANSACTION_VERSION
+                    || self
+                        .metadata_storage
+                        .previous_snapshot_sync_target()?
+                        .is_some()
+                {
+                    return Err(Error::UnexpectedError(format!(
+                        "Partial state sync is enabled, but the node has already synced the full state \
+                        (highest synced version: {:?})! To enable partial state sync, delete your \
+                        storage and restart your node.",
+                        highest_synced_version
+                    )));
+                }
+
+                self.metadata_storage.set_tracked_state(&tracked_state)?;
+                info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                    "Partial state sync is enabled. Tracking state: {:?}",
+                    tracked_state
+                )));
+            },
+        }
+
+        Ok(())
     }
 
     /// Returns the bootstrapping mode of the node
@@ -144,7 +144,7 @@ impl DriverFactory {
         // Create the storage synchronizer
         let event_subscription_service = Arc::new(Mutex::new(event_subscription_service));
         let (storage_synchronizer, _) = StorageSynchronizer::new(
-            node_config.state_sync.state_sync_driver,
+            node_config.state_sync.state_sync_driver.clone(),
             chunk_executor,
             commit_notification_sender.clone(),
             error_notification_sender,
@@ -158,7 +158,7 @@ impl DriverFactory {
 
         // Create the driver configuration
         let driver_configuration = DriverConfiguration::new(
-            node_config.state_sync.state_sync_driver,
+            node_config.state_sync.state_sync_driver.clone(),
             node_config.consensus_observer,
             node_config.base.role,
             waypoint,
@@ -6,13 +6,14 @@ use crate::{
     metadata_storage::database_schema::{MetadataKey, MetadataSchema, MetadataValue},
 };
 use anyhow::{anyhow, Result};
+use aptos_config::config::PartialStateSyncConfig;
 use aptos_logger::prelude::*;
 use aptos_schemadb::{
     define_schema,
     schema::{KeyCodec, ValueCodec},
     ColumnFamilyName, Options, SchemaBatch, DB,
 };
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{account_address::AccountAddress, ledger_info::LedgerInfoWithSignatures};
 use serde::{Deserialize, Serialize};
 use std::{path::Path, sync::Arc, time::Instant};
 
@@ -47,6 +48,13 @@ pub trait MetadataStorageInterface {
         last_persisted_state_value_index: u64,
         snapshot_sync_completed: bool,
     ) -> Result<(), Error>;
+
+    /// Returns the state tracked by the node if the node has previously
+    /// started a partial state sync. Otherwise, None is returned.
+    fn get_tracked_state(&self) -> Result<Option<TrackedState>, Error>;
+
+    /// Records the state tracked by the node for partial state sync
+    fn set_tracked_state(&self, tracked_state: &TrackedState) -> Result<(), Error>;
 }
 
 /// The name of the state sync db file
@@ -93,23 +101,32 @@ impl PersistentMetadataStorage {
         Self { database }
     }
 
+    /// Returns the metadata value for the specified key. Returns None if no value is found.
+    fn get_metadata_value(
+        &self,
+        metadata_key: &MetadataKey,
+    ) -> Result<Option<MetadataValue>, Error> {
+        self.database
+            .get::<MetadataSchema>(metadata_key)
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to read metadata value for key: {:?}. Error: {:?}",
+                    metadata_key, error
+                ))
+            })
+    }
+
     /// Returns the existing snapshot sync progress. Returns None if no progress is found.
     fn get_snapshot_progress(&self) -> Result<Option<StateSnapshotProgress>, Error> {
         let metadata_key = MetadataKey::StateSnapshotSync;
-        let maybe_metadata_value =
-            self.database
-                .get::<MetadataSchema>(&metadata_key)
-                .map_err(|error| {
-                    Error::StorageError(format!(
-                        "Failed to read metadata value for key: {:?}. Error: {:?}",
-                        metadata_key, error
-                    ))
-                })?;
-        match maybe_metadata_value {
-            Some(metadata_value) => {
-                let MetadataValue::StateSnapshotSync(snapshot_progress) = metadata_value;
+        match self.get_metadata_value(&metadata_key)? {
+            Some(MetadataValue::StateSnapshotSync(snapshot_progress)) => {
                 Ok(Some(snapshot_progress))
             },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
             None => Ok(None),
         }
     }
@@ -224,6 +241,24 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
         // Insert the new key/value pair
         self.commit_key_value(metadata_key, metadata_value)
     }
+
+    fn get_tracked_state(&self) -> Result<Option<TrackedState>, Error> {
+        let metadata_key = MetadataKey::TrackedState;
+        match self.get_metadata_value(&metadata_key)? {
+            Some(MetadataValue::TrackedState(tracked_state)) => Ok(Some(tracked_state)),
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    fn set_tracked_state(&self, tracked_state: &TrackedState) -> Result<(), Error> {
+        let metadata_key = MetadataKey::TrackedState;
+        let metadata_value = MetadataValue::TrackedState(tracked_state.clone());
+        self.commit_key_value(metadata_key, metadata_value)
+    }
 }
 
 /// A simple struct for recording the progress of a state snapshot sync
@@ -234,6 +269,32 @@ pub struct StateSnapshotProgress {
     pub snapshot_sync_completed: bool,
 }
 
+/// The state tracked by a node that is partially syncing state
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrackedState {
+    pub tracked_accounts: Vec<AccountAddress>,
+    pub tracked_resource_types: Vec<String>,
+}
+
+impl TrackedState {
+    /// Creates the tracked state from the given config (the
+    /// accounts and resource types are sorted and deduplicated).
+    pub fn new(partial_state_sync_config: &PartialStateSyncConfig) -> Self {
+        let mut tracked_accounts = partial_state_sync_config.tracked_accounts.clone();
+        tracked_accounts.sort();
+        tracked_accounts.dedup();
+
+        let mut tracked_resource_types = partial_state_sync_config.tracked_resource_types.clone();
+        tracked_resource_types.sort();
+        tracked_resource_types.dedup();
+
+        Self {
+            tracked_accounts,
+            tracked_resource_types,
+        }
+    }
+}
+
 /// The raw schema format used by the database
 pub mod database_schema {
     use super::*;
@@ -252,6 +313,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataKey {
         StateSnapshotSync, // A state snapshot sync that was started
+        TrackedState,      // The state tracked by a partial state sync
     }
 
     /// A metadata value that can be inserted into the database
@@ -259,6 +321,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataValue {
         StateSnapshotSync(StateSnapshotProgress), // A state snapshot sync progress marker
+        TrackedState(TrackedState),               // The state tracked by a partial state sync
     }
 
     impl KeyCodec<MetadataSchema> for MetadataKey {
@@ -5,6 +5,7 @@ use crate::{
     bootstrapper::{Bootstrapper, GENESIS_TRANSACTION_VERSION},
     driver::DriverConfiguration,
     error::Error,
+    metadata_storage::TrackedState,
     tests::{
         mocks::{
             create_mock_db_reader, create_mock_streaming_client, create_ready_storage_synchronizer,
@@ -20,7 +21,7 @@ use crate::{
     },
     utils::OutputFallbackHandler,
 };
-use aptos_config::config::BootstrappingMode;
+use aptos_config::config::{BootstrappingMode, PartialStateSyncConfig};
 use aptos_data_client::global_summary::GlobalDataSummary;
 use aptos_data_streaming_service::{
     data_notification::{DataNotification, DataPayload, NotificationId},
@@ -28,6 +29,7 @@ use aptos_data_streaming_service::{
 };
 use aptos_time_service::TimeService;
 use aptos_types::{
+    account_address::AccountAddress,
     transaction::{TransactionOutputListWithProof, Version},
     waypoint::Waypoint,
 };
@@ -1583,6 +1585,95 @@ async fn test_waypoint_satisfiable() {
     assert_matches!(error, Error::UnsatisfiableWaypoint(_));
 }
 
+#[tokio::test]
+async fn test_partial_state_sync_tracked_state() {
+    // Create a driver configuration with partial state sync enabled
+    let mut driver_configuration = create_full_node_driver_configuration();
+    let partial_state_sync_config = create_partial_state_sync_config();
+    driver_configuration.config.partial_state_sync = partial_state_sync_config.clone();
+
+    // Create the mock metadata storage and expect the tracked state to be recorded
+    let tracked_state = TrackedState::new(&partial_state_sync_config);
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_get_tracked_state()
+        .return_once(|| Ok(None));
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .return_once(|| Ok(None));
+    metadata_storage
+        .expect_set_tracked_state()
+        .with(eq(tracked_state))
+        .times(1)
+        .return_once(|_| Ok(()));
+
+    // Create the bootstrapper (at genesis) and verify it's not yet bootstrapped
+    let bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        create_mock_streaming_client(),
+        metadata_storage,
+        None,
+        GENESIS_TRANSACTION_VERSION,
+        true,
+    );
+    assert!(!bootstrapper.is_bootstrapped());
+}
+
+#[tokio::test]
+#[should_panic(expected = "The tracked state does not match the state previously tracked")]
+async fn test_partial_state_sync_tracked_state_mismatch() {
+    // Create a driver configuration with partial state sync enabled
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.partial_state_sync = create_partial_state_sync_config();
+
+    // Create the mock metadata storage with a different tracked state
+    let previously_tracked_state = TrackedState {
+        tracked_accounts: vec![AccountAddress::random()],
+        tracked_resource_types: vec![],
+    };
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_get_tracked_state()
+        .return_once(move || Ok(Some(previously_tracked_state)));
+
+    // Create the bootstrapper and verify it panics
+    create_bootstrapper_with_storage(
+        driver_configuration,
+        create_mock_streaming_client(),
+        metadata_storage,
+        None,
+        GENESIS_TRANSACTION_VERSION,
+        true,
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "Partial state sync is enabled, but the node has already synced")]
+async fn test_partial_state_sync_already_synced() {
+    // Create a driver configuration with partial state sync enabled
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.partial_state_sync = create_partial_state_sync_config();
+
+    // Create the mock metadata storage without a tracked state
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_get_tracked_state()
+        .return_once(|| Ok(None));
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .return_once(|| Ok(None));
+
+    // Create the bootstrapper (beyond genesis) and verify it panics
+    create_bootstrapper_with_storage(
+        driver_configuration,
+        create_mock_streaming_client(),
+        metadata_storage,
+        None,
+        100,
+        true,
+    );
+}
+
 /// Creates a bootstrapper for testing
 fn create_bootstrapper(
     driver_configuration: DriverConfiguration,
@@ -1772,3 +1863,12 @@ async fn handle_storage_synchronizer_error(
         .await
         .unwrap();
 }
+
+/// Creates a partial state sync config that tracks a single account
+fn create_partial_state_sync_config() -> PartialStateSyncConfig {
+    PartialStateSyncConfig {
+        enabled: true,
+        tracked_accounts: vec![AccountAddress::random()],
+        tracked_resource_types: vec![],
+    }
+}
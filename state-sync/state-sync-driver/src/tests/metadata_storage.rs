@@ -4,12 +4,14 @@
 use crate::{
     metadata_storage::{
         database_schema::{MetadataKey, MetadataSchema, MetadataValue},
-        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotProgress,
+        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotProgress, TrackedState,
     },
     tests::utils::{create_epoch_ending_ledger_info, create_ledger_info_at_version},
 };
+use aptos_config::config::PartialStateSyncConfig;
 use aptos_schemadb::schema::fuzzing::assert_encode_decode;
 use aptos_temppath::TempPath;
+use aptos_types::account_address::AccountAddress;
 use claims::{assert_err, assert_none};
 
 #[test]
@@ -99,6 +101,13 @@ fn test_metadata_schema_encode_decode() {
             snapshot_sync_completed: false,
         }),
     );
+    assert_encode_decode::<MetadataSchema>(
+        &MetadataKey::TrackedState,
+        &MetadataValue::TrackedState(TrackedState {
+            tracked_accounts: vec![AccountAddress::random()],
+            tracked_resource_types: vec!["0x1::account::Account".into()],
+        }),
+    );
 }
 
 #[test]
@@ -167,3 +176,40 @@ fn test_writes_to_different_targets() {
         .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
         .unwrap_err();
 }
+
+#[test]
+fn test_tracked_state() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify the storage is empty
+    assert_none!(metadata_storage.get_tracked_state().unwrap());
+
+    // Write the tracked state (with unsorted and duplicate accounts)
+    let account_1 = AccountAddress::new([1; AccountAddress::LENGTH]);
+    let account_2 = AccountAddress::new([2; AccountAddress::LENGTH]);
+    let tracked_state = TrackedState::new(&PartialStateSyncConfig {
+        enabled: true,
+        tracked_accounts: vec![account_2, account_1, account_2],
+        tracked_resource_types: vec!["0x1::account::Account".into()],
+    });
+    metadata_storage.set_tracked_state(&tracked_state).unwrap();
+
+    // Drop the handle to the storage (mimic a reboot)
+    drop(metadata_storage);
+
+    // Create another storage (it should reopen the existing file) and verify the tracked state
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    let expected_tracked_state = TrackedState {
+        tracked_accounts: vec![account_1, account_2],
+        tracked_resource_types: vec!["0x1::account::Account".into()],
+    };
+    assert_eq!(
+        Some(expected_tracked_state),
+        metadata_storage.get_tracked_state().unwrap()
+    );
+
+    // Verify the snapshot sync progress is unaffected
+    assert_none!(metadata_storage.previous_snapshot_sync_target().unwrap());
+}
@@ -3,7 +3,7 @@
 
 use crate::{
     error::Error,
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{MetadataStorageInterface, TrackedState},
     storage_synchronizer::{NotificationMetadata, StorageSynchronizerInterface},
     tests::utils::{create_empty_epoch_state, create_epoch_ending_ledger_info},
 };
@@ -354,6 +354,10 @@ mock! {
             last_persisted_state_value_index: u64,
             snapshot_sync_completed: bool,
         ) -> Result<(), Error>;
+
+        fn get_tracked_state(&self) -> Result<Option<TrackedState>, Error>;
+
+        fn set_tracked_state(&self, tracked_state: &TrackedState) -> Result<(), Error>;
     }
 
     impl Clone for MetadataStorage {
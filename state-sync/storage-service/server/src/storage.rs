@@ -3,16 +3,24 @@
 
 use crate::{error::Error, metrics::increment_network_frame_overflow};
 use aptos_config::config::StorageServiceConfig;
+use aptos_crypto::HashValue;
 use aptos_logger::debug;
 use aptos_storage_interface::{AptosDbError, DbReader, Result as StorageResult};
 use aptos_storage_service_types::responses::{
     CompleteDataRange, DataResponse, DataSummary, TransactionOrOutputListWithProof,
+    BASE_PROTOCOL_VERSION, STATE_VALUES_BY_PREFIX_PROTOCOL_VERSION,
 };
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
-    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+    proof::SparseMerkleProof,
+    state_store::{
+        state_key::{prefix::StateKeyPrefix, StateKey},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesByPrefixWithProof},
+    },
+    transaction::{
+        TransactionListWithProof, TransactionOutputListWithProof, TransactionWithProof, Version,
+    },
 };
 use serde::Serialize;
 use std::{cmp::min, sync::Arc};
@@ -23,6 +31,10 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
     /// Returns a data summary of the underlying storage state.
     fn get_data_summary(&self) -> aptos_storage_service_types::Result<DataSummary, Error>;
 
+    /// Returns the protocol version supported by the storage server. This
+    /// depends on the optional data requests enabled by the server config.
+    fn get_server_protocol_version(&self) -> u64;
+
     /// Returns a list of transactions with a proof relative to the
     /// `proof_version`. The transaction list is expected to start at
     /// `start_version` and end at `end_version` (inclusive). In some cases,
@@ -89,6 +101,21 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error>;
+
+    /// Returns the state values under the specified `key_prefix` at the
+    /// latest state snapshot at or before the given version (each with a
+    /// proof), starting at `start_key` (inclusive) if specified. The snapshot
+    /// transaction info is proven relative to the `proof_version`. In some
+    /// cases, less state values may be returned (e.g., due to network or
+    /// chunk limits), in which case the response contains the key to
+    /// continue from.
+    fn get_state_values_by_prefix_with_proof(
+        &self,
+        proof_version: u64,
+        version: u64,
+        key_prefix: &StateKeyPrefix,
+        start_key: Option<&StateKey>,
+    ) -> aptos_storage_service_types::Result<StateValuesByPrefixWithProof, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -109,12 +136,21 @@ impl StorageReader {
 
     /// Returns the state values range held in the database (lowest to highest).
     /// Note: it is currently assumed that if a node contains a transaction at a
-    /// version, V, the node also contains all state values at V.
+    /// version, V, the node also contains all state values at V (unless only
+    /// partial state is persisted, in which case no state values are served).
     fn fetch_state_values_range(
         &self,
         latest_version: Version,
         transactions_range: &Option<CompleteDataRange<Version>>,
     ) -> aptos_storage_service_types::Result<Option<CompleteDataRange<Version>>, Error> {
+        let partial_state_enabled = self
+            .storage
+            .is_partial_state_enabled()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+        if partial_state_enabled {
+            return Ok(None);
+        }
+
         let pruner_enabled = self
             .storage
             .is_state_merkle_pruner_enabled()
@@ -229,6 +265,14 @@ impl StorageReaderInterface for StorageReader {
         Ok(data_summary)
     }
 
+    fn get_server_protocol_version(&self) -> u64 {
+        if self.config.enable_state_values_by_prefix {
+            STATE_VALUES_BY_PREFIX_PROTOCOL_VERSION
+        } else {
+            BASE_PROTOCOL_VERSION
+        }
+    }
+
     fn get_transactions_with_proof(
         &self,
         proof_version: u64,
@@ -498,6 +542,113 @@ impl StorageReaderInterface for StorageReader {
             version, start_index, end_index
         )))
     }
+
+    fn get_state_values_by_prefix_with_proof(
+        &self,
+        proof_version: u64,
+        version: u64,
+        key_prefix: &StateKeyPrefix,
+        start_key: Option<&StateKey>,
+    ) -> aptos_storage_service_types::Result<StateValuesByPrefixWithProof, Error> {
+        // Verify that state values by prefix can be served
+        if !self.config.enable_state_values_by_prefix {
+            return Err(Error::InvalidRequest(
+                "State values by prefix are not served by this node".into(),
+            ));
+        }
+        if proof_version < version {
+            return Err(Error::InvalidRequest(format!(
+                "The proof version {} is lower than the requested version {}",
+                proof_version, version
+            )));
+        }
+
+        // Identify the latest state snapshot at or before the version (only
+        // state snapshots can be proven).
+        let (snapshot_version, _) = self
+            .storage
+            .get_state_snapshot_before(version.saturating_add(1))
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?
+            .ok_or_else(|| {
+                Error::InvalidRequest(format!(
+                    "No state snapshot exists at or before version {}",
+                    version
+                ))
+            })?;
+
+        // Fetch the transaction info at the snapshot version (with a proof)
+        let transaction_info_with_proof = self
+            .storage
+            .get_transaction_by_version(snapshot_version, proof_version, false)
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?
+            .proof;
+
+        // Fetch the state values under the prefix. We fetch one more state
+        // value than the max chunk size to identify the key to continue from.
+        let max_num_state_values = self.config.max_state_chunk_size as usize;
+        let mut state_values = self
+            .storage
+            .get_prefixed_state_value_iterator(key_prefix, start_key, snapshot_version)
+            .and_then(|state_values| {
+                state_values
+                    .take(max_num_state_values.saturating_add(1))
+                    .collect::<StorageResult<Vec<_>>>()
+            })
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+        let next_key = if state_values.len() > max_num_state_values {
+            state_values
+                .drain(max_num_state_values..)
+                .next()
+                .map(|(state_key, _)| state_key)
+        } else {
+            None
+        };
+
+        // Fetch the proof for each state value
+        let mut values = Vec::with_capacity(state_values.len());
+        for (state_key, state_value) in state_values {
+            let (_, proof) = self
+                .storage
+                .get_state_value_with_proof_by_version(&state_key, snapshot_version)
+                .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+            values.push((state_key, state_value, proof));
+        }
+
+        // Attempt to serve the request
+        let mut num_state_values_to_return = values.len();
+        loop {
+            let state_values_by_prefix_with_proof = StateValuesByPrefixWithProof {
+                version: snapshot_version,
+                values: values[..num_state_values_to_return].to_vec(),
+                next_key: values
+                    .get(num_state_values_to_return)
+                    .map(|(state_key, _, _)| state_key.clone())
+                    .or_else(|| next_key.clone()),
+                transaction_info_with_proof: transaction_info_with_proof.clone(),
+            };
+            if num_state_values_to_return <= 1 {
+                return Ok(state_values_by_prefix_with_proof); // We cannot return less than a single item
+            }
+
+            // Attempt to divide up the request if it overflows the message size
+            let (overflow_frame, num_bytes) = check_overflow_network_frame(
+                &state_values_by_prefix_with_proof,
+                self.config.max_network_chunk_bytes,
+            )?;
+            if !overflow_frame {
+                return Ok(state_values_by_prefix_with_proof);
+            } else {
+                increment_network_frame_overflow(
+                    DataResponse::StateValuesByPrefixWithProof(state_values_by_prefix_with_proof)
+                        .get_label(),
+                );
+                let new_num_state_values_to_return = num_state_values_to_return / 2;
+                debug!("The request for {:?} state values by prefix was too large (num bytes: {:?}). Retrying with {:?}.",
+                    num_state_values_to_return, num_bytes, new_num_state_values_to_return);
+                num_state_values_to_return = new_num_state_values_to_return; // Try again with half the amount of data
+            }
+        }
+    }
 }
 
 // A simple macro that wraps each storage read call with a timer
@@ -541,6 +692,8 @@ impl DbReader for TimedStorageReader {
     timed_read!(
         fn is_state_merkle_pruner_enabled(&self) -> StorageResult<bool>;
 
+        fn is_partial_state_enabled(&self) -> StorageResult<bool>;
+
         fn get_epoch_snapshot_prune_window(&self) -> StorageResult<usize>;
 
         fn get_first_txn_version(&self) -> StorageResult<Option<Version>>;
@@ -578,6 +731,31 @@ impl DbReader for TimedStorageReader {
             start_idx: usize,
             chunk_size: usize,
         ) -> StorageResult<StateValueChunkWithProof>;
+
+        fn get_state_snapshot_before(
+            &self,
+            next_version: Version,
+        ) -> StorageResult<Option<(Version, HashValue)>>;
+
+        fn get_transaction_by_version(
+            &self,
+            version: Version,
+            ledger_version: Version,
+            fetch_events: bool,
+        ) -> StorageResult<TransactionWithProof>;
+
+        fn get_prefixed_state_value_iterator(
+            &self,
+            key_prefix: &StateKeyPrefix,
+            cursor: Option<&StateKey>,
+            version: Version,
+        ) -> StorageResult<Box<dyn Iterator<Item = StorageResult<(StateKey, StateValue)>> + '_>>;
+
+        fn get_state_value_with_proof_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> StorageResult<(Option<StateValue>, SparseMerkleProof)>;
     );
 }
 
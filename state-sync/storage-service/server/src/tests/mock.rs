@@ -354,6 +354,8 @@ mock! {
         fn get_epoch_snapshot_prune_window(&self) -> aptos_storage_interface::Result<usize>;
 
         fn is_state_merkle_pruner_enabled(&self) -> aptos_storage_interface::Result<bool>;
+
+        fn is_partial_state_enabled(&self) -> aptos_storage_interface::Result<bool>;
     }
 }
 
@@ -382,6 +384,9 @@ pub fn create_mock_db_with_summary_updates(
     db_reader
         .expect_is_state_merkle_pruner_enabled()
         .returning(move || Ok(true));
+    db_reader
+        .expect_is_partial_state_enabled()
+        .returning(move || Ok(false));
 
     db_reader
 }
@@ -12,6 +12,7 @@ mod optimistic_fetch;
 mod protocol_version;
 mod request_moderator;
 mod state_values;
+mod state_values_by_prefix;
 mod storage_summary;
 mod subscribe_transaction_outputs;
 mod subscribe_transactions;
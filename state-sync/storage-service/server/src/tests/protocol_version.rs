@@ -2,33 +2,51 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::tests::{mock::MockClient, utils};
+use aptos_config::config::StorageServiceConfig;
 use aptos_storage_service_types::{
     requests::DataRequest,
-    responses::{DataResponse, ServerProtocolVersion, StorageServiceResponse},
+    responses::{
+        DataResponse, ServerProtocolVersion, StorageServiceResponse, BASE_PROTOCOL_VERSION,
+        STATE_VALUES_BY_PREFIX_PROTOCOL_VERSION,
+    },
 };
 use claims::assert_matches;
 
-// Useful test constants
-const PROTOCOL_VERSION: u64 = 1;
-
 #[tokio::test]
 async fn test_get_server_protocol_version() {
-    // Create the storage client and server
-    let (mut mock_client, service, _, _, _) = MockClient::new(None, None);
-    tokio::spawn(service.start());
+    for enable_state_values_by_prefix in [false, true] {
+        // Create the storage client and server
+        let storage_config = StorageServiceConfig {
+            enable_state_values_by_prefix,
+            ..Default::default()
+        };
+        let (mut mock_client, service, _, _, _) = MockClient::new(None, Some(storage_config));
+        tokio::spawn(service.start());
+
+        // Process a request to fetch the protocol version
+        let response = get_protocol_version(&mut mock_client, true).await;
 
-    // Process a request to fetch the protocol version
-    let response = get_protocol_version(&mut mock_client, true).await;
+        // Verify the response is correct
+        let protocol_version = if enable_state_values_by_prefix {
+            STATE_VALUES_BY_PREFIX_PROTOCOL_VERSION
+        } else {
+            BASE_PROTOCOL_VERSION
+        };
+        let expected_data_response =
+            DataResponse::ServerProtocolVersion(ServerProtocolVersion { protocol_version });
+        assert_matches!(response, StorageServiceResponse::CompressedResponse(_, _));
+        assert_eq!(
+            response.get_data_response().unwrap(),
+            expected_data_response
+        );
 
-    // Verify the response is correct
-    let expected_data_response = DataResponse::ServerProtocolVersion(ServerProtocolVersion {
-        protocol_version: PROTOCOL_VERSION,
-    });
-    assert_matches!(response, StorageServiceResponse::CompressedResponse(_, _));
-    assert_eq!(
-        response.get_data_response().unwrap(),
-        expected_data_response
-    );
+        // Verify the server only advertises state values by prefix if enabled
+        let server_protocol_version = ServerProtocolVersion::try_from(response).unwrap();
+        assert_eq!(
+            server_protocol_version.supports_state_values_by_prefix(),
+            enable_state_values_by_prefix
+        );
+    }
 }
 
 /// Sends a protocol version request and processes the response
@@ -0,0 +1,240 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    error::Error,
+    storage::{StorageReader, StorageReaderInterface},
+};
+use aptos_config::config::StorageServiceConfig;
+use aptos_crypto::HashValue;
+use aptos_storage_interface::{DbReader, Result as StorageResult};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{
+        AccountResource, ChainIdResource, CollectionResource, FungibleStoreResource,
+        ObjectCoreResource,
+    },
+    proof::{SparseMerkleProof, TransactionAccumulatorProof, TransactionInfoWithProof},
+    state_store::{
+        state_key::{prefix::StateKeyPrefix, StateKey},
+        state_value::StateValue,
+    },
+    transaction::{ExecutionStatus, Transaction, TransactionInfo, TransactionWithProof, Version},
+};
+use claims::{assert_matches, assert_none};
+use std::sync::Arc;
+
+#[test]
+fn test_get_state_values_by_prefix_with_proof() {
+    // Create a database holding the state values of a single account
+    let address = AccountAddress::random();
+    let db_reader = PrefixDatabaseReader::new(address);
+    let transaction_info = db_reader.transaction_info.clone();
+    let expected_state_values = db_reader.state_values.clone();
+
+    // Create a storage reader that serves at most two state values per chunk
+    let storage_config = StorageServiceConfig {
+        enable_state_values_by_prefix: true,
+        max_state_chunk_size: 2,
+        ..Default::default()
+    };
+    let storage_reader = StorageReader::new(storage_config, Arc::new(db_reader));
+
+    // Fetch all state values under the account prefix, chunk by chunk
+    let (proof_version, version) = (20, 10);
+    let key_prefix = StateKeyPrefix::from(address);
+    let mut state_values = vec![];
+    let mut start_key = None;
+    loop {
+        let response = storage_reader
+            .get_state_values_by_prefix_with_proof(
+                proof_version,
+                version,
+                &key_prefix,
+                start_key.as_ref(),
+            )
+            .unwrap();
+
+        // Verify the response is served at the latest snapshot (before the version)
+        assert_eq!(response.version, SNAPSHOT_VERSION);
+        assert_eq!(
+            response.transaction_info_with_proof.transaction_info(),
+            &transaction_info
+        );
+        assert!(response.values.len() <= 2);
+        state_values.extend(
+            response
+                .values
+                .into_iter()
+                .map(|(state_key, state_value, _)| (state_key, state_value)),
+        );
+
+        // Continue from the next key (if any)
+        match response.next_key {
+            Some(next_key) => {
+                assert_eq!(next_key, expected_state_values[state_values.len()].0);
+                start_key = Some(next_key);
+            },
+            None => break,
+        }
+    }
+    assert_eq!(state_values, expected_state_values);
+
+    // Verify that no state values are returned for another account
+    let response = storage_reader
+        .get_state_values_by_prefix_with_proof(
+            proof_version,
+            version,
+            &StateKeyPrefix::from(AccountAddress::random()),
+            None,
+        )
+        .unwrap();
+    assert!(response.values.is_empty());
+    assert_none!(response.next_key);
+}
+
+#[test]
+fn test_get_state_values_by_prefix_with_proof_disabled() {
+    // Create a storage reader that doesn't serve state values by prefix (the default)
+    let address = AccountAddress::random();
+    let storage_reader = StorageReader::new(
+        StorageServiceConfig::default(),
+        Arc::new(PrefixDatabaseReader::new(address)),
+    );
+
+    // Verify that the request is rejected
+    let error = storage_reader
+        .get_state_values_by_prefix_with_proof(20, 10, &StateKeyPrefix::from(address), None)
+        .unwrap_err();
+    assert_matches!(error, Error::InvalidRequest(_));
+}
+
+#[test]
+fn test_get_state_values_by_prefix_with_proof_invalid() {
+    // Create a storage reader that serves state values by prefix
+    let address = AccountAddress::random();
+    let storage_config = StorageServiceConfig {
+        enable_state_values_by_prefix: true,
+        ..Default::default()
+    };
+    let storage_reader =
+        StorageReader::new(storage_config, Arc::new(PrefixDatabaseReader::new(address)));
+
+    // Verify that requests with a proof version lower than the version are rejected
+    let key_prefix = StateKeyPrefix::from(address);
+    let error = storage_reader
+        .get_state_values_by_prefix_with_proof(9, 10, &key_prefix, None)
+        .unwrap_err();
+    assert_matches!(error, Error::InvalidRequest(_));
+
+    // Verify that requests before the first state snapshot are rejected
+    let error = storage_reader
+        .get_state_values_by_prefix_with_proof(20, SNAPSHOT_VERSION - 1, &key_prefix, None)
+        .unwrap_err();
+    assert_matches!(error, Error::InvalidRequest(_));
+}
+
+// The version of the only state snapshot held by the prefix database reader
+const SNAPSHOT_VERSION: Version = 8;
+
+/// A database reader holding the state values of a single account (in a
+/// single state snapshot). Note: the proofs returned are not valid.
+struct PrefixDatabaseReader {
+    transaction_info: TransactionInfo,
+    state_values: Vec<(StateKey, StateValue)>,
+}
+
+impl PrefixDatabaseReader {
+    fn new(address: AccountAddress) -> Self {
+        let state_keys = [
+            StateKey::resource_typed::<AccountResource>(&address).unwrap(),
+            StateKey::resource_typed::<ChainIdResource>(&address).unwrap(),
+            StateKey::resource_typed::<CollectionResource>(&address).unwrap(),
+            StateKey::resource_typed::<FungibleStoreResource>(&address).unwrap(),
+            StateKey::resource_typed::<ObjectCoreResource>(&address).unwrap(),
+        ];
+        let state_values = state_keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, state_key)| (state_key, StateValue::from(vec![index as u8])))
+            .collect();
+
+        let transaction_info = TransactionInfo::new_placeholder(
+            0,
+            Some(HashValue::random()),
+            ExecutionStatus::Success,
+        );
+
+        Self {
+            transaction_info,
+            state_values,
+        }
+    }
+}
+
+impl DbReader for PrefixDatabaseReader {
+    fn get_state_snapshot_before(
+        &self,
+        next_version: Version,
+    ) -> StorageResult<Option<(Version, HashValue)>> {
+        let root_hash = self
+            .transaction_info
+            .ensure_state_checkpoint_hash()
+            .unwrap();
+        Ok((SNAPSHOT_VERSION < next_version).then_some((SNAPSHOT_VERSION, root_hash)))
+    }
+
+    fn get_transaction_by_version(
+        &self,
+        version: Version,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> StorageResult<TransactionWithProof> {
+        assert_eq!(version, SNAPSHOT_VERSION);
+        let proof = TransactionInfoWithProof::new(
+            TransactionAccumulatorProof::new(vec![]),
+            self.transaction_info.clone(),
+        );
+        Ok(TransactionWithProof::new(
+            version,
+            Transaction::StateCheckpoint(HashValue::random()),
+            None,
+            proof,
+        ))
+    }
+
+    fn get_prefixed_state_value_iterator(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        cursor: Option<&StateKey>,
+        version: Version,
+    ) -> StorageResult<Box<dyn Iterator<Item = StorageResult<(StateKey, StateValue)>> + '_>> {
+        assert_eq!(version, SNAPSHOT_VERSION);
+        let start_index = cursor.map_or(0, |cursor| {
+            self.state_values
+                .iter()
+                .position(|(state_key, _)| state_key == cursor)
+                .unwrap()
+        });
+        let state_values: Vec<_> = self.state_values[start_index..]
+            .iter()
+            .filter(|(state_key, _)| key_prefix.is_prefix(state_key).unwrap())
+            .cloned()
+            .collect();
+        Ok(Box::new(state_values.into_iter().map(Ok)))
+    }
+
+    fn get_state_value_with_proof_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> StorageResult<(Option<StateValue>, SparseMerkleProof)> {
+        assert_eq!(version, SNAPSHOT_VERSION);
+        let state_value = self
+            .state_values
+            .iter()
+            .find(|(key, _)| key == state_key)
+            .map(|(_, state_value)| state_value.clone());
+        Ok((state_value, SparseMerkleProof::new(None, vec![])))
+    }
+}
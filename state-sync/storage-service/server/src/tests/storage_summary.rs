@@ -3,7 +3,7 @@
 
 use crate::{
     refresh_cached_storage_summary,
-    storage::StorageReader,
+    storage::{StorageReader, StorageReaderInterface},
     tests::{
         mock,
         mock::{MockClient, MockDatabaseReader},
@@ -46,6 +46,7 @@ async fn test_refresh_cached_storage_summary() {
         lowest_version,
         state_prune_window,
         highest_ledger_info.clone(),
+        false,
     );
     let storage_reader = StorageReader::new(storage_service_config, Arc::new(db_reader));
 
@@ -145,6 +146,29 @@ async fn test_refresh_cached_storage_summary() {
     );
 }
 
+#[test]
+fn test_get_data_summary_partial_state() {
+    // Create test data
+    let highest_version = 1000;
+    let highest_epoch = 10;
+    let lowest_version = 100;
+    let highest_ledger_info =
+        utils::create_test_ledger_info_with_sigs(highest_epoch, highest_version);
+
+    // Create a storage reader for a node that only persists partial state
+    let db_reader =
+        create_db_reader_with_expectations(lowest_version, 200, highest_ledger_info, true);
+    let storage_reader = StorageReader::new(StorageServiceConfig::default(), Arc::new(db_reader));
+
+    // Verify that the data summary doesn't advertise any states
+    let data_summary = storage_reader.get_data_summary().unwrap();
+    assert_eq!(
+        data_summary.transactions,
+        Some(CompleteDataRange::new(lowest_version, highest_version).unwrap())
+    );
+    assert_eq!(data_summary.states, None);
+}
+
 #[tokio::test]
 async fn test_get_storage_server_summary_advance_time() {
     // Create test data
@@ -160,6 +184,7 @@ async fn test_get_storage_server_summary_advance_time() {
         lowest_version,
         state_prune_window,
         highest_ledger_info.clone(),
+        false,
     );
 
     // Create the storage client and server
@@ -218,6 +243,7 @@ async fn test_get_storage_server_summary_notification() {
         lowest_version,
         state_prune_window,
         highest_ledger_info.clone(),
+        false,
     );
 
     // Create the storage client and server
@@ -268,6 +294,7 @@ fn create_db_reader_with_expectations(
     lowest_version: Version,
     state_prune_window: usize,
     highest_ledger_info: LedgerInfoWithSignatures,
+    partial_state_enabled: bool,
 ) -> MockDatabaseReader {
     // Create the mock reader
     let mut db_reader = mock::create_mock_db_reader();
@@ -289,6 +316,9 @@ fn create_db_reader_with_expectations(
         .expect_is_state_merkle_pruner_enabled()
         .returning(move || Ok(true));
     db_reader
+        .expect_is_partial_state_enabled()
+        .returning(move || Ok(partial_state_enabled));
+    db_reader
 }
 
 /// Sends a storage summary request and processes the response
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::COMPRESSION_SUFFIX_LABEL;
-use aptos_types::transaction::Version;
+use aptos_types::{
+    state_store::state_key::{prefix::StateKeyPrefix, StateKey},
+    transaction::Version,
+};
 use serde::{Deserialize, Serialize};
 
 /// A storage service request.
@@ -47,6 +50,7 @@ pub enum DataRequest {
     SubscribeTransactionOutputsWithProof(SubscribeTransactionOutputsWithProofRequest), // Subscribes to transaction outputs with a proof
     SubscribeTransactionsOrOutputsWithProof(SubscribeTransactionsOrOutputsWithProofRequest), // Subscribes to transactions or outputs with a proof
     SubscribeTransactionsWithProof(SubscribeTransactionsWithProofRequest), // Subscribes to transactions with a proof
+    GetStateValuesByPrefixWithProof(StateValuesByPrefixWithProofRequest), // Fetches the states under a key prefix with proofs
 }
 
 impl DataRequest {
@@ -73,6 +77,7 @@ impl DataRequest {
                 "subscribe_transactions_or_outputs_with_proof"
             },
             Self::SubscribeTransactionsWithProof(_) => "subscribe_transactions_with_proof",
+            Self::GetStateValuesByPrefixWithProof(_) => "get_state_values_by_prefix_with_proof",
         }
     }
 
@@ -130,6 +135,18 @@ pub struct StateValuesWithProofRequest {
     pub end_index: u64,   // The index to stop fetching state values (inclusive)
 }
 
+/// A storage service request for fetching all state values under a key
+/// prefix (e.g., an account address) at a specified version. The state
+/// values are served at the latest state snapshot at or before the version,
+/// and each state value is returned with a proof.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct StateValuesByPrefixWithProofRequest {
+    pub proof_version: u64,          // The version the proof should be relative to
+    pub version: u64,                // The version to fetch the state values at
+    pub key_prefix: StateKeyPrefix,  // The prefix of the state keys to fetch
+    pub start_key: Option<StateKey>, // The key to start fetching state values at (inclusive)
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -5,10 +5,11 @@ use crate::{
     requests::DataRequest::{
         GetEpochEndingLedgerInfos, GetNewTransactionOutputsWithProof,
         GetNewTransactionsOrOutputsWithProof, GetNewTransactionsWithProof,
-        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesWithProof,
-        GetStorageServerSummary, GetTransactionOutputsWithProof, GetTransactionsOrOutputsWithProof,
-        GetTransactionsWithProof, SubscribeTransactionOutputsWithProof,
-        SubscribeTransactionsOrOutputsWithProof, SubscribeTransactionsWithProof,
+        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesByPrefixWithProof,
+        GetStateValuesWithProof, GetStorageServerSummary, GetTransactionOutputsWithProof,
+        GetTransactionsOrOutputsWithProof, GetTransactionsWithProof,
+        SubscribeTransactionOutputsWithProof, SubscribeTransactionsOrOutputsWithProof,
+        SubscribeTransactionsWithProof,
     },
     responses::Error::DegenerateRangeError,
     Epoch, StorageServiceRequest, COMPRESSION_SUFFIX_LABEL,
@@ -21,7 +22,7 @@ use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::state_value::{StateValueChunkWithProof, StateValuesByPrefixWithProof},
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use num_traits::{PrimInt, Zero};
@@ -139,6 +140,7 @@ pub enum DataResponse {
     TransactionsWithProof(TransactionListWithProof),
     NewTransactionsOrOutputsWithProof((TransactionOrOutputListWithProof, LedgerInfoWithSignatures)),
     TransactionsOrOutputsWithProof(TransactionOrOutputListWithProof),
+    StateValuesByPrefixWithProof(StateValuesByPrefixWithProof),
 }
 
 impl DataResponse {
@@ -156,6 +158,7 @@ impl DataResponse {
             Self::TransactionsWithProof(_) => "transactions_with_proof",
             Self::NewTransactionsOrOutputsWithProof(_) => "new_transactions_or_outputs_with_proof",
             Self::TransactionsOrOutputsWithProof(_) => "transactions_or_outputs_with_proof",
+            Self::StateValuesByPrefixWithProof(_) => "state_values_by_prefix_with_proof",
         }
     }
 }
@@ -193,6 +196,21 @@ impl TryFrom<StorageServiceResponse> for StateValueChunkWithProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for StateValuesByPrefixWithProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::StateValuesByPrefixWithProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected state_values_by_prefix_with_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse> for EpochChangeProof {
     type Error = crate::responses::Error;
 
@@ -347,6 +365,11 @@ impl TryFrom<StorageServiceResponse> for TransactionOrOutputListWithProof {
     }
 }
 
+/// The base protocol version, supported by all storage servers
+pub const BASE_PROTOCOL_VERSION: u64 = 1;
+/// The protocol version of servers that also serve state values by prefix
+pub const STATE_VALUES_BY_PREFIX_PROTOCOL_VERSION: u64 = 2;
+
 /// The protocol version run by this server. Clients request this first to
 /// identify what API calls and data requests the server supports.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -354,6 +377,15 @@ pub struct ServerProtocolVersion {
     pub protocol_version: u64, // The storage server version run by this instance.
 }
 
+impl ServerProtocolVersion {
+    /// Returns true iff the server can service state values by prefix
+    /// requests. Such requests are optional (and disabled by default), so
+    /// clients must check this before sending them.
+    pub fn supports_state_values_by_prefix(&self) -> bool {
+        self.protocol_version >= STATE_VALUES_BY_PREFIX_PROTOCOL_VERSION
+    }
+}
+
 /// A storage server summary, containing a summary of the information held
 /// by the corresponding server instance. This is useful for identifying the
 /// data that a server instance can provide, as well as relevant metadata.
@@ -434,7 +466,9 @@ pub struct DataSummary {
 }
 
 impl DataSummary {
-    /// Returns true iff the request can be serviced
+    /// Returns true iff the request can be serviced. Note: state values by
+    /// prefix requests are optional, so clients must also check that the
+    /// server supports them (see `ServerProtocolVersion`).
     pub fn can_service(
         &self,
         aptos_data_client_config: &AptosDataClientConfig,
@@ -488,6 +522,21 @@ impl DataSummary {
 
                 can_serve_states && can_create_proof
             },
+            GetStateValuesByPrefixWithProof(request) => {
+                let can_serve_states = self
+                    .states
+                    .map(|range| range.contains(request.version))
+                    .unwrap_or(false);
+
+                let can_create_proof = request.proof_version >= request.version
+                    && self
+                        .synced_ledger_info
+                        .as_ref()
+                        .map(|li| li.ledger_info().version() >= request.proof_version)
+                        .unwrap_or(false);
+
+                can_serve_states && can_create_proof
+            },
             GetTransactionOutputsWithProof(request) => {
                 let desired_range =
                     match CompleteDataRange::new(request.start_version, request.end_version) {
@@ -5,7 +5,8 @@ use crate::{
     requests::{
         DataRequest, EpochEndingLedgerInfoRequest, NewTransactionOutputsWithProofRequest,
         NewTransactionsOrOutputsWithProofRequest, NewTransactionsWithProofRequest,
-        StateValuesWithProofRequest, SubscribeTransactionOutputsWithProofRequest,
+        StateValuesByPrefixWithProofRequest, StateValuesWithProofRequest,
+        SubscribeTransactionOutputsWithProofRequest,
         SubscribeTransactionsOrOutputsWithProofRequest, SubscribeTransactionsWithProofRequest,
         SubscriptionStreamMetadata, TransactionOutputsWithProofRequest,
         TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
@@ -14,13 +15,23 @@ use crate::{
     Epoch, StorageServiceRequest,
 };
 use aptos_config::config::AptosDataClientConfig;
-use aptos_crypto::hash::HashValue;
+use aptos_crypto::hash::{CryptoHash, HashValue};
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
+    account_address::AccountAddress,
+    account_config::AccountResource,
     aggregate_signature::AggregateSignature,
     block_info::BlockInfo,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    transaction::Version,
+    proof::{
+        SparseMerkleLeafNode, SparseMerkleProof, TransactionAccumulatorProof,
+        TransactionInfoWithProof,
+    },
+    state_store::{
+        state_key::{prefix::StateKeyPrefix, StateKey},
+        state_value::{StateValue, StateValuesByPrefixWithProof},
+    },
+    transaction::{ExecutionStatus, TransactionInfo, Version},
 };
 use claims::{assert_err, assert_ok};
 use proptest::{arbitrary::any, prelude::*};
@@ -466,6 +477,111 @@ fn test_data_summary_can_service_state_chunk_request() {
     }
 }
 
+#[test]
+fn test_data_summary_can_service_state_values_by_prefix_request() {
+    // Create a data client config and data summary
+    let data_client_config = AptosDataClientConfig::default();
+    let data_summary = DataSummary {
+        synced_ledger_info: Some(create_ledger_info_at_version(250)),
+        states: Some(create_data_range(100, 300)),
+        ..Default::default()
+    };
+
+    // Verify the different requests that can be serviced
+    for compression in [true, false] {
+        // Test the valid request versions
+        for (version, proof_version) in [(100, 100), (100, 250), (200, 220), (250, 250)] {
+            let request =
+                create_state_values_by_prefix_request(version, proof_version, compression);
+            verify_serviceability(&data_client_config, &data_summary, None, request, true);
+        }
+
+        // Test invalid request versions
+        for (version, proof_version) in [(50, 250), (99, 100), (251, 251), (300, 300)] {
+            let request =
+                create_state_values_by_prefix_request(version, proof_version, compression);
+            verify_serviceability(&data_client_config, &data_summary, None, request, false);
+        }
+
+        // Test invalid proof versions
+        for (version, proof_version) in [(200, 199), (200, 251), (100, 300)] {
+            let request =
+                create_state_values_by_prefix_request(version, proof_version, compression);
+            verify_serviceability(&data_client_config, &data_summary, None, request, false);
+        }
+    }
+}
+
+#[test]
+fn test_state_values_by_prefix_verification() {
+    // Create a state value under the account address
+    let address = AccountAddress::random();
+    let state_key = StateKey::resource_typed::<AccountResource>(&address).unwrap();
+    let state_value = StateValue::from(vec![0, 1, 2, 3]);
+
+    // Create a single leaf state tree and the proof for the state value
+    let leaf = SparseMerkleLeafNode::new(state_key.hash(), state_value.hash());
+    let proof = SparseMerkleProof::new(Some(leaf), vec![]);
+
+    // Create a single transaction ledger with the state tree root hash
+    let transaction_info =
+        TransactionInfo::new_placeholder(0, Some(leaf.hash()), ExecutionStatus::Success);
+    let ledger_info = create_ledger_info_with_root(transaction_info.hash());
+    let state_values_by_prefix_with_proof = StateValuesByPrefixWithProof {
+        version: 0,
+        values: vec![(state_key.clone(), state_value.clone(), proof.clone())],
+        next_key: None,
+        transaction_info_with_proof: TransactionInfoWithProof::new(
+            TransactionAccumulatorProof::new(vec![]),
+            transaction_info,
+        ),
+    };
+
+    // Verify the state values against the account prefix
+    let key_prefix = StateKeyPrefix::from(address);
+    assert_ok!(state_values_by_prefix_with_proof.verify(&key_prefix, &ledger_info));
+
+    // Verify the state values fail verification against another prefix
+    let other_key_prefix = StateKeyPrefix::from(AccountAddress::random());
+    assert_err!(state_values_by_prefix_with_proof.verify(&other_key_prefix, &ledger_info));
+
+    // Verify the state values fail verification against another ledger info
+    let other_ledger_info = create_ledger_info_with_root(HashValue::random());
+    assert_err!(state_values_by_prefix_with_proof.verify(&key_prefix, &other_ledger_info));
+
+    // Verify the state values fail verification against another root hash
+    let transaction_info =
+        TransactionInfo::new_placeholder(0, Some(HashValue::random()), ExecutionStatus::Success);
+    let mut invalid_root_hash = state_values_by_prefix_with_proof.clone();
+    invalid_root_hash.transaction_info_with_proof = TransactionInfoWithProof::new(
+        TransactionAccumulatorProof::new(vec![]),
+        transaction_info.clone(),
+    );
+    let ledger_info = create_ledger_info_with_root(transaction_info.hash());
+    assert_err!(invalid_root_hash.verify(&key_prefix, &ledger_info));
+
+    // Verify the state values fail verification without a state checkpoint
+    let transaction_info = TransactionInfo::new_placeholder(0, None, ExecutionStatus::Success);
+    let mut invalid_checkpoint = state_values_by_prefix_with_proof.clone();
+    invalid_checkpoint.transaction_info_with_proof = TransactionInfoWithProof::new(
+        TransactionAccumulatorProof::new(vec![]),
+        transaction_info.clone(),
+    );
+    let ledger_info = create_ledger_info_with_root(transaction_info.hash());
+    assert_err!(invalid_checkpoint.verify(&key_prefix, &ledger_info));
+
+    // Verify the state values fail verification with a modified value
+    let ledger_info = create_ledger_info_with_root(
+        state_values_by_prefix_with_proof
+            .transaction_info_with_proof
+            .transaction_info()
+            .hash(),
+    );
+    let mut invalid_value = state_values_by_prefix_with_proof;
+    invalid_value.values = vec![(state_key, StateValue::from(vec![4, 5, 6]), proof)];
+    assert_err!(invalid_value.verify(&key_prefix, &ledger_info));
+}
+
 #[test]
 fn test_protocol_metadata_service() {
     // Create the protocol metadata
@@ -524,6 +640,15 @@ fn create_ledger_info_at_version(version: Version) -> LedgerInfoWithSignatures {
     create_ledger_info_at_version_and_timestamp(version, 0)
 }
 
+/// Creates a new ledger info at version 0 with the given transaction
+/// accumulator root hash (i.e., the hash of the only transaction info)
+fn create_ledger_info_with_root(root_hash: HashValue) -> LedgerInfo {
+    LedgerInfo::new(
+        BlockInfo::new(0, 0, HashValue::zero(), root_hash, 0, 0, None),
+        HashValue::zero(),
+    )
+}
+
 /// Creates a new ledger info at the given version and timestamp
 fn create_ledger_info_at_version_and_timestamp(
     version: Version,
@@ -691,6 +816,22 @@ fn create_state_values_request_at_version(
     create_state_values_request(version, 0, 1000, use_compression)
 }
 
+/// Creates a request for the state values under a random account at the given version
+fn create_state_values_by_prefix_request(
+    version: Version,
+    proof_version: Version,
+    use_compression: bool,
+) -> StorageServiceRequest {
+    let data_request =
+        DataRequest::GetStateValuesByPrefixWithProof(StateValuesByPrefixWithProofRequest {
+            proof_version,
+            version,
+            key_prefix: StateKeyPrefix::from(AccountAddress::random()),
+            start_key: None,
+        });
+    StorageServiceRequest::new(data_request, use_compression)
+}
+
 /// Generates a random u64
 fn get_random_u64() -> u64 {
     thread_rng().gen()
@@ -11,7 +11,7 @@ use crate::{
     AptosDB,
 };
 use aptos_crypto::HashValue;
-use aptos_storage_interface::{DbReader, Result};
+use aptos_storage_interface::{AptosDbError, DbReader, Result};
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
@@ -131,6 +131,22 @@ impl RestoreHandler {
             .map_err(Into::into)
     }
 
+    /// Returns the ledger info that ends `epoch`, if it's saved.
+    pub fn get_epoch_ending_ledger_info_of_epoch(
+        &self,
+        epoch: u64,
+    ) -> Result<Option<LedgerInfoWithSignatures>> {
+        match self
+            .ledger_db
+            .metadata_db()
+            .get_latest_ledger_info_in_epoch(epoch)
+        {
+            Ok(li) => Ok(li.ledger_info().ends_epoch().then_some(li)),
+            Err(AptosDbError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn get_in_progress_state_kv_snapshot_version(&self) -> Result<Option<Version>> {
         let db = self.aptosdb.state_kv_db.metadata_db_arc();
         let mut iter = db.iter::<DbMetadataSchema>()?;
@@ -67,6 +67,7 @@ impl AptosDB {
             indexer: None,
             skip_index_and_usage,
             update_subscriber: None,
+            partial_state_filter: None,
         }
     }
 
@@ -221,6 +222,26 @@ impl AptosDB {
         }
     }
 
+    fn error_if_state_not_tracked(&self, state_key: &StateKey) -> Result<()> {
+        if let Some(partial_state_filter) = &self.partial_state_filter {
+            ensure!(
+                partial_state_filter.is_tracked(state_key),
+                "State value of {:?} is not tracked, only partial state is persisted.",
+                state_key,
+            );
+        }
+        Ok(())
+    }
+
+    fn error_if_partial_state(&self, data_type: &str) -> Result<()> {
+        ensure!(
+            self.partial_state_filter.is_none(),
+            "{} is not available, only partial state is persisted.",
+            data_type,
+        );
+        Ok(())
+    }
+
     fn error_if_state_kv_pruned(&self, data_type: &str, version: Version) -> Result<()> {
         let min_readable_version = self.state_store.state_kv_pruner.get_min_readable_version();
         ensure!(
@@ -27,6 +27,7 @@ impl DbReader for AptosDB {
                 !self.state_kv_db.enabled_sharding(),
                 "This API is not supported with sharded DB"
             );
+            self.error_if_partial_state("Prefixed StateValue iterator")?;
             self.error_if_state_kv_pruned("StateValue", version)?;
 
             Ok(Box::new(
@@ -463,6 +464,7 @@ impl DbReader for AptosDB {
     ) -> Result<Option<StateValue>> {
         gauged_api("get_state_value_by_version", || {
             self.error_if_state_kv_pruned("StateValue", version)?;
+            self.error_if_state_not_tracked(state_store_key)?;
 
             self.state_store
                 .get_state_value_by_version(state_store_key, version)
@@ -505,6 +507,7 @@ impl DbReader for AptosDB {
     ) -> Result<(Option<StateValue>, SparseMerkleProofExt)> {
         gauged_api("get_state_value_with_proof_by_version_ext", || {
             self.error_if_state_merkle_pruned("State merkle", version)?;
+            self.error_if_state_not_tracked(state_store_key)?;
 
             self.state_store.get_state_value_with_proof_by_version_ext(
                 state_store_key,
@@ -712,6 +715,7 @@ impl DbReader for AptosDB {
     ) -> Result<StateValueChunkWithProof> {
         gauged_api("get_state_value_chunk_with_proof", || {
             self.error_if_state_merkle_pruned("State merkle", version)?;
+            self.error_if_partial_state("StateValue chunk")?;
             self.state_store
                 .get_value_chunk_with_proof(version, first_index, chunk_size)
         })
@@ -727,6 +731,12 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn is_partial_state_enabled(&self) -> Result<bool> {
+        gauged_api("is_partial_state_enabled", || {
+            Ok(self.partial_state_filter.is_some())
+        })
+    }
+
     fn get_epoch_snapshot_prune_window(&self) -> Result<usize> {
         gauged_api("get_state_prune_window", || {
             Ok(self
@@ -95,8 +95,11 @@ impl DbWriter for AptosDB {
         expected_root_hash: HashValue,
     ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
         gauged_api("get_state_snapshot_receiver", || {
-            self.state_store
-                .get_snapshot_receiver(version, expected_root_hash)
+            self.state_store.get_snapshot_receiver(
+                version,
+                expected_root_hash,
+                self.partial_state_filter.clone(),
+            )
         })
     }
 
@@ -343,6 +346,7 @@ impl AptosDB {
             chunk.transaction_infos
                 .iter()
                 .rposition(|t| t.state_checkpoint_hash().is_some()),
+            self.partial_state_filter.as_ref(),
         )?;
 
         // Write block index if event index is skipped.
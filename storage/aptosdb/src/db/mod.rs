@@ -59,6 +59,7 @@ use aptos_types::{
     },
     state_proof::StateProof,
     state_store::{
+        partial_state::PartialStateFilter,
         state_key::{prefix::StateKeyPrefix, StateKey},
         state_storage_usage::StateStorageUsage,
         state_value::{StateValue, StateValueChunkWithProof},
@@ -104,6 +105,8 @@ pub struct AptosDB {
     indexer: Option<Indexer>,
     skip_index_and_usage: bool,
     update_subscriber: Option<Sender<Version>>,
+    /// If set, only the state values tracked by the filter are persisted (partial state sync)
+    partial_state_filter: Option<PartialStateFilter>,
 }
 
 // DbReader implementations and private functions used by them.
@@ -194,6 +197,15 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Sets the filter for partial state sync. Once set, the state values not
+    /// tracked by the filter are discarded (instead of persisted), and reading
+    /// them returns an error (instead of an incorrect value). Note: the state
+    /// merkle tree is still persisted in full, so that the state root can be
+    /// computed and verified for all transaction outputs.
+    pub fn set_partial_state_filter(&mut self, filter: PartialStateFilter) {
+        self.partial_state_filter = Some(filter);
+    }
+
     /// Gets an instance of `BackupHandler` for data backup purpose.
     pub fn get_backup_handler(&self) -> BackupHandler {
         BackupHandler::new(Arc::clone(&self.state_store), Arc::clone(&self.ledger_db))
@@ -97,6 +97,7 @@ pub(crate) fn update_store(
                 &sharded_state_kv_batches,
                 /*put_state_value_indices=*/ enable_sharding,
                 /*last_checkpoint_index=*/ None,
+                /*partial_state_filter=*/ None,
             )
             .unwrap();
         store
@@ -9,6 +9,7 @@ pub mod state_kv;
 pub mod state_tree;
 pub mod truncate;
 pub mod validation;
+pub mod verify_all;
 
 use aptos_storage_interface::Result;
 use clap::Parser;
@@ -39,6 +40,8 @@ pub enum Cmd {
 
     #[clap(subcommand)]
     IndexerValidation(validation::Cmd),
+
+    VerifyAll(verify_all::Cmd),
 }
 
 impl Cmd {
@@ -51,6 +54,7 @@ impl Cmd {
             Cmd::Truncate(cmd) => cmd.run(),
             Cmd::Examine(cmd) => cmd.run(),
             Cmd::IndexerValidation(cmd) => cmd.run(),
+            Cmd::VerifyAll(cmd) => cmd.run(),
         }
     }
 }
@@ -0,0 +1,535 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_debugger::common::DbDir,
+    state_kv_db::StateKvDb,
+    state_merkle_db::{LeafNode, Node, StateMerkleDb},
+};
+use anyhow::anyhow;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_jellyfish_merkle::{
+    node_type::{Child, NodeKey},
+    TreeReader,
+};
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_storage_interface::{AptosDbError, Result};
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    nibble::Nibble,
+    state_store::{
+        state_key::{inner::StateKeyInner, StateKey},
+        state_storage_usage::StateStorageUsage,
+        state_value::StateValue,
+        StateViewResult, TStateView,
+    },
+    transaction::Version,
+};
+use clap::Parser;
+use move_core_types::{identifier::Identifier, language_storage::StructTag};
+use owo_colors::OwoColorize;
+use std::{collections::BTreeMap, str::FromStr};
+
+const MAX_RAW_VALUE_BYTES: usize = 1024;
+
+#[derive(Parser)]
+#[clap(about = "Print state keys created, modified or deleted between two versions.")]
+pub struct Cmd {
+    #[clap(flatten)]
+    db_dir: DbDir,
+
+    #[clap(long)]
+    from: Version,
+
+    #[clap(long)]
+    to: Version,
+
+    #[clap(long, help = "Only print keys under this account address.")]
+    address: Option<AccountAddress>,
+
+    #[clap(
+        long,
+        help = "Only print resources, resource groups (or their members) and modules of this \
+        type, e.g. `0x1::coin` or `0x1::coin::CoinStore`. Type arguments are ignored."
+    )]
+    resource_type: Option<TypeFilter>,
+
+    #[clap(
+        long,
+        help = "Print raw value bytes instead of decoding them with the resource viewer."
+    )]
+    raw: bool,
+}
+
+/// A state key whose value differs between the two versions, with its value (and the version
+/// it was written at) at each of them.
+#[derive(Debug)]
+pub struct StateKeyChange {
+    pub key: StateKey,
+    pub old: Option<(Version, StateValue)>,
+    pub new: Option<(Version, StateValue)>,
+}
+
+/// The state keys that changed between two versions.
+#[derive(Debug)]
+pub struct StateDiff {
+    /// The number of keys touched between the versions, before filtering.
+    pub num_keys_touched: usize,
+    /// Whether the touched keys were found by scanning the write sets in between, because either
+    /// version isn't a state snapshot.
+    pub scanned_write_sets: bool,
+    pub changes: Vec<StateKeyChange>,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        println!(
+            "{}",
+            format!(
+                "* Diff state between version {} and version {}. \n",
+                self.from, self.to
+            )
+            .yellow()
+        );
+
+        let StateDiff {
+            num_keys_touched,
+            scanned_write_sets,
+            changes,
+        } = self.diff()?;
+        if scanned_write_sets {
+            println!(
+                "{}",
+                "No state snapshots at both versions, scanned write sets in between."
+                    .to_string()
+                    .yellow()
+            );
+        }
+        println!("keys touched: {}\n", num_keys_touched);
+
+        let state_kv_db = self.db_dir.open_state_kv_db()?;
+        let from_view = StateKvView::new(&state_kv_db, self.from);
+        let to_view = StateKvView::new(&state_kv_db, self.to);
+        let from_annotator = AptosValueAnnotator::new(&from_view);
+        let to_annotator = AptosValueAnnotator::new(&to_view);
+
+        let (mut created, mut modified, mut deleted) = (0, 0, 0);
+        for StateKeyChange { key, old, new } in changes {
+            let path = match key.inner() {
+                StateKeyInner::AccessPath(access_path) => Some(access_path.get_path()),
+                _ => None,
+            };
+            let change = match (&old, &new) {
+                (None, Some(_)) => {
+                    created += 1;
+                    "+ created".green().to_string()
+                },
+                (Some(_), Some(_)) => {
+                    modified += 1;
+                    "~ modified".yellow().to_string()
+                },
+                _ => {
+                    deleted += 1;
+                    "- deleted".red().to_string()
+                },
+            };
+            println!("{} {:?}", change, key);
+            if let Some((version, value)) = &old {
+                println!("   old (version {}):", version);
+                println!(
+                    "{}",
+                    self.format_value(&from_annotator, path.as_ref(), value)
+                );
+            }
+            if let Some((version, value)) = &new {
+                println!("   new (version {}):", version);
+                println!("{}", self.format_value(&to_annotator, path.as_ref(), value));
+            }
+            println!();
+        }
+
+        println!(
+            "{}",
+            format!(
+                "Diff complete. created: {}, modified: {}, deleted: {}",
+                created, modified, deleted
+            )
+            .yellow()
+        );
+        Ok(())
+    }
+
+    /// Returns the state keys created, modified or deleted between the two versions and passing
+    /// the filters, ordered by key hash.
+    pub fn diff(&self) -> Result<StateDiff> {
+        if self.from >= self.to {
+            return Err(AptosDbError::Other(format!(
+                "--from {} must be smaller than --to {}.",
+                self.from, self.to
+            )));
+        }
+
+        let ledger_db = self.db_dir.open_ledger_db()?;
+        let state_kv_db = self.db_dir.open_state_kv_db()?;
+        let state_merkle_db = self.db_dir.open_state_merkle_db()?;
+        let latest_version = ledger_db
+            .metadata_db()
+            .get_synced_version()?
+            .ok_or_else(|| AptosDbError::Other("DB is empty.".to_string()))?;
+        if self.to > latest_version {
+            return Err(AptosDbError::Other(format!(
+                "--to {} is greater than latest version {}.",
+                self.to, latest_version
+            )));
+        }
+
+        let jmt_changed_keys = Self::diff_jmt(&state_merkle_db, self.from, self.to)?;
+        let scanned_write_sets = jmt_changed_keys.is_none();
+        let changed_keys = match jmt_changed_keys {
+            Some(keys) => keys,
+            None => {
+                let mut keys = BTreeMap::new();
+                for write_set in ledger_db
+                    .write_set_db()
+                    .get_write_set_iter(self.from + 1, (self.to - self.from) as usize)?
+                {
+                    for (key, _write_op) in write_set? {
+                        keys.insert(CryptoHash::hash(&key), key);
+                    }
+                }
+                keys
+            },
+        };
+        let num_keys_touched = changed_keys.len();
+
+        let mut changes = Vec::new();
+        for key in changed_keys.into_values() {
+            let path = match key.inner() {
+                StateKeyInner::AccessPath(access_path) => {
+                    if self.address.map_or(false, |a| a != access_path.address) {
+                        continue;
+                    }
+                    Some(access_path.get_path())
+                },
+                _ => {
+                    if self.address.is_some() || self.resource_type.is_some() {
+                        continue;
+                    }
+                    None
+                },
+            };
+
+            let old = state_kv_db.get_state_value_with_version_by_version(&key, self.from)?;
+            let new = state_kv_db.get_state_value_with_version_by_version(&key, self.to)?;
+            if let (Some(filter), Some(path)) = (&self.resource_type, &path) {
+                let value_matches = |v: &Option<(Version, StateValue)>| {
+                    v.as_ref()
+                        .map_or(false, |(_, value)| filter.matches(path, value.bytes()))
+                };
+                if !value_matches(&old) && !value_matches(&new) {
+                    continue;
+                }
+            }
+
+            match (&old, &new) {
+                (None, None) => continue,
+                (Some((_, old_value)), Some((_, new_value))) if old_value == new_value => continue,
+                _ => changes.push(StateKeyChange { key, old, new }),
+            }
+        }
+        Ok(StateDiff {
+            num_keys_touched,
+            scanned_write_sets,
+            changes,
+        })
+    }
+
+    /// Returns the keys whose leaves differ between the trees at the two versions, keyed by key
+    /// hash, or `None` if either version isn't a state snapshot. Subtrees with the same hash are
+    /// skipped, so this reads only the part of the trees that changed.
+    fn diff_jmt(
+        db: &StateMerkleDb,
+        from: Version,
+        to: Version,
+    ) -> Result<Option<BTreeMap<HashValue, StateKey>>> {
+        let get_root = |version| {
+            let node_key = NodeKey::new_empty_path(version);
+            Ok::<_, AptosDbError>(
+                db.get_node_option(&node_key, "diff")?
+                    .map(|node| (node_key, node)),
+            )
+        };
+        let (from_root, to_root) = match (get_root(from)?, get_root(to)?) {
+            (Some(from_root), Some(to_root)) => (from_root, to_root),
+            _ => return Ok(None),
+        };
+
+        let mut changed = BTreeMap::new();
+        Self::diff_subtrees(db, Some(from_root), Some(to_root), &mut changed)?;
+        Ok(Some(changed))
+    }
+
+    fn diff_subtrees(
+        db: &StateMerkleDb,
+        from: Option<(NodeKey, Node)>,
+        to: Option<(NodeKey, Node)>,
+        changed: &mut BTreeMap<HashValue, StateKey>,
+    ) -> Result<()> {
+        match (&from, &to) {
+            (Some((_, from_node)), Some((_, to_node))) if from_node.hash() == to_node.hash() => {
+                return Ok(());
+            },
+            (
+                Some((from_key, Node::Internal(from_node))),
+                Some((to_key, Node::Internal(to_node))),
+            ) => {
+                for n in 0..16u8 {
+                    let nibble = Nibble::from(n);
+                    let (from_child, to_child) = (from_node.child(nibble), to_node.child(nibble));
+                    if let (Some(from_child), Some(to_child)) = (from_child, to_child) {
+                        if from_child.hash == to_child.hash {
+                            continue;
+                        }
+                    }
+                    let load = |parent: &NodeKey, child: &Child| {
+                        Self::load_node(db, parent.gen_child_node_key(child.version, nibble))
+                    };
+                    Self::diff_subtrees(
+                        db,
+                        from_child.map(|c| load(from_key, c)).transpose()?,
+                        to_child.map(|c| load(to_key, c)).transpose()?,
+                        changed,
+                    )?;
+                }
+                return Ok(());
+            },
+            _ => (),
+        }
+
+        // One side is a leaf or missing, so the subtree on the other side is typically small.
+        let mut from_leaves = BTreeMap::new();
+        let mut to_leaves = BTreeMap::new();
+        Self::collect_leaves(db, from, &mut from_leaves)?;
+        Self::collect_leaves(db, to, &mut to_leaves)?;
+        for (key_hash, leaf) in &to_leaves {
+            if from_leaves.get(key_hash).map(|l| l.value_hash()) != Some(leaf.value_hash()) {
+                changed.insert(*key_hash, leaf.value_index().0.clone());
+            }
+        }
+        for (key_hash, leaf) in from_leaves {
+            if !to_leaves.contains_key(&key_hash) {
+                changed.insert(key_hash, leaf.value_index().0.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn collect_leaves(
+        db: &StateMerkleDb,
+        node: Option<(NodeKey, Node)>,
+        leaves: &mut BTreeMap<HashValue, LeafNode>,
+    ) -> Result<()> {
+        match node {
+            None | Some((_, Node::Null)) => (),
+            Some((_, Node::Leaf(leaf))) => {
+                leaves.insert(leaf.account_key(), leaf);
+            },
+            Some((node_key, Node::Internal(internal))) => {
+                for (nibble, child) in internal.children_sorted() {
+                    let child_key = node_key.gen_child_node_key(child.version, *nibble);
+                    Self::collect_leaves(db, Some(Self::load_node(db, child_key)?), leaves)?;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn load_node(db: &StateMerkleDb, node_key: NodeKey) -> Result<(NodeKey, Node)> {
+        let node = db.get_node_option(&node_key, "diff")?.ok_or_else(|| {
+            AptosDbError::NotFound(format!(
+                "JMT node {:?}, the state snapshot might have been pruned",
+                node_key
+            ))
+        })?;
+        Ok((node_key, node))
+    }
+
+    fn format_value(
+        &self,
+        annotator: &AptosValueAnnotator<StateKvView>,
+        path: Option<&Path>,
+        value: &StateValue,
+    ) -> String {
+        let bytes = value.bytes();
+        let decoded = match path {
+            _ if self.raw => Err(anyhow!("raw requested")),
+            Some(Path::Resource(tag)) => annotator.view_resource(tag, bytes).map(|v| v.to_string()),
+            Some(Path::ResourceGroup(_)) => bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(bytes)
+                .map_err(Into::into)
+                .and_then(|members| {
+                    members
+                        .iter()
+                        .map(|(tag, blob)| {
+                            Ok(format!(
+                                "{}: {}",
+                                tag.to_canonical_string(),
+                                annotator.view_resource(tag, blob)?
+                            ))
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()
+                })
+                .map(|members| members.join("\n")),
+            Some(Path::Code(module_id)) => {
+                Ok(format!("module {} ({} bytes)", module_id, bytes.len()))
+            },
+            None => Err(anyhow!("no type information")),
+        };
+        let text = decoded.unwrap_or_else(|_| {
+            if bytes.len() > MAX_RAW_VALUE_BYTES {
+                format!("{} bytes", bytes.len())
+            } else {
+                hex::encode(bytes)
+            }
+        });
+        text.lines()
+            .map(|line| format!("     {}", line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `<address>::<module>` or `<address>::<module>::<name>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeFilter {
+    address: AccountAddress,
+    module: Identifier,
+    name: Option<Identifier>,
+}
+
+impl TypeFilter {
+    fn matches_struct(&self, tag: &StructTag) -> bool {
+        tag.address == self.address
+            && tag.module == self.module
+            && self.name.as_ref().map_or(true, |name| &tag.name == name)
+    }
+
+    /// Resource groups match if the group or any of its members in `value` match.
+    fn matches(&self, path: &Path, value: &[u8]) -> bool {
+        match path {
+            Path::Code(module_id) => {
+                self.name.is_none()
+                    && module_id.address() == &self.address
+                    && module_id.name() == self.module.as_ident_str()
+            },
+            Path::Resource(tag) => self.matches_struct(tag),
+            Path::ResourceGroup(tag) => {
+                self.matches_struct(tag)
+                    || bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(value)
+                        .map_or(false, |members| {
+                            members.keys().any(|tag| self.matches_struct(tag))
+                        })
+            },
+        }
+    }
+}
+
+impl FromStr for TypeFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let parts: Vec<_> = s.split("::").collect();
+        if !(2..=3).contains(&parts.len()) {
+            anyhow::bail!(
+                "Expecting `<address>::<module>` or `<address>::<module>::<name>`, got {}.",
+                s
+            );
+        }
+        Ok(Self {
+            address: AccountAddress::from_str(parts[0])?,
+            module: Identifier::new(parts[1])?,
+            name: parts
+                .get(2)
+                .map(|name| Identifier::new(*name))
+                .transpose()?,
+        })
+    }
+}
+
+/// Reads the state kv DB at a version, to let the resource viewer load modules.
+struct StateKvView<'a> {
+    db: &'a StateKvDb,
+    version: Version,
+}
+
+impl<'a> StateKvView<'a> {
+    fn new(db: &'a StateKvDb, version: Version) -> Self {
+        Self { db, version }
+    }
+}
+
+impl TStateView for StateKvView<'_> {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        Ok(self
+            .db
+            .get_state_value_with_version_by_version(state_key, self.version)?
+            .map(|(_, value)| value))
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::language_storage::ModuleId;
+
+    fn struct_tag(s: &str) -> StructTag {
+        StructTag::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_type_filter() {
+        assert!(TypeFilter::from_str("0x1").is_err());
+        assert!(TypeFilter::from_str("0x1::coin::CoinStore::extra").is_err());
+        assert!(TypeFilter::from_str("0x1::bad-module").is_err());
+
+        let module_filter = TypeFilter::from_str("0x1::coin").unwrap();
+        let struct_filter = TypeFilter::from_str("0x1::coin::CoinStore").unwrap();
+        let coin_store = Path::Resource(struct_tag(
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+        ));
+        let coin_info = Path::Resource(struct_tag("0x1::coin::CoinInfo"));
+        let code = Path::Code(ModuleId::new(
+            AccountAddress::ONE,
+            Identifier::new("coin").unwrap(),
+        ));
+        assert!(module_filter.matches(&coin_store, &[]));
+        assert!(module_filter.matches(&coin_info, &[]));
+        assert!(module_filter.matches(&code, &[]));
+        assert!(struct_filter.matches(&coin_store, &[]));
+        assert!(!struct_filter.matches(&coin_info, &[]));
+        assert!(!struct_filter.matches(&code, &[]));
+        assert!(!TypeFilter::from_str("0x2::coin")
+            .unwrap()
+            .matches(&coin_store, &[]));
+
+        let group = Path::ResourceGroup(struct_tag("0x1::object::ObjectGroup"));
+        let members: BTreeMap<StructTag, Vec<u8>> = [(
+            struct_tag("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"),
+            vec![],
+        )]
+        .into_iter()
+        .collect();
+        let group_value = bcs::to_bytes(&members).unwrap();
+        assert!(struct_filter.matches(&group, &group_value));
+        assert!(!struct_filter.matches(&group, &[]));
+        assert!(TypeFilter::from_str("0x1::object")
+            .unwrap()
+            .matches(&group, &[]));
+    }
+}
@@ -0,0 +1,689 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_debugger::common::DbDir,
+    ledger_db::LedgerDb,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema},
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        state_value::StateValueSchema,
+        state_value_by_key_hash::StateValueByKeyHashSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+    },
+    state_kv_db::StateKvDb,
+    state_merkle_db::StateMerkleDb,
+    utils::truncation_helper::{get_state_kv_commit_progress, get_state_merkle_commit_progress},
+    AptosDB,
+};
+use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
+use aptos_crypto::hash::CryptoHash;
+use aptos_jellyfish_merkle::node_type::NodeKey;
+use aptos_storage_interface::{AptosDbError, Result};
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{accumulator::InMemoryEventAccumulator, position::Position},
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use clap::Parser;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{fs, path::PathBuf};
+
+const BATCH_SIZE: u64 = 10_000;
+
+#[derive(Parser)]
+#[clap(
+    about = "Cross check the ledger DB, the state KV DB and the state merkle DB against each other \
+    and against the transaction accumulator in a version range, printing a JSON report of all \
+    inconsistencies found. Exits with an error if any is found."
+)]
+pub struct Cmd {
+    #[clap(flatten)]
+    db_dir: DbDir,
+
+    /// [Defaults to the first version not pruned from the ledger DB]
+    #[clap(long)]
+    start_version: Option<Version>,
+
+    /// [Defaults to all versions since the start version]
+    #[clap(long)]
+    num_versions: Option<u64>,
+
+    /// Stop checking once more than this many inconsistencies are found, reporting this many.
+    #[clap(long, default_value_t = 1000)]
+    max_inconsistencies: usize,
+
+    /// Write the report to this file instead of stdout.
+    #[clap(long, value_parser)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InconsistencyKind {
+    CommitProgress,
+    MissingTransaction,
+    TransactionHashMismatch,
+    MissingTransactionInfo,
+    AccumulatorLeafMismatch,
+    MissingWriteSet,
+    WriteSetHashMismatch,
+    EventRootHashMismatch,
+    MissingStateValue,
+    StateValueMismatch,
+    StateRootMismatch,
+    AccumulatorRootMismatch,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Inconsistency {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    pub kind: InconsistencyKind,
+    pub details: String,
+}
+
+impl Inconsistency {
+    fn new(version: Option<Version>, kind: InconsistencyKind, details: String) -> Self {
+        Self {
+            version,
+            kind,
+            details,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Progress {
+    pub overall_commit: Option<Version>,
+    pub ledger_commit: Option<Version>,
+    pub state_kv_commit: Option<Version>,
+    pub state_merkle_commit: Option<Version>,
+    pub latest_ledger_info: Option<Version>,
+    pub ledger_pruner: Version,
+    pub state_kv_pruner: Version,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub progress: Progress,
+    pub start_version: Version,
+    pub num_versions_checked: u64,
+    pub num_state_values_checked: u64,
+    pub num_state_roots_checked: u64,
+    pub num_ledger_infos_checked: u64,
+    /// Set if checking stopped early because too many inconsistencies were found.
+    pub truncated: bool,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+#[derive(Default)]
+struct VersionReport {
+    num_state_values_checked: u64,
+    state_root_checked: bool,
+    inconsistencies: Vec<Inconsistency>,
+}
+
+struct Dbs {
+    ledger_db: LedgerDb,
+    state_merkle_db: StateMerkleDb,
+    state_kv_db: StateKvDb,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let (ledger_db, state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(&self.db_dir),
+            RocksdbConfigs {
+                enable_storage_sharding: self.db_dir.sharding_config.enable_storage_sharding,
+                ..Default::default()
+            },
+            /*readonly=*/ true,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )?;
+        let dbs = Dbs {
+            ledger_db,
+            state_merkle_db,
+            state_kv_db,
+        };
+
+        let report = self.verify(&dbs)?;
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| AptosDbError::Other(e.to_string()))?;
+        match &self.output {
+            Some(path) => {
+                fs::write(path, json)?;
+                eprintln!("Report written to {:?}.", path);
+            },
+            None => println!("{}", json),
+        }
+
+        if report.inconsistencies.is_empty() {
+            Ok(())
+        } else {
+            Err(AptosDbError::Other(format!(
+                "Found {}{} inconsistencies.",
+                report.inconsistencies.len(),
+                if report.truncated { "+" } else { "" },
+            )))
+        }
+    }
+
+    fn verify(&self, dbs: &Dbs) -> Result<Report> {
+        let mut report = Report {
+            progress: Self::progress(dbs)?,
+            ..Default::default()
+        };
+        report.inconsistencies = Self::check_progress(&report.progress);
+
+        let end_version = match report.progress.overall_commit {
+            Some(version) => version + 1,
+            None => return Ok(report),
+        };
+        let start_version = self
+            .start_version
+            .unwrap_or(report.progress.ledger_pruner)
+            .max(report.progress.ledger_pruner);
+        let end_version = match self.num_versions {
+            Some(num_versions) => end_version.min(start_version.saturating_add(num_versions)),
+            None => end_version,
+        };
+        report.start_version = start_version;
+
+        let mut batch_start = start_version;
+        while batch_start < end_version {
+            let batch_end = end_version.min(batch_start + BATCH_SIZE);
+            let version_reports = (batch_start..batch_end)
+                .into_par_iter()
+                .map(|version| Self::check_version(dbs, &report.progress, version))
+                .collect::<Result<Vec<_>>>()?;
+            for version_report in version_reports {
+                report.num_state_values_checked += version_report.num_state_values_checked;
+                report.num_state_roots_checked += version_report.state_root_checked as u64;
+                report
+                    .inconsistencies
+                    .extend(version_report.inconsistencies);
+            }
+            report.num_versions_checked += batch_end - batch_start;
+            eprintln!("Checked until version {}.", batch_end - 1);
+
+            if report.inconsistencies.len() > self.max_inconsistencies {
+                report.inconsistencies.truncate(self.max_inconsistencies);
+                report.truncated = true;
+                return Ok(report);
+            }
+            batch_start = batch_end;
+        }
+
+        let (num_checked, inconsistencies) =
+            Self::check_ledger_infos(dbs, start_version, end_version)?;
+        report.num_ledger_infos_checked = num_checked;
+        report.inconsistencies.extend(inconsistencies);
+        if report.inconsistencies.len() > self.max_inconsistencies {
+            report.inconsistencies.truncate(self.max_inconsistencies);
+            report.truncated = true;
+        }
+
+        Ok(report)
+    }
+
+    fn progress(dbs: &Dbs) -> Result<Progress> {
+        let ledger_metadata_db = dbs.ledger_db.metadata_db();
+        Ok(Progress {
+            overall_commit: ledger_metadata_db.get_synced_version()?,
+            ledger_commit: ledger_metadata_db.get_ledger_commit_progress().ok(),
+            state_kv_commit: get_state_kv_commit_progress(&dbs.state_kv_db)?,
+            state_merkle_commit: get_state_merkle_commit_progress(&dbs.state_merkle_db)?,
+            latest_ledger_info: ledger_metadata_db
+                .get_latest_ledger_info_option()
+                .map(|li| li.ledger_info().version()),
+            ledger_pruner: ledger_metadata_db.get_pruner_progress().unwrap_or(0),
+            state_kv_pruner: dbs
+                .state_kv_db
+                .metadata_db()
+                .get::<DbMetadataSchema>(&DbMetadataKey::StateKvPrunerProgress)?
+                .map_or(0, |v| v.expect_version()),
+        })
+    }
+
+    /// Sub-DBs are committed before the overall progress is bumped, so none can be behind it.
+    fn check_progress(progress: &Progress) -> Vec<Inconsistency> {
+        let mut inconsistencies = Vec::new();
+        let overall = match progress.overall_commit {
+            Some(overall) => overall,
+            None => return inconsistencies,
+        };
+        for (name, sub_db_progress) in [
+            ("ledger", progress.ledger_commit),
+            ("state KV", progress.state_kv_commit),
+        ] {
+            if sub_db_progress.map_or(true, |p| p < overall) {
+                inconsistencies.push(Inconsistency::new(
+                    None,
+                    InconsistencyKind::CommitProgress,
+                    format!(
+                        "{} commit progress {:?} is behind the overall commit progress {}.",
+                        name, sub_db_progress, overall,
+                    ),
+                ));
+            }
+        }
+        if progress.latest_ledger_info.map_or(false, |v| v > overall) {
+            inconsistencies.push(Inconsistency::new(
+                None,
+                InconsistencyKind::CommitProgress,
+                format!(
+                    "Latest ledger info at version {:?} is ahead of the overall commit progress {}.",
+                    progress.latest_ledger_info, overall,
+                ),
+            ));
+        }
+        inconsistencies
+    }
+
+    fn check_version(dbs: &Dbs, progress: &Progress, version: Version) -> Result<VersionReport> {
+        use InconsistencyKind::*;
+
+        let mut report = VersionReport::default();
+        let ledger_db = &dbs.ledger_db;
+        let mut push = |kind, details| {
+            report
+                .inconsistencies
+                .push(Inconsistency::new(Some(version), kind, details))
+        };
+
+        let txn_info = match ledger_db
+            .transaction_info_db()
+            .get_transaction_info(version)
+        {
+            Ok(txn_info) => txn_info,
+            Err(e) => {
+                push(MissingTransactionInfo, e.to_string());
+                return Ok(report);
+            },
+        };
+
+        let leaf_hash = ledger_db
+            .transaction_accumulator_db_raw()
+            .get::<TransactionAccumulatorSchema>(&Position::from_leaf_index(version))?;
+        if leaf_hash != Some(txn_info.hash()) {
+            push(
+                AccumulatorLeafMismatch,
+                format!(
+                    "accumulator leaf: {:?}, transaction info hash: {}",
+                    leaf_hash,
+                    txn_info.hash()
+                ),
+            );
+        }
+
+        match ledger_db.transaction_db().get_transaction(version) {
+            Ok(txn) if txn.hash() != txn_info.transaction_hash() => push(
+                TransactionHashMismatch,
+                format!(
+                    "transaction hash: {}, in transaction info: {}",
+                    txn.hash(),
+                    txn_info.transaction_hash()
+                ),
+            ),
+            Ok(_) => (),
+            Err(e) => push(MissingTransaction, e.to_string()),
+        }
+
+        let event_hashes = ledger_db
+            .event_db()
+            .get_events_by_version(version)?
+            .iter()
+            .map(CryptoHash::hash)
+            .collect::<Vec<_>>();
+        let event_root_hash = InMemoryEventAccumulator::from_leaves(&event_hashes).root_hash();
+        if event_root_hash != txn_info.event_root_hash() {
+            push(
+                EventRootHashMismatch,
+                format!(
+                    "{} events with root hash {}, in transaction info: {}",
+                    event_hashes.len(),
+                    event_root_hash,
+                    txn_info.event_root_hash()
+                ),
+            );
+        }
+
+        let write_set = match ledger_db.write_set_db().get_write_set(version) {
+            Ok(write_set) => Some(write_set),
+            Err(e) => {
+                push(MissingWriteSet, e.to_string());
+                None
+            },
+        };
+        if let Some(write_set) = &write_set {
+            let write_set_hash = CryptoHash::hash(write_set);
+            if write_set_hash != txn_info.state_change_hash() {
+                push(
+                    WriteSetHashMismatch,
+                    format!(
+                        "write set hash: {}, in transaction info: {}",
+                        write_set_hash,
+                        txn_info.state_change_hash()
+                    ),
+                );
+            }
+        }
+
+        // Values older than the pruner progress can be gone if they are overwritten later.
+        let check_state_kv = version >= progress.state_kv_pruner
+            && progress.state_kv_commit.map_or(false, |v| version <= v);
+        if let (Some(write_set), true) = (&write_set, check_state_kv) {
+            for (key, expected) in write_set.state_update_refs() {
+                report.num_state_values_checked += 1;
+                match Self::get_state_value(&dbs.state_kv_db, key, version)? {
+                    None => push(
+                        MissingStateValue,
+                        format!("{:?} is in the write set but not in the state KV DB", key),
+                    ),
+                    Some(value) if value.as_ref() != expected => push(
+                        StateValueMismatch,
+                        format!(
+                            "{:?} in write set: {:?}, in state KV DB: {:?}",
+                            key, expected, value
+                        ),
+                    ),
+                    Some(_) => (),
+                }
+            }
+        }
+
+        // Only some of the checkpoints are persisted, and old ones can be pruned.
+        if let Some(expected_root) = txn_info.state_checkpoint_hash() {
+            let root_key = NodeKey::new_empty_path(version);
+            if dbs
+                .state_merkle_db
+                .metadata_db()
+                .get::<JellyfishMerkleNodeSchema>(&root_key)?
+                .is_some()
+            {
+                report.state_root_checked = true;
+                let root_hash = dbs.state_merkle_db.get_root_hash(version)?;
+                if root_hash != expected_root {
+                    push(
+                        StateRootMismatch,
+                        format!(
+                            "state merkle root: {}, state checkpoint hash in transaction info: {}",
+                            root_hash, expected_root
+                        ),
+                    );
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Returns `Some(None)` for a deletion.
+    fn get_state_value(
+        state_kv_db: &StateKvDb,
+        key: &StateKey,
+        version: Version,
+    ) -> Result<Option<Option<StateValue>>> {
+        let shard = state_kv_db.db_shard(key.get_shard_id());
+        if state_kv_db.enabled_sharding() {
+            shard.get::<StateValueByKeyHashSchema>(&(key.hash(), version))
+        } else {
+            shard.get::<StateValueSchema>(&(key.clone(), version))
+        }
+    }
+
+    /// Checks the accumulator root hashes against all epoch ending ledger infos and the latest
+    /// ledger info in the range.
+    fn check_ledger_infos(
+        dbs: &Dbs,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<(u64, Vec<Inconsistency>)> {
+        let ledger_metadata_db = dbs.ledger_db.metadata_db();
+        let latest_li = match ledger_metadata_db.get_latest_ledger_info_option() {
+            Some(li) => li,
+            None => return Ok((0, Vec::new())),
+        };
+        let start_epoch = if start_version > 0 {
+            ledger_metadata_db.get_epoch(start_version)?
+        } else {
+            0
+        };
+        let mut lis = ledger_metadata_db
+            .get_epoch_ending_ledger_info_iter(start_epoch, latest_li.ledger_info().epoch())?
+            .collect::<Result<Vec<LedgerInfoWithSignatures>>>()?;
+        lis.push(latest_li);
+
+        let mut num_checked = 0;
+        let mut inconsistencies = Vec::new();
+        for li in lis {
+            let li = li.ledger_info();
+            if li.version() < start_version || li.version() >= end_version {
+                continue;
+            }
+            num_checked += 1;
+            let root_hash = dbs
+                .ledger_db
+                .transaction_accumulator_db()
+                .get_root_hash(li.version())?;
+            if root_hash != li.transaction_accumulator_hash() {
+                inconsistencies.push(Inconsistency::new(
+                    Some(li.version()),
+                    InconsistencyKind::AccumulatorRootMismatch,
+                    format!(
+                        "accumulator root: {}, in ledger info of epoch {}: {}",
+                        root_hash,
+                        li.epoch(),
+                        li.transaction_accumulator_hash()
+                    ),
+                ));
+            }
+        }
+        Ok((num_checked, inconsistencies))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::test_helper::{arb_blocks_to_commit_with_block_nums, update_in_memory_state},
+        schema::{transaction::TransactionSchema, write_set::WriteSetSchema},
+    };
+    use aptos_config::config::DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD;
+    use aptos_crypto::HashValue;
+    use aptos_temppath::TempPath;
+    use aptos_types::transaction::Transaction;
+    use proptest::prelude::*;
+    use std::path::Path;
+
+    fn progress(
+        overall: Option<Version>,
+        ledger: Option<Version>,
+        state_kv: Option<Version>,
+        latest_li: Option<Version>,
+    ) -> Progress {
+        Progress {
+            overall_commit: overall,
+            ledger_commit: ledger,
+            state_kv_commit: state_kv,
+            latest_ledger_info: latest_li,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_progress() {
+        assert!(Cmd::check_progress(&progress(None, None, None, None)).is_empty());
+        assert!(Cmd::check_progress(&progress(Some(10), Some(10), Some(12), Some(9))).is_empty());
+
+        let inconsistencies = Cmd::check_progress(&progress(Some(10), Some(9), None, Some(11)));
+        assert_eq!(inconsistencies.len(), 3);
+        assert!(inconsistencies
+            .iter()
+            .all(|i| i.kind == InconsistencyKind::CommitProgress && i.version.is_none()));
+    }
+
+    #[test]
+    fn test_report_json() {
+        let report = Report {
+            inconsistencies: vec![Inconsistency::new(
+                Some(5),
+                InconsistencyKind::WriteSetHashMismatch,
+                "details".to_string(),
+            )],
+            ..Default::default()
+        };
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json["inconsistencies"][0]["kind"],
+            serde_json::json!("write_set_hash_mismatch")
+        );
+        assert_eq!(json["inconsistencies"][0]["version"], serde_json::json!(5));
+    }
+
+    fn open_dbs(db_dir: &Path, enable_storage_sharding: bool, readonly: bool) -> Dbs {
+        let (ledger_db, state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(db_dir),
+            RocksdbConfigs {
+                enable_storage_sharding,
+                ..Default::default()
+            },
+            readonly,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )
+        .unwrap();
+        Dbs {
+            ledger_db,
+            state_merkle_db,
+            state_kv_db,
+        }
+    }
+
+    fn versions_and_kinds(report: &Report) -> Vec<(Option<Version>, InconsistencyKind)> {
+        report
+            .inconsistencies
+            .iter()
+            .map(|i| (i.version, i.kind))
+            .collect()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1))]
+
+        #[test]
+        fn test_verify(input in arb_blocks_to_commit_with_block_nums(5, 10)) {
+            let (blocks, enable_storage_sharding) = input;
+            let tmp_dir = TempPath::new();
+            let db = if enable_storage_sharding {
+                AptosDB::new_for_test_with_sharding(
+                    &tmp_dir,
+                    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+                )
+            } else {
+                AptosDB::new_for_test(&tmp_dir)
+            };
+            let mut in_memory_state = db.state_store.current_state_cloned();
+            let _ancestor = in_memory_state.base.clone();
+            let mut version = 0;
+            for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+                update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+                db.save_transactions_for_test(
+                    txns_to_commit,
+                    version,
+                    version.checked_sub(1),
+                    Some(ledger_info_with_sigs),
+                    true,
+                    &in_memory_state,
+                )
+                .unwrap();
+                version += txns_to_commit.len() as u64;
+            }
+            drop(db);
+
+            let cmd = |args: &[&str]| {
+                let mut all_args = vec!["verify-all", "--db-dir", tmp_dir.path().to_str().unwrap()];
+                if enable_storage_sharding {
+                    all_args.push("--enable-storage-sharding");
+                }
+                all_args.extend_from_slice(args);
+                Cmd::try_parse_from(all_args).unwrap()
+            };
+            let verify = |args: &[&str]| {
+                cmd(args)
+                    .verify(&open_dbs(tmp_dir.path(), enable_storage_sharding, true))
+                    .unwrap()
+            };
+
+            // A consistent DB.
+            let report = verify(&[]);
+            prop_assert!(report.inconsistencies.is_empty(), "{:?}", report.inconsistencies);
+            prop_assert!(!report.truncated);
+            prop_assert_eq!(report.progress.overall_commit, Some(version - 1));
+            prop_assert_eq!(report.start_version, 0);
+            prop_assert_eq!(report.num_versions_checked, version);
+            let num_state_values: usize = blocks
+                .iter()
+                .flat_map(|(txns_to_commit, _)| txns_to_commit)
+                .map(|txn| txn.write_set().state_update_refs().count())
+                .sum();
+            prop_assert_eq!(report.num_state_values_checked, num_state_values as u64);
+            prop_assert!(report.num_state_roots_checked > 0);
+            // The epoch ending ledger infos and the latest one, which can be one of them.
+            let num_epoch_endings = blocks[..blocks.len() - 1]
+                .iter()
+                .filter(|(_, li)| li.ledger_info().ends_epoch())
+                .count();
+            prop_assert_eq!(report.num_ledger_infos_checked, num_epoch_endings as u64 + 1);
+            cmd(&[]).run().unwrap();
+
+            // Drop the write set at version 1 and replace the transaction at version 2.
+            {
+                let dbs = open_dbs(tmp_dir.path(), enable_storage_sharding, false);
+                dbs.ledger_db.write_set_db_raw().delete::<WriteSetSchema>(&1).unwrap();
+                let txn = Transaction::StateCheckpoint(HashValue::random());
+                dbs.ledger_db
+                    .transaction_db_raw()
+                    .put::<TransactionSchema>(&2, &txn)
+                    .unwrap();
+            }
+            let report = verify(&[]);
+            prop_assert_eq!(versions_and_kinds(&report), vec![
+                (Some(1), InconsistencyKind::MissingWriteSet),
+                (Some(2), InconsistencyKind::TransactionHashMismatch),
+            ]);
+            prop_assert!(!report.truncated);
+            prop_assert!(cmd(&[]).run().is_err());
+
+            // The report is limited to the versions asked for, and to the max inconsistencies.
+            let report = verify(&["--start-version", "2", "--num-versions", "2"]);
+            prop_assert_eq!(report.start_version, 2);
+            prop_assert_eq!(report.num_versions_checked, 2);
+            prop_assert_eq!(versions_and_kinds(&report), vec![
+                (Some(2), InconsistencyKind::TransactionHashMismatch),
+            ]);
+            let report = verify(&["--start-version", "3"]);
+            prop_assert!(report.inconsistencies.is_empty(), "{:?}", report.inconsistencies);
+            let report = verify(&["--max-inconsistencies", "1"]);
+            prop_assert!(report.truncated);
+            prop_assert_eq!(versions_and_kinds(&report), vec![
+                (Some(1), InconsistencyKind::MissingWriteSet),
+            ]);
+
+            // The report is written to the output file.
+            let output = TempPath::new();
+            let output_arg = output.path().to_str().unwrap();
+            prop_assert!(cmd(&["--output", output_arg]).run().is_err());
+            let json: serde_json::Value =
+                serde_json::from_slice(&fs::read(output.path()).unwrap()).unwrap();
+            prop_assert_eq!(
+                &json["inconsistencies"][0]["kind"],
+                &serde_json::json!("missing_write_set")
+            );
+            prop_assert_eq!(&json["inconsistencies"][1]["version"], &serde_json::json!(2));
+        }
+    }
+}
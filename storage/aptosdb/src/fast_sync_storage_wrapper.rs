@@ -60,6 +60,14 @@ impl FastSyncStorageWrapper {
         if let Some(sender) = update_sender {
             db_main.add_version_update_subscriber(sender)?;
         }
+        if let Some(partial_state_filter) = config
+            .state_sync
+            .state_sync_driver
+            .partial_state_sync
+            .create_filter()?
+        {
+            db_main.set_partial_state_filter(partial_state_filter);
+        }
 
         let mut db_dir = config.storage.dir();
         // when the db is empty and configured to do fast sync, we will create a second DB
@@ -74,6 +74,7 @@ fn put_value_set(
             &sharded_state_kv_batches,
             enable_sharding,
             /*last_checkpoint_index=*/ None,
+            /*partial_state_filter=*/ None,
         )
         .unwrap();
     state_store
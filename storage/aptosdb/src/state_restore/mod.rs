@@ -45,7 +45,7 @@ pub trait StateValueWriter<K, V>: Send + Sync {
     fn get_progress(&self, version: Version) -> Result<Option<StateSnapshotProgress>>;
 }
 
-#[derive(Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub enum StateSnapshotRestoreMode {
     /// Restore both KV and Tree by default
     Default,
@@ -68,6 +68,7 @@ use aptos_storage_interface::{
 use aptos_types::{
     proof::{definition::LeafCount, SparseMerkleProofExt, SparseMerkleRangeProof},
     state_store::{
+        partial_state::PartialStateFilter,
         state_key::{prefix::StateKeyPrefix, StateKey},
         state_storage_usage::StateStorageUsage,
         state_value::{
@@ -708,6 +709,7 @@ impl StateStore {
             sharded_state_kv_batches,
             enable_sharding,
             None, // last_checkpoint_index
+            None, // partial_state_filter
         )
     }
 
@@ -722,6 +724,7 @@ impl StateStore {
         sharded_state_kv_batches: &ShardedStateKvSchemaBatch,
         enable_sharding: bool,
         last_checkpoint_index: Option<usize>,
+        partial_state_filter: Option<&PartialStateFilter>,
     ) -> Result<()> {
         let _timer = OTHER_TIMERS_SECONDS.timer_with(&["put_value_sets"]);
 
@@ -734,6 +737,7 @@ impl StateStore {
             sharded_state_kv_batches,
             last_checkpoint_index,
             enable_sharding,
+            partial_state_filter,
         )?;
 
         self.put_state_values(
@@ -741,15 +745,19 @@ impl StateStore {
             state_update_refs,
             sharded_state_kv_batches,
             enable_sharding,
+            partial_state_filter,
         )
     }
 
+    /// Puts the state values into the batches. If a partial state filter
+    /// is given, only the values of the tracked state keys are persisted.
     pub fn put_state_values(
         &self,
         first_version: Version,
         state_update_refs: &ShardedStateUpdateRefs,
         sharded_state_kv_batches: &ShardedStateKvSchemaBatch,
         enable_sharding: bool,
+        partial_state_filter: Option<&PartialStateFilter>,
     ) -> Result<()> {
         let _timer = OTHER_TIMERS_SECONDS.timer_with(&["add_state_kv_batch"]);
 
@@ -758,17 +766,20 @@ impl StateStore {
             .par_iter()
             .zip_eq(state_update_refs.shards.par_iter())
             .try_for_each(|(batch, updates)| {
-                updates.iter().try_for_each(|(idx, key, val)| {
-                    let ver = first_version + *idx as Version;
-                    if enable_sharding {
-                        batch.put::<StateValueByKeyHashSchema>(
-                            &(CryptoHash::hash(*key), ver),
-                            &val.cloned(),
-                        )
-                    } else {
-                        batch.put::<StateValueSchema>(&((*key).clone(), ver), &val.cloned())
-                    }
-                })
+                updates
+                    .iter()
+                    .filter(|(_idx, key, _val)| is_tracked(partial_state_filter, key))
+                    .try_for_each(|(idx, key, val)| {
+                        let ver = first_version + *idx as Version;
+                        if enable_sharding {
+                            batch.put::<StateValueByKeyHashSchema>(
+                                &(CryptoHash::hash(*key), ver),
+                                &val.cloned(),
+                            )
+                        } else {
+                            batch.put::<StateValueSchema>(&((*key).clone(), ver), &val.cloned())
+                        }
+                    })
             })
     }
 
@@ -800,6 +811,7 @@ impl StateStore {
         sharded_state_kv_batches: &ShardedStateKvSchemaBatch,
         last_checkpoint_index: Option<usize>,
         enable_sharding: bool,
+        partial_state_filter: Option<&PartialStateFilter>,
     ) -> Result<()> {
         let _timer = OTHER_TIMERS_SECONDS.timer_with(&["put_stats_and_indices"]);
 
@@ -813,7 +825,11 @@ impl StateStore {
             if let Some(sharded_state_cache) = sharded_state_cache {
                 // For some entries the base value version is None, here is to fiil those in.
                 // See `ShardedStateCache`.
-                self.prepare_version_in_cache(base_version, sharded_state_cache)?;
+                self.prepare_version_in_cache(
+                    base_version,
+                    sharded_state_cache,
+                    partial_state_filter,
+                )?;
                 state_cache_with_version = sharded_state_cache;
             } else {
                 // TODO(aldenhu): get all updates from StateDelta directly
@@ -857,6 +873,7 @@ impl StateStore {
             enable_sharding,
             &mut state_cache_with_version,
             expected_usage.is_untracked() || base_version.is_none(), // ignore_state_cache_miss
+            partial_state_filter,
         );
 
         {
@@ -906,6 +923,7 @@ impl StateStore {
         enable_sharding: bool,
         sharded_state_cache: &mut &ShardedStateCache,
         ignore_state_cache_miss: bool,
+        partial_state_filter: Option<&PartialStateFilter>,
     ) -> Vec<Vec<(i64, i64)>> {
         let _timer = OTHER_TIMERS_SECONDS.timer_with(&["put_stale_kv_index"]);
         let num_versions = state_update_refs.num_versions;
@@ -930,10 +948,14 @@ impl StateStore {
                     let mut bytes_delta = 0;
 
                     for (_idx, key, value) in ver_iter {
+                        // The values of untracked keys are never persisted, so they need no
+                        // stale indices (there is nothing to prune).
+                        let key_tracked = is_tracked(partial_state_filter, key);
+
                         if let Some(value) = value {
                             items_delta += 1;
                             bytes_delta += (key.size() + value.size()) as i64;
-                        } else {
+                        } else if key_tracked {
                             // Update the stale index of the tombstone at current version to
                             // current version.
                             if enable_sharding {
@@ -974,11 +996,15 @@ impl StateStore {
                         };
 
                         if let Some((old_version, old_value)) = old_version_and_value_opt {
+                            items_delta -= 1;
+                            bytes_delta -= (key.size() + old_value.size()) as i64;
+                            if !key_tracked {
+                                continue;
+                            }
+
                             let old_version = old_version
                                 .context("Must have old version in cache.")
                                 .unwrap();
-                            items_delta -= 1;
-                            bytes_delta -= (key.size() + old_value.size()) as i64;
                             // stale index of the old value at its version.
                             if enable_sharding {
                                 batch
@@ -1138,15 +1164,33 @@ impl StateStore {
         self: &Arc<Self>,
         version: Version,
         expected_root_hash: HashValue,
+        partial_state_filter: Option<PartialStateFilter>,
     ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
-        Ok(Box::new(StateSnapshotRestore::new(
-            &self.state_merkle_db,
-            self,
-            version,
-            expected_root_hash,
-            false, /* async_commit */
-            StateSnapshotRestoreMode::Default,
-        )?))
+        match partial_state_filter {
+            Some(partial_state_filter) => {
+                // The full tree is restored (and verified), but only the tracked values are kept
+                let value_store = Arc::new(PartialStateValueWriter {
+                    state_store: Arc::clone(self),
+                    partial_state_filter,
+                });
+                Ok(Box::new(StateSnapshotRestore::new(
+                    &self.state_merkle_db,
+                    &value_store,
+                    version,
+                    expected_root_hash,
+                    false, /* async_commit */
+                    StateSnapshotRestoreMode::Default,
+                )?))
+            },
+            None => Ok(Box::new(StateSnapshotRestore::new(
+                &self.state_merkle_db,
+                self,
+                version,
+                expected_root_hash,
+                false, /* async_commit */
+                StateSnapshotRestoreMode::Default,
+            )?)),
+        }
     }
 
     #[cfg(test)]
@@ -1191,25 +1235,32 @@ impl StateStore {
         &self,
         base_version: Version,
         sharded_state_cache: &ShardedStateCache,
+        partial_state_filter: Option<&PartialStateFilter>,
     ) -> Result<()> {
         THREAD_MANAGER.get_high_pri_io_pool().scope(|s| {
             sharded_state_cache.par_iter().for_each(|shard| {
                 shard.iter_mut().for_each(|mut entry| {
                     match entry.value() {
-                        (None, Some(_)) => s.spawn(move |_| {
-                            let _timer = OTHER_TIMERS_SECONDS
-                                .with_label_values(&["put_stats_and_indices__get_state_value"])
-                                .start_timer();
-                            let version_and_value = self
-                                .state_db
-                                .get_state_value_with_version_by_version(entry.key(), base_version)
-                                .expect("Must succeed.");
-                            if let Some((version, _)) = version_and_value {
-                                entry.0 = Some(version);
-                            } else {
-                                unreachable!();
-                            }
-                        }),
+                        // The values of untracked keys are not persisted, so their versions
+                        // cannot be found (nor are they needed, see `put_stale_state_value_index`).
+                        (None, Some(_)) if is_tracked(partial_state_filter, entry.key()) => s
+                            .spawn(move |_| {
+                                let _timer = OTHER_TIMERS_SECONDS
+                                    .with_label_values(&["put_stats_and_indices__get_state_value"])
+                                    .start_timer();
+                                let version_and_value = self
+                                    .state_db
+                                    .get_state_value_with_version_by_version(
+                                        entry.key(),
+                                        base_version,
+                                    )
+                                    .expect("Must succeed.");
+                                if let Some((version, _)) = version_and_value {
+                                    entry.0 = Some(version);
+                                } else {
+                                    unreachable!();
+                                }
+                            }),
                         _ => {
                             // I just want a counter.
                             let _timer = OTHER_TIMERS_SECONDS
@@ -1225,6 +1276,43 @@ impl StateStore {
     }
 }
 
+/// Returns true iff the value of the given state key should be persisted
+fn is_tracked(partial_state_filter: Option<&PartialStateFilter>, state_key: &StateKey) -> bool {
+    partial_state_filter.map_or(true, |filter| filter.is_tracked(state_key))
+}
+
+/// A state value writer used to restore state snapshots for partial state sync.
+/// Only the values tracked by the filter are written, the rest are discarded.
+struct PartialStateValueWriter {
+    state_store: Arc<StateStore>,
+    partial_state_filter: PartialStateFilter,
+}
+
+impl StateValueWriter<StateKey, StateValue> for PartialStateValueWriter {
+    fn write_kv_batch(
+        &self,
+        version: Version,
+        node_batch: &StateValueBatch,
+        progress: StateSnapshotProgress,
+    ) -> Result<()> {
+        let tracked_batch = node_batch
+            .iter()
+            .filter(|((state_key, _), _)| self.partial_state_filter.is_tracked(state_key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        self.state_store
+            .write_kv_batch(version, &tracked_batch, progress)
+    }
+
+    fn kv_finish(&self, version: Version, usage: StateStorageUsage) -> Result<()> {
+        self.state_store.kv_finish(version, usage)
+    }
+
+    fn get_progress(&self, version: Version) -> Result<Option<StateSnapshotProgress>> {
+        self.state_store.get_progress(version)
+    }
+}
+
 impl StateValueWriter<StateKey, StateValue> for StateStore {
     // This already turns on sharded KV
     fn write_kv_batch(
@@ -22,7 +22,7 @@ use aptos_types::{
     account_address::AccountAddress,
     account_config::{AccountResource, ChainIdResource, CoinInfoResource, CoinStoreResource},
     nibble::nibble_path::NibblePath,
-    state_store::state_key::inner::StateKeyTag,
+    state_store::state_key::inner::{StateKeyInner, StateKeyTag},
     AptosCoinType,
 };
 use arr_macro::arr;
@@ -61,6 +61,7 @@ fn put_value_set(
             &sharded_state_kv_batches,
             /*put_state_value_indices=*/ false,
             /*last_checkpoint_index=*/ None,
+            /*partial_state_filter=*/ None,
         )
         .unwrap();
     state_store
@@ -447,7 +448,7 @@ proptest! {
         let db2 = AptosDB::new_for_test(&tmp_dir2);
         let store2 = &db2.state_store;
 
-        let mut restore = store2.get_snapshot_receiver(version, expected_root_hash).unwrap();
+        let mut restore = store2.get_snapshot_receiver(version, expected_root_hash, None).unwrap();
         let mut current_idx = 0;
         while current_idx < input.len() {
             let chunk = store1.get_value_chunk_with_proof(version, current_idx, batch_size).unwrap();
@@ -464,6 +465,74 @@ proptest! {
         );
     }
 
+    #[test]
+    fn test_partial_restore(
+        (input, batch_size) in hash_map(any::<StateKey>(), any::<StateValue>(), 2..1000)
+            .prop_flat_map(|input| {
+                let len = input.len();
+                (Just(input), 1..len*2)
+            })
+    ) {
+        let tmp_dir1 = TempPath::new();
+        let db1 = AptosDB::new_for_test(&tmp_dir1);
+        let store1 = &db1.state_store;
+        init_store(store1, input.clone().into_iter());
+
+        let version = (input.len() - 1) as Version;
+        let expected_root_hash = store1.get_root_hash(version).unwrap();
+
+        // Track the state of the first half of the keys
+        let tracked_accounts: Vec<_> = input
+            .keys()
+            .take(input.len() / 2)
+            .filter_map(|state_key| match state_key.inner() {
+                StateKeyInner::AccessPath(access_path) => Some(access_path.address),
+                _ => None,
+            })
+            .collect();
+        let partial_state_filter = PartialStateFilter::new(tracked_accounts, vec![]);
+
+        let tmp_dir2 = TempPath::new();
+        let mut db2 = AptosDB::new_for_test(&tmp_dir2);
+        let store2 = &db2.state_store;
+
+        let mut restore = store2
+            .get_snapshot_receiver(version, expected_root_hash, Some(partial_state_filter.clone()))
+            .unwrap();
+        let mut current_idx = 0;
+        while current_idx < input.len() {
+            let chunk = store1.get_value_chunk_with_proof(version, current_idx, batch_size).unwrap();
+            restore.add_chunk(chunk.raw_values, chunk.proof).unwrap();
+            current_idx += batch_size;
+        }
+        restore.finish_box().unwrap();
+
+        // Verify that the full tree was restored, but only the tracked values were persisted
+        let actual_root_hash = store2.get_root_hash(version).unwrap();
+        prop_assert_eq!(actual_root_hash, expected_root_hash);
+        for (state_key, state_value) in &input {
+            let persisted_value = store2.get_state_value_by_version(state_key, version).unwrap();
+            if partial_state_filter.is_tracked(state_key) {
+                prop_assert_eq!(persisted_value, Some(state_value.clone()));
+            } else {
+                prop_assert_eq!(persisted_value, None);
+            }
+        }
+
+        // Verify that reading the untracked values (or all values) fails once the filter is set
+        db2.set_partial_state_filter(partial_state_filter.clone());
+        prop_assert!(db2.is_partial_state_enabled().unwrap());
+        for (state_key, state_value) in input {
+            let result = db2.get_state_value_by_version(&state_key, version);
+            if partial_state_filter.is_tracked(&state_key) {
+                prop_assert_eq!(result.unwrap(), Some(state_value));
+            } else {
+                prop_assert!(result.is_err());
+            }
+        }
+        prop_assert!(db2.get_state_value_chunk_with_proof(version, 0, batch_size).is_err());
+    }
+
     #[test]
     fn test_get_rightmost_leaf_with_sharding(
         (input, batch1_size) in hash_map(any::<StateKey>(), any::<StateValue>(), 2..1000)
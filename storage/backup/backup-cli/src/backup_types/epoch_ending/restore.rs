@@ -10,8 +10,8 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle, FileHandleRef},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
-        GlobalRestoreOptions, RestoreRunMode,
+        read_record_bytes::ReadRecordBytes, restore_journal::RestoreJournal,
+        storage_ext::BackupStorageExt, stream::StreamX, GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -38,6 +38,7 @@ pub struct EpochEndingRestoreController {
     manifest_handle: FileHandle,
     target_version: Version,
     trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
+    journal: Arc<RestoreJournal>,
 }
 
 impl EpochEndingRestoreController {
@@ -52,6 +53,7 @@ impl EpochEndingRestoreController {
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
             trusted_waypoints: global_opt.trusted_waypoints,
+            journal: global_opt.journal,
         }
     }
 
@@ -247,7 +249,36 @@ impl PreheatedEpochEndingRestore {
             .ledger_info();
         match self.controller.run_mode.as_ref() {
             RestoreRunMode::Restore { restore_handler } => {
-                restore_handler.save_ledger_infos(&preheat_data.ledger_infos)?;
+                let journal = &self.controller.journal;
+                let manifest_handle = &self.controller.manifest_handle;
+                // Still verified above, since the epoch history is needed anyway.
+                let journaled = journal
+                    .epoch_ending_saved_epoch(manifest_handle)
+                    .map_or(false, |epoch| epoch >= last_li.epoch());
+                // The ledger infos are saved in one batch, so the last one being in the DB means
+                // all of them are. The journal alone isn't trusted, since it can be from another
+                // run or outlive a wiped DB.
+                let saved = journaled
+                    && restore_handler
+                        .get_epoch_ending_ledger_info_of_epoch(last_li.epoch())?
+                        .map_or(false, |li| li.ledger_info() == last_li);
+                if saved {
+                    info!(
+                        manifest = manifest_handle,
+                        "Epoch ending ledger infos already saved, skipping."
+                    );
+                } else {
+                    if journaled {
+                        warn!(
+                            manifest = manifest_handle,
+                            "Restore journal disagrees with the DB, saving the ledger infos again."
+                        );
+                    }
+                    restore_handler.save_ledger_infos(&preheat_data.ledger_infos)?;
+                    journal
+                        .record_epoch_ending_saved(manifest_handle, last_li.epoch())
+                        .await?;
+                }
 
                 EPOCH_ENDING_EPOCH.set(last_li.epoch() as i64);
                 EPOCH_ENDING_VERSION.set(last_li.version() as i64);
@@ -369,8 +400,8 @@ impl EpochHistoryRestoreController {
             .preheat()
         });
         let mut futs_stream = futures::stream::iter(futs_iter).buffered_x(
-            self.global_opt.concurrent_downloads * 2, /* buffer size */
-            self.global_opt.concurrent_downloads,     /* concurrency */
+            self.global_opt.concurrent_chunks,    /* buffer size */
+            self.global_opt.concurrent_downloads, /* concurrency */
         );
 
         let mut next_epoch = 0u64;
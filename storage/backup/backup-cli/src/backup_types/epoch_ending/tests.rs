@@ -88,6 +88,8 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                concurrent_chunks: None,
+                restore_journal: None,
                 enable_state_indices: false,
             }
             .try_into()
@@ -222,6 +224,8 @@ async fn test_trusted_waypoints_impl(
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: ConcurrentDownloadsOpt::default(),
             replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            concurrent_chunks: None,
+            restore_journal: None,
             enable_state_indices: false,
         }
         .try_into()
@@ -244,6 +248,8 @@ async fn test_trusted_waypoints_impl(
             rocksdb_opt: RocksdbOpt::default(),
             concurrent_downloads: ConcurrentDownloadsOpt::default(),
             replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+            concurrent_chunks: None,
+            restore_journal: None,
             enable_state_indices: false,
         }
         .try_into()
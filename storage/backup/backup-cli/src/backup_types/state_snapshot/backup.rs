@@ -26,7 +26,16 @@ use bytes::{BufMut, Bytes, BytesMut};
 use clap::Parser;
 use futures::{StreamExt, TryStream, TryStreamExt};
 use once_cell::sync::Lazy;
-use std::{convert::TryInto, str::FromStr, sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 use tokio::{io::AsyncWriteExt, sync::mpsc::Sender};
 use tokio_stream::wrappers::ReceiverStream;
 
@@ -37,6 +46,22 @@ pub struct StateSnapshotBackupOpt {
         help = "Epoch at the end of which a state snapshot is to be taken."
     )]
     pub epoch: u64,
+    #[clap(
+        long,
+        help = "Take the state snapshot incrementally: chunks are cut at boundaries determined by \
+        the content, so a change in the state affects only the chunks around it, and the content \
+        hash of each chunk is recorded in the manifest. Combined with \
+        `--incremental-base-manifest`, chunks identical to ones in the base are referred to \
+        instead of written again."
+    )]
+    pub incremental: bool,
+    #[clap(
+        long,
+        requires = "incremental",
+        help = "Manifest of an earlier incremental state snapshot backup in the same storage, \
+        whose chunks can be reused."
+    )]
+    pub incremental_base_manifest: Option<FileHandle>,
 }
 
 struct Chunk {
@@ -55,13 +80,25 @@ struct ChunkerState<RecordStream> {
     current_idx: usize,
     chunk_first_idx: usize,
     max_chunk_size: usize,
+    content_defined: bool,
 }
 
 impl<RecordStream> ChunkerState<RecordStream>
 where
     RecordStream: TryStream<Ok = Bytes, Error = anyhow::Error> + Unpin,
 {
-    async fn new(mut record_stream: RecordStream, max_chunk_size: usize) -> Result<Self> {
+    /// With content defined chunking, a chunk is also cut before a record whose key hash has the
+    /// `CDC_BOUNDARY_MASK` bits of its last byte all zero (1 in 64 keys), provided the chunk is already at least
+    /// `max_chunk_size / CDC_MIN_CHUNK_SIZE_DIVISOR` bytes. Keys are uniformly distributed, so
+    /// boundaries are stable across snapshots except around where the state changed.
+    const CDC_BOUNDARY_MASK: u8 = 0x3F;
+    const CDC_MIN_CHUNK_SIZE_DIVISOR: usize = 4;
+
+    async fn new(
+        mut record_stream: RecordStream,
+        max_chunk_size: usize,
+        content_defined: bool,
+    ) -> Result<Self> {
         let first_record = record_stream
             .try_next()
             .await?
@@ -82,6 +119,7 @@ where
             current_idx: 0,
             chunk_first_idx: 0,
             max_chunk_size,
+            content_defined,
         })
     }
 
@@ -96,26 +134,32 @@ where
         while let Some(record_bytes) = input.try_next().await? {
             let _timer = BACKUP_TIMER.timer_with(&["state_snapshot_process_records"]);
 
-            // If buf + current_record exceeds max_chunk_size, dump current buf to a new chunk
-            let chunk_cut_opt = should_cut_chunk(&self.buf, &record_bytes, self.max_chunk_size)
-                .then(|| {
-                    let bytes = self.buf.split().freeze();
-                    let last_key = Self::parse_key(&bytes[bytes.len() - self.prev_record_len..])?;
-
-                    let chunk = Chunk {
-                        bytes,
-                        first_key: self.chunk_first_key,
-                        first_idx: self.chunk_first_idx,
-                        last_key,
-                        last_idx: self.current_idx,
-                    };
-
-                    self.chunk_first_idx = self.current_idx + 1;
-                    self.chunk_first_key = Self::parse_key(&record_bytes)?;
-
-                    Result::<_>::Ok(chunk)
-                })
-                .transpose()?;
+            // If buf + current_record exceeds max_chunk_size, or current_record is at a content
+            // defined boundary, dump current buf to a new chunk
+            let chunk_cut_opt = Self::should_cut(
+                &self.buf,
+                &record_bytes,
+                self.max_chunk_size,
+                self.content_defined,
+            )?
+            .then(|| {
+                let bytes = self.buf.split().freeze();
+                let last_key = Self::parse_key(&bytes[bytes.len() - self.prev_record_len..])?;
+
+                let chunk = Chunk {
+                    bytes,
+                    first_key: self.chunk_first_key,
+                    first_idx: self.chunk_first_idx,
+                    last_key,
+                    last_idx: self.current_idx,
+                };
+
+                self.chunk_first_idx = self.current_idx + 1;
+                self.chunk_first_key = Self::parse_key(&record_bytes)?;
+
+                Result::<_>::Ok(chunk)
+            })
+            .transpose()?;
 
             // Append record to buf
             self.prev_record_len = record_bytes.len();
@@ -145,6 +189,7 @@ where
             current_idx,
             chunk_first_idx,
             max_chunk_size: _,
+            content_defined: _,
         } = self;
         ensure!(
             state_snapshot_file.is_none(),
@@ -164,6 +209,24 @@ where
         })
     }
 
+    fn should_cut(
+        buf: &[u8],
+        record_bytes: &[u8],
+        max_chunk_size: usize,
+        content_defined: bool,
+    ) -> Result<bool> {
+        if should_cut_chunk(buf, record_bytes, max_chunk_size) {
+            return Ok(true);
+        }
+        Ok(content_defined
+            && buf.len() >= max_chunk_size / Self::CDC_MIN_CHUNK_SIZE_DIVISOR
+            && Self::is_boundary_key(&Self::parse_key(record_bytes)?))
+    }
+
+    fn is_boundary_key(key: &HashValue) -> bool {
+        key.as_ref()[HashValue::LENGTH - 1] & Self::CDC_BOUNDARY_MASK == 0
+    }
+
     fn parse_key(record: &[u8]) -> Result<HashValue> {
         let (key, _): (StateKey, StateValue) = bcs::from_bytes(record)?;
         Ok(key.hash())
@@ -178,9 +241,13 @@ impl<RecordStream> Chunker<RecordStream>
 where
     RecordStream: TryStream<Ok = Bytes, Error = anyhow::Error> + Unpin,
 {
-    async fn new(record_stream: RecordStream, max_chunk_size: usize) -> Result<Self> {
+    async fn new(
+        record_stream: RecordStream,
+        max_chunk_size: usize,
+        content_defined: bool,
+    ) -> Result<Self> {
         Ok(Self {
-            state: Some(ChunkerState::new(record_stream, max_chunk_size).await?),
+            state: Some(ChunkerState::new(record_stream, max_chunk_size, content_defined).await?),
         })
     }
 
@@ -204,6 +271,11 @@ pub struct StateSnapshotBackupController {
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
     concurrent_data_requests: usize,
+    incremental: bool,
+    base_manifest: Option<FileHandle>,
+    /// Content hash -> `blobs` file of chunks in the base, initialized before using.
+    base_chunks: HashMap<HashValue, FileHandle>,
+    num_reused_chunks: AtomicUsize,
 }
 
 impl StateSnapshotBackupController {
@@ -220,6 +292,10 @@ impl StateSnapshotBackupController {
             client,
             storage,
             concurrent_data_requests: global_opt.concurrent_data_requests,
+            incremental: opt.incremental,
+            base_manifest: opt.incremental_base_manifest,
+            base_chunks: HashMap::new(),
+            num_reused_chunks: AtomicUsize::new(0),
         }
     }
 
@@ -235,13 +311,16 @@ impl StateSnapshotBackupController {
 
     async fn run_impl(mut self) -> Result<FileHandle> {
         self.version = Some(self.get_version_for_epoch_ending(self.epoch).await?);
+        if let Some(base_manifest) = &self.base_manifest {
+            self.base_chunks = self.load_base_chunks(base_manifest).await?;
+        }
         let backup_handle = self
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
 
         let record_stream = Box::pin(self.record_stream(self.concurrent_data_requests).await?);
-        let chunker = Chunker::new(record_stream, self.max_chunk_size).await?;
+        let chunker = Chunker::new(record_stream, self.max_chunk_size, self.incremental).await?;
 
         let start = Instant::now();
         let chunk_stream = futures::stream::try_unfold(chunker, |mut chunker| async {
@@ -265,10 +344,43 @@ impl StateSnapshotBackupController {
             })
             .try_collect()
             .await?;
+        if self.incremental {
+            info!(
+                total_chunks = chunks.len(),
+                reused_chunks = self.num_reused_chunks.load(Ordering::Relaxed),
+                base_manifest = ?self.base_manifest,
+                "Incremental state snapshot chunks written."
+            );
+        }
 
         self.write_manifest(&backup_handle, chunks).await
     }
 
+    async fn load_base_chunks(
+        &self,
+        base_manifest: &FileHandle,
+    ) -> Result<HashMap<HashValue, FileHandle>> {
+        let base: StateSnapshotBackup = self.storage.load_json_file(base_manifest).await?;
+        ensure!(
+            base.version <= self.version(),
+            "Base state snapshot at version {} is newer than the one to take at version {}.",
+            base.version,
+            self.version(),
+        );
+        let base_chunks: HashMap<_, _> = base
+            .chunks
+            .into_iter()
+            .filter_map(|chunk| chunk.blobs_hash.map(|hash| (hash, chunk.blobs)))
+            .collect();
+        if base_chunks.is_empty() {
+            warn!(
+                base_manifest = base_manifest,
+                "Base state snapshot was not taken incrementally, no chunks can be reused."
+            );
+        }
+        Ok(base_chunks)
+    }
+
     async fn record_stream(
         &self,
         concurrency: usize,
@@ -380,6 +492,10 @@ impl StateSnapshotBackupController {
         format!("{}-.chunk", first_idx).try_into().unwrap()
     }
 
+    fn content_addressed_chunk_name(hash: &HashValue) -> ShellSafeName {
+        format!("{}.chunk", hash.to_hex()).try_into().unwrap()
+    }
+
     fn chunk_proof_name(first_idx: usize, last_idx: usize) -> ShellSafeName {
         format!("{}-{}.proof", first_idx, last_idx)
             .try_into()
@@ -416,12 +532,24 @@ impl StateSnapshotBackupController {
             last_key,
         } = chunk;
 
-        let (chunk_handle, mut chunk_file) = self
-            .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
-            .await?;
-        chunk_file.write_all(&bytes).await?;
-        chunk_file.shutdown().await?;
+        let blobs_hash = self.incremental.then(|| HashValue::sha3_256_of(&bytes));
+        let chunk_handle = match blobs_hash.and_then(|hash| self.base_chunks.get(&hash)) {
+            Some(base_chunk_handle) => {
+                self.num_reused_chunks.fetch_add(1, Ordering::Relaxed);
+                base_chunk_handle.clone()
+            },
+            None => {
+                let name = match &blobs_hash {
+                    Some(hash) => Self::content_addressed_chunk_name(hash),
+                    None => Self::chunk_name(first_idx),
+                };
+                let (chunk_handle, mut chunk_file) =
+                    self.storage.create_for_write(backup_handle, &name).await?;
+                chunk_file.write_all(&bytes).await?;
+                chunk_file.shutdown().await?;
+                chunk_handle
+            },
+        };
         let (proof_handle, mut proof_file) = self
             .storage
             .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
@@ -443,6 +571,7 @@ impl StateSnapshotBackupController {
             last_key,
             blobs: chunk_handle,
             proof: proof_handle,
+            blobs_hash,
         })
     }
 
@@ -468,6 +597,7 @@ impl StateSnapshotBackupController {
             root_hash: txn_info.transaction_info().ensure_state_checkpoint_hash()?,
             chunks,
             proof: proof_handle,
+            base_manifest: self.base_manifest.clone(),
         };
 
         let (manifest_handle, mut manifest_file) = self
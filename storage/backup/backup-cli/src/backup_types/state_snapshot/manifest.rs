@@ -25,6 +25,10 @@ pub struct StateSnapshotChunk {
     /// BCS serialized `SparseMerkleRangeProof` that proves this chunk adds up to the root hash
     /// indicated in the backup (`StateSnapshotBackup::root_hash`).
     pub proof: FileHandle,
+    /// SHA3-256 of the content of the `blobs` file. Present in incremental backups, where `blobs`
+    /// can be a file written by an earlier backup, so its content is verified on restore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blobs_hash: Option<HashValue>,
 }
 
 /// State snapshot backup manifest, representing a complete state view at specified version.
@@ -49,4 +53,9 @@ pub struct StateSnapshotBackup {
     /// `EpochStateBackup` recovered prior to this to the DB; Requiring it to be in the same epoch
     /// limits the requirement on such `EpochStateBackup` to no older than the same epoch.
     pub proof: FileHandle,
+    /// Manifest of the earlier state snapshot backup this one was taken incrementally upon, if
+    /// any. Chunks whose content didn't change since the base refer to the `blobs` files of the
+    /// base (or of its own bases) instead of new copies, so `chunks` always lists the full state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_manifest: Option<FileHandle>,
 }
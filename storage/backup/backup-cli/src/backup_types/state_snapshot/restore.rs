@@ -18,11 +18,15 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
+        read_record_bytes::ReadRecordBytes,
+        restore_journal::{RestoreJournal, StateSnapshotJournal},
+        storage_ext::BackupStorageExt,
+        stream::StreamX,
         GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::HashValue;
 use aptos_db::state_restore::StateSnapshotRestoreMode;
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
@@ -43,8 +47,8 @@ use clap::Parser;
 use futures::{stream, TryStreamExt};
 use move_binary_format::CompiledModule;
 use move_bytecode_verifier::verify_module_with_config;
-use std::sync::Arc;
-use tokio::time::Instant;
+use std::{io::Cursor, sync::Arc};
+use tokio::{io::AsyncRead, time::Instant};
 
 #[derive(Parser)]
 pub struct StateSnapshotRestoreOpt {
@@ -69,6 +73,8 @@ pub struct StateSnapshotRestoreController {
     target_version: Version,
     epoch_history: Option<Arc<EpochHistory>>,
     concurrent_downloads: usize,
+    concurrent_chunks: usize,
+    journal: Arc<RestoreJournal>,
     validate_modules: bool,
     restore_mode: StateSnapshotRestoreMode,
 }
@@ -88,6 +94,8 @@ impl StateSnapshotRestoreController {
             target_version: global_opt.target_version,
             epoch_history,
             concurrent_downloads: global_opt.concurrent_downloads,
+            concurrent_chunks: global_opt.concurrent_chunks,
+            journal: global_opt.journal,
             validate_modules: opt.validate_modules,
             restore_mode: opt.restore_mode,
         }
@@ -119,7 +127,10 @@ impl StateSnapshotRestoreController {
             );
             return Ok(());
         }
-
+        let progress = self
+            .journal
+            .state_snapshot(&self.manifest_handle, self.restore_mode)
+            .filter(|p| p.version == self.version);
         let manifest: StateSnapshotBackup =
             self.storage.load_json_file(&self.manifest_handle).await?;
         let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
@@ -138,6 +149,28 @@ impl StateSnapshotRestoreController {
             epoch_history.verify_ledger_info(&li)?;
         }
 
+        // The journal alone isn't trusted to skip the snapshot, since the DB could have been
+        // wiped or replaced after it was written. The KV only mode has no tree to check against,
+        // so it falls through to resuming from the DB progress below, which skips all chunks.
+        if progress.as_ref().map_or(false, |p| p.finished) {
+            if self.restore_mode != StateSnapshotRestoreMode::KvOnly
+                && self
+                    .run_mode
+                    .get_state_snapshot_before(self.version.saturating_add(1))
+                    == Some((self.version, manifest.root_hash))
+            {
+                info!(
+                    version = self.version,
+                    "State snapshot already restored, skipping."
+                );
+                return Ok(());
+            }
+            warn!(
+                version = self.version,
+                "Restore journal disagrees with the DB, restoring the state snapshot again."
+            );
+        }
+
         let receiver = Arc::new(Mutex::new(Some(self.run_mode.get_state_restore_receiver(
             self.version,
             manifest.root_hash,
@@ -162,16 +195,37 @@ impl StateSnapshotRestoreController {
         tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
         let total_chunks = manifest.chunks.len();
 
+        // The DB is the source of truth, since the tree restore resumes from what's persisted in
+        // it, which can be behind the journal if the process died while committing.
         let resume_point_opt = receiver.lock().as_mut().unwrap().previous_key_hash()?;
-        let chunks = if let Some(resume_point) = resume_point_opt {
-            manifest
+        let skip = match resume_point_opt {
+            Some(resume_point) => manifest
                 .chunks
-                .into_iter()
-                .skip_while(|chunk| chunk.last_key <= resume_point)
-                .collect()
-        } else {
-            manifest.chunks
+                .iter()
+                .take_while(|chunk| chunk.last_key <= resume_point)
+                .count(),
+            None => 0,
         };
+        if let Some(p) = &progress {
+            if p.chunks_added != skip {
+                warn!(
+                    journal_chunks_added = p.chunks_added,
+                    db_chunks_added = skip,
+                    "Restore journal disagrees with the DB, resuming from the DB progress."
+                );
+            }
+        }
+        let mut progress = progress.unwrap_or(StateSnapshotJournal {
+            version: self.version,
+            restore_mode: self.restore_mode,
+            chunks_added: 0,
+            last_key: None,
+            finished: false,
+        });
+        progress.chunks_added = skip;
+        progress.last_key = skip.checked_sub(1).map(|idx| manifest.chunks[idx].last_key);
+
+        let chunks = manifest.chunks.into_iter().skip(skip).collect::<Vec<_>>();
         if chunks.len() < total_chunks {
             info!(
                 chunks_to_add = chunks.len(),
@@ -184,37 +238,47 @@ impl StateSnapshotRestoreController {
         let start_idx = chunks.first().map_or(0, |chunk| chunk.first_idx);
 
         let storage = self.storage.clone();
+        let validate_modules = self.validate_modules;
         let futs_iter = chunks.into_iter().enumerate().map(|(chunk_idx, chunk)| {
             let storage = storage.clone();
             async move {
                 tokio::spawn(async move {
-                    let blobs = Self::read_state_value(&storage, chunk.blobs.clone()).await?;
+                    let blobs =
+                        Self::read_state_value(&storage, chunk.blobs.clone(), chunk.blobs_hash)
+                            .await?;
                     let proof = storage.load_bcs_file(&chunk.proof).await?;
+                    let blobs = if validate_modules {
+                        tokio::task::spawn_blocking(move || {
+                            Self::validate_modules(&blobs);
+                            blobs
+                        })
+                        .await?
+                    } else {
+                        blobs
+                    };
                     Result::<_>::Ok((chunk_idx, chunk, blobs, proof))
                 })
                 .await?
             }
         });
-        let con = self.concurrent_downloads;
-        let mut futs_stream = stream::iter(futs_iter).buffered_x(con * 2, con);
+        let mut futs_stream =
+            stream::iter(futs_iter).buffered_x(self.concurrent_chunks, self.concurrent_downloads);
         let mut start = None;
-        while let Some((chunk_idx, chunk, mut blobs, proof)) = futs_stream.try_next().await? {
+        while let Some((chunk_idx, chunk, blobs, proof)) = futs_stream.try_next().await? {
             start = start.or_else(|| Some(Instant::now()));
             let _timer = OTHER_TIMERS_SECONDS
                 .with_label_values(&["add_state_chunk"])
                 .start_timer();
             let receiver = receiver.clone();
-            if self.validate_modules {
-                blobs = tokio::task::spawn_blocking(move || {
-                    Self::validate_modules(&blobs);
-                    blobs
-                })
-                .await?;
-            }
             tokio::task::spawn_blocking(move || {
                 receiver.lock().as_mut().unwrap().add_chunk(blobs, proof)
             })
             .await??;
+            progress.chunks_added += 1;
+            progress.last_key = Some(chunk.last_key);
+            self.journal
+                .record_state_snapshot(&self.manifest_handle, progress.clone())
+                .await?;
             leaf_idx.set(chunk.last_idx as i64);
             info!(
                 chunk = chunk_idx,
@@ -229,6 +293,10 @@ impl StateSnapshotRestoreController {
 
         tokio::task::spawn_blocking(move || receiver.lock().take().unwrap().finish()).await??;
         self.run_mode.finish();
+        progress.finished = true;
+        self.journal
+            .record_state_snapshot(&self.manifest_handle, progress)
+            .await?;
         Ok(())
     }
 
@@ -255,8 +323,25 @@ impl StateSnapshotRestoreController {
     async fn read_state_value(
         storage: &Arc<dyn BackupStorage>,
         file_handle: FileHandle,
+        expected_hash: Option<HashValue>,
     ) -> Result<Vec<(StateKey, StateValue)>> {
-        let mut file = storage.open_for_read(&file_handle).await?;
+        let mut file: Box<dyn AsyncRead + Send + Unpin> = match expected_hash {
+            // The file can be from an earlier backup in the case of an incremental snapshot,
+            // verify it's exactly the chunk the manifest expects.
+            Some(expected_hash) => {
+                let bytes = storage.read_all(&file_handle).await?;
+                let hash = HashValue::sha3_256_of(&bytes);
+                ensure!(
+                    hash == expected_hash,
+                    "State snapshot chunk {} has been tampered with. Content hash: {}, expected: {}",
+                    file_handle,
+                    hash,
+                    expected_hash,
+                );
+                Box::new(Cursor::new(bytes))
+            },
+            None => storage.open_for_read(&file_handle).await?,
+        };
 
         let mut chunk = vec![];
 
@@ -5,21 +5,31 @@
 use crate::{
     backup_types::state_snapshot::{
         backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        manifest::StateSnapshotBackup,
         restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     },
-    storage::{local_fs::LocalFs, BackupStorage},
+    storage::{local_fs::LocalFs, BackupStorage, FileHandle},
     utils::{
         backup_service_client::BackupServiceClient,
+        restore_journal::RestoreJournal,
+        storage_ext::BackupStorageExt,
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
         RocksdbOpt, TrustedWaypointOpt,
     },
 };
+use anyhow::Result;
 use aptos_db::{state_restore::StateSnapshotRestoreMode, AptosDB};
+use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
 use aptos_storage_interface::DbReader;
 use aptos_temppath::TempPath;
-use std::{convert::TryInto, sync::Arc};
-use tokio::time::Duration;
+use aptos_types::transaction::Version;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    sync::Arc,
+};
+use tokio::{runtime::Runtime, time::Duration};
 
 #[test]
 fn end_to_end() {
@@ -61,7 +71,11 @@ fn end_to_end() {
     let manifest_handle = rt
         .block_on(
             StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { epoch },
+                StateSnapshotBackupOpt {
+                    epoch,
+                    incremental: false,
+                    incremental_base_manifest: None,
+                },
                 GlobalBackupOpt {
                     max_chunk_size: 500,
                     concurrent_data_requests: 2,
@@ -89,6 +103,8 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                concurrent_chunks: None,
+                restore_journal: None,
                 enable_state_indices: false,
             }
             .try_into()
@@ -111,3 +127,355 @@ fn end_to_end() {
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
+
+fn restore_state_snapshot(
+    rt: &Runtime,
+    store: &Arc<dyn BackupStorage>,
+    manifest_handle: FileHandle,
+    version: Version,
+    tgt_db_dir: &TempPath,
+) -> Result<()> {
+    rt.block_on(
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle,
+                version,
+                validate_modules: false,
+                restore_mode: StateSnapshotRestoreMode::Default,
+            },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(tgt_db_dir.path().to_path_buf()),
+                target_version: None, // max
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurrent_downloads: ConcurrentDownloadsOpt::default(),
+                replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                concurrent_chunks: None,
+                restore_journal: None,
+                enable_state_indices: false,
+            }
+            .try_into()
+            .unwrap(),
+            Arc::clone(store),
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+}
+
+#[test]
+fn incremental() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    let version = src_db
+        .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .pop()
+        .unwrap()
+        .ledger_info()
+        .version();
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let backup = |incremental_base_manifest: Option<FileHandle>| {
+        rt.block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    epoch,
+                    incremental: true,
+                    incremental_base_manifest,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                    concurrent_data_requests: 2,
+                },
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap()
+    };
+
+    let base_handle = backup(None);
+    let incremental_handle = backup(Some(base_handle.clone()));
+
+    let base: StateSnapshotBackup = rt.block_on(store.load_json_file(&base_handle)).unwrap();
+    let incremental: StateSnapshotBackup = rt
+        .block_on(store.load_json_file(&incremental_handle))
+        .unwrap();
+    assert!(base.base_manifest.is_none());
+    assert_eq!(incremental.base_manifest, Some(base_handle));
+    assert_eq!(incremental.root_hash, base.root_hash);
+    // Nothing changed, all chunks are reused.
+    let base_blobs: HashSet<_> = base.chunks.iter().map(|c| c.blobs.clone()).collect();
+    assert_eq!(incremental.chunks.len(), base.chunks.len());
+    for chunk in &incremental.chunks {
+        assert!(chunk.blobs_hash.is_some());
+        assert!(base_blobs.contains(&chunk.blobs));
+        assert_ne!(chunk.proof, base.chunks[0].proof);
+    }
+
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    restore_state_snapshot(
+        &rt,
+        &store,
+        incremental_handle.clone(),
+        version,
+        &tgt_db_dir,
+    )
+    .unwrap();
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, incremental.root_hash)
+    );
+
+    // Tampering with a chunk in the base is detected when restoring the incremental snapshot.
+    let tampered = backup_dir.path().join(&incremental.chunks[0].blobs);
+    let mut content = std::fs::read(&tampered).unwrap();
+    content.truncate(content.len() - 1);
+    std::fs::write(&tampered, content).unwrap();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let err =
+        restore_state_snapshot(&rt, &store, incremental_handle, version, &tgt_db_dir).unwrap_err();
+    assert!(err.to_string().contains("tampered"));
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn incremental_with_state_changes() {
+    // Genesis ends epoch 0, and the blocks executed after it transfer coins and end later epochs,
+    // so the state changes between the snapshots at the two.
+    let src_db = test_execution_with_storage_impl();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    let epoch_ending_version = |epoch: u64| {
+        src_db
+            .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+            .unwrap()
+            .ledger_info_with_sigs
+            .pop()
+            .unwrap()
+            .ledger_info()
+            .version()
+    };
+    let base_version = epoch_ending_version(0);
+    let version = epoch_ending_version(epoch);
+    let count = src_db.get_state_item_count(version).unwrap();
+    let expected_values = src_db
+        .get_state_value_chunk_with_proof(version, 0, count)
+        .unwrap()
+        .raw_values;
+
+    let (rt, port) = start_local_backup_service(Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let backup = |epoch: u64, incremental_base_manifest: Option<FileHandle>| {
+        rt.block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    epoch,
+                    incremental: true,
+                    incremental_base_manifest,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 2048,
+                    concurrent_data_requests: 2,
+                },
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap()
+    };
+
+    let base_handle = backup(0, None);
+    let incremental_handle = backup(epoch, Some(base_handle.clone()));
+
+    let base: StateSnapshotBackup = rt.block_on(store.load_json_file(&base_handle)).unwrap();
+    let incremental: StateSnapshotBackup = rt
+        .block_on(store.load_json_file(&incremental_handle))
+        .unwrap();
+    assert_eq!(base.version, base_version);
+    assert_eq!(incremental.version, version);
+    assert_eq!(incremental.base_manifest, Some(base_handle));
+    assert_ne!(incremental.root_hash, base.root_hash);
+    // Chunks with the same content as in the base are reused, the changed ones are written anew.
+    let base_blobs: HashMap<_, _> = base
+        .chunks
+        .iter()
+        .map(|c| (c.blobs.clone(), c.blobs_hash.unwrap()))
+        .collect();
+    let mut num_new_chunks = 0;
+    for chunk in &incremental.chunks {
+        let blobs_hash = chunk.blobs_hash.unwrap();
+        match base_blobs.get(&chunk.blobs) {
+            Some(base_blobs_hash) => assert_eq!(*base_blobs_hash, blobs_hash),
+            None => {
+                assert!(!base_blobs.values().any(|hash| *hash == blobs_hash));
+                num_new_chunks += 1;
+            },
+        }
+    }
+    assert!(num_new_chunks > 0);
+
+    // The restored state is the one at the incremental snapshot, not at the base.
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    restore_state_snapshot(&rt, &store, incremental_handle, version, &tgt_db_dir).unwrap();
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, incremental.root_hash)
+    );
+    assert_eq!(tgt_db.get_state_item_count(version).unwrap(), count);
+    assert_eq!(
+        tgt_db
+            .get_state_value_chunk_with_proof(version, 0, count)
+            .unwrap()
+            .raw_values,
+        expected_values
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn resume_interrupted_restore() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    let version = src_db
+        .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .pop()
+        .unwrap()
+        .ledger_info()
+        .version();
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    epoch,
+                    incremental: false,
+                    incremental_base_manifest: None,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 200,
+                    concurrent_data_requests: 2,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    let manifest: StateSnapshotBackup =
+        rt.block_on(store.load_json_file(&manifest_handle)).unwrap();
+    assert!(manifest.chunks.len() > 1);
+
+    // Interrupt the restore by corrupting the last chunk.
+    let last_chunk = backup_dir
+        .path()
+        .join(&manifest.chunks.last().unwrap().blobs);
+    let content = std::fs::read(&last_chunk).unwrap();
+    std::fs::write(&last_chunk, &content[..content.len() - 1]).unwrap();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    restore_state_snapshot(&rt, &store, manifest_handle.clone(), version, &tgt_db_dir).unwrap_err();
+    let journal_path = tgt_db_dir.path().join(RestoreJournal::DEFAULT_FILE_NAME);
+    let progress = RestoreJournal::open(&journal_path)
+        .unwrap()
+        .state_snapshot(&manifest_handle, StateSnapshotRestoreMode::Default)
+        .unwrap();
+    assert!(!progress.finished);
+    assert!(progress.chunks_added > 0 && progress.chunks_added < manifest.chunks.len());
+
+    // Resumes from where it stopped.
+    std::fs::write(&last_chunk, &content).unwrap();
+    restore_state_snapshot(&rt, &store, manifest_handle.clone(), version, &tgt_db_dir).unwrap();
+    let progress = RestoreJournal::open(&journal_path)
+        .unwrap()
+        .state_snapshot(&manifest_handle, StateSnapshotRestoreMode::Default)
+        .unwrap();
+    assert!(progress.finished);
+    assert_eq!(progress.chunks_added, manifest.chunks.len());
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, manifest.root_hash)
+    );
+
+    // A journal claiming the snapshot is restored doesn't skip restoring it into an empty DB.
+    let new_db_dir = TempPath::new();
+    new_db_dir.create_as_dir().unwrap();
+    std::fs::copy(
+        &journal_path,
+        new_db_dir.path().join(RestoreJournal::DEFAULT_FILE_NAME),
+    )
+    .unwrap();
+    restore_state_snapshot(&rt, &store, manifest_handle, version, &new_db_dir).unwrap();
+    let new_db = AptosDB::new_readonly_for_test(&new_db_dir);
+    assert_eq!(
+        new_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, manifest.root_hash)
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
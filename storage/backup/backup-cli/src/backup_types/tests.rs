@@ -93,7 +93,11 @@ fn test_end_to_end_impl(d: TestData) {
     let state_snapshot_manifest = d.state_snapshot_epoch.map(|epoch| {
         rt.block_on(
             StateSnapshotBackupController::new(
-                StateSnapshotBackupOpt { epoch },
+                StateSnapshotBackupOpt {
+                    epoch,
+                    incremental: false,
+                    incremental_base_manifest: None,
+                },
                 global_backup_opt.clone(),
                 Arc::clone(&client),
                 Arc::clone(&store),
@@ -125,6 +129,8 @@ fn test_end_to_end_impl(d: TestData) {
         rocksdb_opt: RocksdbOpt::default(),
         concurrent_downloads: ConcurrentDownloadsOpt::default(),
         replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+        concurrent_chunks: None,
+        restore_journal: None,
         enable_state_indices: false,
     }
     .try_into()
@@ -369,7 +369,7 @@ impl TransactionRestoreBatchController {
                     .await
                 })
             })
-            .try_buffered_x(con * 2, con)
+            .try_buffered_x(self.global_opt.concurrent_chunks, con)
             .and_then(future::ready)
             .peekable()
     }
@@ -422,11 +422,24 @@ impl TransactionRestoreBatchController {
             next_expected_version,
         );
         let target_version = self.global_opt.target_version;
+        let journal = self.global_opt.journal.clone();
+        if let Some(journal_version) = journal.max_transaction_saved_version() {
+            // Replayed transactions are not journaled, so the DB can be ahead.
+            if journal_version >= next_expected_version {
+                warn!(
+                    journal_version = journal_version,
+                    next_expected_version = next_expected_version,
+                    "DB is behind the restore journal, resuming from the DB progress."
+                );
+            }
+        }
 
         let mut txns_to_execute_stream = loaded_chunk_stream
             .and_then(move |chunk| {
                 let restore_handler = restore_handler_clone.clone();
+                let journal = journal.clone();
                 future::ok(async move {
+                    let chunk_file = chunk.manifest.transactions.clone();
                     let mut first_version = chunk.manifest.first_version;
                     let mut last_version = chunk.manifest.last_version;
                     let (mut txns, mut txn_infos, mut event_vecs, mut write_sets) = chunk.unpack();
@@ -452,8 +465,22 @@ impl TransactionRestoreBatchController {
                         first_version = global_first_version;
                     }
 
+                    // remove the txns saved before the restore was interrupted, according to both
+                    // the journal and the DB
+                    if let Some(saved) = journal
+                        .transaction_saved_version(&chunk_file)
+                        .filter(|v| *v >= first_version && *v < next_expected_version)
+                    {
+                        let num_to_remove = (min(saved, last_version) + 1 - first_version) as usize;
+                        txns.drain(..num_to_remove);
+                        txn_infos.drain(..num_to_remove);
+                        event_vecs.drain(..num_to_remove);
+                        write_sets.drain(..num_to_remove);
+                        first_version += num_to_remove as Version;
+                    }
+
                     // identify txns to be saved before the first_to_replay version
-                    if first_version < first_to_replay {
+                    if first_version < first_to_replay && first_version <= last_version {
                         let num_to_save =
                             (min(first_to_replay, last_version + 1) - first_version) as usize;
                         let txns_to_save: Vec<_> = txns.drain(..num_to_save).collect();
@@ -471,6 +498,9 @@ impl TransactionRestoreBatchController {
                         })
                         .await??;
                         let last_saved = first_version + num_to_save as u64 - 1;
+                        journal
+                            .record_transaction_saved(&chunk_file, last_saved)
+                            .await?;
                         TRANSACTION_SAVE_VERSION.set(last_saved as i64);
                         info!(
                             version = last_saved,
@@ -107,6 +107,8 @@ fn end_to_end() {
                 rocksdb_opt: RocksdbOpt::default(),
                 concurrent_downloads: ConcurrentDownloadsOpt::default(),
                 replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+                concurrent_chunks: None,
+                restore_journal: None,
                 enable_state_indices: false,
             }
             .try_into()
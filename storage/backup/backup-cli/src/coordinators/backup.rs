@@ -69,6 +69,13 @@ pub struct BackupCoordinatorOpt {
         many small files. "
     )]
     pub transaction_batch_size: usize,
+    #[clap(
+        long,
+        help = "Take state snapshots incrementally upon the latest state snapshot in the backup \
+        storage, only writing chunks whose content changed since then. See \
+        `backup oneoff state-snapshot --incremental`."
+    )]
+    pub incremental_state_snapshots: bool,
     #[clap(flatten)]
     pub concurrent_downloads: ConcurrentDownloadsOpt,
 }
@@ -90,6 +97,7 @@ pub struct BackupCoordinator {
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval_epochs: usize,
     transaction_batch_size: usize,
+    incremental_state_snapshots: bool,
     concurrent_downloads: usize,
 }
 
@@ -108,6 +116,7 @@ impl BackupCoordinator {
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval_epochs: opt.state_snapshot_interval_epochs,
             transaction_batch_size: opt.transaction_batch_size,
+            incremental_state_snapshots: opt.incremental_state_snapshots,
             concurrent_downloads: opt.concurrent_downloads.get(),
         }
     }
@@ -255,8 +264,17 @@ impl BackupCoordinator {
             return Ok(last_snapshot_epoch_in_backup);
         }
 
+        let incremental_base_manifest = if self.incremental_state_snapshots {
+            self.latest_state_snapshot_manifest().await?
+        } else {
+            None
+        };
         StateSnapshotBackupController::new(
-            StateSnapshotBackupOpt { epoch },
+            StateSnapshotBackupOpt {
+                epoch,
+                incremental: self.incremental_state_snapshots,
+                incremental_base_manifest,
+            },
             self.global_opt.clone(),
             Arc::clone(&self.client),
             Arc::clone(&self.storage),
@@ -267,6 +285,17 @@ impl BackupCoordinator {
         Ok(Some(epoch))
     }
 
+    async fn latest_state_snapshot_manifest(&self) -> Result<Option<FileHandle>> {
+        Ok(metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?
+        .select_state_snapshot(Version::MAX)?
+        .map(|snapshot| snapshot.manifest))
+    }
+
     async fn backup_transactions(
         &self,
         mut last_transaction_version_in_backup: Option<Version>,
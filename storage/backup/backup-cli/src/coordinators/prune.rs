@@ -0,0 +1,842 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::manifest::StateSnapshotBackup, transaction::manifest::TransactionBackup,
+    },
+    metadata::{
+        cache::LoadMetadataLines, view::MetadataView, CompactionTimestampsMeta, Metadata,
+        StateSnapshotBackupMeta, TransactionBackupMeta,
+    },
+    storage::{BackupStorage, FileHandle, ShellSafeName},
+    utils::storage_ext::BackupStorageExt,
+};
+use anyhow::{ensure, Result};
+use aptos_infallible::duration_since_epoch;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, proof::TransactionInfoWithProof, transaction::Version,
+};
+use clap::Parser;
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::Path,
+    sync::Arc,
+};
+
+#[derive(Clone, Debug, Parser)]
+pub struct RetentionPolicy {
+    #[clap(
+        long,
+        help = "Keep everything needed to restore to any version since the latest state snapshot \
+        taken at least this many epochs before the latest epoch in the backup, pruning older \
+        transaction backups and state snapshots. [Defaults to keeping all transactions]"
+    )]
+    pub retain_epochs: Option<u64>,
+    #[clap(
+        long,
+        default_value_t = 7 * 86400,
+        help = "Keep all state snapshots whose ledger timestamp is within this many seconds of the \
+        latest state snapshot."
+    )]
+    pub keep_all_snapshots_secs: u64,
+    #[clap(
+        long,
+        default_value_t = 86400,
+        help = "Thin older state snapshots to one per this many seconds."
+    )]
+    pub thinned_snapshot_interval_secs: u64,
+}
+
+/// A state snapshot backup with the ledger timestamp of its version in seconds.
+#[derive(Clone, Debug)]
+pub struct SnapshotWithTimestamp {
+    pub meta: StateSnapshotBackupMeta,
+    pub timestamp_secs: u64,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct RetentionPlan {
+    /// All versions since this are restorable after pruning, `None` if all transactions are kept.
+    pub retained_since_version: Option<Version>,
+    pub retained_snapshots: Vec<StateSnapshotBackupMeta>,
+    pub pruned_snapshots: Vec<StateSnapshotBackupMeta>,
+    pub pruned_transactions: Vec<TransactionBackupMeta>,
+}
+
+impl RetentionPolicy {
+    /// Decides which state snapshots and transaction backups to prune. The earliest state snapshot
+    /// still needed (the "base") and the latest one are always kept, as well as all transactions
+    /// since the base. Epoch ending backups are always kept, since they are needed to verify
+    /// anything restored.
+    pub fn plan(
+        &self,
+        snapshots: &[SnapshotWithTimestamp],
+        transactions: &[TransactionBackupMeta],
+        latest_epoch: u64,
+    ) -> Result<RetentionPlan> {
+        ensure!(
+            self.thinned_snapshot_interval_secs > 0,
+            "thinned_snapshot_interval_secs must be positive."
+        );
+        let mut snapshots = snapshots.to_vec();
+        snapshots.sort_by(|a, b| a.meta.cmp(&b.meta));
+        if snapshots.is_empty() {
+            // Nothing can be restored without a state snapshot, except replaying from genesis.
+            return Ok(RetentionPlan::default());
+        }
+
+        let base_idx = match self.retain_epochs {
+            Some(epochs) => snapshots
+                .iter()
+                .rposition(|s| s.meta.epoch.saturating_add(epochs) <= latest_epoch)
+                .unwrap_or(0),
+            None => 0,
+        };
+        let retained_since_version = self.retain_epochs.map(|_| snapshots[base_idx].meta.version);
+
+        let latest_timestamp = snapshots.last().expect("Not empty.").timestamp_secs;
+        let mut keep = vec![false; snapshots.len()];
+        keep[base_idx] = true;
+        keep[snapshots.len() - 1] = true;
+        let mut seen_intervals = HashSet::new();
+        for idx in (base_idx..snapshots.len()).rev() {
+            let timestamp = snapshots[idx].timestamp_secs;
+            let first_in_interval =
+                seen_intervals.insert(timestamp / self.thinned_snapshot_interval_secs);
+            if first_in_interval
+                || timestamp.saturating_add(self.keep_all_snapshots_secs) >= latest_timestamp
+            {
+                keep[idx] = true;
+            }
+        }
+
+        let (retained, pruned): (Vec<_>, Vec<_>) =
+            snapshots.into_iter().zip(keep).partition(|(_, keep)| *keep);
+        let pruned_transactions = match retained_since_version {
+            Some(version) => transactions
+                .iter()
+                .filter(|t| t.last_version < version)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(RetentionPlan {
+            retained_since_version,
+            retained_snapshots: retained.into_iter().map(|(s, _)| s.meta).collect(),
+            pruned_snapshots: pruned.into_iter().map(|(s, _)| s.meta).collect(),
+            pruned_transactions,
+        })
+    }
+}
+
+fn state_snapshot_files(
+    manifest_handle: &FileHandle,
+    manifest: &StateSnapshotBackup,
+) -> Vec<FileHandle> {
+    let mut files = vec![manifest_handle.clone(), manifest.proof.clone()];
+    for chunk in &manifest.chunks {
+        files.push(chunk.blobs.clone());
+        files.push(chunk.proof.clone());
+    }
+    files
+}
+
+fn transaction_files(
+    manifest_handle: &FileHandle,
+    manifest: &TransactionBackup,
+) -> Vec<FileHandle> {
+    let mut files = vec![manifest_handle.clone()];
+    for chunk in &manifest.chunks {
+        files.push(chunk.transactions.clone());
+        files.push(chunk.proof.clone());
+    }
+    files
+}
+
+#[derive(Debug, Serialize)]
+pub struct PruneReport {
+    pub dry_run: bool,
+    pub retained_since_version: Option<Version>,
+    pub retained_state_snapshot_versions: Vec<Version>,
+    pub pruned_state_snapshot_versions: Vec<Version>,
+    pub pruned_transaction_ranges: Vec<(Version, Version)>,
+    /// Metadata files listing pruned backups, to be replaced by ones without them.
+    pub rewritten_metadata_files: Vec<FileHandle>,
+    pub deleted_files: Vec<FileHandle>,
+}
+
+/// Prunes backups according to a `RetentionPolicy`: metadata files are rewritten first so that
+/// pruned backups are no longer listed, then the files no longer referred to by any retained
+/// backup are deleted. Interrupting it in the middle can leave unreferenced files behind, but never
+/// a listed backup with files missing. Restores reading the backups being pruned can fail.
+pub struct BackupPruner {
+    storage: Arc<dyn BackupStorage>,
+    policy: RetentionPolicy,
+    concurrent_downloads: usize,
+    dry_run: bool,
+}
+
+impl BackupPruner {
+    const PRUNED_SUFFIX: &'static str = "_pruned_at_";
+
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        policy: RetentionPolicy,
+        concurrent_downloads: usize,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            storage,
+            policy,
+            concurrent_downloads,
+            dry_run,
+        }
+    }
+
+    pub async fn run(self) -> Result<PruneReport> {
+        info!(policy = ?self.policy, dry_run = self.dry_run, "Backup pruning started.");
+
+        // Metadata is read directly instead of through the cache, to know exactly which files
+        // list what.
+        let metadata_files = self.load_metadata_files().await?;
+        let view = MetadataView::new(
+            metadata_files
+                .iter()
+                .flat_map(|(_, lines)| lines.iter().cloned())
+                .collect(),
+            metadata_files.iter().map(|(fh, _)| fh.clone()).collect(),
+        );
+
+        let latest_epoch = view
+            .all_epoch_ending_backups()
+            .iter()
+            .map(|e| e.last_epoch)
+            .chain(view.all_state_snapshots().iter().map(|s| s.epoch))
+            .max()
+            .unwrap_or(0);
+        let snapshot_manifests = self
+            .load_state_snapshots(view.all_state_snapshots())
+            .await?;
+        let snapshots = snapshot_manifests
+            .iter()
+            .map(|(meta, _, timestamp_secs)| SnapshotWithTimestamp {
+                meta: meta.clone(),
+                timestamp_secs: *timestamp_secs,
+            })
+            .collect::<Vec<_>>();
+        let plan = self
+            .policy
+            .plan(&snapshots, view.all_transaction_backups(), latest_epoch)?;
+
+        // Files referred to by pruned backups and not by any retained one. Only state snapshots
+        // can share files, see `StateSnapshotBackupOpt::incremental`.
+        let pruned_snapshots: HashSet<_> = plan.pruned_snapshots.iter().collect();
+        let mut retained_files = HashSet::new();
+        let mut to_delete = BTreeSet::new();
+        for (meta, manifest, _) in &snapshot_manifests {
+            let files = state_snapshot_files(&meta.manifest, manifest);
+            if pruned_snapshots.contains(meta) {
+                to_delete.extend(files);
+            } else {
+                retained_files.extend(files);
+            }
+        }
+        to_delete.retain(|f| !retained_files.contains(f));
+        let txn_manifests = stream::iter(plan.pruned_transactions.iter().map(|t| {
+            let storage = self.storage.clone();
+            async move {
+                let manifest: TransactionBackup = storage.load_json_file(&t.manifest).await?;
+                Result::<_>::Ok(transaction_files(&t.manifest, &manifest))
+            }
+        }))
+        .buffered(self.concurrent_downloads)
+        .try_collect::<Vec<_>>()
+        .await?;
+        to_delete.extend(txn_manifests.into_iter().flatten());
+
+        // Metadata files to rewrite.
+        let pruned_manifests: HashSet<_> = plan
+            .pruned_snapshots
+            .iter()
+            .map(|s| &s.manifest)
+            .chain(plan.pruned_transactions.iter().map(|t| &t.manifest))
+            .collect();
+        let is_pruned = |meta: &Metadata| match meta {
+            Metadata::StateSnapshotBackup(s) => pruned_manifests.contains(&s.manifest),
+            Metadata::TransactionBackup(t) => pruned_manifests.contains(&t.manifest),
+            _ => false,
+        };
+        let to_rewrite = metadata_files
+            .iter()
+            .filter(|(_, lines)| lines.iter().any(is_pruned))
+            .collect::<Vec<_>>();
+
+        let report = PruneReport {
+            dry_run: self.dry_run,
+            retained_since_version: plan.retained_since_version,
+            retained_state_snapshot_versions: plan
+                .retained_snapshots
+                .iter()
+                .map(|s| s.version)
+                .collect(),
+            pruned_state_snapshot_versions: plan
+                .pruned_snapshots
+                .iter()
+                .map(|s| s.version)
+                .collect(),
+            pruned_transaction_ranges: plan
+                .pruned_transactions
+                .iter()
+                .map(|t| (t.first_version, t.last_version))
+                .collect(),
+            rewritten_metadata_files: to_rewrite.iter().map(|(fh, _)| fh.clone()).collect(),
+            deleted_files: to_delete.into_iter().collect(),
+        };
+        if self.dry_run {
+            info!("Dry run, nothing pruned.");
+            return Ok(report);
+        }
+
+        let now = duration_since_epoch().as_secs();
+        let mut new_files = Vec::new();
+        for (file_handle, lines) in &to_rewrite {
+            let retained_lines = lines
+                .iter()
+                .filter(|meta| !is_pruned(*meta))
+                .map(Metadata::to_text_line)
+                .collect::<Result<Vec<_>>>()?;
+            if !retained_lines.is_empty() {
+                let name = Self::rewritten_metadata_file_name(file_handle, now)?;
+                new_files.push(
+                    self.storage
+                        .save_metadata_lines(&name, &retained_lines)
+                        .await?,
+                );
+            }
+        }
+        for (file_handle, _) in &to_rewrite {
+            info!(file = file_handle, "Backup metadata file.");
+            self.storage.backup_metadata_file(file_handle).await?;
+        }
+        if !to_rewrite.is_empty() {
+            // Otherwise the compactor would try to move the files again.
+            let mut compaction_timestamps = view
+                .select_latest_compaction_timestamps()
+                .map(|meta| meta.compaction_timestamps)
+                .unwrap_or_default();
+            for (file_handle, _) in &to_rewrite {
+                compaction_timestamps.remove(file_handle);
+            }
+            for file_handle in new_files {
+                compaction_timestamps.insert(file_handle, None);
+            }
+            let metadata = Metadata::new_compaction_timestamps(CompactionTimestampsMeta::new(
+                compaction_timestamps,
+                now,
+            ));
+            self.storage
+                .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+                .await?;
+        }
+
+        stream::iter(report.deleted_files.iter().map(|file_handle| {
+            let storage = self.storage.clone();
+            async move {
+                storage.delete_file(file_handle).await?;
+                debug!(file = file_handle, "Backup file deleted.");
+                Result::<_>::Ok(())
+            }
+        }))
+        .buffered(self.concurrent_downloads)
+        .try_collect::<Vec<()>>()
+        .await?;
+
+        info!(
+            pruned_state_snapshots = report.pruned_state_snapshot_versions.len(),
+            pruned_transaction_backups = report.pruned_transaction_ranges.len(),
+            deleted_files = report.deleted_files.len(),
+            "Backup pruning finished."
+        );
+        Ok(report)
+    }
+
+    async fn load_metadata_files(&self) -> Result<Vec<(FileHandle, Vec<Metadata>)>> {
+        let file_handles = self.storage.list_metadata_files().await?;
+        stream::iter(file_handles.into_iter().map(|file_handle| {
+            let storage = self.storage.clone();
+            async move {
+                let lines = storage
+                    .open_for_read(&file_handle)
+                    .await?
+                    .load_metadata_lines()
+                    .await?;
+                Result::<_>::Ok((file_handle, lines))
+            }
+        }))
+        .buffered(self.concurrent_downloads)
+        .try_collect()
+        .await
+    }
+
+    /// Loads the manifests and ledger timestamps of the state snapshots.
+    async fn load_state_snapshots(
+        &self,
+        snapshots: &[StateSnapshotBackupMeta],
+    ) -> Result<Vec<(StateSnapshotBackupMeta, StateSnapshotBackup, u64)>> {
+        stream::iter(snapshots.iter().map(|meta| {
+            let storage = self.storage.clone();
+            async move {
+                let manifest: StateSnapshotBackup = storage.load_json_file(&meta.manifest).await?;
+                let (_txn_info, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+                    storage.load_bcs_file(&manifest.proof).await?;
+                let timestamp_secs = li.ledger_info().timestamp_usecs() / 1_000_000;
+                Result::<_>::Ok((meta.clone(), manifest, timestamp_secs))
+            }
+        }))
+        .buffered(self.concurrent_downloads)
+        .try_collect()
+        .await
+    }
+
+    /// Appends the pruning time to the original name, replacing that of an earlier pruning.
+    fn rewritten_metadata_file_name(file_handle: &FileHandle, now: u64) -> Result<ShellSafeName> {
+        let stem = Path::new(file_handle)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("metadata");
+        let stem = stem.split(Self::PRUNED_SUFFIX).next().unwrap_or(stem);
+        format!("{}{}{}.meta", stem, Self::PRUNED_SUFFIX, now).parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backup_types::{
+            state_snapshot::manifest::StateSnapshotChunk, transaction::manifest::TransactionChunk,
+        },
+        storage::{local_fs::LocalFs, BackupHandleRef},
+    };
+    use aptos_crypto::HashValue;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        aggregate_signature::AggregateSignature,
+        block_info::BlockInfo,
+        ledger_info::LedgerInfo,
+        proof::TransactionAccumulatorProof,
+        transaction::{ExecutionStatus, TransactionInfo},
+    };
+    use tokio::io::AsyncWriteExt;
+
+    fn snapshot(epoch: u64, timestamp_secs: u64) -> SnapshotWithTimestamp {
+        SnapshotWithTimestamp {
+            meta: StateSnapshotBackupMeta {
+                epoch,
+                version: epoch * 100,
+                manifest: format!("state_epoch_{}/state.manifest", epoch),
+            },
+            timestamp_secs,
+        }
+    }
+
+    fn txns(first_version: Version, last_version: Version) -> TransactionBackupMeta {
+        TransactionBackupMeta {
+            first_version,
+            last_version,
+            manifest: format!("transaction_{}-/transaction.manifest", first_version),
+        }
+    }
+
+    fn epochs(snapshots: &[StateSnapshotBackupMeta]) -> Vec<u64> {
+        snapshots.iter().map(|s| s.epoch).collect()
+    }
+
+    const DAY: u64 = 86400;
+
+    fn policy(retain_epochs: Option<u64>) -> RetentionPolicy {
+        RetentionPolicy {
+            retain_epochs,
+            keep_all_snapshots_secs: 7 * DAY,
+            thinned_snapshot_interval_secs: DAY,
+        }
+    }
+
+    #[test]
+    fn test_thin_snapshots() {
+        // A snapshot every 6 hours in 10 days.
+        let snapshots = (0..40)
+            .map(|epoch| snapshot(epoch, epoch * DAY / 4))
+            .collect::<Vec<_>>();
+        let transactions = vec![txns(0, 1999), txns(2000, 3999)];
+
+        let plan = policy(None).plan(&snapshots, &transactions, 40).unwrap();
+        assert_eq!(plan.retained_since_version, None);
+        assert!(plan.pruned_transactions.is_empty());
+        // All since 7 days before the latest one (at 9.75 days), the latest of each day before
+        // that, and the first one.
+        assert_eq!(
+            epochs(&plan.retained_snapshots),
+            [0, 3, 7].into_iter().chain(11..40).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            plan.retained_snapshots.len() + plan.pruned_snapshots.len(),
+            40
+        );
+    }
+
+    #[test]
+    fn test_retain_epochs() {
+        let snapshots = (0..10)
+            .map(|epoch| snapshot(epoch * 10, epoch * 10 * DAY))
+            .collect::<Vec<_>>();
+        let transactions = (0..10)
+            .map(|i| txns(i * 1000, i * 1000 + 999))
+            .collect::<Vec<_>>();
+
+        // Latest epoch 95, the base snapshot is the one at epoch 60.
+        let plan = policy(Some(35))
+            .plan(&snapshots, &transactions, 95)
+            .unwrap();
+        assert_eq!(plan.retained_since_version, Some(6000));
+        assert_eq!(epochs(&plan.retained_snapshots), vec![60, 70, 80, 90]);
+        assert_eq!(epochs(&plan.pruned_snapshots), vec![0, 10, 20, 30, 40, 50]);
+        assert_eq!(
+            plan.pruned_transactions
+                .iter()
+                .map(|t| t.first_version)
+                .collect::<Vec<_>>(),
+            vec![0, 1000, 2000, 3000, 4000, 5000]
+        );
+
+        // Not old enough snapshot, keep everything since the earliest one.
+        let plan = policy(Some(1000))
+            .plan(&snapshots, &transactions, 95)
+            .unwrap();
+        assert_eq!(plan.retained_since_version, Some(0));
+        assert!(plan.pruned_transactions.is_empty());
+        assert_eq!(plan.retained_snapshots.len(), 10);
+    }
+
+    #[test]
+    fn test_no_snapshots() {
+        let plan = policy(Some(1)).plan(&[], &[txns(0, 999)], 10).unwrap();
+        assert_eq!(plan, RetentionPlan::default());
+    }
+
+    #[test]
+    fn test_rewritten_metadata_file_name() {
+        let name = BackupPruner::rewritten_metadata_file_name(
+            &"metadata/transaction_0-999.meta".to_string(),
+            100,
+        )
+        .unwrap();
+        assert_eq!(name.as_ref(), "transaction_0-999_pruned_at_100.meta");
+        let name = BackupPruner::rewritten_metadata_file_name(
+            &"metadata/transaction_0-999_pruned_at_100.meta".to_string(),
+            200,
+        )
+        .unwrap();
+        assert_eq!(name.as_ref(), "transaction_0-999_pruned_at_200.meta");
+    }
+
+    async fn write_file(
+        storage: &Arc<dyn BackupStorage>,
+        backup_handle: &BackupHandleRef,
+        name: &str,
+        content: &[u8],
+    ) -> FileHandle {
+        let (file_handle, mut file) = storage
+            .create_for_write(backup_handle, &name.parse().unwrap())
+            .await
+            .unwrap();
+        file.write_all(content).await.unwrap();
+        file.shutdown().await.unwrap();
+        file_handle
+    }
+
+    /// Writes a state snapshot backup, whose chunk refers to `base_blobs` if given, returns its
+    /// metadata and files.
+    async fn add_state_snapshot(
+        storage: &Arc<dyn BackupStorage>,
+        epoch: u64,
+        timestamp_secs: u64,
+        base_blobs: Option<FileHandle>,
+    ) -> (StateSnapshotBackupMeta, Vec<FileHandle>) {
+        let version = epoch * 100;
+        let backup_handle = storage
+            .create_backup(&format!("state_epoch_{}", epoch).parse().unwrap())
+            .await
+            .unwrap();
+        let blobs = match base_blobs {
+            Some(blobs) => blobs,
+            None => write_file(storage, &backup_handle, "0-.chunk", b"blobs").await,
+        };
+        let chunk_proof = write_file(storage, &backup_handle, "0-.proof", b"proof").await;
+        let ledger_info = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(
+                BlockInfo::new(
+                    epoch,
+                    0,
+                    HashValue::zero(),
+                    HashValue::zero(),
+                    version,
+                    timestamp_secs * 1_000_000,
+                    None,
+                ),
+                HashValue::zero(),
+            ),
+            AggregateSignature::empty(),
+        );
+        let txn_info = TransactionInfoWithProof::new(
+            TransactionAccumulatorProof::new(vec![]),
+            TransactionInfo::new_placeholder(0, None, ExecutionStatus::Success),
+        );
+        let proof = write_file(
+            storage,
+            &backup_handle,
+            "state.proof",
+            &bcs::to_bytes(&(txn_info, ledger_info)).unwrap(),
+        )
+        .await;
+        let manifest = StateSnapshotBackup {
+            version,
+            epoch,
+            root_hash: HashValue::zero(),
+            chunks: vec![StateSnapshotChunk {
+                first_idx: 0,
+                last_idx: 0,
+                first_key: HashValue::zero(),
+                last_key: HashValue::zero(),
+                blobs,
+                proof: chunk_proof,
+                blobs_hash: None,
+            }],
+            proof,
+            base_manifest: None,
+        };
+        let manifest_handle = write_file(
+            storage,
+            &backup_handle,
+            "state.manifest",
+            &serde_json::to_vec(&manifest).unwrap(),
+        )
+        .await;
+
+        let metadata = Metadata::new_state_snapshot_backup(epoch, version, manifest_handle.clone());
+        storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line().unwrap())
+            .await
+            .unwrap();
+        let files = state_snapshot_files(&manifest_handle, &manifest);
+        (
+            StateSnapshotBackupMeta {
+                epoch,
+                version,
+                manifest: manifest_handle,
+            },
+            files,
+        )
+    }
+
+    /// Writes a transaction backup, returns its metadata and files. The metadata is saved by the
+    /// caller.
+    async fn add_transactions(
+        storage: &Arc<dyn BackupStorage>,
+        first_version: Version,
+        last_version: Version,
+    ) -> (Metadata, Vec<FileHandle>) {
+        let backup_handle = storage
+            .create_backup(&format!("transaction_{}-", first_version).parse().unwrap())
+            .await
+            .unwrap();
+        let transactions = write_file(storage, &backup_handle, "0-.chunk", b"txns").await;
+        let proof = write_file(storage, &backup_handle, "0-.proof", b"proof").await;
+        let manifest = TransactionBackup {
+            first_version,
+            last_version,
+            chunks: vec![TransactionChunk {
+                first_version,
+                last_version,
+                transactions,
+                proof,
+            }],
+        };
+        let manifest_handle = write_file(
+            storage,
+            &backup_handle,
+            "transaction.manifest",
+            &serde_json::to_vec(&manifest).unwrap(),
+        )
+        .await;
+
+        let files = transaction_files(&manifest_handle, &manifest);
+        (
+            Metadata::new_transaction_backup(first_version, last_version, manifest_handle),
+            files,
+        )
+    }
+
+    async fn load_view(storage: &Arc<dyn BackupStorage>) -> MetadataView {
+        let metadata_files = BackupPruner::new(storage.clone(), policy(None), 1, true)
+            .load_metadata_files()
+            .await
+            .unwrap();
+        MetadataView::new(
+            metadata_files
+                .iter()
+                .flat_map(|(_, lines)| lines.iter().cloned())
+                .collect(),
+            metadata_files.into_iter().map(|(fh, _)| fh).collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let storage: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+        let exists = |file_handle: &FileHandle| tmpdir.path().join(file_handle).exists();
+
+        // Snapshots at epochs 0, 10, 20 and 30, a day apart per epoch. The one at epoch 20 is
+        // incremental, sharing the blobs of the one at epoch 0.
+        let (snapshot_0, snapshot_0_files) = add_state_snapshot(&storage, 0, 0, None).await;
+        let shared_blobs = snapshot_0_files[2].clone();
+        let mut retained_snapshot_files = Vec::new();
+        for (epoch, base_blobs) in [(10, None), (20, Some(shared_blobs.clone())), (30, None)] {
+            let (_, files) = add_state_snapshot(&storage, epoch, epoch * DAY, base_blobs).await;
+            retained_snapshot_files.extend(files);
+        }
+
+        // Transactions in 4 backups, all listed in the same metadata file.
+        let mut txn_metadata = Vec::new();
+        let mut txn_files = Vec::new();
+        for i in 0..4 {
+            let (metadata, files) = add_transactions(&storage, i * 1000, i * 1000 + 999).await;
+            txn_metadata.push(metadata);
+            txn_files.push(files);
+        }
+        let txn_metadata_file = storage
+            .save_metadata_lines(
+                &"transaction_compacted.meta".parse().unwrap(),
+                &txn_metadata
+                    .iter()
+                    .map(|m| m.to_text_line().unwrap())
+                    .collect::<Vec<_>>(),
+            )
+            .await
+            .unwrap();
+        let snapshot_0_metadata_file = storage
+            .list_metadata_files()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|fh| fh.ends_with("state_snapshot_ver_0.meta"))
+            .unwrap();
+
+        // Latest epoch 30, the base snapshot is the one at epoch 10.
+        let retention_policy = policy(Some(15));
+        let expected_deleted_files: BTreeSet<_> = snapshot_0_files
+            .iter()
+            .filter(|f| **f != shared_blobs)
+            .chain(&txn_files[0])
+            .cloned()
+            .collect();
+
+        // Dry run, nothing is touched.
+        let metadata_files_before = storage.list_metadata_files().await.unwrap();
+        let report = BackupPruner::new(storage.clone(), retention_policy.clone(), 2, true)
+            .run()
+            .await
+            .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.retained_since_version, Some(1000));
+        assert_eq!(report.retained_state_snapshot_versions, vec![
+            1000, 2000, 3000
+        ]);
+        assert_eq!(report.pruned_state_snapshot_versions, vec![0]);
+        assert_eq!(report.pruned_transaction_ranges, vec![(0, 999)]);
+        assert_eq!(
+            report
+                .rewritten_metadata_files
+                .iter()
+                .collect::<HashSet<_>>(),
+            HashSet::from([&snapshot_0_metadata_file, &txn_metadata_file])
+        );
+        assert_eq!(
+            report
+                .deleted_files
+                .iter()
+                .cloned()
+                .collect::<BTreeSet<_>>(),
+            expected_deleted_files
+        );
+        assert!(expected_deleted_files.iter().all(exists));
+        assert_eq!(
+            storage.list_metadata_files().await.unwrap(),
+            metadata_files_before
+        );
+
+        // Prune.
+        let report = BackupPruner::new(storage.clone(), retention_policy.clone(), 2, false)
+            .run()
+            .await
+            .unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(
+            report
+                .deleted_files
+                .iter()
+                .cloned()
+                .collect::<BTreeSet<_>>(),
+            expected_deleted_files
+        );
+        assert!(!expected_deleted_files.iter().any(exists));
+        assert!(exists(&shared_blobs));
+        assert!(retained_snapshot_files.iter().all(exists));
+        assert!(txn_files[1..].iter().flatten().all(exists));
+
+        // The metadata lists exactly the retained backups, the rewritten files are moved away.
+        let view = load_view(&storage).await;
+        assert_eq!(
+            view.all_state_snapshots()
+                .iter()
+                .map(|s| s.epoch)
+                .collect::<Vec<_>>(),
+            vec![10, 20, 30]
+        );
+        assert!(!view.all_state_snapshots().contains(&snapshot_0));
+        assert_eq!(
+            view.all_transaction_backups()
+                .iter()
+                .map(|t| t.first_version)
+                .collect::<Vec<_>>(),
+            vec![1000, 2000, 3000]
+        );
+        let metadata_files = storage.list_metadata_files().await.unwrap();
+        assert!(!metadata_files.contains(&snapshot_0_metadata_file));
+        assert!(!metadata_files.contains(&txn_metadata_file));
+        assert!(metadata_files
+            .iter()
+            .any(|fh| fh.contains("transaction_compacted_pruned_at_")));
+        let compaction_timestamps = view
+            .select_latest_compaction_timestamps()
+            .unwrap()
+            .compaction_timestamps;
+        assert!(!compaction_timestamps.contains_key(&txn_metadata_file));
+
+        // Nothing more to prune.
+        let report = BackupPruner::new(storage.clone(), retention_policy, 2, false)
+            .run()
+            .await
+            .unwrap();
+        assert!(report.pruned_state_snapshot_versions.is_empty());
+        assert!(report.pruned_transaction_ranges.is_empty());
+        assert!(report.deleted_files.is_empty());
+    }
+}
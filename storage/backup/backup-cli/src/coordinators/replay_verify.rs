@@ -10,7 +10,9 @@ use crate::{
     metadata,
     metadata::cache::MetadataCacheOpt,
     storage::BackupStorage,
-    utils::{GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{
+        restore_journal::RestoreJournal, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt,
+    },
 };
 use anyhow::Result;
 use aptos_db::backup::restore_handler::RestoreHandler;
@@ -168,7 +170,9 @@ impl ReplayVerifyCoordinator {
             trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
             run_mode,
             concurrent_downloads: self.concurrent_downloads,
+            concurrent_chunks: self.concurrent_downloads * 2,
             replay_concurrency_level: 0, // won't replay, doesn't matter
+            journal: Arc::new(RestoreJournal::disabled()),
         };
 
         if !skip_snapshot {
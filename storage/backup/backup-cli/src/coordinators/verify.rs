@@ -14,7 +14,10 @@ use crate::{
         VERIFY_COORDINATOR_FAIL_TS, VERIFY_COORDINATOR_START_TS, VERIFY_COORDINATOR_SUCC_TS,
     },
     storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{
+        restore_journal::RestoreJournal, unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode,
+        TrustedWaypointOpt,
+    },
 };
 use anyhow::Result;
 use aptos_db::state_restore::StateSnapshotRestoreMode;
@@ -101,7 +104,9 @@ impl VerifyCoordinator {
             trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
             run_mode: Arc::new(RestoreRunMode::Verify),
             concurrent_downloads: self.concurrent_downloads,
+            concurrent_chunks: self.concurrent_downloads * 2,
             replay_concurrency_level: 0, // won't replay, doesn't matter
+            journal: Arc::new(RestoreJournal::disabled()),
         };
 
         let epoch_history = if self.skip_epoch_endings {
@@ -105,10 +105,18 @@ impl MetadataView {
         self.compaction_timestamps.clone()
     }
 
+    pub fn all_epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
     pub fn all_state_snapshots(&self) -> &[StateSnapshotBackupMeta] {
         &self.state_snapshot_backups
     }
 
+    pub fn all_transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
     pub fn select_state_snapshot(
         &self,
         target_version: Version,
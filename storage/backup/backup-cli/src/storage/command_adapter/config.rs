@@ -70,6 +70,11 @@ pub struct Commands {
     pub list_metadata_files: String,
     /// Command line to backup one metadata file to a metadata backup folder
     pub backup_metadata_file: Option<String>,
+    /// Command line to delete a file, used by pruning.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    /// deleting a file that doesn't exist should succeed.
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
@@ -6,8 +6,8 @@ use super::*;
 use crate::storage::{
     command_adapter::config::Commands,
     test_util::{
-        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
-        test_write_and_read_impl,
+        arb_backups, arb_metadata_files, test_delete_file_impl,
+        test_save_and_list_metadata_files_impl, test_write_and_read_impl,
     },
 };
 use aptos_temppath::TempPath;
@@ -31,6 +31,7 @@ commands:
   save_metadata_line: 'cd "$FOLDER" && mkdir -p metadata && cd metadata && FILE_HANDLE="metadata/$FILE_NAME" && echo "$FILE_HANDLE" && echo "$FILE_HANDLE" && exec 1>&- && cat > $FILE_NAME'
   list_metadata_files: 'cd "$FOLDER" && (test -d metadata && cd metadata && ls -1 || exec) | while read f; do echo metadata/$f; done'
   backup_metadata_file: 'cd "$FOLDER" && mkdir -p metadata_backup && mv metadata/$FILE_NAME metadata_backup/$FILE_NAME'
+  delete_file: 'rm -f "$FOLDER/$FILE_HANDLE"'
 "#, tmpdir.path().to_str().unwrap()),
     ).unwrap();
 
@@ -61,6 +62,12 @@ proptest! {
     }
 }
 
+#[test]
+fn test_delete_file() {
+    let tmpdir = TempPath::new();
+    block_on(test_delete_file_impl(get_store(&tmpdir)));
+}
+
 fn dummy_store(cmd: &str) -> CommandAdapter {
     CommandAdapter::new(CommandAdapterConfig {
         commands: Commands {
@@ -70,6 +77,7 @@ fn dummy_store(cmd: &str) -> CommandAdapter {
             save_metadata_line: cmd.to_string(),
             list_metadata_files: cmd.to_string(),
             backup_metadata_file: Some(cmd.to_string()),
+            delete_file: Some(cmd.to_string()),
         },
         env_vars: Vec::new(),
     })
@@ -116,6 +124,9 @@ async fn assert_commands_error(cmd: &str) {
 
     // list_metadata_files
     assert!(store.list_metadata_files().await.is_err());
+
+    // delete_file
+    assert!(store.delete_file(handle).await.is_err());
 }
 
 async fn assert_commands_okay(cmd: &str) {
@@ -0,0 +1,214 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::{encrypted::EncryptionKey, FileHandle};
+use anyhow::{bail, ensure, format_err};
+use bytes::Bytes;
+use futures::{
+    ready,
+    stream::{self, TryStreamExt},
+    task::{Context, Poll},
+};
+use std::{io, pin::Pin, sync::Arc};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio_util::io::StreamReader;
+
+const LAST_FRAME_FLAG: u32 = 1 << 31;
+const TAG_LENGTH: usize = 16;
+
+fn frame_nonce(nonce_prefix: &[u8], frame_idx: u32, last: bool) -> Vec<u8> {
+    let mut nonce = nonce_prefix.to_vec();
+    nonce.extend_from_slice(&frame_idx.to_be_bytes());
+    nonce.push(last as u8);
+    nonce
+}
+
+fn frame_aad(header: &[u8], file_handle: &str) -> Vec<u8> {
+    [header, file_handle.as_bytes()].concat()
+}
+
+/// Buffers written bytes into frames, writing each out encrypted once full. The last frame is
+/// written on shutdown, which is required for the file to be readable.
+pub(super) struct EncryptingWriter {
+    inner: Box<dyn AsyncWrite + Send + Unpin>,
+    key: Arc<EncryptionKey>,
+    nonce_prefix: Vec<u8>,
+    aad: Vec<u8>,
+    frame_size: usize,
+    plaintext: Vec<u8>,
+    num_frames: u32,
+    /// Encrypted bytes yet to be written to `inner`, starting from `pending_offset`.
+    pending: Vec<u8>,
+    pending_offset: usize,
+    finished: bool,
+}
+
+impl EncryptingWriter {
+    pub fn new(
+        inner: Box<dyn AsyncWrite + Send + Unpin>,
+        key: Arc<EncryptionKey>,
+        header: Vec<u8>,
+        nonce_prefix: Vec<u8>,
+        file_handle: FileHandle,
+        frame_size: usize,
+    ) -> Self {
+        Self {
+            inner,
+            key,
+            nonce_prefix,
+            aad: frame_aad(&header, &file_handle),
+            frame_size,
+            plaintext: Vec::with_capacity(frame_size),
+            num_frames: 0,
+            pending: header,
+            pending_offset: 0,
+            finished: false,
+        }
+    }
+
+    fn seal_frame(&mut self, last: bool) -> io::Result<()> {
+        let nonce = frame_nonce(&self.nonce_prefix, self.num_frames, last);
+        let ciphertext = self
+            .key
+            .seal(&nonce, &self.aad, &self.plaintext)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.plaintext.clear();
+        self.num_frames = self
+            .num_frames
+            .checked_add(1)
+            .filter(|n| n & LAST_FRAME_FLAG == 0)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Too many frames."))?;
+
+        let mut len = ciphertext.len() as u32;
+        if last {
+            len |= LAST_FRAME_FLAG;
+        }
+        if self.pending_offset == self.pending.len() {
+            self.pending.clear();
+            self.pending_offset = 0;
+        }
+        self.pending.extend_from_slice(&len.to_be_bytes());
+        self.pending.extend_from_slice(&ciphertext);
+        Ok(())
+    }
+
+    fn poll_write_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.pending_offset < self.pending.len() {
+            let n = ready!(
+                Pin::new(&mut self.inner).poll_write(cx, &self.pending[self.pending_offset..])
+            )?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.pending_offset += n;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for EncryptingWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.poll_write_pending(cx))?;
+        if self.finished {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+
+        let len = buf.len().min(self.frame_size - self.plaintext.len());
+        self.plaintext.extend_from_slice(&buf[..len]);
+        if self.plaintext.len() == self.frame_size {
+            // Written out on the next call.
+            self.seal_frame(false)?;
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Partial frames can't be flushed, since every frame but the last is full.
+        ready!(self.poll_write_pending(cx))?;
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.finished {
+            self.seal_frame(true)?;
+            self.finished = true;
+        }
+        ready!(self.poll_write_pending(cx))?;
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Decrypts frames written by `EncryptingWriter` from `inner`, which is positioned right after
+/// the header. Fails if any frame doesn't authenticate, or the file is truncated or extended.
+pub(super) fn decrypting_reader(
+    inner: Box<dyn AsyncRead + Send + Unpin>,
+    key: Arc<EncryptionKey>,
+    header: Vec<u8>,
+    nonce_prefix: Vec<u8>,
+    file_handle: FileHandle,
+    frame_size: usize,
+) -> impl AsyncRead + Send + Unpin {
+    let aad = Arc::new(frame_aad(&header, &file_handle));
+    let nonce_prefix = Arc::new(nonce_prefix);
+    let file_handle = Arc::new(file_handle);
+
+    let frames = stream::try_unfold(
+        (inner, 0u32, false),
+        move |(mut inner, frame_idx, finished)| {
+            let key = key.clone();
+            let aad = aad.clone();
+            let nonce_prefix = nonce_prefix.clone();
+            let file_handle = file_handle.clone();
+            async move {
+                if finished {
+                    ensure!(
+                        inner.read(&mut [0u8; 1]).await? == 0,
+                        "{}: found data after the last frame, the backup has been tampered with.",
+                        file_handle,
+                    );
+                    return Ok(None);
+                }
+
+                let len = match inner.read_u32().await {
+                    Ok(len) => len,
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => bail!(
+                        "{}: missing the last frame, the backup has been truncated.",
+                        file_handle,
+                    ),
+                    Err(e) => return Err(e.into()),
+                };
+                let last = len & LAST_FRAME_FLAG != 0;
+                let len = (len & !LAST_FRAME_FLAG) as usize;
+                ensure!(
+                    len >= TAG_LENGTH && len <= frame_size + TAG_LENGTH,
+                    "{}: bad frame length {}, the backup has been tampered with.",
+                    file_handle,
+                    len,
+                );
+                let mut ciphertext = vec![0u8; len];
+                inner.read_exact(&mut ciphertext).await.map_err(|e| {
+                    format_err!("{}: failed to read frame {}: {}", file_handle, frame_idx, e)
+                })?;
+                let plaintext = key
+                    .open(
+                        &frame_nonce(&nonce_prefix, frame_idx, last),
+                        &aad,
+                        &ciphertext,
+                    )
+                    .map_err(|e| format_err!("{}: {}", file_handle, e))?;
+
+                Ok(Some((
+                    Bytes::from(plaintext),
+                    (inner, frame_idx.wrapping_add(1), last),
+                )))
+            }
+        },
+    )
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+
+    StreamReader::new(Box::pin(frames))
+}
@@ -0,0 +1,372 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client side encryption of backup files, wrapping any other `BackupStorage`.
+//!
+//! Backup files are encrypted with AES-256-GCM in frames, so they can be streamed:
+//!
+//!   MAGIC | VERSION | frame size (u32) | key ID length (u8) | key ID | nonce prefix | frames
+//!
+//! Each frame is a u32 big endian length, whose highest bit flags the last frame, followed by
+//! the ciphertext of up to "frame size" bytes of plaintext. The nonce of a frame is the per file
+//! random nonce prefix, followed by the u32 big endian frame index and the last frame flag. The
+//! header and the file handle are authenticated as associated data of every frame, so frames
+//! can't be modified, reordered, dropped or moved to another file without failing the read.
+//!
+//! Metadata lines are encrypted one by one into lines of JSON recording the ID of the key, so that
+//! the storage can still reorganise metadata files. The name of the metadata file and the index of
+//! the line in it are authenticated as associated data of every line, so lines can't be reordered,
+//! dropped from the middle of a file or moved to another metadata file without failing the read.
+
+mod io;
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        encrypted::io::{decrypting_reader, EncryptingWriter},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, OsRng, Payload},
+    Aes256Gcm, Key, KeyInit, Nonce,
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_crypto::HashValue;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{io::Cursor, path::PathBuf, sync::Arc};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+
+#[derive(Parser, Clone, Debug, Default)]
+pub struct EncryptionOpt {
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with = "encryption_key_env",
+        help = "Encrypt backup files on the client side with the AES-256 key in this file, which \
+        holds the hex encoded 32 byte key. The same key is required to read the backup."
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Encrypt backup files on the client side with the AES-256 key in this environment \
+        variable, which holds the hex encoded 32 byte key. The same key is required to read the \
+        backup."
+    )]
+    pub encryption_key_env: Option<String>,
+}
+
+impl EncryptionOpt {
+    pub async fn load_key(&self) -> Result<Option<EncryptionKey>> {
+        let hex_key = if let Some(path) = &self.encryption_key_file {
+            tokio::fs::read_to_string(path).await.err_notes(path)?
+        } else if let Some(var) = &self.encryption_key_env {
+            std::env::var(var).map_err(|e| {
+                format_err!("Failed to read encryption key from env var {}: {}", var, e)
+            })?
+        } else {
+            return Ok(None);
+        };
+
+        EncryptionKey::from_hex(hex_key.trim()).map(Some)
+    }
+}
+
+/// An AES-256-GCM key, identified by a fingerprint of it.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl EncryptionKey {
+    const ID_DOMAIN: &'static [u8] = b"APTOS::BackupEncryptionKeyId";
+    const ID_LENGTH: usize = 8;
+    pub const LENGTH: usize = 32;
+
+    pub fn new(key: &[u8]) -> Result<Self> {
+        ensure!(
+            key.len() == Self::LENGTH,
+            "Encryption key must be {} bytes, got {} bytes.",
+            Self::LENGTH,
+            key.len(),
+        );
+        let fingerprint = HashValue::sha3_256_of(&[Self::ID_DOMAIN, key].concat());
+
+        Ok(Self {
+            id: fingerprint.to_hex()[..Self::ID_LENGTH * 2].to_string(),
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+
+    pub fn from_hex(hex_key: &str) -> Result<Self> {
+        Self::new(
+            &hex::decode(hex_key).map_err(|e| format_err!("Malformed encryption key: {}", e))?,
+        )
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn seal(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        self.cipher
+            .encrypt(Nonce::from_slice(nonce), Payload {
+                msg: plaintext,
+                aad,
+            })
+            .map_err(|_| format_err!("Failed to encrypt."))
+    }
+
+    fn open(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload {
+                msg: ciphertext,
+                aad,
+            })
+            .map_err(|_| format_err!("Failed to decrypt, the backup has been tampered with."))
+    }
+
+    fn ensure_id(&self, key_id: &str, file_handle: &FileHandleRef) -> Result<()> {
+        ensure!(
+            key_id == self.id,
+            "{} is encrypted with key {}, but the configured encryption key is {}.",
+            file_handle,
+            key_id,
+            self.id,
+        );
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct EncryptedLine {
+    key_id: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// A BackupStorage that encrypts everything on the client side before handing it to the
+/// underlying storage. See the module level doc for the format.
+pub struct EncryptedStorage {
+    inner: Arc<dyn BackupStorage>,
+    key: Arc<EncryptionKey>,
+    frame_size: usize,
+}
+
+impl EncryptedStorage {
+    const DEFAULT_FRAME_SIZE: usize = 1 << 20;
+    const MAGIC: &'static [u8] = b"\0APTOSBK";
+    const MAX_FRAME_SIZE: usize = 64 << 20;
+    const METADATA_LINE_AAD: &'static [u8] = b"APTOS::BackupMetadataLine";
+    const NONCE_PREFIX_LENGTH: usize = 7;
+    const VERSION: u8 = 1;
+
+    pub fn new(inner: Arc<dyn BackupStorage>, key: EncryptionKey) -> Self {
+        Self {
+            inner,
+            key: Arc::new(key),
+            frame_size: Self::DEFAULT_FRAME_SIZE,
+        }
+    }
+
+    fn header(frame_size: usize, key_id: &[u8], nonce_prefix: &[u8]) -> Vec<u8> {
+        let mut header = Self::MAGIC.to_vec();
+        header.push(Self::VERSION);
+        header.extend_from_slice(&(frame_size as u32).to_be_bytes());
+        header.push(key_id.len() as u8);
+        header.extend_from_slice(key_id);
+        header.extend_from_slice(nonce_prefix);
+        header
+    }
+
+    /// Reads the rest of the header after the magic, returns the header in full and the nonce
+    /// prefix and frame size in it.
+    async fn read_header(
+        &self,
+        file: &mut (dyn AsyncRead + Send + Unpin),
+        file_handle: &FileHandleRef,
+    ) -> Result<(Vec<u8>, Vec<u8>, usize)> {
+        let version = file.read_u8().await?;
+        ensure!(
+            version == Self::VERSION,
+            "{}: unknown encryption format version {}.",
+            file_handle,
+            version,
+        );
+        let frame_size = file.read_u32().await? as usize;
+        ensure!(
+            frame_size > 0 && frame_size <= Self::MAX_FRAME_SIZE,
+            "{}: bad frame size {}.",
+            file_handle,
+            frame_size,
+        );
+        let mut key_id = vec![0u8; file.read_u8().await? as usize];
+        file.read_exact(&mut key_id).await?;
+        self.key
+            .ensure_id(&String::from_utf8_lossy(&key_id), file_handle)?;
+        let mut nonce_prefix = vec![0u8; Self::NONCE_PREFIX_LENGTH];
+        file.read_exact(&mut nonce_prefix).await?;
+
+        let header = Self::header(frame_size, &key_id, &nonce_prefix);
+        Ok((header, nonce_prefix, frame_size))
+    }
+
+    /// The associated data of a metadata line, binding it to its position in the metadata file.
+    fn metadata_line_aad(file_name: &str, index: usize) -> Vec<u8> {
+        let mut aad = Self::METADATA_LINE_AAD.to_vec();
+        aad.extend_from_slice(&(file_name.len() as u32).to_be_bytes());
+        aad.extend_from_slice(file_name.as_bytes());
+        aad.extend_from_slice(&(index as u64).to_be_bytes());
+        aad
+    }
+
+    /// The name a metadata file was saved with, which is the last component of its file handle.
+    fn metadata_file_name(file_handle: &FileHandleRef) -> &str {
+        file_handle
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(file_handle)
+    }
+
+    fn encrypt_line(&self, file_name: &str, index: usize, line: &TextLine) -> Result<TextLine> {
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let plaintext = line.as_ref().trim_end_matches('\n');
+        let ciphertext = self.key.seal(
+            &nonce,
+            &Self::metadata_line_aad(file_name, index),
+            plaintext.as_bytes(),
+        )?;
+
+        TextLine::new(&serde_json::to_string(&EncryptedLine {
+            key_id: self.key.id().to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })?)
+    }
+
+    fn decrypt_lines(&self, content: &[u8], file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+        let file_name = Self::metadata_file_name(file_handle);
+        let mut plaintext = Vec::new();
+        for (index, line) in std::str::from_utf8(content)?.lines().enumerate() {
+            let encrypted: EncryptedLine = serde_json::from_str(line).map_err(|e| {
+                format_err!(
+                    "{} is not encrypted or has been tampered with: {}",
+                    file_handle,
+                    e
+                )
+            })?;
+            self.key.ensure_id(&encrypted.key_id, file_handle)?;
+            let nonce = hex::decode(&encrypted.nonce)?;
+            ensure!(nonce.len() == 12, "{}: bad nonce.", file_handle);
+            plaintext.extend(self.key.open(
+                &nonce,
+                &Self::metadata_line_aad(file_name, index),
+                &hex::decode(&encrypted.ciphertext)?,
+            )?);
+            plaintext.push(b'\n');
+        }
+        Ok(plaintext)
+    }
+}
+
+#[async_trait]
+impl BackupStorage for EncryptedStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.inner.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let (file_handle, file) = self.inner.create_for_write(backup_handle, name).await?;
+
+        let mut nonce_prefix = vec![0u8; Self::NONCE_PREFIX_LENGTH];
+        OsRng.fill_bytes(&mut nonce_prefix);
+        let header = Self::header(self.frame_size, self.key.id().as_bytes(), &nonce_prefix);
+        let writer = EncryptingWriter::new(
+            file,
+            self.key.clone(),
+            header,
+            nonce_prefix,
+            file_handle.clone(),
+            self.frame_size,
+        );
+        Ok((file_handle, Box::new(writer)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let mut file = self.inner.open_for_read(file_handle).await?;
+
+        // Encrypted metadata files are JSON lines, which can't start with the magic.
+        let mut magic = vec![0u8; Self::MAGIC.len()];
+        let mut len = 0;
+        while len < magic.len() {
+            match file.read(&mut magic[len..]).await.err_notes(file_handle)? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        if len == Self::MAGIC.len() && magic == Self::MAGIC {
+            let (header, nonce_prefix, frame_size) =
+                self.read_header(file.as_mut(), file_handle).await?;
+            Ok(Box::new(decrypting_reader(
+                file,
+                self.key.clone(),
+                header,
+                nonce_prefix,
+                file_handle.to_string(),
+                frame_size,
+            )))
+        } else {
+            let mut content = magic[..len].to_vec();
+            file.read_to_end(&mut content)
+                .await
+                .err_notes(file_handle)?;
+            if content.starts_with(b"{") || content.is_empty() {
+                Ok(Box::new(Cursor::new(
+                    self.decrypt_lines(&content, file_handle)?,
+                )))
+            } else {
+                bail!("{} is not encrypted.", file_handle)
+            }
+        }
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.inner.list_metadata_files().await
+    }
+
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.backup_metadata_file(file_handle).await
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.delete_file(file_handle).await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let encrypted = lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| self.encrypt_line(name.as_ref(), index, line))
+            .collect::<Result<Vec<_>>>()?;
+        self.inner.save_metadata_lines(name, &encrypted).await
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    local_fs::LocalFs,
+    test_util::{
+        arb_backups, arb_metadata_files, test_delete_file_impl,
+        test_save_and_list_metadata_files_impl, test_write_and_read_impl,
+    },
+};
+use aptos_temppath::TempPath;
+use proptest::prelude::*;
+use std::path::Path;
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+
+fn get_store(dir: &Path, key_byte: u8, frame_size: usize) -> EncryptedStorage {
+    let mut store = EncryptedStorage::new(
+        Arc::new(LocalFs::new(dir.to_path_buf())),
+        EncryptionKey::new(&[key_byte; EncryptionKey::LENGTH]).unwrap(),
+    );
+    store.frame_size = frame_size;
+    store
+}
+
+async fn write_file(store: &EncryptedStorage, name: &str, content: &[u8]) -> FileHandle {
+    let backup_handle = store
+        .create_backup(&"backup".parse().unwrap())
+        .await
+        .unwrap();
+    let (file_handle, mut file) = store
+        .create_for_write(&backup_handle, &name.parse().unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    file_handle
+}
+
+async fn read_file(store: &EncryptedStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+    Ok(buf)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups(),
+        frame_size in 1usize..100,
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = get_store(tmpdir.path(), 1, frame_size);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = get_store(tmpdir.path(), 1, 10);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+}
+
+#[tokio::test]
+async fn test_delete_file() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    test_delete_file_impl(Box::new(get_store(tmpdir.path(), 1, 10))).await
+}
+
+#[tokio::test]
+async fn test_ciphertext_on_disk() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = get_store(tmpdir.path(), 1, 10);
+
+    let content = b"some very secret state values".repeat(10);
+    let file_handle = write_file(&store, "file", &content).await;
+    let on_disk = std::fs::read(tmpdir.path().join(&file_handle)).unwrap();
+    assert!(!on_disk.windows(11).any(|w| w == b"very secret"));
+    assert_eq!(read_file(&store, &file_handle).await.unwrap(), content);
+
+    let line = TextLine::new("{\"secret\":1}").unwrap();
+    let file_handle = store
+        .save_metadata_line(&"metadata.meta".parse().unwrap(), &line)
+        .await
+        .unwrap();
+    let on_disk =
+        String::from_utf8(std::fs::read(tmpdir.path().join(&file_handle)).unwrap()).unwrap();
+    assert!(!on_disk.contains("secret"));
+    assert!(on_disk.contains(store.key.id()));
+    assert_eq!(
+        read_file(&store, &file_handle).await.unwrap(),
+        line.as_ref().as_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_tampering() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = get_store(tmpdir.path(), 1, 10);
+
+    let content = (0..100u8).collect::<Vec<_>>();
+    let file_handle = write_file(&store, "file", &content).await;
+    let path = tmpdir.path().join(&file_handle);
+    let original = std::fs::read(&path).unwrap();
+
+    let mut tampered_files = Vec::new();
+    // Flip a bit in any byte.
+    for i in 0..original.len() {
+        let mut tampered = original.clone();
+        tampered[i] ^= 1;
+        tampered_files.push(tampered);
+    }
+    // Truncate, dropping the last frame.
+    tampered_files.push(original[..original.len() - (4 + 16)].to_vec());
+    // Extend.
+    tampered_files.push([&original[..], &[0u8; 20]].concat());
+
+    for tampered in tampered_files {
+        std::fs::write(&path, &tampered).unwrap();
+        assert!(read_file(&store, &file_handle).await.is_err());
+    }
+
+    // A file moved to another handle.
+    let other_handle = write_file(&store, "other", &content).await;
+    std::fs::write(tmpdir.path().join(&other_handle), &original).unwrap();
+    assert!(read_file(&store, &other_handle).await.is_err());
+
+    // A file not encrypted at all.
+    std::fs::write(&path, &content).unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+
+    // Tampered metadata.
+    let line = TextLine::new("a line").unwrap();
+    let file_handle = store
+        .save_metadata_line(&"metadata.meta".parse().unwrap(), &line)
+        .await
+        .unwrap();
+    let path = tmpdir.path().join(&file_handle);
+    let mut encrypted: EncryptedLine =
+        serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    let flipped = if encrypted.ciphertext.starts_with('0') {
+        "1"
+    } else {
+        "0"
+    };
+    encrypted.ciphertext.replace_range(0..1, flipped);
+    std::fs::write(&path, serde_json::to_string(&encrypted).unwrap()).unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+
+    // Plain text metadata.
+    std::fs::write(&path, line.as_ref()).unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+}
+
+#[tokio::test]
+async fn test_tampering_metadata_lines() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = get_store(tmpdir.path(), 1, 10);
+
+    let lines = (0..3)
+        .map(|i| TextLine::new(&format!("line {}", i)).unwrap())
+        .collect::<Vec<_>>();
+    let file_handle = store
+        .save_metadata_lines(&"metadata.meta".parse().unwrap(), &lines)
+        .await
+        .unwrap();
+    let path = tmpdir.path().join(&file_handle);
+    let original = std::fs::read_to_string(&path).unwrap();
+    let encrypted_lines = original.lines().collect::<Vec<_>>();
+    assert_eq!(
+        read_file(&store, &file_handle).await.unwrap(),
+        b"line 0\nline 1\nline 2\n"
+    );
+
+    // Reordered lines.
+    let reordered = [encrypted_lines[1], encrypted_lines[0], encrypted_lines[2]];
+    std::fs::write(&path, reordered.join("\n") + "\n").unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+
+    // A line dropped from the middle.
+    let dropped = [encrypted_lines[0], encrypted_lines[2]];
+    std::fs::write(&path, dropped.join("\n") + "\n").unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+
+    // A line moved to another metadata file.
+    let other_handle = store
+        .save_metadata_line(&"other.meta".parse().unwrap(), &lines[0])
+        .await
+        .unwrap();
+    std::fs::write(
+        tmpdir.path().join(&other_handle),
+        format!("{}\n", encrypted_lines[0]),
+    )
+    .unwrap();
+    assert!(read_file(&store, &other_handle).await.is_err());
+
+    // The untouched file still reads.
+    std::fs::write(&path, &original).unwrap();
+    assert_eq!(
+        read_file(&store, &file_handle).await.unwrap(),
+        b"line 0\nline 1\nline 2\n"
+    );
+}
+
+#[tokio::test]
+async fn test_wrong_key() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = get_store(tmpdir.path(), 1, 10);
+    let other_store = get_store(tmpdir.path(), 2, 10);
+
+    let file_handle = write_file(&store, "file", b"content").await;
+    let err = read_file(&other_store, &file_handle).await.unwrap_err();
+    assert!(err.to_string().contains(store.key.id()));
+
+    let file_handle = store
+        .save_metadata_line(
+            &"metadata.meta".parse().unwrap(),
+            &TextLine::new("a line").unwrap(),
+        )
+        .await
+        .unwrap();
+    let err = read_file(&other_store, &file_handle).await.unwrap_err();
+    assert!(err.to_string().contains(store.key.id()));
+}
+
+#[test]
+fn test_key_from_hex() {
+    let key = EncryptionKey::from_hex(&"ab".repeat(32)).unwrap();
+    assert_eq!(key.id().len(), 16);
+    assert_ne!(key.id(), EncryptionKey::new(&[0xCD; 32]).unwrap().id());
+
+    assert!(EncryptionKey::from_hex(&"ab".repeat(16)).is_err());
+    assert!(EncryptionKey::from_hex("not hex").is_err());
+}
@@ -22,7 +22,7 @@ use std::{
     str::FromStr,
 };
 use tokio::{
-    fs::{create_dir_all, read_dir, rename, OpenOptions},
+    fs::{create_dir_all, read_dir, remove_file, rename, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -147,6 +147,14 @@ impl BackupStorage for LocalFs {
         Ok(())
     }
 
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        match remove_file(&path).await {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            res => Ok(res.err_notes(&path)?),
+        }
+    }
+
     async fn save_metadata_lines(
         &self,
         name: &ShellSafeName,
@@ -4,7 +4,7 @@
 
 use super::*;
 use crate::storage::test_util::{
-    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    arb_backups, arb_metadata_files, test_delete_file_impl, test_save_and_list_metadata_files_impl,
     test_write_and_read_impl,
 };
 use aptos_temppath::TempPath;
@@ -38,3 +38,12 @@ proptest! {
         rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
     }
 }
+
+#[tokio::test]
+async fn test_delete_file() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = LocalFs::new(tmpdir.path().to_path_buf());
+
+    test_delete_file_impl(Box::new(store)).await
+}
@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod command_adapter;
+pub mod encrypted;
 pub mod local_fs;
+pub mod s3;
 
 #[cfg(test)]
 mod test_util;
@@ -12,7 +14,9 @@ mod tests;
 
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
+    encrypted::{EncryptedStorage, EncryptionOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    s3::{S3Opt, S3},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -178,6 +182,9 @@ pub trait BackupStorage: Send + Sync {
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
     /// Move a metadata file to the metadata file backup folder.
     async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()>;
+    /// Delete a file created by `create_for_write()`, which is no longer referred to by any
+    /// backup, for pruning. Deleting a file that doesn't exist is not an error.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
     /// Save a vector of metadata lines to file and return the file handle of saved file.
     /// If the file exists, this will overwrite
     async fn save_metadata_lines(
@@ -198,6 +205,13 @@ pub enum StorageOpt {
     https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/command_adapter/sample_configs/"
     )]
     CommandAdapter(CommandAdapterOpt),
+    #[clap(
+        about = "Select the S3 backup storage type, which talks to an S3 compatible object store \
+    (AWS S3, MinIO, etc.) directly over HTTP, with multipart uploads, ranged reads and retries. See a \
+    sample config here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/s3/sample_configs/"
+    )]
+    S3(S3Opt),
 }
 
 impl StorageOpt {
@@ -205,6 +219,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::S3(opt) => Arc::new(S3::new_with_opt(opt).await?),
         })
     }
 }
@@ -213,7 +228,7 @@ impl StorageOpt {
 #[clap(group(
     ArgGroup::new("storage")
     .required(true)
-    .args(&["local_fs_dir", "command_adapter_config"]),
+    .args(&["local_fs_dir", "command_adapter_config", "s3_config"]),
 ))]
 pub struct DBToolStorageOpt {
     #[clap(
@@ -229,14 +244,31 @@ pub struct DBToolStorageOpt {
     https://github.com/aptos-labs/aptos-networks/tree/main/testnet/backups "
     )]
     command_adapter_config: Option<CommandAdapterOpt>,
+    #[clap(
+        long,
+        help = "Select the S3 backup storage type, which talks to an S3 compatible object store \
+    (AWS S3, MinIO, etc.) directly over HTTP, with multipart uploads, ranged reads and retries. See a \
+    sample config here: \
+    https://github.com/aptos-labs/aptos-core/tree/main/storage/backup/backup-cli/src/storage/s3/sample_configs/"
+    )]
+    s3_config: Option<S3Opt>,
+    #[clap(flatten)]
+    encryption: EncryptionOpt,
 }
 
 impl DBToolStorageOpt {
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
-        Ok(if self.local_fs_dir.is_some() {
+        let storage: Arc<dyn BackupStorage> = if self.local_fs_dir.is_some() {
             Arc::new(LocalFs::new_with_opt(self.local_fs_dir.unwrap()))
+        } else if self.s3_config.is_some() {
+            Arc::new(S3::new_with_opt(self.s3_config.unwrap()).await?)
         } else {
             Arc::new(CommandAdapter::new_with_opt(self.command_adapter_config.unwrap()).await?)
+        };
+
+        Ok(match self.encryption.load_key().await? {
+            Some(key) => Arc::new(EncryptedStorage::new(storage, key)),
+            None => storage,
         })
     }
 }
@@ -0,0 +1,132 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::error_notes::ErrorNotes;
+use anyhow::{ensure, Result};
+use aptos_s3_client::{S3ClientConfig, S3Credentials};
+use serde::Deserialize;
+use std::{path::Path, time::Duration};
+use tokio::io::AsyncReadExt;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// Endpoint of the S3 compatible service, like "https://s3.us-west-2.amazonaws.com" or
+    /// "http://127.0.0.1:9000" for a local MinIO.
+    pub endpoint: String,
+    /// Region used to sign requests. MinIO and most S3 compatible services accept "us-east-1".
+    #[serde(default = "S3Config::default_region")]
+    pub region: String,
+    /// Bucket holding the backups.
+    pub bucket: String,
+    /// Key prefix under which everything is stored, like "testnet/e1". Can be empty.
+    #[serde(default)]
+    pub prefix: String,
+    /// Address the bucket as "{endpoint}/{bucket}/{key}" instead of
+    /// "{bucket}.{endpoint host}/{key}". Most self-hosted services require this.
+    #[serde(default)]
+    pub path_style: bool,
+    /// Credentials. When missing, read from the standard AWS environment variables.
+    #[serde(default)]
+    pub credentials: Option<S3Credentials>,
+    /// Files larger than this are uploaded with multipart uploads, in parts of this size.
+    /// S3 requires parts other than the last one to be at least 5MiB.
+    #[serde(default = "S3Config::default_multipart_part_size")]
+    pub multipart_part_size: usize,
+    /// Files are downloaded with ranged reads of this size, each one retried separately.
+    #[serde(default = "S3Config::default_read_chunk_size")]
+    pub read_chunk_size: usize,
+    /// Number of times a failed request is retried before giving up.
+    #[serde(default = "S3Config::default_max_retries")]
+    pub max_retries: usize,
+    /// Delay before the first retry, doubled on each succeeding retry.
+    #[serde(default = "S3Config::default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// Per request timeout.
+    #[serde(default = "S3Config::default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+impl S3Config {
+    const MIN_MULTIPART_PART_SIZE: usize = 5 * 1024 * 1024;
+
+    fn default_region() -> String {
+        "us-east-1".to_string()
+    }
+
+    fn default_multipart_part_size() -> usize {
+        64 * 1024 * 1024
+    }
+
+    fn default_read_chunk_size() -> usize {
+        16 * 1024 * 1024
+    }
+
+    fn default_max_retries() -> usize {
+        5
+    }
+
+    fn default_retry_base_delay_ms() -> u64 {
+        200
+    }
+
+    fn default_request_timeout_secs() -> u64 {
+        300
+    }
+
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let path_str = path.to_str().unwrap_or_default();
+        let mut file = tokio::fs::File::open(path).await.err_notes(path_str)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).await.err_notes(path_str)?;
+
+        let config: Self = serde_yaml::from_slice(&content)?;
+        config.sanitize()?;
+        Ok(config)
+    }
+
+    pub fn load_from_str(content: &str) -> Result<Self> {
+        let config: Self = serde_yaml::from_str(content)?;
+        config.sanitize()?;
+        Ok(config)
+    }
+
+    fn sanitize(&self) -> Result<()> {
+        ensure!(!self.bucket.is_empty(), "bucket can't be empty.");
+        ensure!(
+            self.read_chunk_size > 0,
+            "read_chunk_size must be positive."
+        );
+        ensure!(
+            self.multipart_part_size > 0,
+            "multipart_part_size must be positive."
+        );
+        if self.multipart_part_size < Self::MIN_MULTIPART_PART_SIZE {
+            aptos_logger::warn!(
+                multipart_part_size = self.multipart_part_size,
+                "multipart_part_size is smaller than 5MiB, AWS S3 will reject multipart uploads."
+            );
+        }
+        Ok(())
+    }
+
+    pub fn credentials(&self) -> Result<S3Credentials> {
+        match &self.credentials {
+            Some(credentials) => Ok(credentials.clone()),
+            None => S3Credentials::from_env(),
+        }
+    }
+
+    pub fn client_config(&self) -> Result<S3ClientConfig> {
+        Ok(S3ClientConfig {
+            endpoint: self.endpoint.clone(),
+            region: self.region.clone(),
+            bucket: self.bucket.clone(),
+            path_style: self.path_style,
+            credentials: self.credentials()?,
+            max_retries: self.max_retries,
+            retry_base_delay: Duration::from_millis(self.retry_base_delay_ms),
+            request_timeout: Duration::from_secs(self.request_timeout_secs),
+        })
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_logger::prelude::*;
+use aptos_s3_client::{CompletedPart, S3Client};
+use bytes::Bytes;
+use futures::{
+    future::BoxFuture,
+    ready,
+    stream::{self, Stream},
+    task::{Context, Poll},
+    Future, FutureExt,
+};
+use std::{io, mem, pin::Pin, sync::Arc};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::io::StreamReader;
+
+/// Streams an object by issuing ranged reads of `chunk_size` bytes, so a dropped connection only
+/// costs retrying the current chunk.
+pub(super) fn ranged_reader(
+    client: Arc<S3Client>,
+    key: String,
+    chunk_size: usize,
+) -> impl AsyncRead + Send + Unpin {
+    let chunks = stream::try_unfold(Some(0u64), move |offset| {
+        let client = client.clone();
+        let key = key.clone();
+        async move {
+            let offset = match offset {
+                Some(offset) => offset,
+                None => return Ok(None),
+            };
+            let range = client.get_range(&key, offset, chunk_size).await?;
+            if range.data.is_empty() {
+                return Ok(None);
+            }
+            let next_offset = offset + range.data.len() as u64;
+            let next = (next_offset < range.total_size).then_some(next_offset);
+            Ok(Some((range.data, next)))
+        }
+    });
+    StreamReader::new(Box::pin(into_io_errors(chunks)))
+}
+
+fn into_io_errors(
+    s: impl Stream<Item = Result<Bytes>> + Send,
+) -> impl Stream<Item = io::Result<Bytes>> + Send {
+    use futures::TryStreamExt;
+    s.map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// Buffers written bytes and uploads them as a single object on shutdown, or as a multipart
+/// upload once more than `part_size` bytes are written. At most one part is being uploaded at a
+/// time while the next one is being buffered.
+pub(super) struct MultipartWriter {
+    client: Arc<S3Client>,
+    key: String,
+    part_size: usize,
+    buf: Vec<u8>,
+    upload_id: Option<String>,
+    parts: Vec<CompletedPart>,
+    uploading_part: Option<BoxFuture<'static, Result<(String, CompletedPart)>>>,
+    finishing: Option<BoxFuture<'static, Result<()>>>,
+}
+
+impl MultipartWriter {
+    pub fn new(client: Arc<S3Client>, key: String, part_size: usize) -> Self {
+        Self {
+            client,
+            key,
+            part_size,
+            buf: Vec::new(),
+            upload_id: None,
+            parts: Vec::new(),
+            uploading_part: None,
+            finishing: None,
+        }
+    }
+
+    fn start_uploading_part(&mut self) {
+        let client = self.client.clone();
+        let key = self.key.clone();
+        let upload_id = self.upload_id.clone();
+        let part_number = self.parts.len() + 1;
+        let data = Bytes::from(mem::take(&mut self.buf));
+
+        self.uploading_part = Some(
+            async move {
+                let upload_id = match upload_id {
+                    Some(upload_id) => upload_id,
+                    None => client.create_multipart_upload(&key).await?,
+                };
+                let part = client
+                    .upload_part(&key, &upload_id, part_number, data)
+                    .await?;
+                Ok((upload_id, part))
+            }
+            .boxed(),
+        );
+    }
+
+    fn poll_uploading_part(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(fut) = self.uploading_part.as_mut() {
+            let res = ready!(fut.as_mut().poll(cx));
+            self.uploading_part = None;
+            match res {
+                Ok((upload_id, part)) => {
+                    self.upload_id = Some(upload_id);
+                    self.parts.push(part);
+                },
+                Err(e) => {
+                    self.abort_in_background();
+                    return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
+                },
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_finishing(&mut self) {
+        let client = self.client.clone();
+        let key = self.key.clone();
+        let data = Bytes::from(mem::take(&mut self.buf));
+
+        self.finishing = Some(match self.upload_id.take() {
+            None => async move { client.put_object(&key, data, None).await }.boxed(),
+            Some(upload_id) => {
+                let mut parts = mem::take(&mut self.parts);
+                async move {
+                    let res = async {
+                        if !data.is_empty() {
+                            let part_number = parts.len() + 1;
+                            parts.push(
+                                client
+                                    .upload_part(&key, &upload_id, part_number, data)
+                                    .await?,
+                            );
+                        }
+                        client
+                            .complete_multipart_upload(&key, &upload_id, &parts)
+                            .await
+                    }
+                    .await;
+                    if res.is_err() {
+                        if let Err(e) = client.abort_multipart_upload(&key, &upload_id).await {
+                            warn!(key = key, error = %e, "Failed to abort multipart upload.");
+                        }
+                    }
+                    res
+                }
+                .boxed()
+            },
+        });
+    }
+
+    fn abort_in_background(&mut self) {
+        if let Some(upload_id) = self.upload_id.take() {
+            let client = self.client.clone();
+            let key = self.key.clone();
+            tokio::spawn(async move {
+                if let Err(e) = client.abort_multipart_upload(&key, &upload_id).await {
+                    warn!(key = key, error = %e, "Failed to abort multipart upload.");
+                }
+            });
+        }
+    }
+}
+
+impl AsyncWrite for MultipartWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.finishing.is_some() {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if self.buf.len() >= self.part_size {
+            // Wait for the previous part before buffering more.
+            ready!(self.poll_uploading_part(cx))?;
+            self.start_uploading_part();
+            // Kick off the upload, it's driven further by succeeding calls.
+            if let Poll::Ready(Err(e)) = self.poll_uploading_part(cx) {
+                return Poll::Ready(Err(e));
+            }
+        }
+        let len = buf.len().min(self.part_size);
+        self.buf.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Buffered bytes can't be flushed before the part is full, only make sure a started part
+        // upload finishes.
+        self.poll_uploading_part(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.finishing.is_none() {
+            ready!(self.poll_uploading_part(cx))?;
+            self.start_finishing();
+        }
+
+        Pin::new(self.finishing.as_mut().unwrap())
+            .poll(cx)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+mod io;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        s3::{
+            config::S3Config,
+            io::{ranged_reader, MultipartWriter},
+        },
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_s3_client::S3Client;
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[derive(Parser, Clone, Debug, Serialize, Deserialize)]
+pub struct S3Opt {
+    #[clap(long = "config", help = "Config file for the S3 backup store.")]
+    config: PathBuf,
+}
+
+impl FromStr for S3Opt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(S3Opt {
+            config: PathBuf::from(s),
+        })
+    }
+}
+
+/// A BackupStorage that talks to an S3 compatible object store directly over HTTP.
+/// The layout of files under the configured prefix is the same as that of `LocalFs`.
+/// see `S3Config`.
+pub struct S3 {
+    client: Arc<S3Client>,
+    config: S3Config,
+}
+
+impl S3 {
+    const METADATA_BACKUP_DIR: &'static str = "metadata_backup";
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(config: S3Config) -> Result<Self> {
+        Ok(Self {
+            client: Arc::new(S3Client::new(config.client_config()?)?),
+            config,
+        })
+    }
+
+    pub async fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        let config = S3Config::load_from_file(&opt.config).await?;
+
+        Self::new(config)
+    }
+
+    /// Object key for a file handle, which is the path relative to the configured prefix.
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        let prefix = self.config.prefix.trim_matches('/');
+        if prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", prefix, file_handle)
+        }
+    }
+
+    fn file_handle(&self, key: &str) -> Result<FileHandle> {
+        let prefix = self.key("");
+        key.strip_prefix(&prefix)
+            .map(str::to_string)
+            .ok_or_else(|| format_err!("Key {} is not under prefix {}", key, prefix))
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3 {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // No directories in an object store, the backup handle is merely a key prefix.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let key = self.key(&file_handle);
+        ensure!(
+            !self.client.object_exists(&key).await.err_notes(&key)?,
+            "File already exists: {}",
+            key,
+        );
+        let writer =
+            MultipartWriter::new(self.client.clone(), key, self.config.multipart_part_size);
+        Ok((file_handle, Box::new(writer)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        Ok(Box::new(ranged_reader(
+            self.client.clone(),
+            self.key(file_handle),
+            self.config.read_chunk_size,
+        )))
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.key(&format!("{}/", Self::METADATA_DIR));
+        self.client
+            .list_objects(&prefix)
+            .await
+            .err_notes(&prefix)?
+            .iter()
+            .map(|key| self.file_handle(key))
+            .collect()
+    }
+
+    /// file_handle are expected to be the return results from list_metadata_files
+    /// file_handle is a path with `metadata` in the path, Ex: metadata/epoch_ending_1.meta
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let name = Path::new(file_handle)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .ok_or_else(|| format_err!("cannot extract filename from {}", file_handle))?;
+        let src = self.key(file_handle);
+        let dst = self.key(&format!("{}/{}", Self::METADATA_BACKUP_DIR, name));
+
+        self.client.copy_object(&src, &dst).await.err_notes(&src)?;
+        self.client.delete_object(&src).await.err_notes(&src)?;
+        Ok(())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let key = self.key(file_handle);
+        self.client.delete_object(&key).await.err_notes(&key)
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        self.client
+            .put_object(&self.key(&file_handle), Bytes::from(content), None)
+            .await
+            .err_notes(name)?;
+        Ok(file_handle)
+    }
+}
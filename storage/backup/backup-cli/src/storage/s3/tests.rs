@@ -0,0 +1,175 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_delete_file_impl, test_save_and_list_metadata_files_impl,
+    test_write_and_read_impl,
+};
+use aptos_s3_client::fake_server::FakeS3Server;
+use proptest::prelude::*;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    runtime::Runtime,
+};
+
+const BUCKET: &str = "backups";
+
+fn get_store(endpoint: &str, prefix: &str, part_size: usize) -> S3 {
+    let config = S3Config::load_from_str(&format!(
+        r#"
+endpoint: "{}"
+bucket: "{}"
+prefix: "{}"
+path_style: true
+credentials:
+  access_key_id: "minioadmin"
+  secret_access_key: "minioadmin"
+multipart_part_size: {}
+read_chunk_size: 128
+retry_base_delay_ms: 1
+"#,
+        endpoint, BUCKET, prefix, part_size,
+    ))
+    .unwrap();
+    S3::new(config).unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (_server, endpoint) = FakeS3Server::start(BUCKET, None);
+            let store = get_store(&endpoint, "e1", 100);
+            test_write_and_read_impl(Box::new(store), backups).await
+        });
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (_server, endpoint) = FakeS3Server::start(BUCKET, None);
+            let store = get_store(&endpoint, "", 100);
+            test_save_and_list_metadata_files_impl(Box::new(store), input).await
+        });
+    }
+}
+
+#[tokio::test]
+async fn test_multipart_upload_with_retries() {
+    // Every 4th request fails with a 503.
+    let (server, endpoint) = FakeS3Server::start(BUCKET, Some(4));
+    let store = get_store(&endpoint, "testnet/e1", 10);
+    let backup_handle = store
+        .create_backup(&"backup".parse().unwrap())
+        .await
+        .unwrap();
+
+    let content = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+    let (file_handle, mut file) = store
+        .create_for_write(&backup_handle, &"file".parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(file_handle, "backup/file");
+    for chunk in content.chunks(33) {
+        file.write_all(chunk).await.unwrap();
+    }
+    file.shutdown().await.unwrap();
+
+    assert_eq!(
+        server.object("testnet/e1/backup/file").unwrap().as_ref(),
+        &content[..]
+    );
+    assert_eq!(server.num_pending_uploads(), 0);
+
+    let mut buf = Vec::new();
+    store
+        .open_for_read(&file_handle)
+        .await
+        .unwrap()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap();
+    assert_eq!(buf, content);
+
+    // Existing files are not overwritten.
+    assert!(store
+        .create_for_write(&backup_handle, &"file".parse().unwrap())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_empty_file() {
+    let (_server, endpoint) = FakeS3Server::start(BUCKET, None);
+    let store = get_store(&endpoint, "", 10);
+
+    let (file_handle, mut file) = store
+        .create_for_write("backup", &"empty".parse().unwrap())
+        .await
+        .unwrap();
+    file.shutdown().await.unwrap();
+
+    let mut buf = Vec::new();
+    store
+        .open_for_read(&file_handle)
+        .await
+        .unwrap()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap();
+    assert!(buf.is_empty());
+
+    // Missing files fail on read.
+    assert!(store
+        .open_for_read("backup/missing")
+        .await
+        .unwrap()
+        .read_to_end(&mut buf)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_backup_metadata_file() {
+    let (server, endpoint) = FakeS3Server::start(BUCKET, None);
+    let store = get_store(&endpoint, "e1", 10);
+
+    let line = TextLine::new("a line").unwrap();
+    let file_handle = store
+        .save_metadata_line(&"epoch_ending_1.meta".parse().unwrap(), &line)
+        .await
+        .unwrap();
+    assert_eq!(file_handle, "metadata/epoch_ending_1.meta");
+    assert_eq!(store.list_metadata_files().await.unwrap(), vec![
+        file_handle.clone()
+    ]);
+
+    store.backup_metadata_file(&file_handle).await.unwrap();
+    assert!(store.list_metadata_files().await.unwrap().is_empty());
+    assert_eq!(
+        server
+            .object("e1/metadata_backup/epoch_ending_1.meta")
+            .unwrap()
+            .as_ref(),
+        line.as_ref().as_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_delete_file() {
+    let (_server, endpoint) = FakeS3Server::start(BUCKET, None);
+    test_delete_file_impl(Box::new(get_store(&endpoint, "e1", 10))).await
+}
+
+#[test]
+fn test_sample_configs() {
+    S3Config::load_from_str(include_str!("sample_configs/s3.sample.yaml")).unwrap();
+    S3Config::load_from_str(include_str!("sample_configs/minio.sample.yaml")).unwrap();
+}
@@ -48,6 +48,49 @@ pub async fn test_write_and_read_impl(
     }
 }
 
+pub async fn test_delete_file_impl(store: Box<dyn BackupStorage>) {
+    let backup_handle = store
+        .create_backup(&"backup".parse().unwrap())
+        .await
+        .unwrap();
+    let mut handles = Vec::new();
+    for name in ["file1", "file2"] {
+        let (handle, mut file) = store
+            .create_for_write(&backup_handle, &name.parse().unwrap())
+            .await
+            .unwrap();
+        file.write_all(name.as_bytes()).await.unwrap();
+        file.shutdown().await.unwrap();
+        handles.push(handle);
+    }
+
+    store.delete_file(&handles[0]).await.unwrap();
+    // Deleting again is fine.
+    store.delete_file(&handles[0]).await.unwrap();
+
+    let mut buf = Vec::new();
+    assert!(async {
+        store
+            .open_for_read(&handles[0])
+            .await?
+            .read_to_end(&mut buf)
+            .await?;
+        Result::<()>::Ok(())
+    }
+    .await
+    .is_err());
+    // Other files are untouched.
+    let mut buf = Vec::new();
+    store
+        .open_for_read(&handles[1])
+        .await
+        .unwrap()
+        .read_to_end(&mut buf)
+        .await
+        .unwrap();
+    assert_eq!(buf, b"file2");
+}
+
 pub fn arb_backups(
 ) -> impl Strategy<Value = HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>> {
     hash_map(
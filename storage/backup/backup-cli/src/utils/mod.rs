@@ -5,12 +5,14 @@
 pub mod backup_service_client;
 pub(crate) mod error_notes;
 pub mod read_record_bytes;
+pub mod restore_journal;
 pub mod storage_ext;
 pub(crate) mod stream;
 
 #[cfg(any(test, feature = "testing"))]
 pub mod test_utils;
 
+use crate::utils::restore_journal::RestoreJournal;
 use aptos_config::config::{
     RocksdbConfig, RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
     DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
@@ -165,6 +167,23 @@ pub struct GlobalRestoreOpt {
     #[clap(flatten)]
     pub replay_concurrency_level: ReplayConcurrencyLevelOpt,
 
+    #[clap(
+        long,
+        help = "Number of chunks being downloaded, verified or waiting to be applied at a time \
+        by each restore, of which at most --concurrent-downloads are being downloaded. Bounds \
+        memory usage. [Defaults to twice the number of concurrent downloads]"
+    )]
+    pub concurrent_chunks: Option<usize>,
+
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with = "dry_run",
+        help = "File recording the restored chunks of each backup, so that an interrupted restore \
+        resumes where it stopped. [Defaults to `restore_journal.jsonl` in the target DB dir]"
+    )]
+    pub restore_journal: Option<PathBuf>,
+
     #[clap(long, help = "Restore the state indices when restore the snapshot")]
     pub enable_state_indices: bool,
 }
@@ -287,7 +306,10 @@ pub struct GlobalRestoreOptions {
     pub trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
     pub run_mode: Arc<RestoreRunMode>,
     pub concurrent_downloads: usize,
+    /// Number of chunks in flight in each restore, no less than `concurrent_downloads`.
+    pub concurrent_chunks: usize,
     pub replay_concurrency_level: usize,
+    pub journal: Arc<RestoreJournal>,
 }
 
 impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
@@ -297,6 +319,18 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
         let target_version = opt.target_version.unwrap_or(Version::max_value());
         let concurrent_downloads = opt.concurrent_downloads.get();
         let replay_concurrency_level = opt.replay_concurrency_level.get();
+        let concurrent_chunks = opt
+            .concurrent_chunks
+            .unwrap_or(concurrent_downloads * 2)
+            .max(concurrent_downloads);
+        let journal = match (&opt.restore_journal, &opt.db_dir) {
+            (Some(path), _) => RestoreJournal::open(path)?,
+            (None, Some(db_dir)) => {
+                std::fs::create_dir_all(db_dir)?;
+                RestoreJournal::open(db_dir.join(RestoreJournal::DEFAULT_FILE_NAME))?
+            },
+            (None, None) => RestoreJournal::disabled(),
+        };
         let run_mode = if let Some(db_dir) = &opt.db_dir {
             // for restore, we can always start state store with empty buffered_state since we will restore
             // TODO(grao): Support path override here.
@@ -326,7 +360,9 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
             trusted_waypoints: Arc::new(opt.trusted_waypoints.verify()?),
             run_mode: Arc::new(run_mode),
             concurrent_downloads,
+            concurrent_chunks,
             replay_concurrency_level,
+            journal: Arc::new(journal),
         })
     }
 }
@@ -0,0 +1,396 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::{FileHandle, FileHandleRef};
+use anyhow::{Context, Result};
+use aptos_crypto::HashValue;
+use aptos_db::state_restore::StateSnapshotRestoreMode;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::Write, path::Path, sync::Arc};
+
+/// Progress of restoring a state snapshot, recorded after every chunk added.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct StateSnapshotJournal {
+    pub version: Version,
+    pub restore_mode: StateSnapshotRestoreMode,
+    /// Number of chunks in the manifest added to the DB, in order.
+    pub chunks_added: usize,
+    pub last_key: Option<HashValue>,
+    pub finished: bool,
+}
+
+/// An update of the journal, appended to the journal file as a line of JSON.
+#[derive(Deserialize, Serialize)]
+enum JournalRecord {
+    EpochEndingSaved {
+        manifest: FileHandle,
+        last_saved_epoch: u64,
+    },
+    StateSnapshot {
+        manifest: FileHandle,
+        progress: StateSnapshotJournal,
+    },
+    TransactionSaved {
+        chunk: FileHandle,
+        last_saved_version: Version,
+    },
+}
+
+#[derive(Default)]
+struct JournalData {
+    /// Last epoch whose ledger info is saved to the DB, keyed by the manifest of the epoch ending
+    /// backup.
+    epoch_endings: BTreeMap<FileHandle, u64>,
+    /// Keyed by manifest.
+    state_snapshots: BTreeMap<FileHandle, StateSnapshotJournal>,
+    /// Last version saved to the DB, keyed by the transactions file of the chunk.
+    transactions: BTreeMap<FileHandle, Version>,
+}
+
+impl JournalData {
+    fn apply(&mut self, record: JournalRecord) {
+        match record {
+            JournalRecord::EpochEndingSaved {
+                manifest,
+                last_saved_epoch,
+            } => Self::record_max(&mut self.epoch_endings, manifest, last_saved_epoch),
+            JournalRecord::StateSnapshot { manifest, progress } => {
+                self.state_snapshots.insert(manifest, progress);
+            },
+            JournalRecord::TransactionSaved {
+                chunk,
+                last_saved_version,
+            } => Self::record_max(&mut self.transactions, chunk, last_saved_version),
+        }
+    }
+
+    fn record_max(map: &mut BTreeMap<FileHandle, u64>, key: FileHandle, value: u64) {
+        map.entry(key)
+            .and_modify(|v| *v = (*v).max(value))
+            .or_insert(value);
+    }
+
+    /// Parses the lines of a journal file. A partially written last line, left by a process
+    /// killed while appending it, is ignored.
+    fn parse(path: &Path, bytes: &[u8]) -> Result<Self> {
+        let mut data = Self::default();
+        let mut lines = bytes.split(|b| *b == b'\n').peekable();
+        while let Some(line) = lines.next() {
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_slice(line) {
+                Ok(record) => data.apply(record),
+                Err(e) if lines.peek().is_none() => {
+                    warn!(path = ?path, error = ?e, "Ignoring incomplete last restore journal record.");
+                },
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to parse restore journal {:?}.", path))
+                },
+            }
+        }
+        Ok(data)
+    }
+
+    /// Serializes the data as one record per key.
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        let records = self
+            .epoch_endings
+            .iter()
+            .map(|(manifest, epoch)| JournalRecord::EpochEndingSaved {
+                manifest: manifest.clone(),
+                last_saved_epoch: *epoch,
+            })
+            .chain(self.state_snapshots.iter().map(|(manifest, progress)| {
+                JournalRecord::StateSnapshot {
+                    manifest: manifest.clone(),
+                    progress: progress.clone(),
+                }
+            }))
+            .chain(self.transactions.iter().map(|(chunk, version)| {
+                JournalRecord::TransactionSaved {
+                    chunk: chunk.clone(),
+                    last_saved_version: *version,
+                }
+            }));
+        let mut bytes = vec![];
+        for record in records {
+            serde_json::to_writer(&mut bytes, &record)?;
+            bytes.push(b'\n');
+        }
+        Ok(bytes)
+    }
+}
+
+/// Records on disk which chunks of which backups have been restored, so that an interrupted
+/// restore resumes where it stopped instead of starting over. The DB stays the source of truth
+/// where it tracks progress itself; the journal is checked against it and never makes a restore
+/// skip anything the DB doesn't have.
+///
+/// Each update is appended to the journal file as a line of JSON, so recording a chunk costs a
+/// small append regardless of how large the journal has grown. The file is compacted to one
+/// record per backup or chunk whenever the journal is opened.
+pub struct RestoreJournal {
+    /// The journal file, opened for appending.
+    file: Option<Arc<Mutex<fs::File>>>,
+    data: Mutex<JournalData>,
+    /// Serializes appending records, so that they're in the same order in the file as in memory.
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl RestoreJournal {
+    pub const DEFAULT_FILE_NAME: &'static str = "restore_journal.jsonl";
+
+    /// Opens the journal at `path`, creating an empty one if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = match fs::read(path) {
+            Ok(bytes) => JournalData::parse(path, &bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => JournalData::default(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}.", path)),
+        };
+        Self::write_file(path, &data.to_bytes()?)?;
+        let file = fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {:?}.", path))?;
+        info!(path = ?path, "Opened restore journal.");
+        Ok(Self {
+            file: Some(Arc::new(Mutex::new(file))),
+            data: Mutex::new(data),
+            write_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// A journal that records nothing, for verifying backups without restoring them.
+    pub fn disabled() -> Self {
+        Self {
+            file: None,
+            data: Mutex::new(JournalData::default()),
+            write_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    pub fn epoch_ending_saved_epoch(&self, manifest: &FileHandleRef) -> Option<u64> {
+        self.data.lock().epoch_endings.get(manifest).copied()
+    }
+
+    pub async fn record_epoch_ending_saved(
+        &self,
+        manifest: &FileHandleRef,
+        last_saved_epoch: u64,
+    ) -> Result<()> {
+        self.append(JournalRecord::EpochEndingSaved {
+            manifest: manifest.to_string(),
+            last_saved_epoch,
+        })
+        .await
+    }
+
+    /// The progress of restoring the state snapshot at `manifest` in `restore_mode`, if any.
+    pub fn state_snapshot(
+        &self,
+        manifest: &FileHandleRef,
+        restore_mode: StateSnapshotRestoreMode,
+    ) -> Option<StateSnapshotJournal> {
+        self.data
+            .lock()
+            .state_snapshots
+            .get(manifest)
+            .filter(|s| s.restore_mode == restore_mode)
+            .cloned()
+    }
+
+    pub async fn record_state_snapshot(
+        &self,
+        manifest: &FileHandleRef,
+        progress: StateSnapshotJournal,
+    ) -> Result<()> {
+        self.append(JournalRecord::StateSnapshot {
+            manifest: manifest.to_string(),
+            progress,
+        })
+        .await
+    }
+
+    pub fn transaction_saved_version(&self, chunk: &FileHandleRef) -> Option<Version> {
+        self.data.lock().transactions.get(chunk).copied()
+    }
+
+    /// The latest transaction version saved by any chunk.
+    pub fn max_transaction_saved_version(&self) -> Option<Version> {
+        self.data.lock().transactions.values().max().copied()
+    }
+
+    pub async fn record_transaction_saved(
+        &self,
+        chunk: &FileHandleRef,
+        last_saved_version: Version,
+    ) -> Result<()> {
+        self.append(JournalRecord::TransactionSaved {
+            chunk: chunk.to_string(),
+            last_saved_version,
+        })
+        .await
+    }
+
+    /// Applies the record and appends it to the journal file, syncing it, so that the journal
+    /// never claims progress that could be lost if the machine crashes.
+    async fn append(&self, record: JournalRecord) -> Result<()> {
+        let file = match &self.file {
+            Some(file) => file.clone(),
+            None => return Ok(()),
+        };
+        let _write_guard = self.write_lock.lock().await;
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        self.data.lock().apply(record);
+        tokio::task::spawn_blocking(move || {
+            let mut file = file.lock();
+            file.write_all(&line).and_then(|_| file.sync_data())
+        })
+        .await?
+        .context("Failed to append to the restore journal.")
+    }
+
+    /// Writes to a temporary file and renames it over `path`, syncing both, so that compacting
+    /// the journal never loses recorded progress.
+    fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)
+            .with_context(|| format!("Failed to create {:?}.", tmp_path))?;
+        file.write_all(bytes)
+            .and_then(|_| file.sync_all())
+            .with_context(|| format!("Failed to write {:?}.", tmp_path))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to rename {:?} to {:?}.", tmp_path, path))?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::File::open(dir)
+                .and_then(|dir| dir.sync_all())
+                .with_context(|| format!("Failed to sync {:?}.", dir))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+
+    #[tokio::test]
+    async fn test_persisted_across_opens() {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let path = tmpdir.path().join(RestoreJournal::DEFAULT_FILE_NAME);
+
+        let journal = RestoreJournal::open(&path).unwrap();
+        assert_eq!(journal.epoch_ending_saved_epoch("epoch/manifest"), None);
+        journal
+            .record_epoch_ending_saved("epoch/manifest", 3)
+            .await
+            .unwrap();
+        let progress = StateSnapshotJournal {
+            version: 100,
+            restore_mode: StateSnapshotRestoreMode::KvOnly,
+            chunks_added: 2,
+            last_key: Some(HashValue::random()),
+            finished: false,
+        };
+        journal
+            .record_state_snapshot("state/manifest", progress.clone())
+            .await
+            .unwrap();
+        journal
+            .record_transaction_saved("txn/chunk0", 10)
+            .await
+            .unwrap();
+        journal
+            .record_transaction_saved("txn/chunk0", 5)
+            .await
+            .unwrap();
+
+        let journal = RestoreJournal::open(&path).unwrap();
+        assert_eq!(journal.epoch_ending_saved_epoch("epoch/manifest"), Some(3));
+        assert_eq!(
+            journal.state_snapshot("state/manifest", StateSnapshotRestoreMode::KvOnly),
+            Some(progress),
+        );
+        assert_eq!(
+            journal.state_snapshot("state/manifest", StateSnapshotRestoreMode::TreeOnly),
+            None,
+        );
+        journal
+            .record_transaction_saved("txn/chunk1", 20)
+            .await
+            .unwrap();
+        assert_eq!(journal.transaction_saved_version("txn/chunk0"), Some(10));
+        assert_eq!(journal.transaction_saved_version("txn/chunk2"), None);
+        assert_eq!(journal.max_transaction_saved_version(), Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_incomplete_last_record() {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let path = tmpdir.path().join(RestoreJournal::DEFAULT_FILE_NAME);
+
+        let journal = RestoreJournal::open(&path).unwrap();
+        journal
+            .record_transaction_saved("txn/chunk0", 10)
+            .await
+            .unwrap();
+        journal
+            .record_transaction_saved("txn/chunk0", 20)
+            .await
+            .unwrap();
+        drop(journal);
+
+        // The process was killed while appending a record.
+        let mut bytes = fs::read(&path).unwrap();
+        let len = bytes.len();
+        bytes.truncate(len - 5);
+        fs::write(&path, &bytes).unwrap();
+        let journal = RestoreJournal::open(&path).unwrap();
+        assert_eq!(journal.transaction_saved_version("txn/chunk0"), Some(10));
+
+        // The file is compacted on open, so records appended after that are readable.
+        journal
+            .record_transaction_saved("txn/chunk1", 30)
+            .await
+            .unwrap();
+        let journal = RestoreJournal::open(&path).unwrap();
+        assert_eq!(journal.transaction_saved_version("txn/chunk0"), Some(10));
+        assert_eq!(journal.transaction_saved_version("txn/chunk1"), Some(30));
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        // Corruption before the last record is an error.
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[0] = b'x';
+        fs::write(&path, &bytes).unwrap();
+        assert!(RestoreJournal::open(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let journal = RestoreJournal::disabled();
+        assert!(!journal.is_enabled());
+        journal
+            .record_epoch_ending_saved("epoch/manifest", 3)
+            .await
+            .unwrap();
+        journal
+            .record_transaction_saved("txn/chunk0", 10)
+            .await
+            .unwrap();
+        assert_eq!(journal.transaction_saved_version("txn/chunk0"), None);
+    }
+}
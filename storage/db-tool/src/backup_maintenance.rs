@@ -2,8 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::backup::BackupCompactor, metadata::cache::MetadataCacheOpt,
-    storage::DBToolStorageOpt, utils::ConcurrentDownloadsOpt,
+    coordinators::{
+        backup::BackupCompactor,
+        prune::{BackupPruner, RetentionPolicy},
+    },
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::ConcurrentDownloadsOpt,
 };
 use clap::{Parser, Subcommand};
 
@@ -14,6 +19,8 @@ pub enum Command {
     Compact(CompactionOpt),
     #[clap(about = "Cleanup the backup metadata files")]
     Cleanup(CleanupOpt),
+    #[clap(about = "Prune backups according to a retention policy")]
+    Prune(PruneOpt),
 }
 
 #[derive(Parser)]
@@ -42,6 +49,19 @@ pub struct CompactionOpt {
     pub remove_compacted_file_after: u64,
 }
 
+#[derive(Parser)]
+pub struct PruneOpt {
+    #[clap(flatten)]
+    pub policy: RetentionPolicy,
+    #[clap(flatten)]
+    pub storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+    /// Only print what would be pruned, without changing anything in the backup storage
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
 #[derive(Parser)]
 pub struct CleanupOpt {
     #[clap(flatten)]
@@ -63,6 +83,17 @@ impl Command {
                 );
                 compactor.run().await?
             },
+            Command::Prune(opt) => {
+                let report = BackupPruner::new(
+                    opt.storage.init_storage().await?,
+                    opt.policy,
+                    opt.concurrent_downloads.get(),
+                    opt.dry_run,
+                )
+                .run()
+                .await?;
+                println!("{}", serde_json::to_string_pretty(&report)?)
+            },
             Command::Cleanup(_) => {
                 // TODO: add cleanup logic for removing obsolete metadata files
             },
@@ -0,0 +1,543 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Context, Result};
+use aptos_backup_cli::utils::RocksdbOpt;
+use aptos_config::config::{
+    StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_crypto::hash::CryptoHash;
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    contract_event::ContractEvent,
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::{TransactionWrite, WriteOpKind, WriteSet},
+};
+use clap::Parser;
+use parquet::{
+    basic::{Compression, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedColumnWriter, SerializedFileWriter},
+    },
+    format::KeyValue,
+    schema::{parser::parse_message_type, types::Type},
+};
+use std::{
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Bumped whenever a column is added, removed or changes meaning, so consumers can tell exports
+/// apart. Written to the key-value metadata of every file.
+pub const SCHEMA_VERSION: u64 = 1;
+pub const SCHEMA_VERSION_KEY: &str = "aptos_export_schema_version";
+
+const TRANSACTIONS_SCHEMA: &str = "
+    message transactions {
+        REQUIRED INT64 version (INTEGER(64, false));
+        REQUIRED BYTE_ARRAY hash (UTF8);
+        REQUIRED BYTE_ARRAY type (UTF8);
+        OPTIONAL BYTE_ARRAY sender (UTF8);
+        OPTIONAL INT64 sequence_number (INTEGER(64, false));
+        OPTIONAL INT64 block_timestamp_usecs (INTEGER(64, false));
+        REQUIRED INT64 gas_used (INTEGER(64, false));
+        REQUIRED BOOLEAN success;
+        REQUIRED BYTE_ARRAY vm_status (UTF8);
+        REQUIRED BYTE_ARRAY state_change_hash (UTF8);
+        REQUIRED BYTE_ARRAY event_root_hash (UTF8);
+        OPTIONAL BYTE_ARRAY state_checkpoint_hash (UTF8);
+        REQUIRED INT64 num_events (INTEGER(64, false));
+        REQUIRED INT64 num_write_set_changes (INTEGER(64, false));
+        REQUIRED BYTE_ARRAY bcs;
+    }
+";
+
+const EVENTS_SCHEMA: &str = "
+    message events {
+        REQUIRED INT64 version (INTEGER(64, false));
+        REQUIRED INT64 event_index (INTEGER(64, false));
+        REQUIRED BYTE_ARRAY type_tag (UTF8);
+        OPTIONAL BYTE_ARRAY creator_address (UTF8);
+        OPTIONAL INT64 creation_number (INTEGER(64, false));
+        OPTIONAL INT64 sequence_number (INTEGER(64, false));
+        REQUIRED BYTE_ARRAY data;
+    }
+";
+
+const WRITE_SET_CHANGES_SCHEMA: &str = "
+    message write_set_changes {
+        REQUIRED INT64 version (INTEGER(64, false));
+        REQUIRED INT64 change_index (INTEGER(64, false));
+        REQUIRED BYTE_ARRAY state_key_hash (UTF8);
+        REQUIRED BYTE_ARRAY state_key;
+        REQUIRED BYTE_ARRAY op (UTF8);
+        OPTIONAL BYTE_ARRAY value;
+    }
+";
+
+/// Exports transactions, events and write sets in a version range from the ledger DB into
+/// Parquet files, one per table, for offline analytics.
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    #[clap(flatten)]
+    rocksdb_opt: RocksdbOpt,
+
+    #[clap(long, help = "The first version to export.")]
+    start_version: Version,
+
+    #[clap(
+        long,
+        help = "The number of versions to export. [Defaults to all versions synced after \
+        --start-version]"
+    )]
+    num_versions: Option<u64>,
+
+    #[clap(
+        long,
+        value_parser,
+        help = "Directory to write the files into. Existing files are never overwritten."
+    )]
+    output_dir: PathBuf,
+
+    #[clap(
+        long,
+        default_value_t = 100_000,
+        help = "Maximum number of rows in a Parquet row group."
+    )]
+    row_group_size: usize,
+}
+
+impl Opt {
+    pub async fn run(self) -> Result<()> {
+        ensure!(
+            self.row_group_size > 0,
+            "--row-group-size must be positive."
+        );
+        let db = AptosDB::open(
+            StorageDirPaths::from_path(&self.db_dir),
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            self.rocksdb_opt.clone().into(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+            None,
+        )?;
+
+        let synced_version = db
+            .get_synced_version()?
+            .context("DB is empty, nothing to export.")?;
+        let first_txn_version = db.get_first_txn_version()?.unwrap_or(0);
+        ensure!(
+            self.start_version >= first_txn_version,
+            "--start-version {} is pruned, first available version is {}.",
+            self.start_version,
+            first_txn_version,
+        );
+        ensure!(
+            self.start_version <= synced_version,
+            "--start-version {} is beyond the latest synced version {}.",
+            self.start_version,
+            synced_version,
+        );
+        let max_num_versions = synced_version - self.start_version + 1;
+        let num_versions = self
+            .num_versions
+            .map_or(max_num_versions, |n| n.min(max_num_versions));
+        ensure!(num_versions > 0, "--num-versions must be positive.");
+        let last_version = self.start_version + num_versions - 1;
+
+        std::fs::create_dir_all(&self.output_dir)?;
+        let file_path = |table: &str| {
+            self.output_dir.join(format!(
+                "{}_{}-{}.parquet",
+                table, self.start_version, last_version
+            ))
+        };
+        let mut exporter = Exporter {
+            transactions: TableWriter::create(
+                &file_path("transactions"),
+                TRANSACTIONS_SCHEMA,
+                self.row_group_size,
+            )?,
+            events: TableWriter::create(&file_path("events"), EVENTS_SCHEMA, self.row_group_size)?,
+            write_set_changes: TableWriter::create(
+                &file_path("write_set_changes"),
+                WRITE_SET_CHANGES_SCHEMA,
+                self.row_group_size,
+            )?,
+            // Transactions before the first block metadata in the range belong to the block
+            // started before it; genesis is not in any block.
+            block_timestamp_usecs: db.get_block_timestamp(self.start_version).ok(),
+        };
+
+        let backup_handler = db.get_backup_handler();
+        let mut version = self.start_version;
+        for item in
+            backup_handler.get_transaction_iter(self.start_version, num_versions as usize)?
+        {
+            let (txn, txn_info, events, write_set) = item?;
+            exporter.add(version, &txn, &txn_info, &events, &write_set)?;
+            version += 1;
+            if (version - self.start_version) % 100_000 == 0 {
+                info!(version = version, "Exporting to Parquet.");
+            }
+        }
+        ensure!(
+            version == last_version + 1,
+            "Expected to export up to version {}, but the ledger DB ends at version {}.",
+            last_version,
+            version - 1,
+        );
+
+        let (num_txns, num_events, num_changes) = exporter.finish()?;
+        info!(
+            start_version = self.start_version,
+            last_version = last_version,
+            num_txns = num_txns,
+            num_events = num_events,
+            num_write_set_changes = num_changes,
+            output_dir = ?self.output_dir,
+            "Exported to Parquet."
+        );
+        Ok(())
+    }
+}
+
+struct Exporter {
+    transactions: TableWriter,
+    events: TableWriter,
+    write_set_changes: TableWriter,
+    block_timestamp_usecs: Option<u64>,
+}
+
+impl Exporter {
+    fn add(
+        &mut self,
+        version: Version,
+        txn: &Transaction,
+        txn_info: &TransactionInfo,
+        events: &[ContractEvent],
+        write_set: &WriteSet,
+    ) -> Result<()> {
+        match txn {
+            Transaction::BlockMetadata(b) => self.block_timestamp_usecs = Some(b.timestamp_usecs()),
+            Transaction::BlockMetadataExt(b) => {
+                self.block_timestamp_usecs = Some(b.timestamp_usecs())
+            },
+            Transaction::GenesisTransaction(_) => self.block_timestamp_usecs = None,
+            _ => (),
+        }
+        let user_txn = txn.try_as_signed_user_txn();
+
+        self.transactions.push_row(vec![
+            Value::UInt64(version),
+            Value::string(txn_info.transaction_hash().to_hex_literal()),
+            Value::string(txn.type_name()),
+            Value::optional_string(user_txn.map(|t| t.sender().to_hex_literal())),
+            Value::optional_u64(user_txn.map(|t| t.sequence_number())),
+            Value::optional_u64(self.block_timestamp_usecs),
+            Value::UInt64(txn_info.gas_used()),
+            Value::Boolean(txn_info.status().is_success()),
+            Value::string(format!("{:?}", txn_info.status())),
+            Value::string(txn_info.state_change_hash().to_hex_literal()),
+            Value::string(txn_info.event_root_hash().to_hex_literal()),
+            Value::optional_string(txn_info.state_checkpoint_hash().map(|h| h.to_hex_literal())),
+            Value::UInt64(events.len() as u64),
+            Value::UInt64(write_set.iter().count() as u64),
+            Value::Bytes(bcs::to_bytes(txn)?),
+        ])?;
+
+        for (idx, event) in events.iter().enumerate() {
+            let v1 = event.v1().ok();
+            self.events.push_row(vec![
+                Value::UInt64(version),
+                Value::UInt64(idx as u64),
+                Value::string(event.type_tag().to_canonical_string()),
+                Value::optional_string(v1.map(|e| e.key().get_creator_address().to_hex_literal())),
+                Value::optional_u64(v1.map(|e| e.key().get_creation_number())),
+                Value::optional_u64(v1.map(|e| e.sequence_number())),
+                Value::Bytes(event.event_data().to_vec()),
+            ])?;
+        }
+
+        for (idx, (state_key, write_op)) in write_set.iter().enumerate() {
+            let op = match write_op.write_op_kind() {
+                WriteOpKind::Creation => "creation",
+                WriteOpKind::Modification => "modification",
+                WriteOpKind::Deletion => "deletion",
+            };
+            self.write_set_changes.push_row(vec![
+                Value::UInt64(version),
+                Value::UInt64(idx as u64),
+                Value::string(CryptoHash::hash(state_key).to_hex_literal()),
+                Value::Bytes(bcs::to_bytes(state_key)?),
+                Value::string(op),
+                write_op
+                    .bytes()
+                    .map_or(Value::Null, |bytes| Value::Bytes(bytes.to_vec())),
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Completes the files, and only moves them into place once all of them are complete.
+    fn finish(self) -> Result<(u64, u64, u64)> {
+        let (num_txns, transactions) = self.transactions.finish()?;
+        let (num_events, events) = self.events.finish()?;
+        let (num_changes, write_set_changes) = self.write_set_changes.finish()?;
+        for file in [transactions, events, write_set_changes] {
+            file.persist()?;
+        }
+        Ok((num_txns, num_events, num_changes))
+    }
+}
+
+enum Value {
+    Boolean(bool),
+    UInt64(u64),
+    Bytes(Vec<u8>),
+    Null,
+}
+
+impl Value {
+    fn string(s: impl Into<String>) -> Self {
+        Self::Bytes(s.into().into_bytes())
+    }
+
+    fn optional_string(s: Option<String>) -> Self {
+        s.map_or(Self::Null, Self::string)
+    }
+
+    fn optional_u64(v: Option<u64>) -> Self {
+        v.map_or(Self::Null, Self::UInt64)
+    }
+}
+
+enum ColumnValues {
+    Boolean(Vec<bool>),
+    Int64(Vec<i64>),
+    ByteArray(Vec<ByteArray>),
+}
+
+/// Buffers a row group worth of values for one column.
+struct ColumnBuffer {
+    name: String,
+    values: ColumnValues,
+    /// Definition levels, only for optional columns.
+    def_levels: Option<Vec<i16>>,
+}
+
+impl ColumnBuffer {
+    fn new(field: &Type) -> Result<Self> {
+        let name = field.name().to_string();
+        let values = match field.get_physical_type() {
+            PhysicalType::BOOLEAN => ColumnValues::Boolean(Vec::new()),
+            PhysicalType::INT64 => ColumnValues::Int64(Vec::new()),
+            PhysicalType::BYTE_ARRAY => ColumnValues::ByteArray(Vec::new()),
+            t => anyhow::bail!("Unsupported physical type {} of column {}.", t, name),
+        };
+        let def_levels = match field.get_basic_info().repetition() {
+            Repetition::REQUIRED => None,
+            Repetition::OPTIONAL => Some(Vec::new()),
+            Repetition::REPEATED => anyhow::bail!("Repeated column {} not supported.", name),
+        };
+        Ok(Self {
+            name,
+            values,
+            def_levels,
+        })
+    }
+
+    fn push(&mut self, value: Value) -> Result<()> {
+        match (&mut self.def_levels, &value) {
+            (None, Value::Null) => anyhow::bail!("Null value in required column {}.", self.name),
+            (None, _) => (),
+            (Some(levels), Value::Null) => {
+                levels.push(0);
+                return Ok(());
+            },
+            (Some(levels), _) => levels.push(1),
+        }
+        match (&mut self.values, value) {
+            (ColumnValues::Boolean(values), Value::Boolean(v)) => values.push(v),
+            // Stored as the bit pattern, the column is annotated as unsigned.
+            (ColumnValues::Int64(values), Value::UInt64(v)) => values.push(v as i64),
+            (ColumnValues::ByteArray(values), Value::Bytes(v)) => values.push(v.into()),
+            _ => anyhow::bail!("Value of the wrong type for column {}.", self.name),
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, column: &mut SerializedColumnWriter) -> Result<()> {
+        let def_levels = self.def_levels.as_deref();
+        let written = match (column.untyped(), &self.values) {
+            (ColumnWriter::BoolColumnWriter(w), ColumnValues::Boolean(values)) => {
+                w.write_batch(values, def_levels, None)?
+            },
+            (ColumnWriter::Int64ColumnWriter(w), ColumnValues::Int64(values)) => {
+                w.write_batch(values, def_levels, None)?
+            },
+            (ColumnWriter::ByteArrayColumnWriter(w), ColumnValues::ByteArray(values)) => {
+                w.write_batch(values, def_levels, None)?
+            },
+            _ => anyhow::bail!("Column writer of the wrong type for column {}.", self.name),
+        };
+        let expected = match &self.values {
+            ColumnValues::Boolean(values) => values.len(),
+            ColumnValues::Int64(values) => values.len(),
+            ColumnValues::ByteArray(values) => values.len(),
+        };
+        ensure!(
+            written == expected,
+            "Wrote {} of {} values for column {}.",
+            written,
+            expected,
+            self.name,
+        );
+        self.clear();
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        match &mut self.values {
+            ColumnValues::Boolean(values) => values.clear(),
+            ColumnValues::Int64(values) => values.clear(),
+            ColumnValues::ByteArray(values) => values.clear(),
+        }
+        if let Some(levels) = &mut self.def_levels {
+            levels.clear();
+        }
+    }
+}
+
+/// A file written under a temporary path, and moved to its final path once complete. Removed if
+/// dropped before that, so a failed export doesn't leave partial files behind.
+struct PartialFile {
+    temp_path: PathBuf,
+    path: PathBuf,
+}
+
+impl PartialFile {
+    fn create(path: &Path) -> Result<(Self, File)> {
+        ensure!(!path.exists(), "{:?} already exists.", path);
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        let partial_file = Self {
+            temp_path: temp_path.into(),
+            path: path.to_path_buf(),
+        };
+        // A temporary file can only be left over by a crashed export, so it's overwritten.
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&partial_file.temp_path)
+            .with_context(|| format!("Failed to create {:?}.", partial_file.temp_path))?;
+        Ok((partial_file, file))
+    }
+
+    fn persist(self) -> Result<()> {
+        std::fs::rename(&self.temp_path, &self.path)
+            .with_context(|| format!("Failed to move {:?} to {:?}.", self.temp_path, self.path))
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        // Nothing to remove once persisted.
+        let _ = std::fs::remove_file(&self.temp_path);
+    }
+}
+
+/// Writes rows of a single table into a Parquet file, one row group at a time.
+struct TableWriter {
+    writer: SerializedFileWriter<File>,
+    file: PartialFile,
+    columns: Vec<ColumnBuffer>,
+    row_group_size: usize,
+    rows_in_group: usize,
+    num_rows: u64,
+}
+
+impl TableWriter {
+    fn create(path: &Path, schema: &str, row_group_size: usize) -> Result<Self> {
+        let schema = Arc::new(parse_message_type(schema)?);
+        let columns = schema
+            .get_fields()
+            .iter()
+            .map(|field| ColumnBuffer::new(field))
+            .collect::<Result<_>>()?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::LZ4_RAW)
+            .set_max_row_group_size(row_group_size)
+            .set_key_value_metadata(Some(vec![KeyValue::new(
+                SCHEMA_VERSION_KEY.to_string(),
+                SCHEMA_VERSION.to_string(),
+            )]))
+            .build();
+        let (partial_file, file) = PartialFile::create(path)?;
+        info!(path = ?path, "Writing Parquet file.");
+
+        Ok(Self {
+            writer: SerializedFileWriter::new(file, schema, Arc::new(props))?,
+            file: partial_file,
+            columns,
+            row_group_size,
+            rows_in_group: 0,
+            num_rows: 0,
+        })
+    }
+
+    fn push_row(&mut self, row: Vec<Value>) -> Result<()> {
+        ensure!(
+            row.len() == self.columns.len(),
+            "Row has {} values, expecting {}.",
+            row.len(),
+            self.columns.len(),
+        );
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            column.push(value)?;
+        }
+        self.rows_in_group += 1;
+        self.num_rows += 1;
+        if self.rows_in_group == self.row_group_size {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    fn flush_row_group(&mut self) -> Result<()> {
+        if self.rows_in_group == 0 {
+            return Ok(());
+        }
+        let mut row_group = self.writer.next_row_group()?;
+        for column in &mut self.columns {
+            let mut column_writer = row_group
+                .next_column()?
+                .with_context(|| format!("No writer for column {}.", column.name))?;
+            column.write(&mut column_writer)?;
+            column_writer.close()?;
+        }
+        row_group.close()?;
+        self.rows_in_group = 0;
+        Ok(())
+    }
+
+    /// Returns the number of rows written, and the complete file.
+    fn finish(mut self) -> Result<(u64, PartialFile)> {
+        self.flush_row_group()?;
+        self.writer.close()?;
+        Ok((self.num_rows, self.file))
+    }
+}
@@ -64,6 +64,34 @@ fn test_various_cmd_parsing() {
         "--start-version",
         "Max",
     ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "debug",
+        "state-kv",
+        "diff",
+        "--db-dir",
+        ".",
+        "--from",
+        "10",
+        "--to",
+        "20",
+        "--address",
+        "0x1",
+        "--resource-type",
+        "0x1::coin::CoinStore",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export-parquet",
+        "--db-dir",
+        ".",
+        "--start-version",
+        "0",
+        "--num-versions",
+        "100",
+        "--output-dir",
+        ".",
+    ]);
 }
 
 fn run_cmd(args: &[&str]) {
@@ -690,6 +718,237 @@ mod dbtool_tests {
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 
+    #[test]
+    fn test_export_parquet() {
+        use crate::export_parquet::{SCHEMA_VERSION, SCHEMA_VERSION_KEY};
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let db_dir = TempPath::new();
+        db_dir.create_as_dir().unwrap();
+        let output_dir = TempPath::new();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let synced_version = db.get_synced_version().unwrap().unwrap();
+        let (num_events, num_changes) = db
+            .get_backup_handler()
+            .get_transaction_iter(1, synced_version as usize)
+            .unwrap()
+            .fold((0, 0), |(events, changes), item| {
+                let (_, _, txn_events, write_set) = item.unwrap();
+                (
+                    events + txn_events.len() as i64,
+                    changes + write_set.iter().count() as i64,
+                )
+            });
+        drop(db);
+
+        let export = || {
+            let rt = Runtime::new().unwrap();
+            rt.block_on(
+                DBTool::try_parse_from([
+                    "aptos-db-tool",
+                    "export-parquet",
+                    "--db-dir",
+                    db_dir.path().to_str().unwrap(),
+                    "--start-version",
+                    "1",
+                    "--output-dir",
+                    output_dir.path().to_str().unwrap(),
+                    "--row-group-size",
+                    "3",
+                ])
+                .unwrap()
+                .run(),
+            )
+        };
+        export().unwrap();
+
+        for (table, expected_rows) in [
+            ("transactions", synced_version as i64),
+            ("events", num_events),
+            ("write_set_changes", num_changes),
+        ] {
+            let path = output_dir
+                .path()
+                .join(format!("{}_1-{}.parquet", table, synced_version));
+            let reader = SerializedFileReader::new(fs::File::open(path).unwrap()).unwrap();
+            let file_metadata = reader.metadata().file_metadata();
+            assert_eq!(file_metadata.num_rows(), expected_rows);
+            let schema_version = file_metadata
+                .key_value_metadata()
+                .unwrap()
+                .iter()
+                .find(|kv| kv.key == SCHEMA_VERSION_KEY)
+                .and_then(|kv| kv.value.clone());
+            assert_eq!(schema_version, Some(SCHEMA_VERSION.to_string()));
+        }
+
+        // Existing files are not overwritten.
+        assert!(export().is_err());
+
+        // A failed export doesn't leave partial files behind.
+        let transactions_path = output_dir
+            .path()
+            .join(format!("transactions_1-{}.parquet", synced_version));
+        fs::remove_file(&transactions_path).unwrap();
+        assert!(export().is_err());
+        assert!(!transactions_path.exists());
+        assert_eq!(fs::read_dir(output_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_state_kv_diff() {
+        use aptos_crypto::hash::CryptoHash;
+        use aptos_db::db_debugger::state_kv::diff::{Cmd as DiffCmd, StateKeyChange};
+        use aptos_types::{
+            access_path::Path as StatePath,
+            account_address::AccountAddress,
+            state_store::{
+                state_key::{inner::StateKeyInner, StateKey},
+                state_value::StateValue,
+            },
+            write_set::TransactionWrite,
+        };
+        use std::collections::BTreeMap;
+
+        let db_dir = TempPath::new();
+        db_dir.create_as_dir().unwrap();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let synced_version = db.get_synced_version().unwrap().unwrap();
+
+        // The changes expected between two versions, according to the write sets in between.
+        type Change = (StateKey, Option<StateValue>, Option<(Version, StateValue)>);
+        let expected_changes = |from: Version, to: Version| -> Vec<Change> {
+            let mut last_writes = BTreeMap::new();
+            for (offset, item) in db
+                .get_backup_handler()
+                .get_transaction_iter(from + 1, (to - from) as usize)
+                .unwrap()
+                .enumerate()
+            {
+                let (_, _, _, write_set) = item.unwrap();
+                for (key, write_op) in write_set {
+                    last_writes.insert(
+                        key,
+                        (from + 1 + offset as Version, write_op.as_state_value()),
+                    );
+                }
+            }
+            let mut changes: Vec<_> = last_writes
+                .into_iter()
+                .filter_map(|(key, (version, new_value))| {
+                    let old_value = db.get_state_value_by_version(&key, from).unwrap();
+                    (old_value != new_value).then(|| {
+                        let new = new_value.map(|value| (version, value));
+                        (key, old_value, new)
+                    })
+                })
+                .collect();
+            changes.sort_by_key(|(key, _, _)| CryptoHash::hash(key));
+            changes
+        };
+        let all_changes = expected_changes(0, synced_version);
+        let inner_changes = expected_changes(1, synced_version - 1);
+        drop(db);
+
+        let diff = |from: Version, to: Version, filters: &[&str]| {
+            let from_arg = from.to_string();
+            let to_arg = to.to_string();
+            let mut args = vec![
+                "diff",
+                "--db-dir",
+                db_dir.path().to_str().unwrap(),
+                "--from",
+                &from_arg,
+                "--to",
+                &to_arg,
+            ];
+            args.extend_from_slice(filters);
+            DiffCmd::try_parse_from(args).unwrap().diff().map(|diff| {
+                diff.changes
+                    .into_iter()
+                    .map(|StateKeyChange { key, old, new }| {
+                        let old = old.map(|(version, value)| {
+                            assert!(version <= from);
+                            value
+                        });
+                        (key, old, new)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // Between state snapshots, and scanning the write sets in between.
+        let changes = diff(0, synced_version, &[]).unwrap();
+        assert!(!changes.is_empty());
+        assert_eq!(changes, all_changes);
+        let changes = diff(1, synced_version - 1, &["--raw"]).unwrap();
+        assert!(!changes.is_empty());
+        assert_eq!(changes, inner_changes);
+
+        // Filtered by address and by resource type.
+        let is_under = |key: &StateKey, address: AccountAddress| match key.inner() {
+            StateKeyInner::AccessPath(access_path) => access_path.address == address,
+            _ => false,
+        };
+        let changes = diff(1, synced_version - 1, &["--address", "0x1"]).unwrap();
+        assert!(!changes.is_empty());
+        assert_eq!(
+            changes,
+            inner_changes
+                .into_iter()
+                .filter(|(key, _, _)| is_under(key, AccountAddress::ONE))
+                .collect::<Vec<_>>()
+        );
+        let changes = diff(0, synced_version, &[
+            "--resource-type",
+            "0x1::coin::CoinStore",
+        ])
+        .unwrap();
+        assert!(changes.len() < all_changes.len());
+        for change in &changes {
+            assert!(all_changes.contains(change));
+            match change.0.inner() {
+                StateKeyInner::AccessPath(ap) => match ap.get_path() {
+                    StatePath::Resource(tag) => {
+                        assert_eq!(tag.address, AccountAddress::ONE);
+                        assert_eq!(tag.module.as_str(), "coin");
+                        assert_eq!(tag.name.as_str(), "CoinStore");
+                    },
+                    StatePath::ResourceGroup(_) => (),
+                    StatePath::Code(_) => panic!("Modules don't match a resource type."),
+                },
+                _ => panic!("Only resources match a resource type."),
+            }
+        }
+
+        // Bad version ranges.
+        assert!(diff(synced_version, 0, &[]).is_err());
+        assert!(diff(0, synced_version + 1, &[]).is_err());
+
+        // The command itself runs.
+        let run = |filters: &[&str]| {
+            let to = synced_version.to_string();
+            let mut args = vec![
+                "aptos-db-tool",
+                "debug",
+                "state-kv",
+                "diff",
+                "--db-dir",
+                db_dir.path().to_str().unwrap(),
+                "--from",
+                "0",
+                "--to",
+                &to,
+            ];
+            args.extend_from_slice(filters);
+            Runtime::new()
+                .unwrap()
+                .block_on(DBTool::try_parse_from(args).unwrap().run())
+        };
+        run(&[]).unwrap();
+        run(&["--address", "0x1", "--raw"]).unwrap();
+    }
+
     fn dir_size<P: AsRef<Path>>(path: P) -> u64 {
         let mut size = 0;
 
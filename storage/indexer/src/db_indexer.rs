@@ -9,8 +9,8 @@ use aptos_config::config::internal_indexer_db_config::InternalIndexerDBConfig;
 use aptos_db_indexer_schemas::{
     metadata::{MetadataKey, MetadataValue, StateSnapshotProgress},
     schema::{
-        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
-        event_sequence_number::EventSequenceNumberSchema,
+        event_by_key::EventByKeySchema, event_by_type::EventByTypeSchema,
+        event_by_version::EventByVersionSchema, event_sequence_number::EventSequenceNumberSchema,
         indexer_metadata::InternalIndexerMetadataSchema, state_keys::StateKeysSchema,
         transaction_by_account::TransactionByAccountSchema,
         translated_v1_event::TranslatedV1EventSchema,
@@ -38,6 +38,7 @@ use aptos_types::{
     transaction::{AccountTransactionsWithProof, Transaction, Version},
     write_set::{TransactionWrite, WriteSet},
 };
+use move_core_types::language_storage::{StructTag, TypeTag};
 use std::{
     cmp::min,
     collections::HashSet,
@@ -126,6 +127,25 @@ impl InternalIndexerDB {
         self.get_version(&MetadataKey::EventV2TranslationVersion)
     }
 
+    /// The events by type index was added after the other event indices, so on an existing
+    /// indexer DB it only covers versions indexed since the upgrade.
+    pub fn get_events_by_type_start_version(&self) -> Result<Option<Version>> {
+        self.get_version(&MetadataKey::EventByTypeStartVersion)
+    }
+
+    /// Records the next version to index as the first one covered by the events by type index,
+    /// unless it's already recorded.
+    fn init_events_by_type_start_version(&self) -> Result<()> {
+        if self.get_events_by_type_start_version()?.is_none() {
+            let start_version = self.get_event_version()?.map_or(0, |v| v + 1);
+            self.db.put::<InternalIndexerMetadataSchema>(
+                &MetadataKey::EventByTypeStartVersion,
+                &MetadataValue::Version(start_version),
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn event_enabled(&self) -> bool {
         self.config.enable_event
     }
@@ -243,6 +263,54 @@ impl InternalIndexerDB {
         Ok(result)
     }
 
+    /// Given `struct_tag` and `start_version`, returns module events of that type identified by
+    /// transaction version and index among all events emitted by the same transaction. Result
+    /// won't contain records with a transaction version > `ledger_version` and is in ascending
+    /// order.
+    ///
+    /// The events of a transaction are never split, so the caller can resume from the version
+    /// after the last one returned without missing any event. If `limit` is hit in the middle of
+    /// a transaction, its events are dropped, unless it's the only transaction in the result, in
+    /// which case all of its events are returned even if there are more than `limit`.
+    pub fn lookup_events_by_type(
+        &self,
+        struct_tag: &StructTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<
+        Vec<(
+            Version, // transaction version it belongs to
+            u64,     // index among events for the same transaction
+        )>,
+    > {
+        let mut iter = self.db.iter::<EventByTypeSchema>()?;
+        iter.seek(&(struct_tag.clone(), start_version, 0))?;
+
+        let mut result: Vec<(Version, u64)> = Vec::new();
+        for res in iter {
+            let ((tag, ver, idx), ()) = res?;
+            if &tag != struct_tag || ver > ledger_version {
+                break;
+            }
+            if result.len() as u64 >= limit {
+                let last_ver = result.last().map(|(last_ver, _)| *last_ver);
+                if last_ver != Some(ver) {
+                    break;
+                }
+                let first_of_last_txn = result.partition_point(|(v, _)| *v < ver);
+                if first_of_last_txn > 0 {
+                    result.truncate(first_of_last_txn);
+                    break;
+                }
+                // The only transaction in the result, keep all of its events.
+            }
+            result.push((ver, idx));
+        }
+
+        Ok(result)
+    }
+
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn get_restore_version_and_progress(
         &self,
@@ -323,9 +391,11 @@ impl Drop for DBIndexer {
 }
 
 impl DBIndexer {
-    pub fn new(indexer_db: InternalIndexerDB, db_reader: Arc<dyn DbReader>) -> Self {
+    pub fn new(indexer_db: InternalIndexerDB, db_reader: Arc<dyn DbReader>) -> Result<Self> {
+        if indexer_db.event_enabled() {
+            indexer_db.init_events_by_type_start_version()?;
+        }
         let (sender, reciver) = mpsc::channel();
-
         let db = indexer_db.get_inner_db_ref().to_owned();
         let internal_indexer_db = db.clone();
         let committer_handle = thread::spawn(move || {
@@ -333,7 +403,7 @@ impl DBIndexer {
             committer.run();
         });
 
-        Self {
+        Ok(Self {
             indexer_db,
             main_db_reader: db_reader.clone(),
             sender,
@@ -342,7 +412,7 @@ impl DBIndexer {
                 db_reader,
                 internal_indexer_db,
             ),
-        }
+        })
     }
 
     pub fn get_main_db_lowest_viable_version(&self) -> Result<Version> {
@@ -427,6 +497,16 @@ impl DBIndexer {
                             )
                             .expect("Failed to put events by version to a batch");
                     }
+                    if let ContractEvent::V2(v2) = event {
+                        if let TypeTag::Struct(struct_tag) = v2.type_tag() {
+                            batch
+                                .put::<EventByTypeSchema>(
+                                    &(struct_tag.as_ref().clone(), version, idx as u64),
+                                    &(),
+                                )
+                                .expect("Failed to put events by type to a batch");
+                        }
+                    }
                     if self.indexer_db.event_v2_translation_enabled() {
                         if let ContractEvent::V2(v2) = event {
                             if let Some(translated_v1_event) =
@@ -625,6 +705,46 @@ impl DBIndexer {
         self.get_events_by_event_key(event_key, start, order, limit, ledger_version)
     }
 
+    pub fn get_events_by_type(
+        &self,
+        struct_tag: &StructTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.indexer_db
+            .ensure_cover_ledger_version(ledger_version)?;
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+        match self.indexer_db.get_events_by_type_start_version()? {
+            Some(indexed_start_version) => ensure!(
+                start_version >= indexed_start_version,
+                "Events by type are only indexed since version {}, requested version {}.",
+                indexed_start_version,
+                start_version,
+            ),
+            None => bail!("Events by type are not indexed."),
+        }
+        let type_tag = TypeTag::Struct(Box::new(struct_tag.clone()));
+
+        self.indexer_db
+            .lookup_events_by_type(struct_tag, start_version, limit, ledger_version)?
+            .into_iter()
+            .map(|(ver, idx)| {
+                let event = self
+                    .main_db_reader
+                    .get_event_by_version_and_index(ver, idx)?;
+                ensure!(
+                    matches!(&event, ContractEvent::V2(v2) if v2.type_tag() == &type_tag),
+                    "Index broken, event {} of txn {} is not of type {}",
+                    idx,
+                    ver,
+                    struct_tag,
+                );
+                Ok(EventWithVersion::new(ver, event))
+            })
+            .collect()
+    }
+
     pub fn get_events_by_event_key(
         &self,
         event_key: &EventKey,
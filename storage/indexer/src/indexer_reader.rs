@@ -15,6 +15,7 @@ use aptos_types::{
     },
     transaction::{AccountTransactionsWithProof, Version},
 };
+use move_core_types::language_storage::StructTag;
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -113,6 +114,41 @@ impl IndexerReader for IndexerReaders {
         anyhow::bail!("DB indexer reader is not available")
     }
 
+    fn get_events_by_type(
+        &self,
+        struct_tag: &StructTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> anyhow::Result<Vec<EventWithVersion>> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.indexer_db.event_enabled() {
+                return Ok(db_indexer_reader.get_events_by_type(
+                    struct_tag,
+                    start_version,
+                    limit,
+                    ledger_version,
+                )?);
+            } else {
+                anyhow::bail!("Internal event index is not enabled")
+            }
+        }
+        anyhow::bail!("DB indexer reader is not available")
+    }
+
+    fn get_events_by_type_start_version(&self) -> anyhow::Result<Option<Version>> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.indexer_db.event_enabled() {
+                return Ok(db_indexer_reader
+                    .indexer_db
+                    .get_events_by_type_start_version()?);
+            } else {
+                anyhow::bail!("Internal event index is not enabled")
+            }
+        }
+        anyhow::bail!("DB indexer reader is not available")
+    }
+
     fn get_account_transactions(
         &self,
         address: AccountAddress,
@@ -39,6 +39,7 @@ pub enum MetadataKey {
     StateVersion,
     TransactionVersion,
     EventV2TranslationVersion,
+    EventByTypeStartVersion,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
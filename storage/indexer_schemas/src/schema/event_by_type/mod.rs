@@ -0,0 +1,74 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index via which module events (a.k.a. V2
+//! events, represented by a <txn_version, event_idx> tuple so that they can be fetched from the
+//! main DB) can be found by the struct type of the event.
+//!
+//! The BCS encoding of a `StructTag` is self-delimiting, so all entries of the same type are
+//! stored contiguously and ordered by version and index.
+//!
+//! ```text
+//! |<--------------key------------->|<-value->|
+//! | bcs(struct_tag) | txn_ver | idx |   ()    |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, EVENT_BY_TYPE_CF_NAME};
+use anyhow::{ensure, Result};
+use aptos_schemadb::{
+    define_pub_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use move_core_types::language_storage::StructTag;
+use std::mem::size_of;
+
+define_pub_schema!(EventByTypeSchema, Key, Value, EVENT_BY_TYPE_CF_NAME);
+
+type Index = u64;
+type Key = (StructTag, Version, Index);
+type Value = ();
+
+impl KeyCodec<EventByTypeSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref struct_tag, version, index) = *self;
+
+        let mut encoded = bcs::to_bytes(struct_tag)?;
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        const VER_AND_IDX_LEN: usize = size_of::<(Version, Index)>();
+        ensure!(
+            data.len() > VER_AND_IDX_LEN,
+            "Unexpected data len {}, expected more than {}.",
+            data.len(),
+            VER_AND_IDX_LEN,
+        );
+
+        let tag_len = data.len() - VER_AND_IDX_LEN;
+        let struct_tag = bcs::from_bytes(&data[..tag_len])?;
+        let version = (&data[tag_len..]).read_u64::<BigEndian>()?;
+        let index = (&data[tag_len + size_of::<Version>()..]).read_u64::<BigEndian>()?;
+
+        Ok((struct_tag, version, index))
+    }
+}
+
+impl ValueCodec<EventByTypeSchema> for Value {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
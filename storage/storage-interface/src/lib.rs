@@ -434,6 +434,10 @@ pub trait DbReader: Send + Sync {
         /// Returns if the state store pruner is enabled.
         fn is_state_merkle_pruner_enabled(&self) -> Result<bool>;
 
+        /// Returns if only a subset of the state values is persisted (i.e.,
+        /// partial state sync is enabled).
+        fn is_partial_state_enabled(&self) -> Result<bool>;
+
         /// Get the state prune window config value.
         fn get_epoch_snapshot_prune_window(&self) -> Result<usize>;
 
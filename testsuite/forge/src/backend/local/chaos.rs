@@ -0,0 +1,738 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    SwarmChaos, SwarmNetEm, SwarmNetworkBandwidth, SwarmNetworkDelay, SwarmNetworkLoss,
+    SwarmNetworkPartition,
+};
+use anyhow::{bail, Result};
+use aptos_logger::{debug, info, warn};
+use aptos_sdk::types::PeerId;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+    sync::{mpsc, watch},
+    time::Instant,
+};
+
+/// The maximum number of bytes read from a connection at a time
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// The (approximate) size of a TCP segment. This is used to emulate packet loss.
+const SEGMENT_SIZE: usize = 1448;
+
+/// The delay added to a chunk of data if any of its segments are "lost". This
+/// emulates a TCP retransmission (i.e., the minimum retransmission timeout on Linux).
+const RETRANSMISSION_DELAY: Duration = Duration::from_millis(200);
+
+/// The maximum number of chunks buffered per connection direction (before applying backpressure)
+const MAX_BUFFERED_CHUNKS: usize = 1024;
+
+/// The time to wait for a dialer to identify itself (i.e., send its peer id)
+const DIALER_IDENTIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A userspace proxy that sits in front of the validator network listener of
+/// each validator in a local swarm, and is used to inject network chaos
+/// between the validators (e.g., delays, partitions, loss and bandwidth limits).
+///
+/// Each validator listens on a private address, while the proxy listens on the
+/// address advertised on-chain. The proxy identifies the dialer of each inbound
+/// connection using the prologue of the noise handshake (which starts with the
+/// dialer's peer id, in the clear), and applies the chaos of the link between
+/// the dialer and the validator to each direction of the connection.
+///
+/// Note: as the proxy operates on TCP streams (and not packets), packet loss is
+/// emulated by delaying the affected data by a retransmission timeout.
+#[derive(Debug)]
+pub struct ChaosProxy {
+    chaoses: HashSet<SwarmChaos>,
+    rules_sender: watch::Sender<Arc<ChaosRules>>,
+    runtime: Option<Runtime>,
+    validators: BTreeMap<usize, PeerId>, // Validator index to peer id
+}
+
+impl ChaosProxy {
+    pub fn new() -> Self {
+        let (rules_sender, _) = watch::channel(Arc::new(ChaosRules::default()));
+        let runtime = aptos_runtimes::spawn_named_runtime("chaos-proxy".into(), Some(2));
+
+        Self {
+            chaoses: HashSet::new(),
+            rules_sender,
+            runtime: Some(runtime),
+            validators: BTreeMap::new(),
+        }
+    }
+
+    /// Starts proxying the connections made to the proxy address (of the
+    /// given validator) to the node address (i.e., where the validator listens).
+    pub fn add_validator(
+        &mut self,
+        index: usize,
+        peer_id: PeerId,
+        proxy_address: SocketAddr,
+        node_address: SocketAddr,
+    ) -> Result<()> {
+        // Bind the listener now, so that the proxy address is reserved before the node starts
+        let listener = std::net::TcpListener::bind(proxy_address)?;
+        listener.set_nonblocking(true)?;
+
+        let rules_receiver = self.rules_sender.subscribe();
+        self.runtime().spawn(async move {
+            match TcpListener::from_std(listener) {
+                Ok(listener) => {
+                    run_proxy_listener(listener, peer_id, node_address, rules_receiver).await
+                },
+                Err(error) => warn!(
+                    "Failed to start the chaos proxy for validator {} at {}! Error: {:?}",
+                    peer_id, proxy_address, error
+                ),
+            }
+        });
+        self.validators.insert(index, peer_id);
+
+        info!(
+            "Started the chaos proxy for validator {} at {} (forwarding to {})",
+            peer_id, proxy_address, node_address
+        );
+        Ok(())
+    }
+
+    /// Injects the given chaos into the network between the validators
+    pub fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        let mut chaoses = self.chaoses.clone();
+        chaoses.insert(chaos);
+        self.update_chaoses(chaoses)
+    }
+
+    /// Removes the given chaos from the network between the validators
+    pub fn remove_chaos(&mut self, chaos: &SwarmChaos) -> Result<()> {
+        let mut chaoses = self.chaoses.clone();
+        if !chaoses.remove(chaos) {
+            bail!("Chaos {:?} not found", chaos);
+        }
+        self.update_chaoses(chaoses)
+    }
+
+    /// Removes all chaos from the network between the validators
+    pub fn remove_all_chaos(&mut self) -> Result<()> {
+        self.update_chaoses(HashSet::new())
+    }
+
+    /// Updates the active chaoses and the rules applied by the proxy
+    fn update_chaoses(&mut self, chaoses: HashSet<SwarmChaos>) -> Result<()> {
+        let validators: Vec<_> = self.validators.values().copied().collect();
+        let rules = ChaosRules::new(&chaoses, &validators)?;
+        info!("Updating the chaos of the local swarm to: {:?}", chaoses);
+
+        self.chaoses = chaoses;
+        self.rules_sender.send_replace(Arc::new(rules));
+        Ok(())
+    }
+
+    fn runtime(&self) -> &Runtime {
+        self.runtime
+            .as_ref()
+            .expect("The chaos proxy runtime should exist until the proxy is dropped!")
+    }
+}
+
+impl Default for ChaosProxy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ChaosProxy {
+    fn drop(&mut self) {
+        // The proxy may be dropped inside an async context, so we
+        // can't block on the runtime shutdown.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// The delay applied to the data sent over a link
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct LinkDelay {
+    latency_ms: u64,
+    jitter_ms: u64,
+    correlation_percentage: u64,
+}
+
+/// The (emulated) packet loss applied to the data sent over a link
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct LinkLoss {
+    loss_percentage: u64,
+    correlation_percentage: u64,
+}
+
+/// The chaos applied to the data sent from one validator to another
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct LinkChaos {
+    blocked: bool,
+    delay: Option<LinkDelay>,
+    loss: Option<LinkLoss>,
+    rate_bytes_per_sec: Option<u64>,
+}
+
+impl LinkChaos {
+    /// Adds the given delay to the link. Delays from different chaoses accumulate.
+    fn add_delay(&mut self, latency_ms: u64, jitter_ms: u64, correlation_percentage: u64) {
+        let delay = self.delay.get_or_insert_with(LinkDelay::default);
+        delay.latency_ms += latency_ms;
+        delay.jitter_ms += jitter_ms;
+        delay.correlation_percentage = delay.correlation_percentage.max(correlation_percentage);
+    }
+
+    /// Adds the given loss to the link. Only the highest loss is applied.
+    fn add_loss(&mut self, loss_percentage: u64, correlation_percentage: u64) {
+        let loss = LinkLoss {
+            loss_percentage,
+            correlation_percentage,
+        };
+        match self.loss {
+            Some(existing_loss) if existing_loss.loss_percentage >= loss_percentage => {},
+            _ => self.loss = Some(loss),
+        }
+    }
+
+    /// Adds the given rate limit (in megabits per second, as for the k8s backend) to the
+    /// link. Only the lowest rate is applied.
+    fn add_rate_limit(&mut self, rate_mbps: u64) {
+        let rate_bytes_per_sec = rate_mbps * 125_000;
+        self.rate_bytes_per_sec = Some(
+            self.rate_bytes_per_sec
+                .map_or(rate_bytes_per_sec, |rate| rate.min(rate_bytes_per_sec)),
+        );
+    }
+
+    /// Returns the delay to apply to the next chunk of data sent over the link
+    fn sample_delay(&self, random: &mut CorrelatedRandom) -> Duration {
+        let delay = match self.delay {
+            Some(delay) => delay,
+            None => return Duration::ZERO,
+        };
+
+        let jitter_ms =
+            (random.next(delay.correlation_percentage) * 2.0 - 1.0) * delay.jitter_ms as f64;
+        let delay_ms = (delay.latency_ms as f64 + jitter_ms).max(0.0);
+        Duration::from_secs_f64(delay_ms / 1000.0)
+    }
+
+    /// Returns true iff any segment of the next chunk of data (of the given size) is lost
+    fn sample_loss(&self, num_bytes: usize, random: &mut CorrelatedRandom) -> bool {
+        let loss = match self.loss {
+            Some(loss) => loss,
+            None => return false,
+        };
+
+        let num_segments = num_bytes.div_ceil(SEGMENT_SIZE);
+        let mut lost = false;
+        for _ in 0..num_segments {
+            lost |= random.next(loss.correlation_percentage) * 100.0 < loss.loss_percentage as f64;
+        }
+        lost
+    }
+}
+
+/// The chaos applied to each (directed) link between the validators
+#[derive(Debug, Default)]
+struct ChaosRules {
+    links: HashMap<(PeerId, PeerId), LinkChaos>,
+}
+
+impl ChaosRules {
+    /// Creates the rules for the given chaoses. The validators must be ordered by index.
+    fn new(chaoses: &HashSet<SwarmChaos>, validators: &[PeerId]) -> Result<Self> {
+        let mut rules = Self::default();
+        for chaos in chaoses {
+            match chaos {
+                SwarmChaos::Delay(delay) => rules.add_network_delay(delay),
+                SwarmChaos::Partition(partition) => {
+                    rules.add_network_partition(partition, validators)
+                },
+                SwarmChaos::Bandwidth(bandwidth) => {
+                    rules.add_network_bandwidth(bandwidth, validators)
+                },
+                SwarmChaos::Loss(loss) => rules.add_network_loss(loss, validators),
+                SwarmChaos::NetEm(netem) => rules.add_netem(netem),
+                SwarmChaos::CpuStress(_) => {
+                    bail!("CPU stress chaos is not supported by the local swarm!")
+                },
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Returns the chaos applied to the data sent from the source to the target
+    fn link(&self, source: PeerId, target: PeerId) -> LinkChaos {
+        self.links
+            .get(&(source, target))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn link_mut(&mut self, source: PeerId, target: PeerId) -> &mut LinkChaos {
+        self.links.entry((source, target)).or_default()
+    }
+
+    /// Returns all (directed) links between the given nodes
+    fn all_links(nodes: &[PeerId]) -> impl Iterator<Item = (PeerId, PeerId)> + '_ {
+        nodes.iter().flat_map(move |source| {
+            nodes
+                .iter()
+                .filter(move |target| *target != source)
+                .map(move |target| (*source, *target))
+        })
+    }
+
+    /// Delays the traffic (in both directions) between the source and target nodes of each group
+    fn add_network_delay(&mut self, network_delay: &SwarmNetworkDelay) {
+        for group in &network_delay.group_network_delays {
+            for source in &group.source_nodes {
+                for target in group.target_nodes.iter().filter(|target| *target != source) {
+                    for (from, to) in [(*source, *target), (*target, *source)] {
+                        self.link_mut(from, to).add_delay(
+                            group.latency_ms,
+                            group.jitter_ms,
+                            group.correlation_percentage,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Partitions the given percentage of the validators (ordered by index) from the rest
+    fn add_network_partition(
+        &mut self,
+        network_partition: &SwarmNetworkPartition,
+        validators: &[PeerId],
+    ) {
+        let num_partitioned =
+            validators.len() * network_partition.partition_percentage.min(100) as usize / 100;
+        let (partitioned, others) = validators.split_at(num_partitioned);
+        for partitioned in partitioned {
+            for other in others {
+                self.link_mut(*partitioned, *other).blocked = true;
+                self.link_mut(*other, *partitioned).blocked = true;
+            }
+        }
+    }
+
+    /// Limits the bandwidth of the traffic between all validators
+    fn add_network_bandwidth(
+        &mut self,
+        network_bandwidth: &SwarmNetworkBandwidth,
+        validators: &[PeerId],
+    ) {
+        for group in &network_bandwidth.group_network_bandwidths {
+            for (source, target) in Self::all_links(validators) {
+                self.link_mut(source, target).add_rate_limit(group.rate);
+            }
+        }
+    }
+
+    /// Adds loss to the traffic between all validators
+    fn add_network_loss(&mut self, network_loss: &SwarmNetworkLoss, validators: &[PeerId]) {
+        for (source, target) in Self::all_links(validators) {
+            self.link_mut(source, target).add_loss(
+                network_loss.loss_percentage,
+                network_loss.correlation_percentage,
+            );
+        }
+    }
+
+    /// Applies the netem of each group to the traffic sent from the source to the target nodes
+    fn add_netem(&mut self, netem: &SwarmNetEm) {
+        for group in &netem.group_netems {
+            for source in &group.source_nodes {
+                for target in group.target_nodes.iter().filter(|target| *target != source) {
+                    let link = self.link_mut(*source, *target);
+                    link.add_delay(
+                        group.delay_latency_ms,
+                        group.delay_jitter_ms,
+                        group.delay_correlation_percentage,
+                    );
+                    link.add_loss(group.loss_percentage, group.loss_correlation_percentage);
+                    if group.rate_in_mbps > 0 {
+                        link.add_rate_limit(group.rate_in_mbps);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A random number generator where each number is correlated with the
+/// previous one (similar to the generator used by netem).
+#[derive(Debug, Default)]
+struct CorrelatedRandom {
+    last_value: f64,
+}
+
+impl CorrelatedRandom {
+    /// Returns a random number in [0, 1) that is correlated (by the given percentage) with the last
+    fn next(&mut self, correlation_percentage: u64) -> f64 {
+        let correlation = correlation_percentage.min(100) as f64 / 100.0;
+        let value = (1.0 - correlation) * rand::random::<f64>() + correlation * self.last_value;
+        self.last_value = value;
+        value
+    }
+}
+
+/// Accepts the connections made to the given validator and proxies them to the node address
+async fn run_proxy_listener(
+    listener: TcpListener,
+    validator: PeerId,
+    node_address: SocketAddr,
+    rules_receiver: watch::Receiver<Arc<ChaosRules>>,
+) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, remote_address)) => {
+                let rules_receiver = rules_receiver.clone();
+                tokio::spawn(async move {
+                    if let Err(error) =
+                        proxy_connection(stream, validator, node_address, rules_receiver).await
+                    {
+                        debug!(
+                            "Chaos proxy connection from {} to validator {} closed with error: {:?}",
+                            remote_address, validator, error
+                        );
+                    }
+                });
+            },
+            Err(error) => {
+                warn!(
+                    "Chaos proxy for validator {} failed to accept a connection! Error: {:?}",
+                    validator, error
+                );
+            },
+        }
+    }
+}
+
+/// Proxies the given inbound connection to the node address, applying the
+/// chaos of the link between the dialer and the validator to the traffic.
+async fn proxy_connection(
+    mut inbound_stream: TcpStream,
+    validator: PeerId,
+    node_address: SocketAddr,
+    rules_receiver: watch::Receiver<Arc<ChaosRules>>,
+) -> Result<()> {
+    // Identify the dialer. The noise handshake prologue starts with the dialer's peer id.
+    let mut dialer_peer_id = [0u8; PeerId::LENGTH];
+    tokio::time::timeout(
+        DIALER_IDENTIFICATION_TIMEOUT,
+        inbound_stream.read_exact(&mut dialer_peer_id),
+    )
+    .await??;
+    let dialer = PeerId::new(dialer_peer_id);
+
+    // Connect to the validator
+    let outbound_stream = TcpStream::connect(node_address).await?;
+    inbound_stream.set_nodelay(true)?;
+    outbound_stream.set_nodelay(true)?;
+
+    // Forward the traffic in both directions
+    let (inbound_reader, inbound_writer) = inbound_stream.into_split();
+    let (outbound_reader, outbound_writer) = outbound_stream.into_split();
+    tokio::try_join!(
+        forward_traffic(
+            inbound_reader,
+            outbound_writer,
+            (dialer, validator),
+            dialer_peer_id.to_vec(),
+            rules_receiver.clone(),
+        ),
+        forward_traffic(
+            outbound_reader,
+            inbound_writer,
+            (validator, dialer),
+            vec![],
+            rules_receiver,
+        ),
+    )?;
+
+    Ok(())
+}
+
+/// Forwards the traffic from the reader to the writer (starting with the given
+/// initial data), applying the chaos of the given (source, target) link.
+async fn forward_traffic<R, W>(
+    reader: R,
+    writer: W,
+    link: (PeerId, PeerId),
+    initial_data: Vec<u8>,
+    rules_receiver: watch::Receiver<Arc<ChaosRules>>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let (chunk_sender, chunk_receiver) = mpsc::channel(MAX_BUFFERED_CHUNKS);
+    tokio::try_join!(
+        read_chunks(
+            reader,
+            chunk_sender,
+            link,
+            initial_data,
+            rules_receiver.clone()
+        ),
+        write_chunks(writer, chunk_receiver, link, rules_receiver),
+    )?;
+    Ok(())
+}
+
+/// Reads the chunks of data from the reader, and determines
+/// when each chunk should be delivered (based on the link chaos).
+async fn read_chunks<R: AsyncRead + Unpin>(
+    mut reader: R,
+    chunk_sender: mpsc::Sender<(Instant, Vec<u8>)>,
+    (source, target): (PeerId, PeerId),
+    initial_data: Vec<u8>,
+    rules_receiver: watch::Receiver<Arc<ChaosRules>>,
+) -> io::Result<()> {
+    let mut delay_random = CorrelatedRandom::default();
+    let mut loss_random = CorrelatedRandom::default();
+    let mut last_delivery_time = Instant::now();
+    let mut initial_data = Some(initial_data).filter(|data| !data.is_empty());
+    loop {
+        let chunk = match initial_data.take() {
+            Some(data) => data,
+            None => {
+                let mut buffer = vec![0; READ_BUFFER_SIZE];
+                let num_bytes = reader.read(&mut buffer).await?;
+                if num_bytes == 0 {
+                    return Ok(()); // The connection was closed
+                }
+                buffer.truncate(num_bytes);
+                buffer
+            },
+        };
+
+        let link = rules_receiver.borrow().link(source, target);
+        let mut delivery_time = Instant::now() + link.sample_delay(&mut delay_random);
+        if link.sample_loss(chunk.len(), &mut loss_random) {
+            delivery_time += RETRANSMISSION_DELAY;
+        }
+
+        // Jitter must not reorder the chunks
+        delivery_time = delivery_time.max(last_delivery_time);
+        last_delivery_time = delivery_time;
+
+        if chunk_sender.send((delivery_time, chunk)).await.is_err() {
+            return Ok(()); // The writer has stopped
+        }
+    }
+}
+
+/// Writes the chunks of data to the writer once they're due (and
+/// the link isn't blocked), limiting the rate of the link (if any).
+async fn write_chunks<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    mut chunk_receiver: mpsc::Receiver<(Instant, Vec<u8>)>,
+    (source, target): (PeerId, PeerId),
+    mut rules_receiver: watch::Receiver<Arc<ChaosRules>>,
+) -> io::Result<()> {
+    let mut next_send_time = Instant::now();
+    while let Some((delivery_time, chunk)) = chunk_receiver.recv().await {
+        tokio::time::sleep_until(delivery_time).await;
+
+        let link = wait_until_unblocked(&mut rules_receiver, source, target).await;
+        if let Some(rate_bytes_per_sec) = link.rate_bytes_per_sec {
+            next_send_time = next_send_time.max(Instant::now());
+            tokio::time::sleep_until(next_send_time).await;
+            next_send_time +=
+                Duration::from_secs_f64(chunk.len() as f64 / rate_bytes_per_sec as f64);
+        }
+
+        writer.write_all(&chunk).await?;
+    }
+    writer.shutdown().await
+}
+
+/// Waits until the link from the source to the target is no longer blocked
+/// (e.g., partitioned), and returns the chaos of the link.
+async fn wait_until_unblocked(
+    rules_receiver: &mut watch::Receiver<Arc<ChaosRules>>,
+    source: PeerId,
+    target: PeerId,
+) -> LinkChaos {
+    loop {
+        let link = rules_receiver.borrow_and_update().link(source, target);
+        if !link.blocked || rules_receiver.changed().await.is_err() {
+            return link;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupNetEm, GroupNetworkBandwidth, GroupNetworkDelay};
+
+    #[test]
+    fn test_chaos_rules() {
+        let validators: Vec<_> = (0..4).map(|_| PeerId::random()).collect();
+
+        // Delay the traffic between the first and the last validators
+        let delay = SwarmChaos::Delay(SwarmNetworkDelay {
+            group_network_delays: vec![GroupNetworkDelay {
+                name: "delay".into(),
+                source_nodes: vec![validators[0]],
+                target_nodes: vec![validators[3]],
+                latency_ms: 100,
+                jitter_ms: 10,
+                correlation_percentage: 50,
+            }],
+        });
+
+        // Delay the traffic sent from the first to the second validator
+        let netem = SwarmChaos::NetEm(SwarmNetEm {
+            group_netems: vec![GroupNetEm {
+                name: "netem".into(),
+                source_nodes: vec![validators[0]],
+                target_nodes: vec![validators[1], validators[3]],
+                delay_latency_ms: 50,
+                delay_jitter_ms: 0,
+                delay_correlation_percentage: 0,
+                loss_percentage: 5,
+                loss_correlation_percentage: 0,
+                rate_in_mbps: 100,
+            }],
+        });
+
+        // Partition half of the validators and limit the bandwidth
+        let partition = SwarmChaos::Partition(SwarmNetworkPartition {
+            partition_percentage: 50,
+        });
+        let bandwidth = SwarmChaos::Bandwidth(SwarmNetworkBandwidth {
+            group_network_bandwidths: vec![GroupNetworkBandwidth {
+                name: "bandwidth".into(),
+                rate: 10,
+                limit: 0,
+                buffer: 0,
+            }],
+        });
+
+        let chaoses = HashSet::from([delay, netem, partition, bandwidth]);
+        let rules = ChaosRules::new(&chaoses, &validators).unwrap();
+
+        // Verify the delays and the loss
+        let link = rules.link(validators[0], validators[3]);
+        assert_eq!(
+            link.delay,
+            Some(LinkDelay {
+                latency_ms: 150,
+                jitter_ms: 10,
+                correlation_percentage: 50,
+            })
+        );
+        assert_eq!(link.loss.unwrap().loss_percentage, 5);
+        let link = rules.link(validators[3], validators[0]);
+        assert_eq!(link.delay.unwrap().latency_ms, 100);
+        assert_eq!(link.loss, None);
+        assert_eq!(
+            rules
+                .link(validators[0], validators[1])
+                .delay
+                .unwrap()
+                .latency_ms,
+            50
+        );
+        assert_eq!(rules.link(validators[1], validators[0]).delay, None);
+
+        // Verify the partition
+        for (source, target) in ChaosRules::all_links(&validators) {
+            let source_partitioned = source == validators[0] || source == validators[1];
+            let target_partitioned = target == validators[0] || target == validators[1];
+            assert_eq!(
+                rules.link(source, target).blocked,
+                source_partitioned != target_partitioned
+            );
+        }
+
+        // Verify the bandwidth limits (the lowest rate applies)
+        assert_eq!(
+            rules.link(validators[0], validators[1]).rate_bytes_per_sec,
+            Some(1_250_000)
+        );
+        assert_eq!(
+            rules.link(validators[2], validators[3]).rate_bytes_per_sec,
+            Some(1_250_000)
+        );
+
+        // Verify that CPU stress is not supported
+        let cpu_stress = SwarmChaos::CpuStress(crate::SwarmCpuStress {
+            group_cpu_stresses: vec![],
+        });
+        ChaosRules::new(&HashSet::from([cpu_stress]), &validators).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_chaos_proxy_partition() {
+        let validator = PeerId::random();
+        let dialer = PeerId::random();
+
+        // Create an echo server (i.e., the validator)
+        let node_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node_address = node_listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = node_listener.accept().await.unwrap();
+            let (mut reader, mut writer) = stream.split();
+            tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+        });
+
+        // Create the proxy in front of the validator
+        let mut chaos_proxy = ChaosProxy::new();
+        let proxy_address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        chaos_proxy
+            .add_validator(0, validator, proxy_address, node_address)
+            .unwrap();
+        chaos_proxy
+            .add_validator(1, dialer, "127.0.0.1:0".parse().unwrap(), node_address)
+            .unwrap();
+
+        // Verify the data (including the dialer's peer id) is forwarded
+        let mut stream = TcpStream::connect(proxy_address).await.unwrap();
+        stream.write_all(dialer.as_ref()).await.unwrap();
+        let mut echoed_peer_id = [0u8; PeerId::LENGTH];
+        stream.read_exact(&mut echoed_peer_id).await.unwrap();
+        assert_eq!(PeerId::new(echoed_peer_id), dialer);
+
+        // Partition the validators and verify the data is held
+        let partition = SwarmChaos::Partition(SwarmNetworkPartition {
+            partition_percentage: 50,
+        });
+        chaos_proxy.inject_chaos(partition.clone()).unwrap();
+        stream.write_all(&[1, 2, 3]).await.unwrap();
+        let mut echoed_data = [0u8; 3];
+        tokio::time::timeout(
+            Duration::from_millis(500),
+            stream.read_exact(&mut echoed_data),
+        )
+        .await
+        .unwrap_err();
+
+        // Remove the partition and verify the data is delivered
+        chaos_proxy.remove_chaos(&partition).unwrap();
+        stream.read_exact(&mut echoed_data).await.unwrap();
+        assert_eq!(echoed_data, [1, 2, 3]);
+        chaos_proxy.remove_chaos(&partition).unwrap_err();
+    }
+}
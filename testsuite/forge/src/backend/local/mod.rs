@@ -18,10 +18,12 @@ use std::{
 };
 
 mod cargo;
+mod chaos;
 mod node;
 mod swarm;
 pub use self::swarm::ActiveNodesGuard;
 pub use cargo::cargo_build_common_args;
+pub use chaos::ChaosProxy;
 pub use node::LocalNode;
 pub use swarm::{LocalSwarm, SwarmDirectory};
 
@@ -48,6 +50,7 @@ impl LocalVersion {
 pub struct LocalFactory {
     versions: Arc<HashMap<Version, LocalVersion>>,
     swarm_dir: Option<String>,
+    enable_chaos_proxy: bool,
 }
 
 impl LocalFactory {
@@ -55,9 +58,17 @@ impl LocalFactory {
         Self {
             versions: Arc::new(versions),
             swarm_dir,
+            enable_chaos_proxy: false,
         }
     }
 
+    /// Puts a chaos proxy in front of the validator network of the swarms launched by the
+    /// factory, so that network chaos can be injected between the validators.
+    pub fn with_chaos_proxy(mut self) -> Self {
+        self.enable_chaos_proxy = true;
+        self
+    }
+
     pub fn from_workspace(swarm_dir: Option<String>) -> Result<Self> {
         let mut versions = HashMap::new();
         let new_version = cargo::get_aptos_node_binary_from_worktree().map(|(revision, bin)| {
@@ -124,6 +135,7 @@ impl LocalFactory {
         vfn_config: Option<NodeConfig>,
         init_genesis_stake: Option<InitGenesisStakeFn>,
         init_genesis_config: Option<InitGenesisConfigFn>,
+        enable_chaos_proxy: bool,
         guard: ActiveNodesGuard,
     ) -> Result<LocalSwarm>
     where
@@ -141,6 +153,7 @@ impl LocalFactory {
             init_genesis_config,
             swarmdir,
             genesis_framework,
+            enable_chaos_proxy,
             guard,
         )?;
 
@@ -209,6 +222,7 @@ impl Factory for LocalFactory {
                 None,
                 None,
                 None,
+                self.enable_chaos_proxy,
                 guard,
             )
             .await?;
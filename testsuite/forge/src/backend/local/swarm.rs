@@ -3,14 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    ChainInfo, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, Swarm, SwarmChaos,
-    SwarmExt, Validator, Version,
+    ChainInfo, ChaosProxy, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, Swarm,
+    SwarmChaos, SwarmExt, Validator, Version,
 };
 use anyhow::{anyhow, bail, Result};
 use aptos_config::{
     config::{NetworkConfig, NodeConfig, OverrideNodeConfig, PersistableConfig},
     keys::ConfigKey,
     network_id::NetworkId,
+    utils::get_available_port,
 };
 use aptos_framework::ReleaseBundle;
 use aptos_genesis::builder::{
@@ -21,8 +22,8 @@ use aptos_logger::{info, warn};
 use aptos_sdk::{
     crypto::{ed25519::Ed25519PrivateKey, encoding_type::EncodingType},
     types::{
-        chain_id::ChainId, transaction::Transaction, waypoint::Waypoint, AccountKey, LocalAccount,
-        PeerId,
+        chain_id::ChainId, network_address::NetworkAddress, transaction::Transaction,
+        waypoint::Waypoint, AccountKey, LocalAccount, PeerId,
     },
 };
 use prometheus_http_query::response::{PromqlResult, Sample};
@@ -32,6 +33,7 @@ use std::{
     fs::File,
     io::Write,
     mem,
+    net::{Ipv4Addr, SocketAddr},
     num::NonZeroUsize,
     ops,
     path::{Path, PathBuf},
@@ -99,6 +101,7 @@ pub struct LocalSwarm {
     root_account: Arc<LocalAccount>,
     chain_id: ChainId,
     root_key: ConfigKey<Ed25519PrivateKey>,
+    chaos_proxy: Option<ChaosProxy>,
 
     launched: bool,
     #[allow(dead_code)]
@@ -116,6 +119,7 @@ impl LocalSwarm {
         init_genesis_config: Option<InitGenesisConfigFn>,
         dir: Option<PathBuf>,
         genesis_framework: Option<ReleaseBundle>,
+        enable_chaos_proxy: bool,
         guard: ActiveNodesGuard,
     ) -> Result<LocalSwarm>
     where
@@ -192,7 +196,11 @@ impl LocalSwarm {
             })
             .collect::<Result<HashMap<_, _>>>()?;
 
-        // After genesis, remove public network from validator and add to public_networks
+        // After genesis, remove public network from validator and add to public_networks.
+        // If the chaos proxy is enabled, we also move the validator network listener of each
+        // validator to a private address, and put the proxy in front of it (on the address
+        // advertised on-chain). This allows us to inject network chaos between the validators.
+        let mut chaos_proxy = enable_chaos_proxy.then(ChaosProxy::new);
         let public_networks = validators
             .values_mut()
             .map(|validator| {
@@ -212,6 +220,23 @@ impl LocalSwarm {
                         .expect("Validator should have a public network");
                     validator_config.full_node_networks.remove(i)
                 };
+                if let Some(chaos_proxy) = chaos_proxy.as_mut() {
+                    let validator_network = validator_config
+                        .validator_network
+                        .as_mut()
+                        .expect("Validator should have a validator network");
+                    let proxy_address = get_socket_address(&validator_network.listen_address)?;
+                    let node_address =
+                        SocketAddr::new(Ipv4Addr::LOCALHOST.into(), get_available_port());
+                    validator_network.listen_address = NetworkAddress::from(node_address);
+                    chaos_proxy.add_validator(
+                        validator.index(),
+                        validator.peer_id(),
+                        proxy_address,
+                        node_address,
+                    )?;
+                }
+
                 validator_config.set_data_dir(validator.base_dir());
                 *validator.config_mut() = validator_config.clone();
                 // Since the validator's config has changed we need to save it
@@ -259,6 +284,7 @@ impl LocalSwarm {
             root_account,
             chain_id: ChainId::test(),
             root_key,
+            chaos_proxy,
             launched: false,
             guard,
         })
@@ -465,6 +491,14 @@ impl LocalSwarm {
     pub fn dir(&self) -> &Path {
         self.dir.as_ref()
     }
+
+    fn chaos_proxy(&mut self) -> Result<&mut ChaosProxy> {
+        self.chaos_proxy.as_mut().ok_or_else(|| {
+            anyhow!(
+                "The chaos proxy is not enabled for this swarm! Enable it when building the swarm."
+            )
+        })
+    }
 }
 
 impl Drop for LocalSwarm {
@@ -587,16 +621,16 @@ impl Swarm for LocalSwarm {
         self.dir.display().to_string()
     }
 
-    async fn inject_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    async fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        self.chaos_proxy()?.inject_chaos(chaos)
     }
 
-    async fn remove_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    async fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        self.chaos_proxy()?.remove_chaos(&chaos)
     }
 
     async fn remove_all_chaos(&mut self) -> Result<()> {
-        todo!()
+        self.chaos_proxy()?.remove_all_chaos()
     }
 
     async fn ensure_no_validator_restart(&self) -> Result<()> {
@@ -656,6 +690,17 @@ impl Swarm for LocalSwarm {
     }
 }
 
+/// Returns the socket address of the given (TCP) network address
+fn get_socket_address(network_address: &NetworkAddress) -> Result<SocketAddr> {
+    match (network_address.find_ip_addr(), network_address.find_port()) {
+        (Some(ip_address), Some(port)) => Ok(SocketAddr::new(ip_address, port)),
+        _ => bail!(
+            "Network address {} is not an IP and TCP address",
+            network_address
+        ),
+    }
+}
+
 #[derive(Debug)]
 pub struct ActiveNodesGuard {
     counter: Arc<Mutex<usize>>,
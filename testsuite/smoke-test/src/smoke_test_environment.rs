@@ -29,6 +29,7 @@ pub struct SwarmBuilder {
     vfn_config: Option<NodeConfig>,
     init_genesis_stake: Option<InitGenesisStakeFn>,
     init_genesis_config: Option<InitGenesisConfigFn>,
+    enable_chaos_proxy: bool,
 }
 
 impl SwarmBuilder {
@@ -42,6 +43,7 @@ impl SwarmBuilder {
             vfn_config: None,
             init_genesis_stake: None,
             init_genesis_config: None,
+            enable_chaos_proxy: false,
         }
     }
 
@@ -84,6 +86,13 @@ impl SwarmBuilder {
         self
     }
 
+    /// Puts a chaos proxy in front of the validator network, so that network chaos can be
+    /// injected between the validators
+    pub fn with_chaos_proxy(mut self) -> Self {
+        self.enable_chaos_proxy = true;
+        self
+    }
+
     // Gas is not enabled with this setup, it's enabled via forge instance.
     pub async fn build_inner(&mut self) -> anyhow::Result<LocalSwarm> {
         ::aptos_logger::Logger::new().init();
@@ -118,6 +127,7 @@ impl SwarmBuilder {
                         (init_genesis_config)(genesis_config);
                     }
                 })),
+                builder.enable_chaos_proxy,
                 guard,
             )
             .await
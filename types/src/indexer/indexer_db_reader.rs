@@ -13,6 +13,7 @@ use crate::{
     transaction::{AccountTransactionsWithProof, Version},
 };
 use anyhow::Result;
+use move_core_types::language_storage::StructTag;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum Order {
@@ -43,6 +44,20 @@ pub trait IndexerReader: Send + Sync {
         ledger_version: Version,
     ) -> Result<Vec<EventWithVersion>>;
 
+    /// Returns module events of the given struct type, across all accounts, emitted at or after
+    /// `start_version`, in ascending order.
+    fn get_events_by_type(
+        &self,
+        struct_tag: &StructTag,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>>;
+
+    /// Returns the first version whose module events are indexed by type. Events of earlier
+    /// versions can't be queried by type.
+    fn get_events_by_type_start_version(&self) -> Result<Option<Version>>;
+
     fn get_account_transactions(
         &self,
         address: AccountAddress,
@@ -64,6 +64,14 @@ pub enum MempoolStatusCode {
     // transaction didn't pass vm_validation
     VmError = 5,
     UnknownStatus = 6,
+    // Sender reached the max number of pending transactions allowed for client submissions
+    SenderQuotaExceeded = 7,
+    // Client IP exceeded its transaction submission rate
+    ClientRateLimited = 8,
+    // Peer exceeded its transaction broadcast rate
+    PeerRateLimited = 9,
+    // Transaction was denied by the mempool transaction filter
+    RejectedByFilter = 10,
 }
 
 impl TryFrom<u64> for MempoolStatusCode {
@@ -78,6 +86,10 @@ impl TryFrom<u64> for MempoolStatusCode {
             4 => Ok(MempoolStatusCode::InvalidUpdate),
             5 => Ok(MempoolStatusCode::VmError),
             6 => Ok(MempoolStatusCode::UnknownStatus),
+            7 => Ok(MempoolStatusCode::SenderQuotaExceeded),
+            8 => Ok(MempoolStatusCode::ClientRateLimited),
+            9 => Ok(MempoolStatusCode::PeerRateLimited),
+            10 => Ok(MempoolStatusCode::RejectedByFilter),
             _ => Err("invalid StatusCode"),
         }
     }
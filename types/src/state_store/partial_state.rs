@@ -0,0 +1,123 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    access_path::Path,
+    account_address::AccountAddress,
+    state_store::state_key::{inner::StateKeyInner, StateKey},
+};
+use move_core_types::language_storage::StructTag;
+use std::collections::HashSet;
+
+/// A filter that determines which state values are persisted by a node that
+/// only tracks a subset of the blockchain state (i.e., partial state sync).
+///
+/// A state key is tracked if: (i) it belongs to a framework (special) account;
+/// (ii) it is a resource, resource group or module of one of the tracked accounts;
+/// or (iii) it is a resource (or resource group) of one of the tracked resource types.
+///
+/// Note: table items are never tracked. A table handle is derived from the address
+/// of the table's creator, so the owner of a table item can't be determined from
+/// its state key alone.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PartialStateFilter {
+    tracked_accounts: HashSet<AccountAddress>,
+    tracked_resource_types: HashSet<StructTag>,
+}
+
+impl PartialStateFilter {
+    pub fn new(
+        tracked_accounts: impl IntoIterator<Item = AccountAddress>,
+        tracked_resource_types: impl IntoIterator<Item = StructTag>,
+    ) -> Self {
+        Self {
+            tracked_accounts: tracked_accounts.into_iter().collect(),
+            tracked_resource_types: tracked_resource_types.into_iter().collect(),
+        }
+    }
+
+    /// Returns true iff the value of the given state key should be persisted
+    pub fn is_tracked(&self, state_key: &StateKey) -> bool {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => {
+                let address = access_path.address;
+                if address.is_special() || self.tracked_accounts.contains(&address) {
+                    return true;
+                }
+
+                // Avoid decoding the path if no resource types are tracked
+                if self.tracked_resource_types.is_empty() {
+                    return false;
+                }
+                match access_path.get_path() {
+                    Path::Resource(struct_tag) | Path::ResourceGroup(struct_tag) => {
+                        self.tracked_resource_types.contains(&struct_tag)
+                    },
+                    Path::Code(_) => false,
+                }
+            },
+            StateKeyInner::TableItem { .. } | StateKeyInner::Raw(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        account_config::{AccountResource, CoinStoreResource, ObjectGroupResource},
+        state_store::{partial_state::PartialStateFilter, state_key::StateKey, table::TableHandle},
+        AptosCoinType,
+    };
+    use move_core_types::{
+        account_address::AccountAddress, ident_str, move_resource::MoveStructType,
+    };
+
+    #[test]
+    fn test_partial_state_filter() {
+        let tracked_account = AccountAddress::new([12u8; AccountAddress::LENGTH]);
+        let other_account = AccountAddress::new([22u8; AccountAddress::LENGTH]);
+        let filter = PartialStateFilter::new(vec![tracked_account], vec![CoinStoreResource::<
+            AptosCoinType,
+        >::struct_tag()]);
+
+        // The state of the framework accounts is always tracked
+        for address in [
+            AccountAddress::ONE,
+            AccountAddress::THREE,
+            AccountAddress::FOUR,
+        ] {
+            assert!(
+                filter.is_tracked(&StateKey::resource_typed::<AccountResource>(&address).unwrap())
+            );
+            assert!(filter.is_tracked(&StateKey::module(&address, ident_str!("coin"))));
+        }
+
+        // The state of the tracked accounts is tracked
+        assert!(filter
+            .is_tracked(&StateKey::resource_typed::<AccountResource>(&tracked_account).unwrap()));
+        assert!(filter.is_tracked(&StateKey::module(&tracked_account, ident_str!("module"))));
+        assert!(filter.is_tracked(&StateKey::resource_group(
+            &tracked_account,
+            &ObjectGroupResource::struct_tag()
+        )));
+
+        // The tracked resource types are tracked for all accounts
+        assert!(filter.is_tracked(
+            &StateKey::resource_typed::<CoinStoreResource<AptosCoinType>>(&other_account).unwrap()
+        ));
+
+        // All other state is not tracked
+        assert!(!filter
+            .is_tracked(&StateKey::resource_typed::<AccountResource>(&other_account).unwrap()));
+        assert!(!filter.is_tracked(&StateKey::module(&other_account, ident_str!("module"))));
+        assert!(!filter.is_tracked(&StateKey::resource_group(
+            &other_account,
+            &ObjectGroupResource::struct_tag()
+        )));
+        assert!(!filter.is_tracked(&StateKey::table_item(&TableHandle(other_account), &[1])));
+
+        // Table items are not tracked, even if the handle matches a tracked account
+        assert!(!filter.is_tracked(&StateKey::table_item(&TableHandle(tracked_account), &[1])));
+        assert!(!filter.is_tracked(&StateKey::raw(&[1, 2, 3])));
+    }
+}
@@ -2,10 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    on_chain_config::CurrentTimeMicroseconds, proof::SparseMerkleRangeProof,
-    state_store::state_key::StateKey, transaction::Version,
+    ledger_info::LedgerInfo,
+    on_chain_config::CurrentTimeMicroseconds,
+    proof::{SparseMerkleProof, SparseMerkleRangeProof, TransactionInfoWithProof},
+    state_store::state_key::{prefix::StateKeyPrefix, StateKey},
+    transaction::Version,
+};
+use anyhow::ensure;
+use aptos_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
 };
-use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use bytes::Bytes;
 #[cfg(any(test, feature = "fuzzing"))]
@@ -320,6 +327,59 @@ impl StateValueChunkWithProof {
     }
 }
 
+/// A list of state values under a state key prefix at a specific version.
+/// Each state value is accompanied by a proof of inclusion in the sparse
+/// merkle tree at the version, and the transaction info at the version is
+/// accompanied by a proof to the ledger info at the request proof version.
+///
+/// Note: there is no proof of completeness, i.e., a server can omit state
+/// values under the prefix (or stop early) without failing verification.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValuesByPrefixWithProof {
+    pub version: Version, // The version of the state values (the latest state snapshot at or before the requested version)
+    pub values: Vec<(StateKey, StateValue, SparseMerkleProof)>, // The state keys, values and proofs (ordered by key)
+    pub next_key: Option<StateKey>, // The key to continue from (if more state values exist under the prefix)
+    pub transaction_info_with_proof: TransactionInfoWithProof, // The transaction info at the version (holding the state root hash)
+}
+
+impl StateValuesByPrefixWithProof {
+    /// Verifies that all state values are under the given key prefix and
+    /// exist in the sparse merkle tree of the state checkpoint at the version,
+    /// and that the transaction info at the version is proven by the ledger
+    /// info. Note: the ledger info itself must be trusted by the caller.
+    pub fn verify(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        ledger_info: &LedgerInfo,
+    ) -> anyhow::Result<()> {
+        self.transaction_info_with_proof
+            .verify(ledger_info, self.version)?;
+        let root_hash = self
+            .transaction_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+
+        for (state_key, state_value, proof) in &self.values {
+            ensure!(
+                key_prefix.is_prefix(state_key)?,
+                "State key {:?} is not under the requested prefix {:?}",
+                state_key,
+                key_prefix
+            );
+            proof.verify(root_hash, state_key.hash(), Some(state_value))?;
+        }
+        if let Some(next_key) = &self.next_key {
+            ensure!(
+                key_prefix.is_prefix(next_key)?,
+                "Next state key {:?} is not under the requested prefix {:?}",
+                next_key,
+                key_prefix
+            );
+        }
+        Ok(())
+    }
+}
+
 /// Indicates a state value becomes stale since `stale_since_version`.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]